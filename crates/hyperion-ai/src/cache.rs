@@ -0,0 +1,98 @@
+//! Caching of computed paths so many entities heading to the same place share work.
+
+use std::sync::Arc;
+
+use flecs_ecs::macros::Component;
+use hyperion::glam::IVec3;
+use rustc_hash::FxHashMap;
+
+/// How many ticks a cached path stays valid for.
+const DEFAULT_TTL: i64 = 40;
+
+/// Upper bound on cached paths before stale entries are swept.
+const DEFAULT_CAPACITY: usize = 4096;
+
+struct CachedPath {
+    path: Arc<[IVec3]>,
+    computed_at: i64,
+}
+
+/// Paths keyed by their start and goal cell.
+///
+/// Entries expire after a number of ticks, as the world may have changed since they were computed.
+/// Modules that change blocks can call [`PathCache::invalidate_near`] to drop affected paths early.
+#[derive(Component)]
+pub struct PathCache {
+    entries: FxHashMap<(IVec3, IVec3), CachedPath>,
+    ttl: i64,
+    capacity: usize,
+}
+
+impl Default for PathCache {
+    fn default() -> Self {
+        Self {
+            entries: FxHashMap::default(),
+            ttl: DEFAULT_TTL,
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+}
+
+impl PathCache {
+    /// Sets the number of ticks a path is considered valid for.
+    pub const fn set_ttl(&mut self, ttl: i64) {
+        self.ttl = ttl;
+    }
+
+    #[must_use]
+    pub fn get(&self, start: IVec3, goal: IVec3, tick: i64) -> Option<Arc<[IVec3]>> {
+        let entry = self.entries.get(&(start, goal))?;
+
+        if tick - entry.computed_at > self.ttl {
+            return None;
+        }
+
+        Some(entry.path.clone())
+    }
+
+    pub fn insert(&mut self, start: IVec3, goal: IVec3, path: Arc<[IVec3]>, tick: i64) {
+        if self.entries.len() >= self.capacity {
+            self.evict_expired(tick);
+        }
+
+        if self.entries.len() >= self.capacity {
+            self.entries.clear();
+        }
+
+        self.entries.insert((start, goal), CachedPath {
+            path,
+            computed_at: tick,
+        });
+    }
+
+    /// Removes all paths which pass within `radius` blocks of `position`.
+    pub fn invalidate_near(&mut self, position: IVec3, radius: i32) {
+        self.entries.retain(|_, entry| {
+            !entry
+                .path
+                .iter()
+                .any(|cell| (*cell - position).abs().max_element() <= radius)
+        });
+    }
+
+    pub fn evict_expired(&mut self, tick: i64) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, entry| tick - entry.computed_at <= ttl);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,111 @@
+//! Goals an entity can pursue and the components which enable them.
+//!
+//! Each behaviour is opt-in: adding [`Wander`], [`Chase`], [`Flee`] or [`Attack`] to an entity lets
+//! the goal selector pick it. When several are applicable, the selector prefers them in the order
+//! flee, attack, chase, wander.
+
+use flecs_ecs::{core::Entity, macros::Component};
+
+/// Randomly walk around when there is nothing better to do.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct Wander {
+    /// The maximum horizontal distance of a wander destination from the entity.
+    pub radius: i32,
+    /// On average, an idle entity starts wandering once every `chance` ticks.
+    pub chance: u32,
+}
+
+impl Default for Wander {
+    fn default() -> Self {
+        // vanilla `RandomStrollGoal` uses a radius of 10 and an interval of 120 ticks
+        Self {
+            radius: 10,
+            chance: 120,
+        }
+    }
+}
+
+/// Path toward the closest [`hyperion::simulation::AiTargetable`] entity within `range` blocks.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct Chase {
+    pub range: f32,
+}
+
+impl Default for Chase {
+    fn default() -> Self {
+        Self { range: 16.0 }
+    }
+}
+
+/// Run away from the closest [`hyperion::simulation::AiTargetable`] entity within `range` blocks.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct Flee {
+    pub range: f32,
+    /// How far to run in a single path before re-evaluating.
+    pub distance: f32,
+}
+
+impl Default for Flee {
+    fn default() -> Self {
+        Self {
+            range: 8.0,
+            distance: 8.0,
+        }
+    }
+}
+
+/// Attack the closest [`hyperion::simulation::AiTargetable`] entity within `range` blocks.
+///
+/// Attacks are pushed as [`hyperion::simulation::event::AttackEntity`] events, so damage is handled
+/// by whichever module consumes those.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct Attack {
+    pub range: f32,
+    pub damage: f32,
+    /// The minimum number of ticks between two attacks.
+    pub cooldown: i64,
+    pub last_attack: i64,
+}
+
+impl Attack {
+    #[must_use]
+    pub const fn new(range: f32, damage: f32, cooldown: i64) -> Self {
+        Self {
+            range,
+            damage,
+            cooldown,
+            last_attack: i64::MIN / 2,
+        }
+    }
+}
+
+impl Default for Attack {
+    fn default() -> Self {
+        Self::new(2.0, 1.0, 20)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum GoalKind {
+    #[default]
+    Idle,
+    Wander,
+    Chase,
+    Flee,
+    Attack,
+}
+
+/// The goal the entity is currently pursuing. This is written by the goal selector every tick.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ActiveGoal {
+    pub kind: GoalKind,
+    /// The entity being chased, fled from or attacked.
+    pub target: Option<Entity>,
+}
+
+impl ActiveGoal {
+    #[must_use]
+    pub const fn new(kind: GoalKind, target: Option<Entity>) -> Self {
+        Self { kind, target }
+    }
+}
@@ -0,0 +1,391 @@
+//! Reusable AI for non-player entities.
+//!
+//! The [`AiModule`] runs three stages every tick:
+//!
+//! 1. **goal selection**: pick an [`ActiveGoal`] from the behaviours ([`Wander`], [`Chase`], [`Flee`],
+//!    [`Attack`]) the entity has.
+//! 2. **planning**: turn the goal into a destination cell and find a path to it with
+//!    [`path::find_path`], sharing results through the [`PathCache`].
+//! 3. **steering**: move the entity's [`Position`] along the path and face it in the direction of
+//!    travel.
+//!
+//! Attacks are emitted as [`event::AttackEntity`] events.
+
+use std::sync::Arc;
+
+use flecs_ecs::prelude::*;
+use hyperion::{
+    glam::{IVec3, Vec3},
+    net::Compose,
+    simulation::{
+        AiTargetable, EntitySize, Position, RunningSpeed, Yaw, blocks::Blocks,
+        effect::ActiveEffects, event,
+    },
+    storage::Events,
+};
+use spatial::SpatialIndex;
+
+mod cache;
+mod goal;
+pub mod path;
+
+pub use cache::PathCache;
+pub use goal::{ActiveGoal, Attack, Chase, Flee, GoalKind, Wander};
+use path::{Walkability, find_path};
+
+/// The maximum number of cells expanded by a single path search.
+const MAX_VISITED: usize = 512;
+
+/// The minimum number of ticks between two path searches of the same entity.
+const REPATH_INTERVAL: i64 = 10;
+
+/// How far up or down a destination is moved to find ground an entity can stand on.
+const SNAP_RANGE: i32 = 4;
+
+/// How close a chasing entity gets to its target before it stops moving.
+const STOP_DISTANCE: f32 = 1.5;
+
+/// The maximum vertical distance an entity moves in a single tick when stepping up or down.
+const MAX_VERTICAL_STEP: f32 = 0.5;
+
+/// Used for pathfinding when an entity has no [`EntitySize`].
+const DEFAULT_HEIGHT: f32 = 1.8;
+
+#[derive(Component)]
+pub struct AiModule;
+
+/// The path an entity is currently following.
+#[derive(Component, Debug, Default)]
+pub struct Navigator {
+    path: Arc<[IVec3]>,
+    index: usize,
+    destination: Option<IVec3>,
+    next_repath: i64,
+}
+
+impl Navigator {
+    /// The cell the entity is currently walking toward.
+    #[must_use]
+    pub fn waypoint(&self) -> Option<IVec3> {
+        self.path.get(self.index).copied()
+    }
+
+    /// The cell at the end of the current path.
+    #[must_use]
+    pub const fn destination(&self) -> Option<IVec3> {
+        self.destination
+    }
+
+    /// Whether the entity has no remaining waypoints.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.index >= self.path.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.path = Arc::default();
+        self.index = 0;
+        self.destination = None;
+    }
+
+    fn set_path(&mut self, path: Arc<[IVec3]>, destination: IVec3, next_repath: i64) {
+        self.path = path;
+        self.index = 0;
+        self.destination = Some(destination);
+        self.next_repath = next_repath;
+    }
+
+    fn advance(&mut self) {
+        self.index += 1;
+    }
+}
+
+fn target_position(world: &World, target: Entity) -> Option<Vec3> {
+    world
+        .entity_from_id(target)
+        .try_get::<&Position>(|position| **position)
+}
+
+fn cell_of(position: Vec3) -> IVec3 {
+    position.floor().as_ivec3()
+}
+
+fn wander_destination(position: Vec3, wander: &Wander) -> IVec3 {
+    let radius = wander.radius.max(1);
+    let offset = IVec3::new(
+        fastrand::i32(-radius..=radius),
+        0,
+        fastrand::i32(-radius..=radius),
+    );
+
+    cell_of(position) + offset
+}
+
+impl Module for AiModule {
+    fn module(world: &World) {
+        world.import::<spatial::SpatialModule>();
+
+        world.component::<Navigator>();
+        world.component::<ActiveGoal>();
+        world.component::<PathCache>();
+
+        world.component::<Wander>();
+        world.component::<Chase>();
+        world.component::<Flee>();
+        world.component::<Attack>();
+
+        world.set(PathCache::default());
+
+        // any entity with a behaviour needs somewhere to store its goal and path
+        world
+            .component::<Wander>()
+            .add_trait::<(flecs::With, ActiveGoal)>()
+            .add_trait::<(flecs::With, Navigator)>();
+        world
+            .component::<Chase>()
+            .add_trait::<(flecs::With, ActiveGoal)>()
+            .add_trait::<(flecs::With, Navigator)>();
+        world
+            .component::<Flee>()
+            .add_trait::<(flecs::With, ActiveGoal)>()
+            .add_trait::<(flecs::With, Navigator)>();
+        world
+            .component::<Attack>()
+            .add_trait::<(flecs::With, ActiveGoal)>()
+            .add_trait::<(flecs::With, Navigator)>();
+
+        system!(
+            "ai_select_goal",
+            world,
+            &SpatialIndex($),
+            &Position,
+            &mut ActiveGoal,
+            ?&Wander,
+            ?&Chase,
+            ?&Flee,
+            ?&Attack,
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(
+            |it, row, (index, position, goal, wander, chase, flee, attack)| {
+                let world = it.world();
+                let entity = it.entity(row);
+
+                // the furthest any behaviour looks for a target
+                let range = [
+                    flee.map(|flee| flee.range),
+                    attack.map(|attack| attack.range),
+                    chase.map(|chase| chase.range),
+                ]
+                .into_iter()
+                .flatten()
+                .reduce(f32::max);
+
+                let target = range
+                    .and_then(|range| {
+                        index.closest_matching(**position, range, &world, |target| {
+                            target.id() != entity.id() && target.has::<AiTargetable>()
+                        })
+                    })
+                    .and_then(|target| {
+                        let target_position = target_position(&world, target.id())?;
+                        Some((target.id(), position.distance(target_position)))
+                    });
+
+                let in_range = |range: Option<f32>| {
+                    target.and_then(|(target, distance)| {
+                        range
+                            .is_some_and(|range| distance <= range)
+                            .then_some(target)
+                    })
+                };
+
+                *goal = if let Some(target) = in_range(flee.map(|flee| flee.range)) {
+                    ActiveGoal::new(GoalKind::Flee, Some(target))
+                } else if let Some(target) = in_range(attack.map(|attack| attack.range)) {
+                    ActiveGoal::new(GoalKind::Attack, Some(target))
+                } else if let Some(target) = in_range(chase.map(|chase| chase.range)) {
+                    ActiveGoal::new(GoalKind::Chase, Some(target))
+                } else if wander.is_some() {
+                    ActiveGoal::new(GoalKind::Wander, None)
+                } else {
+                    ActiveGoal::default()
+                };
+            },
+        );
+
+        system!(
+            "ai_plan_path",
+            world,
+            &Blocks($),
+            &mut PathCache($),
+            &Compose($),
+            &Position,
+            &ActiveGoal,
+            &mut Navigator,
+            ?&EntitySize,
+            ?&Wander,
+            ?&Flee,
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(
+            |it, _, (blocks, cache, compose, position, goal, navigator, size, wander, flee)| {
+                let world = it.world();
+                let tick = compose.global().tick;
+
+                let walkability =
+                    Walkability::new(blocks, size.map_or(DEFAULT_HEIGHT, |size| size.height));
+
+                let target = goal
+                    .target
+                    .and_then(|target| target_position(&world, target));
+
+                let destination = match (goal.kind, target) {
+                    (GoalKind::Chase, Some(target)) => {
+                        (position.distance(target) > STOP_DISTANCE).then(|| cell_of(target))
+                    }
+                    (GoalKind::Flee, Some(target)) => {
+                        let distance = flee.map_or(Flee::default().distance, |flee| flee.distance);
+                        let away = (**position - target).with_y(0.0).normalize_or_zero();
+                        Some(cell_of(**position + away * distance))
+                    }
+                    (GoalKind::Wander, _) => {
+                        let wander = wander.copied().unwrap_or_default();
+
+                        if !navigator.is_idle() {
+                            navigator.destination()
+                        } else if fastrand::u32(..wander.chance.max(1)) == 0 {
+                            Some(wander_destination(**position, &wander))
+                        } else {
+                            None
+                        }
+                    }
+                    // attacking entities stand still; if the target walks away the selector
+                    // switches back to chasing
+                    _ => None,
+                };
+
+                let Some(destination) =
+                    destination.and_then(|cell| walkability.snap_to_ground(cell, SNAP_RANGE))
+                else {
+                    navigator.clear();
+                    return;
+                };
+
+                if navigator.destination() == Some(destination) && !navigator.is_idle() {
+                    return;
+                }
+
+                if tick < navigator.next_repath {
+                    return;
+                }
+
+                let current = cell_of(**position);
+                let start = walkability.snap_to_ground(current, 1).unwrap_or(current);
+
+                let path = cache.get(start, destination, tick).or_else(|| {
+                    let path: Arc<[IVec3]> = find_path(start, destination, MAX_VISITED, |cell| {
+                        walkability.is_walkable(cell)
+                    })?
+                    .into();
+
+                    cache.insert(start, destination, path.clone(), tick);
+                    Some(path)
+                });
+
+                match path {
+                    Some(path) => navigator.set_path(path, destination, tick + REPATH_INTERVAL),
+                    None => {
+                        navigator.clear();
+                        navigator.next_repath = tick + REPATH_INTERVAL;
+                    }
+                }
+            },
+        );
+
+        system!(
+            "ai_steer",
+            world,
+            &mut Position,
+            &mut Yaw,
+            &mut Navigator,
+            ?&RunningSpeed,
+            ?&ActiveEffects,
+        )
+        .multi_threaded()
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each(|(position, yaw, navigator, speed, effects)| {
+            let Some(waypoint) = navigator.waypoint() else {
+                return;
+            };
+
+            let speed = speed.copied().unwrap_or_default().0
+                * effects.map_or(1.0, ActiveEffects::speed_multiplier);
+
+            let target = waypoint.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+            let delta = target - **position;
+            let horizontal = delta.with_y(0.0);
+
+            if horizontal.length() <= speed && delta.y.abs() <= MAX_VERTICAL_STEP {
+                **position = target;
+                navigator.advance();
+                return;
+            }
+
+            let step = horizontal.clamp_length_max(speed)
+                + Vec3::Y * delta.y.clamp(-MAX_VERTICAL_STEP, MAX_VERTICAL_STEP);
+
+            **position += step;
+
+            if horizontal != Vec3::ZERO {
+                **yaw = (-horizontal.x).atan2(horizontal.z).to_degrees();
+            }
+        });
+
+        system!(
+            "ai_attack",
+            world,
+            &Events($),
+            &Compose($),
+            &Position,
+            &ActiveGoal,
+            &mut Attack,
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, row, (events, compose, position, goal, attack)| {
+            let world = it.world();
+            let tick = compose.global().tick;
+
+            if goal.kind != GoalKind::Attack {
+                return;
+            }
+
+            let Some(target) = goal.target else {
+                return;
+            };
+
+            if tick - attack.last_attack < attack.cooldown {
+                return;
+            }
+
+            let Some(target_position) = target_position(&world, target) else {
+                return;
+            };
+
+            if position.distance(target_position) > attack.range {
+                return;
+            }
+
+            attack.last_attack = tick;
+
+            events.push(
+                event::AttackEntity {
+                    origin: it.entity(row).id(),
+                    target,
+                    damage: attack.damage,
+                },
+                &world,
+            );
+        });
+    }
+}
@@ -0,0 +1,291 @@
+//! A* pathfinding over the block grid.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use hyperion::{glam::IVec3, simulation::blocks::Blocks};
+use rustc_hash::FxHashMap;
+
+/// Cost of moving one block along an axis.
+const STRAIGHT_COST: u32 = 10;
+
+/// Cost of moving one block diagonally (≈ 10 * √2).
+const DIAGONAL_COST: u32 = 14;
+
+/// Extra cost of stepping up a block so flat paths are preferred.
+const STEP_UP_COST: u32 = 5;
+
+/// The furthest an entity is willing to drop in a single step.
+const MAX_DROP: i32 = 3;
+
+const HORIZONTAL_NEIGHBORS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// A view of [`Blocks`] which answers whether an entity of a given height can stand in a cell.
+///
+/// A cell is walkable when the block below it has a collision shape and the cell itself (plus
+/// `clearance - 1` cells above it) has none. Cells in unloaded chunks are never walkable.
+#[derive(Copy, Clone)]
+pub struct Walkability<'a> {
+    blocks: &'a Blocks,
+    clearance: i32,
+}
+
+impl<'a> Walkability<'a> {
+    /// `height` is the height of the entity in blocks (see [`hyperion::simulation::EntitySize`]).
+    #[must_use]
+    #[expect(clippy::cast_possible_truncation)]
+    pub fn new(blocks: &'a Blocks, height: f32) -> Self {
+        let clearance = (height.ceil() as i32).max(1);
+        Self { blocks, clearance }
+    }
+
+    fn is_solid(&self, cell: IVec3) -> Option<bool> {
+        let block = self.blocks.get_block(cell)?;
+        Some(block.collision_shapes().next().is_some())
+    }
+
+    #[must_use]
+    pub fn is_walkable(&self, cell: IVec3) -> bool {
+        if self.is_solid(cell - IVec3::Y) != Some(true) {
+            return false;
+        }
+
+        (0..self.clearance).all(|dy| self.is_solid(cell + IVec3::Y * dy) == Some(false))
+    }
+
+    /// Finds the closest walkable cell in the column of `cell`, searching up to `range` blocks
+    /// above and below it.
+    #[must_use]
+    pub fn snap_to_ground(&self, cell: IVec3, range: i32) -> Option<IVec3> {
+        (0..=range)
+            .flat_map(|dy| [cell - IVec3::Y * dy, cell + IVec3::Y * dy])
+            .find(|&candidate| self.is_walkable(candidate))
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Node {
+    cost: u32,
+    parent: Option<IVec3>,
+}
+
+fn heuristic(from: IVec3, to: IVec3) -> u32 {
+    let delta = (to - from).abs().as_uvec3();
+    let (min, max) = if delta.x < delta.z {
+        (delta.x, delta.z)
+    } else {
+        (delta.z, delta.x)
+    };
+
+    DIAGONAL_COST * min + STRAIGHT_COST * (max - min) + STRAIGHT_COST * delta.y
+}
+
+/// Finds a path from `start` to `goal` using A* with an octile heuristic.
+///
+/// Entities may walk in eight directions, step up one block, or drop up to [`MAX_DROP`] blocks.
+/// Diagonal moves are only allowed when both adjacent straight moves are possible, so paths never
+/// cut corners.
+///
+/// At most `max_visited` cells are expanded. If the goal is not reached within that budget, the path
+/// to the visited cell closest to the goal is returned instead so entities still make progress.
+///
+/// The returned path excludes `start` and ends at `goal` (or the closest reachable cell). [`None`] is
+/// returned if no progress toward the goal is possible.
+pub fn find_path(
+    start: IVec3,
+    goal: IVec3,
+    max_visited: usize,
+    mut is_walkable: impl FnMut(IVec3) -> bool,
+) -> Option<Vec<IVec3>> {
+    let mut nodes: FxHashMap<IVec3, Node> = FxHashMap::default();
+    let mut open = BinaryHeap::new();
+
+    nodes.insert(start, Node {
+        cost: 0,
+        parent: None,
+    });
+    open.push(Reverse((heuristic(start, goal), start.to_array())));
+
+    let mut closest = (heuristic(start, goal), start);
+    let mut visited = 0;
+
+    while let Some(Reverse((_, current))) = open.pop() {
+        let current = IVec3::from_array(current);
+
+        if current == goal {
+            closest = (0, goal);
+            break;
+        }
+
+        visited += 1;
+        if visited > max_visited {
+            break;
+        }
+
+        let current_cost = nodes[&current].cost;
+
+        let mut straight_walkable = [None; 4];
+
+        for (idx, (dx, dz)) in HORIZONTAL_NEIGHBORS.into_iter().enumerate() {
+            let diagonal = idx >= 4;
+
+            if diagonal {
+                // only allow a diagonal if both straight moves into it land on the same level
+                let x_idx = usize::from(dx < 0);
+                let z_idx = 2 + usize::from(dz < 0);
+
+                let (Some(x_dy), Some(z_dy)) = (straight_walkable[x_idx], straight_walkable[z_idx])
+                else {
+                    continue;
+                };
+
+                if x_dy != 0 || z_dy != 0 {
+                    continue;
+                }
+            }
+
+            let column = current + IVec3::new(dx, 0, dz);
+
+            let Some(dy) = std::iter::once(0)
+                .chain(std::iter::once(1))
+                .chain((1..=MAX_DROP).map(|drop| -drop))
+                .find(|&dy| is_walkable(column + IVec3::Y * dy))
+            else {
+                continue;
+            };
+
+            if !diagonal {
+                straight_walkable[idx] = Some(dy);
+            }
+
+            let neighbor = column + IVec3::Y * dy;
+
+            let mut cost = current_cost
+                + if diagonal {
+                    DIAGONAL_COST
+                } else {
+                    STRAIGHT_COST
+                };
+
+            if dy > 0 {
+                cost += STEP_UP_COST;
+            }
+
+            if nodes.get(&neighbor).is_some_and(|node| node.cost <= cost) {
+                continue;
+            }
+
+            nodes.insert(neighbor, Node {
+                cost,
+                parent: Some(current),
+            });
+
+            let h = heuristic(neighbor, goal);
+
+            if h < closest.0 {
+                closest = (h, neighbor);
+            }
+
+            open.push(Reverse((cost + h, neighbor.to_array())));
+        }
+    }
+
+    let (_, end) = closest;
+
+    if end == start {
+        return (start == goal).then(Vec::new);
+    }
+
+    let mut path = vec![end];
+    let mut current = end;
+
+    while let Some(parent) = nodes[&current].parent {
+        if parent == start {
+            break;
+        }
+        path.push(parent);
+        current = parent;
+    }
+
+    path.reverse();
+
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A flat floor at y = 0 with walls at the given cells.
+    fn flat(walls: &[IVec3]) -> impl FnMut(IVec3) -> bool + '_ {
+        move |cell| cell.y == 1 && !walls.contains(&cell)
+    }
+
+    #[test]
+    fn straight_line() {
+        let path = find_path(IVec3::new(0, 1, 0), IVec3::new(5, 1, 0), 1000, flat(&[])).unwrap();
+
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.last(), Some(&IVec3::new(5, 1, 0)));
+    }
+
+    #[test]
+    fn diagonal_is_shorter_than_manhattan() {
+        let path = find_path(IVec3::new(0, 1, 0), IVec3::new(4, 1, 4), 1000, flat(&[])).unwrap();
+
+        assert_eq!(path.len(), 4);
+    }
+
+    #[test]
+    fn walks_around_wall() {
+        let walls: Vec<_> = (-3..=3).map(|z| IVec3::new(2, 1, z)).collect();
+
+        let path = find_path(IVec3::new(0, 1, 0), IVec3::new(4, 1, 0), 1000, flat(&walls)).unwrap();
+
+        assert_eq!(path.last(), Some(&IVec3::new(4, 1, 0)));
+        assert!(path.iter().all(|cell| !walls.contains(cell)));
+    }
+
+    #[test]
+    fn steps_up_and_drops_down() {
+        // a one block high platform from x = 2 to x = 3
+        let is_walkable = |cell: IVec3| match cell.x {
+            2 | 3 => cell.y == 2,
+            _ => cell.y == 1,
+        };
+
+        let path = find_path(IVec3::new(0, 1, 0), IVec3::new(5, 1, 0), 1000, is_walkable).unwrap();
+
+        assert!(path.contains(&IVec3::new(2, 2, 0)));
+        assert_eq!(path.last(), Some(&IVec3::new(5, 1, 0)));
+    }
+
+    #[test]
+    fn partial_path_when_unreachable() {
+        // the goal is enclosed
+        let walls = [
+            IVec3::new(9, 1, 0),
+            IVec3::new(11, 1, 0),
+            IVec3::new(10, 1, 1),
+            IVec3::new(10, 1, -1),
+            IVec3::new(9, 1, 1),
+            IVec3::new(9, 1, -1),
+            IVec3::new(11, 1, 1),
+            IVec3::new(11, 1, -1),
+        ];
+
+        let path = find_path(IVec3::new(0, 1, 0), IVec3::new(10, 1, 0), 200, flat(&walls)).unwrap();
+
+        let end = *path.last().unwrap();
+        assert_ne!(end, IVec3::new(10, 1, 0));
+        assert!(end.x >= 7);
+    }
+}
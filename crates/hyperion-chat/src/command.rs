@@ -0,0 +1,158 @@
+use clap::Parser;
+use flecs_ecs::{
+    core::{Entity, EntityView, EntityViewGet, WorldGet, WorldProvider},
+    prelude::*,
+};
+use hyperion::{
+    net::Compose,
+    simulation::{IgnMap, Name, Uuid},
+};
+use hyperion_clap::{
+    CommandPermission, MinecraftCommand, PlayerArg, hyperion_command::CommandRegistry,
+};
+use hyperion_permission::{Groups, Permissions};
+
+use crate::{Channel, Ignores, ReplyTarget, private_message, tell};
+
+pub fn register(registry: &mut CommandRegistry, world: &World) {
+    ChannelCommand::register(registry, world);
+    IgnoreCommand::register(registry, world);
+    MsgCommand::register(registry, world);
+    ReplyCommand::register(registry, world);
+}
+
+/// The online player called `name`, telling `caller` if there is none.
+fn find_player<'a>(
+    system: EntityView<'a>,
+    caller: EntityView<'a>,
+    name: &str,
+) -> Option<EntityView<'a>> {
+    let world = system.world();
+
+    let player = world.get::<&IgnMap>(|ign_map| ign_map.get(name).copied());
+
+    if player.is_none() {
+        world.get::<&Compose>(|compose| {
+            tell(compose, system, caller, format!("§c{name} is not online"));
+        });
+    }
+
+    player.map(|player| player.entity_view(world))
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "msg")]
+#[command(about = "Send a private message to a player")]
+#[command_permission(group = "Normal")]
+pub struct MsgCommand {
+    player: PlayerArg,
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    message: Vec<String>,
+}
+
+impl MinecraftCommand for MsgCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let caller = caller.entity_view(system.world());
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        private_message(system, caller, target, &self.message.join(" "));
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "reply")]
+#[command(about = "Answer the last private message")]
+#[command_permission(group = "Normal")]
+pub struct ReplyCommand {
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    message: Vec<String>,
+}
+
+impl MinecraftCommand for ReplyCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+
+        let target = caller
+            .try_get::<&ReplyTarget>(|target| target.0)
+            .filter(|target| world.is_alive(*target));
+
+        let Some(target) = target else {
+            world.get::<&Compose>(|compose| {
+                tell(compose, system, caller, "§cThere is nobody to reply to");
+            });
+            return;
+        };
+
+        private_message(
+            system,
+            caller,
+            target.entity_view(world),
+            &self.message.join(" "),
+        );
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "ignore")]
+#[command(about = "Stop or start receiving messages from a player")]
+#[command_permission(group = "Normal")]
+pub struct IgnoreCommand {
+    player: PlayerArg,
+}
+
+impl MinecraftCommand for IgnoreCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        let (uuid, name) = target.get::<(&Uuid, &Name)>(|(uuid, name)| (**uuid, name.to_string()));
+
+        let message = if target == caller {
+            "§cYou cannot ignore yourself".to_owned()
+        } else if caller.get::<&mut Ignores>(|ignores| ignores.toggle(uuid)) {
+            format!("§7You no longer receive messages from §b{name}")
+        } else {
+            format!("§7You receive messages from §b{name} §7again")
+        };
+
+        world.get::<&Compose>(|compose| tell(compose, system, caller, message));
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "channel")]
+#[command(about = "Choose who your chat messages go to")]
+#[command_permission(group = "Normal")]
+pub struct ChannelCommand {
+    #[arg(value_enum)]
+    channel: Channel,
+}
+
+impl MinecraftCommand for ChannelCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+
+        let allowed = world.get::<&Groups>(|groups| {
+            caller
+                .get::<Option<&Permissions>>(|permissions| self.channel.allows(groups, permissions))
+        });
+
+        let message = if allowed {
+            caller.set(self.channel);
+            format!("§7You are now chatting in §b{:?}", self.channel)
+        } else {
+            "§cYou cannot use that channel".to_owned()
+        };
+
+        world.get::<&Compose>(|compose| tell(compose, system, caller, message));
+    }
+}
@@ -0,0 +1,237 @@
+//! Filters every chat message goes through before it is sent.
+//!
+//! Filters run in the order they were added to [`ChatConfig`](crate::ChatConfig). Each one may
+//! rewrite the message, and the first one to block it stops the chain.
+
+use flecs_ecs::core::EntityView;
+use rustc_hash::FxHashSet;
+
+/// What a [`ChatFilter`] decided about a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// The message is not sent, and the reason is shown to its sender.
+    Block(String),
+}
+
+/// What a [`ChatFilter`] knows about the message it checks.
+pub struct FilterContext<'a> {
+    pub sender: EntityView<'a>,
+    /// The last message the sender sent, if any.
+    pub previous: Option<&'a str>,
+}
+
+pub trait ChatFilter: Send + Sync {
+    /// Checks `message`, which the filter may also rewrite.
+    fn check(&self, message: &mut String, context: &FilterContext<'_>) -> Verdict;
+}
+
+/// Replaces listed words with asterisks. Only whole words match, so a listed word inside a longer
+/// word is kept.
+#[derive(Clone, Debug, Default)]
+pub struct ProfanityFilter {
+    words: FxHashSet<String>,
+}
+
+impl ProfanityFilter {
+    #[must_use]
+    pub fn new(words: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        Self {
+            words: words
+                .into_iter()
+                .map(|word| word.as_ref().to_lowercase())
+                .collect(),
+        }
+    }
+}
+
+impl ChatFilter for ProfanityFilter {
+    fn check(&self, message: &mut String, _: &FilterContext<'_>) -> Verdict {
+        if let Some(censored) = censor(&self.words, message) {
+            *message = censored;
+        }
+
+        Verdict::Allow
+    }
+}
+
+/// `message` with the words in `words` replaced by asterisks, if it has any.
+fn censor(words: &FxHashSet<String>, message: &str) -> Option<String> {
+    let mut censored = String::with_capacity(message.len());
+    let mut changed = false;
+
+    for piece in message.split_inclusive(|c: char| !c.is_alphanumeric()) {
+        let word = piece.trim_end_matches(|c: char| !c.is_alphanumeric());
+
+        if words.contains(&word.to_lowercase()) {
+            censored.extend(word.chars().map(|_| '*'));
+            censored.push_str(&piece[word.len()..]);
+            changed = true;
+        } else {
+            censored.push_str(piece);
+        }
+    }
+
+    changed.then_some(censored)
+}
+
+/// Lowercases messages which are mostly capital letters.
+#[derive(Copy, Clone, Debug)]
+pub struct CapsFilter {
+    /// The share of letters which may be capitals, between 0 and 1.
+    pub max_ratio: f32,
+    /// Messages with fewer letters than this are never changed, so short messages like `GG` stay.
+    pub min_letters: usize,
+}
+
+impl Default for CapsFilter {
+    fn default() -> Self {
+        Self {
+            max_ratio: 0.5,
+            min_letters: 8,
+        }
+    }
+}
+
+impl ChatFilter for CapsFilter {
+    fn check(&self, message: &mut String, _: &FilterContext<'_>) -> Verdict {
+        let letters = message.chars().filter(|c| c.is_alphabetic()).count();
+        let capitals = message.chars().filter(|c| c.is_uppercase()).count();
+
+        if letters >= self.min_letters && capitals as f32 > letters as f32 * self.max_ratio {
+            *message = message.to_lowercase();
+        }
+
+        Verdict::Allow
+    }
+}
+
+/// Blocks messages containing links.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LinkFilter;
+
+/// Top-level domains which are blocked even without `http://` or `www.` in front of them.
+const TOP_LEVEL_DOMAINS: &[&str] = &[
+    "com", "net", "org", "io", "gg", "me", "co", "tv", "xyz", "dev", "app", "info", "ru", "de",
+    "uk", "us",
+];
+
+fn is_link(word: &str) -> bool {
+    let word = word.to_lowercase();
+    let word = word.trim_end_matches(|c: char| !c.is_alphanumeric());
+
+    if word.contains("://") || word.starts_with("www.") {
+        return true;
+    }
+
+    // the domain ends at the first slash, as in `example.com/path`
+    let domain = word.split('/').next().unwrap_or_default();
+
+    match domain.rsplit_once('.') {
+        Some((name, tld)) => !name.is_empty() && TOP_LEVEL_DOMAINS.contains(&tld),
+        None => false,
+    }
+}
+
+impl ChatFilter for LinkFilter {
+    fn check(&self, message: &mut String, _: &FilterContext<'_>) -> Verdict {
+        if message.split_whitespace().any(is_link) {
+            return Verdict::Block("§cLinks are not allowed in chat".to_owned());
+        }
+
+        Verdict::Allow
+    }
+}
+
+/// Blocks messages which are too similar to the previous message of their sender.
+#[derive(Copy, Clone, Debug)]
+pub struct SpamFilter {
+    /// How similar two messages may be before the second one is blocked, between 0 and 1.
+    pub max_similarity: f32,
+}
+
+impl Default for SpamFilter {
+    fn default() -> Self {
+        Self {
+            max_similarity: 0.85,
+        }
+    }
+}
+
+impl ChatFilter for SpamFilter {
+    fn check(&self, message: &mut String, context: &FilterContext<'_>) -> Verdict {
+        let Some(previous) = context.previous else {
+            return Verdict::Allow;
+        };
+
+        if similarity(previous, message) > self.max_similarity {
+            return Verdict::Block("§cPlease do not repeat the same message".to_owned());
+        }
+
+        Verdict::Allow
+    }
+}
+
+/// How similar two messages are, from 0 for nothing in common to 1 for the same message ignoring
+/// case. This is one minus the edit distance relative to the longer message.
+fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    let longest = a.len().max(b.len());
+
+    if longest == 0 {
+        return 1.0;
+    }
+
+    // edit distance, keeping only the previous row of the table
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    1.0 - row[b.len()] as f32 / longest as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn censors_whole_words() {
+        let filter = ProfanityFilter::new(["Heck"]);
+
+        assert_eq!(
+            censor(&filter.words, "what the HECK, heck!").as_deref(),
+            Some("what the ****, ****!")
+        );
+        assert_eq!(censor(&filter.words, "checkmate"), None);
+    }
+
+    #[test]
+    fn detects_links() {
+        assert!(is_link("https://example.org"));
+        assert!(is_link("www.example"));
+        assert!(is_link("Example.com/invite,"));
+        assert!(!is_link("3.14"));
+        assert!(!is_link("end."));
+        assert!(!is_link("file.txt"));
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp, reason = "the similarities are exact")]
+    fn similarity_is_relative_edit_distance() {
+        assert_eq!(similarity("hello", "HELLO"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("abcd", "abce"), 0.75);
+        assert_eq!(similarity("abc", ""), 0.0);
+    }
+}
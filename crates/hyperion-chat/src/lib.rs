@@ -0,0 +1,769 @@
+//! Chat channels, private messages, ignore lists, mutes and message filters.
+//!
+//! Every player writes to one [`Channel`], which they switch with `/channel`. Before a message
+//! is sent it goes through the checks below, in order:
+//!
+//! 1. senders who are [`Muted`] or cooling down are told so and nothing is sent.
+//! 2. the [`ChatFilter`]s of the [`ChatConfig`] may rewrite or block the message.
+//! 3. the listeners added with [`ChatConfig::on_chat`] may rewrite or cancel the [`ChatEvent`].
+//!
+//! Players never receive messages from players they [`Ignores`]. Private messages are sent with
+//! `/msg` and answered with `/reply`.
+//!
+//! Messages signed by a chat session keep their signature, so clients can verify them, unless
+//! [`Global::secure_chat`](hyperion::Global::secure_chat) is off. Private messages are always
+//! system messages.
+//!
+//! ```ignore
+//! world.get::<&mut ChatConfig>(|config| {
+//!     config.cooldown = 40;
+//!     config.add_filter(LinkFilter);
+//!     config.on_chat(|sender, event| {
+//!         event.display_name = format!("§6[VIP] §b{}", event.display_name);
+//!     });
+//! });
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use flecs_ecs::prelude::*;
+use hyperion::{
+    PacketBundle,
+    glam::I16Vec2,
+    net::{Compose, ConnectionId, DataBundle, agnostic},
+    simulation::{
+        Name, PacketState, Player, Position, Uuid, event,
+        scoreboard::Teams,
+        secure_chat::{self, LastSeenMessages},
+    },
+    storage::{EventQueue, Persist, PersistExt},
+};
+use hyperion_clap::hyperion_command::{self, CommandRegistry};
+use hyperion_permission::{Groups, Permissions};
+use hyperion_text::Text;
+use rustc_hash::FxHashSet;
+use tracing::error;
+
+mod command;
+pub mod filter;
+
+pub use command::{ChannelCommand, IgnoreCommand, MsgCommand, ReplyCommand};
+pub use filter::{
+    CapsFilter, ChatFilter, FilterContext, LinkFilter, ProfanityFilter, SpamFilter, Verdict,
+};
+
+/// How far away in chunks players receive [`Channel::Local`] messages. This is the area the
+/// proxy uses for local broadcasts.
+const LOCAL_RADIUS: i32 = 16;
+
+/// The permission node needed for [`Channel::Staff`], which moderators have by default.
+pub const STAFF_NODE: &str = "hyperion_chat.channel.staff";
+
+/// Who a chat message goes to.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Default, ValueEnum)]
+pub enum Channel {
+    /// Everyone.
+    #[default]
+    Global,
+    /// Players in the same [`Teams`] team.
+    Team,
+    /// Players nearby.
+    Local,
+    /// Moderators and admins.
+    Staff,
+}
+
+impl Channel {
+    /// What is shown in front of messages in the channel.
+    #[must_use]
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            Self::Global => "",
+            Self::Team => "§a[Team] ",
+            Self::Local => "§7[Local] ",
+            Self::Staff => "§c[Staff] ",
+        }
+    }
+
+    /// Whether a player with `permissions` may write to and read the channel.
+    #[must_use]
+    pub fn allows(self, groups: &Groups, permissions: Option<&Permissions>) -> bool {
+        match self {
+            Self::Staff => {
+                permissions.is_some_and(|permissions| groups.check(permissions, STAFF_NODE))
+            }
+            Self::Global | Self::Team | Self::Local => true,
+        }
+    }
+}
+
+impl Persist for Channel {
+    const KEY: &'static str = "hyperion-chat:channel";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let name = self.to_possible_value().context("variant is skipped")?;
+        Ok(name.get_name().as_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let name = std::str::from_utf8(bytes)?;
+        Self::from_str(name, false).map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+/// The players whose messages a player does not receive.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ignores {
+    players: FxHashSet<uuid::Uuid>,
+}
+
+impl Ignores {
+    #[must_use]
+    pub fn contains(&self, player: uuid::Uuid) -> bool {
+        self.players.contains(&player)
+    }
+
+    /// Ignores `player`, or stops ignoring them if they already were. Returns whether they are
+    /// ignored now.
+    pub fn toggle(&mut self, player: uuid::Uuid) -> bool {
+        if self.players.remove(&player) {
+            return false;
+        }
+
+        self.players.insert(player)
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = uuid::Uuid> + '_ {
+        self.players.iter().copied()
+    }
+}
+
+impl Persist for Ignores {
+    const KEY: &'static str = "hyperion-chat:ignores";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self
+            .players
+            .iter()
+            .flat_map(|uuid| uuid.to_bytes_le())
+            .collect())
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let uuids = bytes.chunks_exact(16);
+
+        anyhow::ensure!(
+            uuids.remainder().is_empty(),
+            "ignores are not a list of uuids"
+        );
+
+        Ok(Self {
+            players: uuids
+                .map(uuid::Uuid::from_slice_le)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Stops a player from chatting, either for good or until `until`.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct Muted {
+    pub until: Option<SystemTime>,
+    pub reason: Option<String>,
+}
+
+impl Muted {
+    #[must_use]
+    pub const fn permanent() -> Self {
+        Self {
+            until: None,
+            reason: None,
+        }
+    }
+
+    #[must_use]
+    pub fn temporary(duration: Duration) -> Self {
+        Self {
+            until: Some(SystemTime::now() + duration),
+            reason: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    #[must_use]
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.until.is_some_and(|until| until <= now)
+    }
+
+    /// What the muted player is told when they try to chat.
+    fn message(&self, now: SystemTime) -> String {
+        let message = match self.until {
+            Some(until) => {
+                let remaining = until.duration_since(now).unwrap_or_default();
+                let remaining = Duration::from_secs(remaining.as_secs() + 1);
+                format!(
+                    "§cYou are muted for {}",
+                    humantime::format_duration(remaining)
+                )
+            }
+            None => "§cYou are muted".to_owned(),
+        };
+
+        match &self.reason {
+            Some(reason) => format!("{message}: {reason}"),
+            None => message,
+        }
+    }
+}
+
+/// Stored as the seconds since the Unix epoch the mute ends at, or `u64::MAX` if it never does,
+/// followed by the reason.
+impl Persist for Muted {
+    const KEY: &'static str = "hyperion-chat:muted";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let until = match self.until {
+            Some(until) => until.duration_since(UNIX_EPOCH)?.as_secs(),
+            None => u64::MAX,
+        };
+
+        let mut bytes = until.to_le_bytes().to_vec();
+
+        if let Some(reason) = &self.reason {
+            bytes.extend_from_slice(reason.as_bytes());
+        }
+
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let (until, reason) = bytes
+            .split_first_chunk::<8>()
+            .context("mute has no end time")?;
+
+        let until = match u64::from_le_bytes(*until) {
+            u64::MAX => None,
+            secs => Some(UNIX_EPOCH + Duration::from_secs(secs)),
+        };
+
+        let reason = std::str::from_utf8(reason)?;
+
+        Ok(Self {
+            until,
+            reason: (!reason.is_empty()).then(|| reason.to_owned()),
+        })
+    }
+}
+
+/// The tick a player may chat again at.
+#[derive(Default, Component)]
+#[meta]
+pub struct ChatCooldown {
+    pub expires: i64,
+}
+
+/// The player `/reply` answers, who is the last player a private message was sent to or received
+/// from.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReplyTarget(pub Entity);
+
+/// The last message a player sent, for [`FilterContext::previous`].
+#[derive(Component, Default)]
+struct LastMessage(Option<String>);
+
+/// Where a [`ChatEvent`] is sent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Destination {
+    Channel(Channel),
+    /// A private message to this player.
+    Private(Entity),
+}
+
+/// A message which passed the filters and is about to be sent.
+#[derive(Clone, Debug)]
+pub struct ChatEvent {
+    destination: Destination,
+    /// The name shown for the sender, which is their name unless a listener changes it.
+    pub display_name: String,
+    pub message: String,
+    pub cancelled: bool,
+}
+
+impl ChatEvent {
+    #[must_use]
+    pub const fn destination(&self) -> Destination {
+        self.destination
+    }
+
+    /// Stops the message from being sent. The sender is not told.
+    pub const fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    /// The line shown in a channel.
+    fn line(&self, channel: Channel) -> String {
+        format!(
+            "{}§8<§b{}§8>§r {}",
+            channel.prefix(),
+            self.display_name,
+            self.message
+        )
+    }
+
+    /// The name of the sender shown by the client in a player chat message.
+    fn sender_name(&self, channel: Channel) -> Text<'static> {
+        hyperion_text::legacy::parse(&format!("{}§b{}", channel.prefix(), self.display_name))
+    }
+}
+
+/// Runs before a [`ChatEvent`] is sent.
+pub type ChatListener = Box<dyn Fn(EntityView<'_>, &mut ChatEvent) + Send + Sync>;
+
+/// How chat behaves.
+#[derive(Component)]
+pub struct ChatConfig {
+    /// Ticks a player waits between two messages.
+    pub cooldown: i64,
+    /// The channel players write to until they pick another one.
+    pub default_channel: Channel,
+    filters: Vec<Box<dyn ChatFilter>>,
+    listeners: Vec<ChatListener>,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            cooldown: 20,
+            default_channel: Channel::Global,
+            filters: Vec::new(),
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl ChatConfig {
+    /// Adds a filter to the end of the chain.
+    pub fn add_filter(&mut self, filter: impl ChatFilter + 'static) {
+        self.filters.push(Box::new(filter));
+    }
+
+    /// Adds a listener which runs for every message after the filters, in the order listeners were
+    /// added.
+    pub fn on_chat(
+        &mut self,
+        listener: impl Fn(EntityView<'_>, &mut ChatEvent) + Send + Sync + 'static,
+    ) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// Runs the checks on a message of `sender`. Returns `None` if it should not be sent, in which
+    /// case the sender has been told why unless a listener cancelled it.
+    fn prepare(
+        &self,
+        compose: &Compose,
+        system: EntityView<'_>,
+        sender: EntityView<'_>,
+        destination: Destination,
+        message: &str,
+    ) -> Option<ChatEvent> {
+        let now = SystemTime::now();
+
+        let muted =
+            sender.try_get::<&Muted>(|muted| (!muted.is_expired(now)).then(|| muted.message(now)));
+
+        match muted {
+            Some(Some(reason)) => {
+                tell(compose, system, sender, reason);
+                return None;
+            }
+            Some(None) => {
+                sender.remove::<Muted>();
+            }
+            None => {}
+        }
+
+        let tick = compose.global().tick;
+
+        // the cooldown only starts once a message is delivered
+        let remaining = sender
+            .try_get::<&ChatCooldown>(|cooldown| {
+                (cooldown.expires > tick).then(|| cooldown.expires - tick)
+            })
+            .flatten();
+
+        if let Some(remaining) = remaining {
+            let remaining = remaining as f32 / 20.0;
+            tell(
+                compose,
+                system,
+                sender,
+                format!("§cPlease wait {remaining:.2} seconds before sending another message"),
+            );
+            return None;
+        }
+
+        let mut message = message.to_owned();
+
+        let verdict = sender
+            .try_get::<&mut LastMessage>(|last| {
+                let context = FilterContext {
+                    sender,
+                    previous: last.0.as_deref(),
+                };
+
+                let verdict = self
+                    .filters
+                    .iter()
+                    .map(|filter| filter.check(&mut message, &context))
+                    .find(|verdict| *verdict != Verdict::Allow)
+                    .unwrap_or(Verdict::Allow);
+
+                if verdict == Verdict::Allow {
+                    last.0 = Some(message.clone());
+                }
+
+                verdict
+            })
+            .unwrap_or(Verdict::Allow);
+
+        if let Verdict::Block(reason) = verdict {
+            tell(compose, system, sender, reason);
+            return None;
+        }
+
+        let mut event = ChatEvent {
+            destination,
+            display_name: sender
+                .try_get::<&Name>(ToString::to_string)
+                .unwrap_or_default(),
+            message,
+            cancelled: false,
+        };
+
+        for listener in &self.listeners {
+            listener(sender, &mut event);
+        }
+
+        (!event.cancelled).then_some(event)
+    }
+
+    /// Makes `sender` wait before their next message, after one was delivered.
+    fn start_cooldown(&self, compose: &Compose, sender: EntityView<'_>) {
+        let tick = compose.global().tick;
+
+        sender.try_get::<&mut ChatCooldown>(|cooldown| {
+            cooldown.expires = tick + self.cooldown;
+        });
+    }
+}
+
+/// Sends `message` to `player` in chat.
+fn tell(
+    compose: &Compose,
+    system: EntityView<'_>,
+    player: EntityView<'_>,
+    message: impl Into<String>,
+) {
+    let chat = agnostic::chat(message);
+
+    player.try_get::<&ConnectionId>(|io| {
+        if let Err(e) = compose.unicast(&chat, *io, system) {
+            error!("failed to send chat message: {e}");
+        }
+    });
+}
+
+/// Sends a chat packet to `recipients`. Broadcasts are cheaper, but cannot leave anyone out, so
+/// they are only used if `everyone` in the channel receives the packet.
+fn deliver(
+    compose: &Compose,
+    system: EntityView<'_>,
+    pkt: impl PacketBundle + Copy,
+    channel: Channel,
+    center: I16Vec2,
+    everyone: bool,
+    recipients: &[ConnectionId],
+) -> anyhow::Result<()> {
+    match channel {
+        Channel::Global if everyone => compose.broadcast(pkt, system).send(),
+        Channel::Local if everyone => compose.broadcast_local(pkt, center, system).send(),
+        _ => {
+            let mut bundle = DataBundle::new(compose, system);
+            bundle.add_packet(pkt)?;
+
+            recipients.iter().try_for_each(|io| bundle.unicast(*io))
+        }
+    }
+}
+
+/// Sends a private message from `sender` to `target`, going through the same checks as chat
+/// messages.
+pub fn private_message(
+    system: EntityView<'_>,
+    sender: EntityView<'_>,
+    target: EntityView<'_>,
+    message: &str,
+) {
+    let world = system.world();
+
+    world.get::<&Compose>(|compose| {
+        if sender == target {
+            tell(compose, system, sender, "§cYou cannot message yourself");
+            return;
+        }
+
+        let sender_uuid = sender.get::<&Uuid>(|uuid| **uuid);
+
+        if target
+            .try_get::<&Ignores>(|ignores| ignores.contains(sender_uuid))
+            .unwrap_or_default()
+        {
+            tell(
+                compose,
+                system,
+                sender,
+                "§cThat player is not accepting your messages",
+            );
+            return;
+        }
+
+        let Some(event) = world.get::<&ChatConfig>(|config| {
+            config.prepare(
+                compose,
+                system,
+                sender,
+                Destination::Private(*target),
+                message,
+            )
+        }) else {
+            return;
+        };
+
+        let target_name = target.get::<&Name>(ToString::to_string);
+
+        tell(
+            compose,
+            system,
+            target,
+            format!("§d{} §7→ §dyou§7: §f{}", event.display_name, event.message),
+        );
+        tell(
+            compose,
+            system,
+            sender,
+            format!("§dyou §7→ §d{target_name}§7: §f{}", event.message),
+        );
+
+        world.get::<&ChatConfig>(|config| config.start_cooldown(compose, sender));
+
+        sender.set(ReplyTarget(*target));
+        target.set(ReplyTarget(*sender));
+    });
+}
+
+#[derive(Component)]
+pub struct ChatModule;
+
+impl Module for ChatModule {
+    fn module(world: &World) {
+        world.component::<Channel>();
+        world.component::<Ignores>();
+        world.component::<Muted>();
+        world.component::<ChatCooldown>().meta();
+        world.component::<ReplyTarget>();
+        world.component::<LastMessage>();
+        world.component::<ChatConfig>();
+
+        world.set(ChatConfig::default());
+
+        world
+            .component::<Player>()
+            .add_trait::<(flecs::With, Channel)>()
+            .add_trait::<(flecs::With, Ignores)>()
+            .add_trait::<(flecs::With, ChatCooldown)>()
+            .add_trait::<(flecs::With, LastMessage)>();
+
+        // a stored channel is restored over the default
+        observer!(world, flecs::OnAdd, &mut Channel, &ChatConfig($)).each(|(channel, config)| {
+            *channel = config.default_channel;
+        });
+
+        world.persist::<Channel>();
+        world.persist::<Ignores>();
+        world.persist::<Muted>();
+
+        world.import::<hyperion_command::CommandModule>();
+
+        world.get::<&mut Groups>(|groups| groups.grant_default("moderator", STAFF_NODE));
+
+        world.get::<&mut CommandRegistry>(|registry| {
+            command::register(registry, world);
+        });
+
+        let players = world
+            .query::<(
+                &ConnectionId,
+                &Name,
+                &Position,
+                &Ignores,
+                Option<&Permissions>,
+                &mut LastSeenMessages,
+            )>()
+            .with_enum(PacketState::Play)
+            .build();
+
+        system!(
+            "handle_chat_messages",
+            world,
+            &mut EventQueue<event::ChatMessage<'static>>($),
+            &Compose($),
+            &ChatConfig($),
+            &Teams($),
+            &Groups($),
+        )
+        .each_iter(move |it, _, (queue, compose, config, teams, groups)| {
+            let world = it.world();
+            let system = it.system();
+
+            for event::ChatMessage { msg, by, signed } in queue.drain() {
+                let sender = world.entity_from_id(by);
+
+                // todo: we should not need this; death should occur such that this is always valid
+                if !sender.is_alive() {
+                    continue;
+                }
+
+                let (channel, allowed) =
+                    sender.get::<(&Channel, Option<&Permissions>)>(|(channel, permissions)| {
+                        (*channel, channel.allows(groups, permissions))
+                    });
+
+                if !allowed {
+                    sender.set(config.default_channel);
+                    tell(
+                        compose,
+                        system,
+                        sender,
+                        "§cYou can no longer use that channel, so you are back in the default one",
+                    );
+                    continue;
+                }
+
+                let (sender_uuid, sender_name, center) =
+                    sender.get::<(&Uuid, &Name, &Position)>(|(uuid, name, position)| {
+                        (**uuid, name.to_string(), position.to_chunk())
+                    });
+
+                let team = match channel {
+                    Channel::Team => {
+                        let Some(team) = teams.team_of(&sender_name) else {
+                            tell(compose, system, sender, "§cYou are not in a team");
+                            continue;
+                        };
+
+                        Some(team)
+                    }
+                    _ => None,
+                };
+
+                let Some(event) =
+                    config.prepare(compose, system, sender, Destination::Channel(channel), msg)
+                else {
+                    continue;
+                };
+
+                let mut recipients = Vec::new();
+                let mut ignored = false;
+
+                // with secure chat off, player messages are system messages instead
+                let signed = signed.filter(|_| compose.global().secure_chat);
+
+                players.each_entity(|player, (io, name, position, ignores, permissions, seen)| {
+                    let included = match channel {
+                        Channel::Global => true,
+                        Channel::Team => teams.team_of(name) == team,
+                        Channel::Local => {
+                            let distance =
+                                (position.to_chunk().as_ivec2() - center.as_ivec2()).abs();
+                            distance.max_element() <= LOCAL_RADIUS
+                        }
+                        Channel::Staff => channel.allows(groups, permissions),
+                    };
+
+                    if !included {
+                        return;
+                    }
+
+                    if ignores.contains(sender_uuid) {
+                        ignored = true;
+                        return;
+                    }
+
+                    // the recipient acknowledges signed messages in its own messages
+                    if let Some(Err(e)) = signed
+                        .as_ref()
+                        .map(|signed| seen.add_pending(&signed.signature))
+                    {
+                        secure_chat::reject(player, &e);
+                        return;
+                    }
+
+                    recipients.push(*io);
+                });
+
+                let result = match &signed {
+                    // clients only accept signatures the server tracks for them, and a broadcast
+                    // may reach players who are not recipients, so each recipient is sent it
+                    Some(signed) => {
+                        let content =
+                            (event.message != msg).then(|| Text::from(event.message.as_str()));
+                        let chat = signed.packet(msg, content, event.sender_name(channel));
+
+                        deliver(compose, system, &chat, channel, center, false, &recipients)
+                    }
+                    None if compose.global().secure_chat => {
+                        let chat = secure_chat::unsigned_message(
+                            Text::from(event.message.as_str()),
+                            event.sender_name(channel),
+                        );
+
+                        deliver(
+                            compose,
+                            system,
+                            &chat,
+                            channel,
+                            center,
+                            !ignored,
+                            &recipients,
+                        )
+                    }
+                    None => {
+                        let chat = agnostic::chat(event.line(channel));
+
+                        deliver(
+                            compose,
+                            system,
+                            &chat,
+                            channel,
+                            center,
+                            !ignored,
+                            &recipients,
+                        )
+                    }
+                };
+
+                match result {
+                    Ok(()) => config.start_cooldown(compose, sender),
+                    Err(e) => error!("failed to send chat message: {e}"),
+                }
+            }
+        });
+    }
+}
@@ -1,7 +1,12 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{DeriveInput, Error, Ident, Lit, parse_macro_input};
+use syn::{DeriveInput, Error, Expr, ExprLit, Lit, LitStr, Token, parse_macro_input};
 
+/// Implements `CommandPermission` from `#[command_permission(group = "Moderator")]`.
+///
+/// The group, which is lowercased, has the permission node of the command by default. The node
+/// is `<crate>.command.<name>` for the name in `#[command(name = "...")]`, unless one is given
+/// with `#[command_permission(node = "...")]`.
 #[proc_macro_derive(CommandPermission, attributes(command_permission))]
 pub fn derive_command_permission(input: TokenStream) -> TokenStream {
     // Parse the input as a DeriveInput (struct or enum)
@@ -10,6 +15,9 @@ pub fn derive_command_permission(input: TokenStream) -> TokenStream {
 
     // Extract the group from the `#[command_permission(group = "Admin")]` attribute
     let mut group = None;
+    let mut node = None;
+    let mut command_name = None;
+
     for attr in &input.attrs {
         if attr.path().is_ident("command_permission") {
             if let Err(err) = attr.parse_nested_meta(|meta| {
@@ -17,6 +25,10 @@ pub fn derive_command_permission(input: TokenStream) -> TokenStream {
                     if let Ok(Lit::Str(lit)) = meta.value()?.parse::<Lit>() {
                         group = Some(lit);
                     }
+                } else if meta.path.is_ident("node") {
+                    if let Ok(Lit::Str(lit)) = meta.value()?.parse::<Lit>() {
+                        node = Some(lit);
+                    }
                 }
                 Ok(())
             }) {
@@ -25,10 +37,32 @@ pub fn derive_command_permission(input: TokenStream) -> TokenStream {
                     .into();
             }
         }
+
+        // the name clap gives the command; everything else in the attribute is left to clap
+        if attr.path().is_ident("command") {
+            let _ = attr.parse_nested_meta(|meta| {
+                if !meta.input.peek(Token![=]) {
+                    return Ok(());
+                }
+
+                let value = meta.value()?.parse::<Expr>()?;
+
+                if meta.path.is_ident("name") {
+                    if let Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit), ..
+                    }) = value
+                    {
+                        command_name = Some(lit);
+                    }
+                }
+
+                Ok(())
+            });
+        }
     }
 
-    let group_ident = match group {
-        Some(g) => Ident::new(&g.value(), g.span()),
+    let group = match group {
+        Some(g) => LitStr::new(&g.value().to_lowercase(), g.span()),
         None => {
             return Error::new_spanned(
                 input,
@@ -39,19 +73,27 @@ pub fn derive_command_permission(input: TokenStream) -> TokenStream {
         }
     };
 
+    let node = match (node, command_name) {
+        (Some(node), _) => quote! { #node },
+        (None, Some(command_name)) => quote! {
+            concat!(env!("CARGO_CRATE_NAME"), ".command.", #command_name)
+        },
+        (None, None) => {
+            return Error::new_spanned(
+                input,
+                "Missing `#[command(name = \"...\")]` or `#[command_permission(node = \"...\")]` \
+                 attribute to name the permission node after.",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
     // Generate the trait implementation
     let expanded = quote! {
         impl CommandPermission for #name {
-            fn has_required_permission(user_group: ::hyperion_permission::Group) -> bool {
-                const REQUIRED_GROUP: ::hyperion_permission::Group = ::hyperion_permission::Group::#group_ident;
-
-                if REQUIRED_GROUP == ::hyperion_permission::Group::Banned {
-                    // When checking for the group "Banned" we don't want to check for higher groups.
-                    return REQUIRED_GROUP == user_group;
-                } else {
-                    return user_group as u32 >= REQUIRED_GROUP as u32
-                }
-            }
+            const GROUP: &'static str = #group;
+            const NODE: &'static str = #node;
         }
     };
 
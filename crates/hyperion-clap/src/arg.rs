@@ -0,0 +1,540 @@
+//! Argument types with their own Brigadier parsers, so the client can check and highlight them.
+//!
+//! Numbers, booleans and strings need nothing special: a `u16` field becomes an integer between
+//! 0 and 65535, and a string with `trailing_var_arg` takes the rest of the input. The types here
+//! cover what has no Rust type of its own, such as coordinates relative to the caller.
+
+use std::{any::TypeId, fmt, str::FromStr};
+
+use clap::{Arg as ClapArg, ValueHint};
+use derive_more::{Deref, Display};
+use flecs_ecs::core::{EntityView, EntityViewGet};
+use hyperion::{
+    ItemKind,
+    glam::{IVec3, Vec3},
+    simulation::Position,
+};
+use valence_protocol::{
+    block::{BlockKind, BlockState, PropName, PropValue},
+    packets::play::command_tree_s2c::{Parser, StringArg, Suggestion},
+};
+
+/// How far from the origin a coordinate may be, the same limit vanilla uses.
+const MAX_COORDINATE: f32 = 30_000_000.0;
+
+/// One axis of a position, either absolute or relative to the caller with `~`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Coordinate {
+    Absolute(f32),
+    Relative(f32),
+}
+
+impl Coordinate {
+    /// The coordinate on the axis where `origin` is.
+    #[must_use]
+    pub fn resolve(self, origin: f32) -> f32 {
+        match self {
+            Self::Absolute(value) => value,
+            Self::Relative(offset) => origin + offset,
+        }
+    }
+
+    fn parse(word: &str) -> Result<Self, String> {
+        if word.starts_with('^') {
+            return Err("local coordinates (^) are not supported".to_owned());
+        }
+
+        let (relative, number) = word
+            .strip_prefix('~')
+            .map_or((false, word), |offset| (true, offset));
+
+        let value: f32 = if relative && number.is_empty() {
+            0.0
+        } else {
+            number
+                .parse()
+                .map_err(|_| format!("{word} is not a coordinate"))?
+        };
+
+        // `f32::from_str` accepts NaN and infinity
+        if !value.is_finite() || value.abs() > MAX_COORDINATE {
+            return Err(format!("{word} is outside of the world"));
+        }
+
+        Ok(if relative {
+            Self::Relative(value)
+        } else {
+            Self::Absolute(value)
+        })
+    }
+}
+
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absolute(value) => write!(f, "{value}"),
+            Self::Relative(offset) if *offset == 0.0 => write!(f, "~"),
+            Self::Relative(offset) => write!(f, "~{offset}"),
+        }
+    }
+}
+
+fn axes(input: &str) -> Result<[&str; 3], String> {
+    let words: Vec<_> = input.split_whitespace().collect();
+
+    words
+        .try_into()
+        .map_err(|_| format!("{input} is not three coordinates"))
+}
+
+/// A position such as `~ ~1 ~`, parsed by the client as a `vec3`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Vec3Arg {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl Vec3Arg {
+    #[must_use]
+    pub fn resolve(self, origin: Vec3) -> Vec3 {
+        Vec3::new(
+            self.x.resolve(origin.x),
+            self.y.resolve(origin.y),
+            self.z.resolve(origin.z),
+        )
+    }
+
+    /// The position relative to where `caller` is.
+    #[must_use]
+    pub fn resolve_for(self, caller: EntityView<'_>) -> Vec3 {
+        caller.get::<&Position>(|position| self.resolve(**position))
+    }
+}
+
+impl FromStr for Vec3Arg {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let [x, y, z] = axes(input)?;
+
+        // like in vanilla, whole numbers on the horizontal axes mean the center of the block
+        let centered = |word: &str| {
+            Coordinate::parse(word).map(|coordinate| match coordinate {
+                Coordinate::Absolute(value) if !word.contains('.') => {
+                    Coordinate::Absolute(value + 0.5)
+                }
+                coordinate => coordinate,
+            })
+        };
+
+        Ok(Self {
+            x: centered(x)?,
+            y: Coordinate::parse(y)?,
+            z: centered(z)?,
+        })
+    }
+}
+
+impl fmt::Display for Vec3Arg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.x, self.y, self.z)
+    }
+}
+
+/// The position of a block such as `~ ~-1 ~`, parsed by the client as a `block_pos`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlockPosArg {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl BlockPosArg {
+    /// The block relative to `origin`. Relative coordinates are taken from the exact origin and
+    /// then rounded down.
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "parsed coordinates and offsets are at most 30 million, and so is the origin"
+    )]
+    pub fn resolve(self, origin: Vec3) -> IVec3 {
+        let block = |coordinate: Coordinate, origin: f32| coordinate.resolve(origin).floor() as i32;
+
+        IVec3::new(
+            block(self.x, origin.x),
+            block(self.y, origin.y),
+            block(self.z, origin.z),
+        )
+    }
+
+    /// The block relative to where `caller` is.
+    #[must_use]
+    pub fn resolve_for(self, caller: EntityView<'_>) -> IVec3 {
+        caller.get::<&Position>(|position| self.resolve(**position))
+    }
+}
+
+impl FromStr for BlockPosArg {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let [x, y, z] = axes(input)?;
+
+        let whole = |word: &str| {
+            let coordinate = Coordinate::parse(word)?;
+
+            match coordinate {
+                Coordinate::Absolute(value) if value.fract() != 0.0 => {
+                    Err(format!("{word} is not a whole number"))
+                }
+                coordinate => Ok(coordinate),
+            }
+        };
+
+        Ok(Self {
+            x: whole(x)?,
+            y: whole(y)?,
+            z: whole(z)?,
+        })
+    }
+}
+
+impl fmt::Display for BlockPosArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.x, self.y, self.z)
+    }
+}
+
+/// The name of an online player, parsed by the client as an `entity` which may only be a single
+/// player.
+#[derive(Clone, Debug, PartialEq, Eq, Deref, Display)]
+pub struct PlayerArg(pub String);
+
+impl FromStr for PlayerArg {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.starts_with('@') {
+            return Err("selectors are not supported, use the name of a player".to_owned());
+        }
+
+        Ok(Self(input.to_owned()))
+    }
+}
+
+/// The name or UUID of any player, online or not, parsed by the client as a `game_profile`.
+#[derive(Clone, Debug, PartialEq, Eq, Deref, Display)]
+pub struct ProfileArg(pub String);
+
+impl FromStr for ProfileArg {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.starts_with('@') {
+            return Err("selectors are not supported, use a name or UUID".to_owned());
+        }
+
+        Ok(Self(input.to_owned()))
+    }
+}
+
+/// A single word, or several words in double quotes.
+#[derive(Clone, Debug, PartialEq, Eq, Deref, Display)]
+pub struct PhraseArg(pub String);
+
+impl FromStr for PhraseArg {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        // the quotes are removed when the input is split
+        Ok(Self(input.to_owned()))
+    }
+}
+
+/// An item such as `minecraft:diamond_sword`, parsed by the client as an `item_stack`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ItemArg(pub ItemKind);
+
+impl FromStr for ItemArg {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.contains('{') {
+            return Err("item data is not supported".to_owned());
+        }
+
+        let name = input.strip_prefix("minecraft:").unwrap_or(input);
+
+        ItemKind::from_str(name)
+            .map(Self)
+            .ok_or_else(|| format!("unknown item {input}"))
+    }
+}
+
+/// A block with properties such as `oak_stairs[facing=north]`, parsed by the client as a
+/// `block_state`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BlockArg(pub BlockState);
+
+impl FromStr for BlockArg {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.contains('{') {
+            return Err("block entity data is not supported".to_owned());
+        }
+
+        let (name, properties) = match input.split_once('[') {
+            Some((name, properties)) => {
+                let properties = properties
+                    .strip_suffix(']')
+                    .ok_or_else(|| format!("{input} is missing a closing ]"))?;
+                (name, properties)
+            }
+            None => (input, ""),
+        };
+
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+
+        let kind = BlockKind::from_str(name).ok_or_else(|| format!("unknown block {name}"))?;
+        let mut state = kind.to_state();
+
+        for property in properties
+            .split(',')
+            .filter(|property| !property.is_empty())
+        {
+            let invalid = || format!("{name} has no property {property}");
+
+            let (key, value) = property.split_once('=').ok_or_else(invalid)?;
+            let key = PropName::from_str(key.trim()).ok_or_else(invalid)?;
+            let value = PropValue::from_str(value.trim()).ok_or_else(invalid)?;
+
+            state = state.set(key, value);
+
+            if state.get(key) != Some(value) {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Self(state))
+    }
+}
+
+fn is<T: 'static>(arg: &ClapArg) -> bool {
+    arg.get_value_parser().type_id() == TypeId::of::<T>()
+}
+
+/// How many words of the input `arg` takes as one value.
+pub(crate) fn words(arg: &ClapArg) -> usize {
+    if is::<Vec3Arg>(arg) || is::<BlockPosArg>(arg) {
+        3
+    } else {
+        1
+    }
+}
+
+/// Lets the values of `arg` start with a `-` if they are numbers or coordinates.
+pub(crate) fn allow_negative(arg: ClapArg) -> ClapArg {
+    match parser(&arg) {
+        Parser::Integer { .. }
+        | Parser::Long { .. }
+        | Parser::Float { .. }
+        | Parser::Double { .. } => arg.allow_negative_numbers(true),
+        // the input was already split, so the value holds every axis
+        Parser::Vec3 | Parser::BlockPos => arg.allow_hyphen_values(true),
+        _ => arg,
+    }
+}
+
+/// Whether `arg` takes the rest of the input.
+pub(crate) fn is_greedy(arg: &ClapArg) -> bool {
+    arg.is_trailing_var_arg_set()
+}
+
+/// Whether `arg` may be a quoted phrase.
+pub(crate) fn is_phrase(arg: &ClapArg) -> bool {
+    is::<PhraseArg>(arg)
+}
+
+/// The Brigadier parser for `arg`, from the type of its values. Integers are limited to the range
+/// of their type.
+pub(crate) fn parser(arg: &ClapArg) -> Parser {
+    macro_rules! integers {
+        ($($ty:ty),*) => {
+            $(
+                if is::<$ty>(arg) {
+                    return Parser::Integer {
+                        min: Some(i32::from(<$ty>::MIN)),
+                        max: Some(i32::from(<$ty>::MAX)),
+                    };
+                }
+            )*
+        };
+    }
+
+    integers!(i8, u8, i16, u16);
+
+    if is::<i32>(arg) {
+        return Parser::Integer {
+            min: None,
+            max: None,
+        };
+    }
+
+    if is::<u32>(arg) {
+        return Parser::Integer {
+            min: Some(0),
+            max: None,
+        };
+    }
+
+    if is::<i64>(arg) {
+        return Parser::Long {
+            min: None,
+            max: None,
+        };
+    }
+
+    if is::<u64>(arg) || is::<usize>(arg) {
+        return Parser::Long {
+            min: Some(0),
+            max: None,
+        };
+    }
+
+    if is::<f32>(arg) {
+        return Parser::Float {
+            min: None,
+            max: None,
+        };
+    }
+
+    if is::<f64>(arg) {
+        return Parser::Double {
+            min: None,
+            max: None,
+        };
+    }
+
+    if is::<bool>(arg) {
+        return Parser::Bool;
+    }
+
+    if is::<Vec3Arg>(arg) {
+        return Parser::Vec3;
+    }
+
+    if is::<BlockPosArg>(arg) {
+        return Parser::BlockPos;
+    }
+
+    if is::<ItemArg>(arg) {
+        return Parser::ItemStack;
+    }
+
+    if is::<BlockArg>(arg) {
+        return Parser::BlockState;
+    }
+
+    if is::<ProfileArg>(arg) {
+        return Parser::GameProfile;
+    }
+
+    if is::<PlayerArg>(arg) || arg.get_value_hint() == ValueHint::Username {
+        return Parser::Entity {
+            single: true,
+            only_players: true,
+        };
+    }
+
+    if is_greedy(arg) {
+        return Parser::String(StringArg::GreedyPhrase);
+    }
+
+    if is_phrase(arg) {
+        return Parser::String(StringArg::QuotablePhrase);
+    }
+
+    Parser::String(StringArg::SingleWord)
+}
+
+/// How the client completes an argument with `parser`. Strings, such as names and the values of
+/// enums, are completed by the server, while the other parsers suggest by themselves.
+pub(crate) fn suggestion(parser: &Parser) -> Option<Suggestion> {
+    matches!(parser, Parser::String(_)).then_some(Suggestion::AskServer)
+}
+
+#[cfg(test)]
+mod tests {
+    use flecs_ecs::core::World;
+
+    use super::*;
+
+    #[test]
+    fn parses_relative_coordinates() {
+        let position: Vec3Arg = "~ ~1.5 -3".parse().unwrap();
+
+        assert_eq!(position.x, Coordinate::Relative(0.0));
+        assert_eq!(position.y, Coordinate::Relative(1.5));
+        assert_eq!(position.z, Coordinate::Absolute(-2.5));
+        assert_eq!(
+            position.resolve(Vec3::new(10.0, 64.0, 0.0)),
+            Vec3::new(10.0, 65.5, -2.5)
+        );
+
+        assert!("~ ~".parse::<Vec3Arg>().is_err());
+        assert!("^ ^ ^1".parse::<Vec3Arg>().is_err());
+        assert!("~x ~ ~".parse::<Vec3Arg>().is_err());
+    }
+
+    #[test]
+    fn block_positions_round_down() {
+        let block: BlockPosArg = "~ ~-1 5".parse().unwrap();
+
+        assert_eq!(
+            block.resolve(Vec3::new(-0.5, 64.2, 0.0)),
+            IVec3::new(-1, 63, 5)
+        );
+
+        assert!("1.5 2 3".parse::<BlockPosArg>().is_err());
+    }
+
+    #[test]
+    fn rejects_coordinates_outside_of_the_world() {
+        assert!("NaN 64 0".parse::<Vec3Arg>().is_err());
+        assert!("~inf ~ ~".parse::<Vec3Arg>().is_err());
+        assert!("0 64 30000001".parse::<BlockPosArg>().is_err());
+        assert!("~-30000000 ~ ~30000000".parse::<BlockPosArg>().is_ok());
+    }
+
+    #[test]
+    fn resolves_for_the_caller() {
+        let world = World::new();
+        let caller = world
+            .entity()
+            .set(Position::from(Vec3::new(10.5, 64.0, -3.2)));
+
+        let position: Vec3Arg = "~ ~2 7".parse().unwrap();
+        assert_eq!(position.resolve_for(caller), Vec3::new(10.5, 66.0, 7.5));
+
+        let block: BlockPosArg = "~ ~-1 ~".parse().unwrap();
+        assert_eq!(block.resolve_for(caller), IVec3::new(10, 63, -4));
+    }
+
+    #[test]
+    fn parses_blocks_and_items() {
+        let BlockArg(stairs) = "minecraft:oak_stairs[facing=north]".parse().unwrap();
+
+        assert_eq!(stairs.to_kind(), BlockKind::OakStairs);
+        assert_eq!(stairs.get(PropName::Facing), Some(PropValue::North));
+
+        assert!("stone[facing=north]".parse::<BlockArg>().is_err());
+        assert!("not_a_block".parse::<BlockArg>().is_err());
+
+        assert_eq!(
+            "diamond_sword".parse::<ItemArg>(),
+            Ok(ItemArg(ItemKind::DiamondSword))
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Turning what a player typed into the values clap parses.
+
+use clap::{Arg, ArgAction, Command, error::ErrorKind};
+
+use crate::arg;
+
+/// The words of some input, taken one at a time.
+struct Words<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Words<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.rest.split_whitespace().next()
+    }
+
+    fn word(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+
+        let (word, rest) = rest.split_at(end);
+        self.rest = rest;
+
+        (!word.is_empty()).then_some(word)
+    }
+
+    /// Up to `count` words, separated by single spaces.
+    fn words(&mut self, count: usize) -> String {
+        (0..count)
+            .map_while(|_| self.word())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// A word, or the text between double quotes with `\"` and `\\` escaped.
+    fn phrase(&mut self) -> Result<String, String> {
+        let rest = self.rest.trim_start();
+
+        let Some(quoted) = rest.strip_prefix('"') else {
+            return Ok(self.word().unwrap_or_default().to_owned());
+        };
+
+        let mut phrase = String::new();
+        let mut chars = quoted.char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &quoted[i + 1..];
+                    return Ok(phrase);
+                }
+                '\\' => match chars.next() {
+                    Some((_, escaped @ ('"' | '\\'))) => phrase.push(escaped),
+                    _ => return Err("only \\\" and \\\\ can be escaped".to_owned()),
+                },
+                c => phrase.push(c),
+            }
+        }
+
+        Err("a quoted phrase is missing its closing quote".to_owned())
+    }
+
+    fn remaining(&mut self) -> impl Iterator<Item = &'a str> {
+        std::mem::take(&mut self.rest).split_whitespace()
+    }
+}
+
+fn value(words: &mut Words<'_>, arg: &Arg) -> Result<String, String> {
+    if arg::is_phrase(arg) {
+        words.phrase()
+    } else {
+        Ok(words.words(arg::words(arg)))
+    }
+}
+
+/// The option of `command` which `word` names, such as `--ip` or `-d`.
+fn flag<'a>(command: &'a Command, word: &str) -> Option<&'a Arg> {
+    if let Some(long) = word.strip_prefix("--") {
+        let long = long.split('=').next().unwrap_or_default();
+        return command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long));
+    }
+
+    let mut short = word.strip_prefix('-')?.chars();
+
+    let (Some(short), None) = (short.next(), short.next()) else {
+        return None;
+    };
+
+    command
+        .get_arguments()
+        .find(|arg| arg.get_short() == Some(short))
+}
+
+/// Splits `input`, which starts with the name of `command`, into the values clap parses. A
+/// quoted phrase becomes one value without its quotes, coordinates become one value with a word
+/// for each axis, and a greedy argument takes every remaining word.
+pub(crate) fn split(input: &str, command: &Command) -> Result<Vec<String>, String> {
+    let mut words = Words { rest: input };
+    let mut values: Vec<String> = words.word().map(str::to_owned).into_iter().collect();
+
+    let mut command = command;
+    let mut positionals: Vec<_> = command.get_positionals().collect();
+    let mut next = 0;
+
+    while let Some(word) = words.peek() {
+        if let Some(subcommand) = command.find_subcommand(word) {
+            values.extend(words.word().map(str::to_owned));
+
+            command = subcommand;
+            positionals = command.get_positionals().collect();
+            next = 0;
+            continue;
+        }
+
+        if let Some(flag) = flag(command, word) {
+            values.extend(words.word().map(str::to_owned));
+
+            if flag.get_action().takes_values() && !word.contains('=') {
+                values.push(value(&mut words, flag)?);
+            }
+
+            continue;
+        }
+
+        let Some(arg) = positionals.get(next) else {
+            // more than the command takes, which clap reports
+            values.extend(words.word().map(str::to_owned));
+            continue;
+        };
+
+        if arg::is_greedy(arg) {
+            values.extend(words.remaining().map(str::to_owned));
+            break;
+        }
+
+        values.push(value(&mut words, arg)?);
+
+        if !matches!(arg.get_action(), ArgAction::Append) {
+            next += 1;
+        }
+    }
+
+    Ok(values)
+}
+
+/// `command` with every negative number and coordinate allowed to start with a `-`.
+fn allow_negative(command: Command) -> Command {
+    let subcommands: Vec<_> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect();
+
+    subcommands.into_iter().fold(
+        command.mut_args(arg::allow_negative),
+        |command, subcommand| command.mut_subcommand(subcommand, allow_negative),
+    )
+}
+
+/// Parses `input`, which starts with the name of the command.
+pub(crate) fn parse<T: clap::Parser>(input: &str) -> Result<T, clap::Error> {
+    let mut command = allow_negative(T::command());
+
+    let values = match split(input, &command) {
+        Ok(values) => values,
+        Err(e) => return Err(command.error(ErrorKind::InvalidValue, e)),
+    };
+
+    let matches = command.try_get_matches_from(values)?;
+    T::from_arg_matches(&matches)
+}
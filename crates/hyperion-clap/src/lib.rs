@@ -7,21 +7,28 @@ use flecs_ecs::{
 };
 use hyperion::{
     net::{Compose, ConnectionId, DataBundle, agnostic},
-    simulation::{IgnMap, command::get_root_command_entity, handlers::PacketSwitchQuery},
+    simulation::{command::get_root_command_entity, handlers::PacketSwitchQuery},
     storage::{CommandCompletionRequest, EventFn},
 };
 pub use hyperion_clap_macros::CommandPermission;
 pub use hyperion_command;
 use hyperion_command::{CommandHandler, CommandRegistry};
-use hyperion_permission::Group;
+use hyperion_permission::{Groups, has_permission};
 use valence_protocol::{
     VarInt,
-    packets::{
-        play,
-        play::{command_suggestions_s2c::CommandSuggestionsMatch, command_tree_s2c::StringArg},
-    },
+    packets::{play, play::command_suggestions_s2c::CommandSuggestionsMatch},
 };
 
+pub mod arg;
+mod input;
+mod permission;
+pub mod tree;
+
+pub use arg::{
+    BlockArg, BlockPosArg, Coordinate, ItemArg, PhraseArg, PlayerArg, ProfileArg, Vec3Arg,
+};
+pub use permission::PermissionCommand;
+
 pub trait MinecraftCommand: Parser + CommandPermission {
     fn execute(self, system: EntityView<'_>, caller: Entity);
 
@@ -33,58 +40,32 @@ pub trait MinecraftCommand: Parser + CommandPermission {
         let cmd = Self::command();
         let name = cmd.get_name();
 
-        let has_permissions = |world: &World, caller: Entity| {
-            caller
-                .entity_view(world)
-                .get::<&Group>(|group| Self::has_required_permission(*group))
-        };
-
-        let node_to_register =
-            hyperion::simulation::command::Command::literal(name, has_permissions);
-
-        let mut on = world
-            .entity()
-            .set(node_to_register)
-            .child_of_id(get_root_command_entity());
+        world.get::<&mut Groups>(|groups| groups.grant_default(Self::GROUP, Self::NODE));
 
-        for arg in cmd.get_arguments() {
-            use valence_protocol::packets::play::command_tree_s2c::Parser as ValenceParser;
-            let name = arg.get_value_names().unwrap().first().unwrap();
-            let name = name.to_ascii_lowercase();
-            let node_to_register = hyperion::simulation::command::Command::argument(
-                name,
-                ValenceParser::String(StringArg::SingleWord),
-            );
+        let has_permissions =
+            |world: &World, caller: Entity| has_permission(world, caller, Self::NODE);
 
-            on = world.entity().set(node_to_register).child_of_id(on);
-        }
+        tree::add(world, get_root_command_entity(), &cmd, has_permissions);
 
         let on_execute = |input: &str, system: EntityView<'_>, caller: Entity| {
-            let input = input.split_whitespace();
             let world = system.world();
 
-            match Self::try_parse_from(input) {
+            match input::parse::<Self>(input) {
                 Ok(elem) => {
-                    if world.get::<&Compose>(|compose| {
-                        caller.entity_view(world).get::<(&ConnectionId, &Group)>(
-                            |(stream, group)| {
-                                if Self::has_required_permission(*group) {
-                                    true
-                                } else {
-                                    let chat = agnostic::chat(
-                                        "§cYou do not have permission to use this command!",
-                                    );
-
-                                    let mut bundle = DataBundle::new(compose, system);
-                                    bundle.add_packet(&chat).unwrap();
-                                    bundle.unicast(*stream).unwrap();
-
-                                    false
-                                }
-                            },
-                        )
-                    }) {
+                    if has_permission(&world, caller, Self::NODE) {
                         elem.execute(system, caller);
+                    } else {
+                        world.get::<&Compose>(|compose| {
+                            caller.entity_view(world).get::<&ConnectionId>(|stream| {
+                                let chat = agnostic::chat(
+                                    "§cYou do not have permission to use this command!",
+                                );
+
+                                let mut bundle = DataBundle::new(compose, system);
+                                bundle.add_packet(&chat).unwrap();
+                                bundle.unicast(*stream).unwrap();
+                            });
+                        });
                     }
                 }
                 Err(e) => {
@@ -111,7 +92,7 @@ pub trait MinecraftCommand: Parser + CommandPermission {
             };
         };
 
-        let on_tab_complete: EventFn<CommandCompletionRequest<'static>> = Box::new(
+        let on_tab_complete =
             |packet_switch_query: &mut PacketSwitchQuery<'_>,
              completion: &CommandCompletionRequest<'_>| {
                 let full_query = completion.query;
@@ -226,18 +207,23 @@ pub trait MinecraftCommand: Parser + CommandPermission {
                         packet_switch_query.system,
                     )
                     .unwrap();
-            },
-        );
+            };
 
-        let handler = CommandHandler {
-            on_execute,
-            on_tab_complete,
-            has_permissions,
-        };
+        // aliases run the same way, as the name of the command is skipped when parsing
+        for name in std::iter::once(name).chain(cmd.get_all_aliases()) {
+            let on_tab_complete: EventFn<CommandCompletionRequest<'static>> =
+                Box::new(on_tab_complete);
 
-        tracing::info!("registering command {name}");
+            let handler = CommandHandler {
+                on_execute,
+                on_tab_complete,
+                permission: Self::NODE,
+            };
+
+            tracing::info!("registering command {name}");
 
-        registry.register(name, handler);
+            registry.register(name, handler);
+        }
     }
 }
 
@@ -261,7 +247,10 @@ impl MinecraftArg for ClapArg {
 }
 
 pub trait CommandPermission {
-    fn has_required_permission(user_group: hyperion_permission::Group) -> bool;
+    /// The group which may use the command unless it is changed in game.
+    const GROUP: &'static str;
+    /// The permission node needed to use the command.
+    const NODE: &'static str;
 }
 
 #[derive(Clone, Debug, ValueEnum, PartialEq, Eq)]
@@ -275,88 +264,6 @@ pub enum GameMode {
 #[derive(Component)]
 pub struct ClapCommandModule;
 
-#[derive(clap::Parser, Debug)]
-pub struct SetCommand {
-    player: String,
-    group: Group,
-}
-
-#[derive(clap::Parser, Debug)]
-pub struct GetCommand {
-    player: String,
-}
-
-#[derive(Parser, CommandPermission, Debug)]
-#[command(name = "perms")]
-#[command_permission(group = "Normal")]
-pub enum PermissionCommand {
-    Set(SetCommand),
-    Get(GetCommand),
-}
-
-impl MinecraftCommand for PermissionCommand {
-    fn execute(self, system: EntityView<'_>, caller: Entity) {
-        let world = system.world();
-        world.get::<&IgnMap>(|ign_map| {
-            match self {
-                Self::Set(cmd) => {
-                    // Handle setting permissions
-                    let Some(entity) = ign_map.get(cmd.player.as_str()) else {
-                        caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                            let msg = format!("§c{} not found", cmd.player);
-                            let chat = hyperion::net::agnostic::chat(msg);
-                            world.get::<&Compose>(|compose| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                        });
-                        return;
-                    };
-
-                    entity.entity_view(world).get::<&mut Group>(|group| {
-                        if *group != cmd.group {
-                            *group = cmd.group;
-                            entity.entity_view(world).modified::<Group>();
-                        }
-
-                        caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                            let msg = format!(
-                                "§b{}§r's group has been set to §e{:?}",
-                                cmd.player, cmd.group
-                            );
-                            let chat = hyperion::net::agnostic::chat(msg);
-                            world.get::<&Compose>(|compose| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                        });
-                    });
-                }
-                Self::Get(cmd) => {
-                    let Some(entity) = ign_map.get(cmd.player.as_str()) else {
-                        caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                            let msg = format!("§c{} not found", cmd.player);
-                            let chat = hyperion::net::agnostic::chat(msg);
-                            world.get::<&Compose>(|compose| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                        });
-                        return;
-                    };
-
-                    entity.entity_view(world).get::<&Group>(|group| {
-                        caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                            let msg = format!("§b{}§r's group is §e{:?}", cmd.player, group);
-                            let chat = hyperion::net::agnostic::chat(msg);
-                            world.get::<&Compose>(|compose| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                        });
-                    });
-                }
-            }
-        });
-    }
-}
-
 impl Module for ClapCommandModule {
     fn module(world: &World) {
         world.import::<hyperion_command::CommandModule>();
@@ -0,0 +1,474 @@
+use clap::{Parser, Subcommand};
+use flecs_ecs::{
+    core::{Entity, EntityView, EntityViewGet, WorldGet, WorldProvider},
+    prelude::*,
+};
+use hyperion::{
+    net::{Compose, ConnectionId, agnostic},
+    simulation::IgnMap,
+};
+use hyperion_permission::{
+    Groups, PermissionGroup, PermissionStorage, Permissions, has_permission, node,
+};
+
+use crate::{CommandPermission, MinecraftCommand, PlayerArg};
+
+fn tell(system: EntityView<'_>, caller: Entity, message: impl Into<String>) {
+    let world = system.world();
+    let chat = agnostic::chat(message);
+
+    caller.entity_view(world).get::<&ConnectionId>(|stream| {
+        world.get::<&Compose>(|compose| {
+            compose.unicast(&chat, *stream, system).unwrap();
+        });
+    });
+}
+
+/// Whether `node` can be given or taken away, which it cannot if it already starts with `-`.
+fn is_valid_node(node: &str) -> bool {
+    !node.starts_with('-') && node::is_valid(node)
+}
+
+fn is_valid_group(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-'))
+}
+
+/// The online player called `player`, telling `caller` if there is none.
+fn find_player<'a>(system: EntityView<'a>, caller: Entity, player: &str) -> Option<EntityView<'a>> {
+    let world = system.world();
+
+    let entity = world.get::<&IgnMap>(|ign_map| ign_map.get(player).copied());
+
+    if entity.is_none() {
+        tell(system, caller, format!("§c{player} not found"));
+    }
+
+    entity.map(|entity| entity.entity_view(world))
+}
+
+/// Whether there is a group called `group`, telling `caller` if there is not.
+fn group_exists(system: EntityView<'_>, caller: Entity, group: &str) -> bool {
+    let exists = system
+        .world()
+        .get::<&Groups>(|groups| groups.get(group).is_some());
+
+    if !exists {
+        tell(
+            system,
+            caller,
+            format!("§cThere is no group called {group}"),
+        );
+    }
+
+    exists
+}
+
+/// Changes the [`Permissions`] of the online player called `player` with `change`, which
+/// returns what `caller` is told.
+fn edit_player(
+    system: EntityView<'_>,
+    caller: Entity,
+    player: &str,
+    change: impl FnOnce(&mut Permissions) -> String,
+) {
+    let Some(entity) = find_player(system, caller, player) else {
+        return;
+    };
+
+    let message = entity.get::<&mut Permissions>(change);
+
+    // sends the player the commands they may use now
+    entity.modified::<Permissions>();
+
+    tell(system, caller, message);
+}
+
+/// Stores the group called `name` as it is now.
+fn store_group(system: EntityView<'_>, name: &str) {
+    let world = system.world();
+
+    let Some(group) = world.get::<&Groups>(|groups| groups.get(name).cloned()) else {
+        return;
+    };
+
+    if let Err(e) = world.get::<&PermissionStorage>(|storage| storage.set_group(name, &group)) {
+        tracing::error!("failed to store group {name}: {e}");
+    }
+}
+
+/// Changes the group called `name` with `change`, which returns what `caller` is told, and
+/// stores it.
+fn edit_group(
+    system: EntityView<'_>,
+    caller: Entity,
+    name: &str,
+    change: impl FnOnce(&mut PermissionGroup) -> String,
+) {
+    let message = system
+        .world()
+        .get::<&mut Groups>(|groups| groups.get_mut(name).map(change));
+
+    let Some(message) = message else {
+        tell(system, caller, format!("§cThere is no group called {name}"));
+        return;
+    };
+
+    store_group(system, name);
+    tell(system, caller, message);
+}
+
+/// What a change to a node is called in messages.
+fn describe(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "given",
+        Some(false) => "denied",
+        None => "unset",
+    }
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct SetCommand {
+    player: PlayerArg,
+    group: String,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct GetCommand {
+    player: PlayerArg,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct PlayerNodeCommand {
+    player: PlayerArg,
+    node: String,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct GroupNameCommand {
+    group: String,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct CreateGroupCommand {
+    group: String,
+    /// The groups the new group inherits from.
+    parents: Vec<String>,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct GroupNodeCommand {
+    group: String,
+    node: String,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct GroupParentCommand {
+    group: String,
+    parent: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GroupCommand {
+    /// List every group
+    List,
+    /// Show the parents and nodes of a group
+    Info(GroupNameCommand),
+    /// Add a group
+    Create(CreateGroupCommand),
+    /// Remove a group
+    Delete(GroupNameCommand),
+    /// Give a node to a group
+    Allow(GroupNodeCommand),
+    /// Take a node away from a group
+    Deny(GroupNodeCommand),
+    /// Leave a node of a group to its parents
+    Unset(GroupNodeCommand),
+    /// Make a group inherit from another
+    AddParent(GroupParentCommand),
+    /// Stop a group inheriting from another
+    RemoveParent(GroupParentCommand),
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "perms")]
+#[command(about = "Manage permission groups and the permissions of players")]
+#[command_permission(group = "Admin")]
+pub enum PermissionCommand {
+    /// Put a player in one group only
+    Set(SetCommand),
+    /// Show the groups and nodes of a player
+    Get(GetCommand),
+    /// Add a player to a group
+    Add(SetCommand),
+    /// Remove a player from a group
+    Remove(SetCommand),
+    /// Give a node to a player
+    Allow(PlayerNodeCommand),
+    /// Take a node away from a player
+    Deny(PlayerNodeCommand),
+    /// Leave a node of a player to their groups
+    Unset(PlayerNodeCommand),
+    /// Check whether a player has a node
+    Check(PlayerNodeCommand),
+    #[command(subcommand)]
+    Group(GroupCommand),
+}
+
+impl MinecraftCommand for PermissionCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        match self {
+            Self::Set(cmd) => {
+                if !group_exists(system, caller, &cmd.group) {
+                    return;
+                }
+
+                edit_player(system, caller, &cmd.player, |permissions| {
+                    permissions.groups = vec![cmd.group.clone()];
+                    format!("§b{}§r's group has been set to §e{}", cmd.player, cmd.group)
+                });
+            }
+            Self::Add(cmd) => {
+                if !group_exists(system, caller, &cmd.group) {
+                    return;
+                }
+
+                edit_player(system, caller, &cmd.player, |permissions| {
+                    if permissions.groups.contains(&cmd.group) {
+                        return format!("§c{} is already in {}", cmd.player, cmd.group);
+                    }
+
+                    permissions.groups.push(cmd.group.clone());
+                    format!("§b{}§r was added to §e{}", cmd.player, cmd.group)
+                });
+            }
+            Self::Remove(cmd) => edit_player(system, caller, &cmd.player, |permissions| {
+                if !permissions.groups.contains(&cmd.group) {
+                    return format!("§c{} is not in {}", cmd.player, cmd.group);
+                }
+
+                permissions.groups.retain(|group| *group != cmd.group);
+                format!("§b{}§r was removed from §e{}", cmd.player, cmd.group)
+            }),
+            Self::Get(cmd) => {
+                let Some(entity) = find_player(system, caller, &cmd.player) else {
+                    return;
+                };
+
+                let message = entity.get::<&Permissions>(|permissions| {
+                    let groups = permissions.groups().collect::<Vec<_>>().join(", ");
+                    let nodes = if permissions.nodes.is_empty() {
+                        "none".to_owned()
+                    } else {
+                        permissions.nodes.join(", ")
+                    };
+
+                    format!(
+                        "§b{}§r's groups are §e{groups}§r and their own nodes are §e{nodes}",
+                        cmd.player
+                    )
+                });
+
+                tell(system, caller, message);
+            }
+            Self::Allow(cmd) => set_player_node(system, caller, &cmd, Some(true)),
+            Self::Deny(cmd) => set_player_node(system, caller, &cmd, Some(false)),
+            Self::Unset(cmd) => set_player_node(system, caller, &cmd, None),
+            Self::Check(cmd) => {
+                let Some(entity) = find_player(system, caller, &cmd.player) else {
+                    return;
+                };
+
+                let verdict = if has_permission(&system.world(), *entity, &cmd.node) {
+                    "§ahas"
+                } else {
+                    "§cdoes not have"
+                };
+
+                tell(
+                    system,
+                    caller,
+                    format!("§b{} {verdict}§r §e{}", cmd.player, cmd.node),
+                );
+            }
+            Self::Group(cmd) => cmd.execute(system, caller),
+        }
+    }
+}
+
+fn set_player_node(
+    system: EntityView<'_>,
+    caller: Entity,
+    cmd: &PlayerNodeCommand,
+    value: Option<bool>,
+) {
+    if !is_valid_node(&cmd.node) {
+        tell(
+            system,
+            caller,
+            format!("§c{} is not a valid node", cmd.node),
+        );
+        return;
+    }
+
+    edit_player(system, caller, &cmd.player, |permissions| {
+        if node::set(&mut permissions.nodes, &cmd.node, value) {
+            format!(
+                "§e{}§r was {} for §b{}",
+                cmd.node,
+                describe(value),
+                cmd.player
+            )
+        } else {
+            format!("§c{} has no node {}", cmd.player, cmd.node)
+        }
+    });
+}
+
+fn set_group_node(
+    system: EntityView<'_>,
+    caller: Entity,
+    cmd: &GroupNodeCommand,
+    value: Option<bool>,
+) {
+    if !is_valid_node(&cmd.node) {
+        tell(
+            system,
+            caller,
+            format!("§c{} is not a valid node", cmd.node),
+        );
+        return;
+    }
+
+    edit_group(system, caller, &cmd.group, |group| {
+        if node::set(&mut group.nodes, &cmd.node, value) {
+            format!(
+                "§e{}§r was {} for §b{}",
+                cmd.node,
+                describe(value),
+                cmd.group
+            )
+        } else {
+            format!("§c{} has no node {}", cmd.group, cmd.node)
+        }
+    });
+}
+
+impl GroupCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+
+        match self {
+            Self::List => {
+                let names =
+                    world.get::<&Groups>(|groups| groups.names().collect::<Vec<_>>().join(", "));
+                tell(system, caller, format!("Groups: §e{names}"));
+            }
+            Self::Info(cmd) => {
+                let info = world.get::<&Groups>(|groups| {
+                    let group = groups.get(&cmd.group)?;
+                    let nodes = group
+                        .nodes
+                        .iter()
+                        .chain(&group.defaults)
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    Some(format!(
+                        "§b{}§r inherits from §e{}§r and has the nodes §e{}",
+                        cmd.group,
+                        group.parents.join(", "),
+                        nodes.join(", ")
+                    ))
+                });
+
+                let message =
+                    info.unwrap_or_else(|| format!("§cThere is no group called {}", cmd.group));
+                tell(system, caller, message);
+            }
+            Self::Create(cmd) => {
+                if !is_valid_group(&cmd.group) {
+                    tell(
+                        system,
+                        caller,
+                        format!("§c{} is not a valid group name", cmd.group),
+                    );
+                    return;
+                }
+
+                let created = world.get::<&mut Groups>(|groups| {
+                    if let Some(missing) = cmd.parents.iter().find(|p| groups.get(p).is_none()) {
+                        return Err(format!("§cThere is no group called {missing}"));
+                    }
+
+                    if !groups.create(&cmd.group, cmd.parents) {
+                        return Err(format!("§c{} already exists", cmd.group));
+                    }
+
+                    Ok(())
+                });
+
+                if let Err(message) = created {
+                    tell(system, caller, message);
+                    return;
+                }
+
+                store_group(system, &cmd.group);
+                tell(system, caller, format!("§b{}§r was created", cmd.group));
+            }
+            Self::Delete(cmd) => {
+                if world
+                    .get::<&mut Groups>(|groups| groups.remove(&cmd.group))
+                    .is_none()
+                {
+                    tell(
+                        system,
+                        caller,
+                        format!("§c{} does not exist or cannot be deleted", cmd.group),
+                    );
+                    return;
+                }
+
+                if let Err(e) =
+                    world.get::<&PermissionStorage>(|storage| storage.remove_group(&cmd.group))
+                {
+                    tracing::error!("failed to remove group {}: {e}", cmd.group);
+                }
+
+                tell(system, caller, format!("§b{}§r was deleted", cmd.group));
+            }
+            Self::Allow(cmd) => set_group_node(system, caller, &cmd, Some(true)),
+            Self::Deny(cmd) => set_group_node(system, caller, &cmd, Some(false)),
+            Self::Unset(cmd) => set_group_node(system, caller, &cmd, None),
+            Self::AddParent(cmd) => {
+                if world.get::<&Groups>(|groups| groups.get(&cmd.parent).is_none()) {
+                    tell(
+                        system,
+                        caller,
+                        format!("§cThere is no group called {}", cmd.parent),
+                    );
+                    return;
+                }
+
+                edit_group(system, caller, &cmd.group, |group| {
+                    if cmd.parent == cmd.group || group.parents.contains(&cmd.parent) {
+                        return format!("§c{} cannot inherit from {}", cmd.group, cmd.parent);
+                    }
+
+                    group.parents.push(cmd.parent.clone());
+                    format!("§b{}§r now inherits from §e{}", cmd.group, cmd.parent)
+                });
+            }
+            Self::RemoveParent(cmd) => edit_group(system, caller, &cmd.group, |group| {
+                if !group.parents.contains(&cmd.parent) {
+                    return format!("§c{} does not inherit from {}", cmd.group, cmd.parent);
+                }
+
+                group.parents.retain(|parent| *parent != cmd.parent);
+                format!("§b{}§r no longer inherits from §e{}", cmd.group, cmd.parent)
+            }),
+        }
+    }
+}
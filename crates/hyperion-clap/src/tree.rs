@@ -0,0 +1,274 @@
+//! Turning clap commands into nodes of the Brigadier command tree the client is sent.
+//!
+//! A command becomes a literal with its subcommands as literals below it and its positional
+//! arguments as a chain of argument nodes. A node is executable if the input may end there,
+//! which is when every argument after it is optional. Aliases are literals which redirect to the
+//! node they stand for.
+//!
+//! Options such as `-d 10m` or `--ip` can follow the literal and every positional argument except
+//! one taking the rest of the input. Each option is a literal for its long and short name, with
+//! an argument node below it if it takes a value, and redirects back to where it was given so the
+//! remaining arguments and options can follow.
+//!
+//! Only the option given last is known at a node, so a command with more than one required option
+//! is never shown as complete, and neither is one whose required option is followed by a
+//! positional argument. The server still runs such commands.
+
+use clap::ArgAction;
+use flecs_ecs::core::{Entity, IdOperations, World};
+use hyperion::simulation::command::Command;
+
+use crate::arg;
+
+/// The arguments of `command` which are given with `-s` or `--long`, without the ones clap adds
+/// for help and the version.
+fn options(command: &clap::Command) -> impl Iterator<Item = &clap::Arg> {
+    command.get_arguments().filter(|arg| {
+        !arg.is_positional()
+            && !matches!(
+                arg.get_action(),
+                ArgAction::Help | ArgAction::HelpShort | ArgAction::HelpLong | ArgAction::Version
+            )
+    })
+}
+
+/// Whether `command` may run with nothing after the arguments taken so far, given the
+/// positional arguments from `next` on are still to come and `given` is the option which was
+/// just given, if any.
+fn executable_from(command: &clap::Command, next: usize, given: Option<&clap::Arg>) -> bool {
+    !command.is_subcommand_required_set()
+        && command
+            .get_positionals()
+            .skip(next)
+            .all(|arg| !arg.is_required_set())
+        && options(command).all(|option| {
+            !option.is_required_set()
+                || given.is_some_and(|given| given.get_id() == option.get_id())
+        })
+}
+
+/// Adds the options of `command` below `node`, after the first `next` positional arguments.
+fn add_options(world: &World, node: Entity, command: &clap::Command, next: usize) {
+    for option in options(command) {
+        let executable = executable_from(command, next, Some(option));
+
+        let mut names = option
+            .get_long()
+            .map(|long| format!("--{long}"))
+            .into_iter()
+            .chain(option.get_short().map(|short| format!("-{short}")));
+
+        let Some(first) = names.next() else {
+            continue;
+        };
+
+        // a flag is complete by itself
+        if !option.get_action().takes_values() {
+            for name in std::iter::once(first).chain(names) {
+                let flag = Command::literal(name, |_: _, _: _| true)
+                    .with_executable(executable)
+                    .with_redirect(node);
+
+                world.entity().set(flag).child_of_id(node);
+            }
+
+            continue;
+        }
+
+        let literal = world
+            .entity()
+            .set(Command::literal(first, |_: _, _: _| true).with_executable(false))
+            .child_of_id(node)
+            .id();
+
+        let name = option
+            .get_value_names()
+            .and_then(|names| names.first())
+            .map_or_else(|| option.get_id().to_string(), ToString::to_string)
+            .to_ascii_lowercase();
+
+        let parser = arg::parser(option);
+        let suggestion = arg::suggestion(&parser);
+
+        let value = Command::argument(name, parser)
+            .with_suggestion(suggestion)
+            .with_executable(executable)
+            .with_redirect(node);
+
+        world.entity().set(value).child_of_id(literal);
+
+        // the short name stands for the long one
+        for name in names {
+            let short = Command::literal(name, |_: _, _: _| true)
+                .with_executable(false)
+                .with_redirect(literal);
+
+            world.entity().set(short).child_of_id(node);
+        }
+    }
+}
+
+/// Adds the subcommands and positional arguments of `command` below `node`.
+fn add_children(world: &World, node: Entity, command: &clap::Command) {
+    for subcommand in command.get_subcommands() {
+        add(world, node, subcommand, |_: _, _: _| true);
+    }
+
+    add_options(world, node, command, 0);
+
+    let mut on = node;
+
+    for (i, arg) in command.get_positionals().enumerate() {
+        let name = arg.get_value_names().unwrap().first().unwrap();
+        let name = name.to_ascii_lowercase();
+
+        let parser = arg::parser(arg);
+        let suggestion = arg::suggestion(&parser);
+
+        let node = Command::argument(name, parser)
+            .with_suggestion(suggestion)
+            .with_executable(executable_from(command, i + 1, None));
+
+        on = world.entity().set(node).child_of_id(on).id();
+
+        // nothing can follow the rest of the input
+        if arg::is_greedy(arg) {
+            break;
+        }
+
+        add_options(world, on, command, i + 1);
+    }
+}
+
+/// Adds `command` below `parent`, which is usually the root of the command tree, returning its
+/// literal. Only players for whom `has_permission` is true are sent the command.
+pub fn add(
+    world: &World,
+    parent: Entity,
+    command: &clap::Command,
+    has_permission: fn(world: &World, caller: Entity) -> bool,
+) -> Entity {
+    let executable = executable_from(command, 0, None);
+
+    let literal = world
+        .entity()
+        .set(Command::literal(command.get_name(), has_permission).with_executable(executable))
+        .child_of_id(parent)
+        .id();
+
+    add_children(world, literal, command);
+
+    for alias in command.get_all_aliases() {
+        world
+            .entity()
+            .set(
+                Command::literal(alias, has_permission)
+                    .with_executable(executable)
+                    .with_redirect(literal),
+            )
+            .child_of_id(parent);
+    }
+
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+    use hyperion::simulation::command::get_command_packet;
+    use valence_protocol::packets::play::{CommandTreeS2c, command_tree_s2c::NodeData};
+
+    use super::*;
+
+    #[derive(clap::Parser, Debug)]
+    #[command(name = "mute")]
+    struct Mute {
+        player: String,
+        #[arg(short, long)]
+        duration: Option<u32>,
+        #[arg(long)]
+        ip: bool,
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        reason: Vec<String>,
+    }
+
+    #[derive(clap::Parser, Debug)]
+    #[command(name = "give")]
+    struct Give {
+        #[arg(long)]
+        amount: u32,
+    }
+
+    fn tree(command: &clap::Command) -> CommandTreeS2c {
+        let world = World::new();
+        world.component::<Command>();
+
+        let root = world.entity().id();
+        add(&world, root, command, |_: _, _: _| true);
+
+        get_command_packet(&world, root, None)
+    }
+
+    /// The index of the child of `parent` called `name`.
+    fn child(tree: &CommandTreeS2c, parent: usize, name: &str) -> usize {
+        tree.commands[parent]
+            .children
+            .iter()
+            .map(|idx| usize::try_from(idx.0).unwrap())
+            .find(|&idx| match &tree.commands[idx].data {
+                NodeData::Literal { name: found } | NodeData::Argument { name: found, .. } => {
+                    found == name
+                }
+                NodeData::Root => false,
+            })
+            .unwrap_or_else(|| panic!("{name} is not below {parent}"))
+    }
+
+    fn redirect(tree: &CommandTreeS2c, idx: usize) -> Option<usize> {
+        tree.commands[idx]
+            .redirect_node
+            .map(|target| usize::try_from(target.0).unwrap())
+    }
+
+    #[test]
+    fn options_follow_arguments() {
+        let tree = tree(&Mute::command());
+
+        let mute = child(&tree, 0, "mute");
+        let player = child(&tree, mute, "player");
+        assert!(tree.commands[player].executable);
+
+        for node in [mute, player] {
+            let duration = child(&tree, node, "--duration");
+            assert!(!tree.commands[duration].executable);
+
+            let value = child(&tree, duration, "duration");
+            assert_eq!(redirect(&tree, value), Some(node));
+
+            let short = child(&tree, node, "-d");
+            assert_eq!(redirect(&tree, short), Some(duration));
+
+            let ip = child(&tree, node, "--ip");
+            assert_eq!(redirect(&tree, ip), Some(node));
+        }
+
+        // the executable flags follow the positional arguments
+        assert!(!tree.commands[child(&tree, mute, "--ip")].executable);
+        assert!(tree.commands[child(&tree, player, "--ip")].executable);
+
+        // nothing follows the reason
+        let reason = child(&tree, player, "reason");
+        assert!(tree.commands[reason].children.is_empty());
+    }
+
+    #[test]
+    fn required_options_must_be_given() {
+        let tree = tree(&Give::command());
+
+        let give = child(&tree, 0, "give");
+        assert!(!tree.commands[give].executable);
+
+        let amount = child(&tree, give, "--amount");
+        assert!(tree.commands[child(&tree, amount, "amount")].executable);
+    }
+}
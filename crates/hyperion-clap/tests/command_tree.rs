@@ -0,0 +1,161 @@
+use clap::{CommandFactory, Parser, Subcommand};
+use flecs_ecs::core::{Entity, IdOperations, World};
+use hyperion::simulation::command::{Command, get_command_packet};
+use hyperion_clap::ItemArg;
+use valence_protocol::{
+    VarInt,
+    packets::play::{
+        CommandTreeS2c,
+        command_tree_s2c::{Node, NodeData, Parser as BrigadierParser, StringArg, Suggestion},
+    },
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "give", alias = "g")]
+struct Give {
+    item: ItemArg,
+    count: Option<u8>,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "group")]
+struct Group {
+    #[command(subcommand)]
+    command: GroupCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum GroupCommand {
+    List,
+    Create {
+        name: String,
+        #[arg(trailing_var_arg = true)]
+        parents: Vec<String>,
+    },
+    #[command(alias = "rm")]
+    Remove {
+        name: String,
+    },
+}
+
+fn tree(command: &clap::Command) -> CommandTreeS2c {
+    let world = World::new();
+    world.component::<Command>();
+
+    let root = world.entity().id();
+    hyperion_clap::tree::add(&world, root, command, |_: &World, _: Entity| true);
+
+    get_command_packet(&world, root, None)
+}
+
+fn name(node: &Node) -> &str {
+    match &node.data {
+        NodeData::Root => "",
+        NodeData::Literal { name } | NodeData::Argument { name, .. } => name,
+    }
+}
+
+/// The index of the child of `parent` called `child`.
+fn child(tree: &CommandTreeS2c, parent: usize, child: &str) -> usize {
+    tree.commands[parent]
+        .children
+        .iter()
+        .map(|VarInt(index)| usize::try_from(*index).unwrap())
+        .find(|index| name(&tree.commands[*index]) == child)
+        .unwrap_or_else(|| panic!("{child} is not a child of {}", name(&tree.commands[parent])))
+}
+
+fn parser(node: &Node) -> &BrigadierParser {
+    match &node.data {
+        NodeData::Argument { parser, .. } => parser,
+        _ => panic!("{} is not an argument", name(node)),
+    }
+}
+
+fn suggestion(node: &Node) -> Option<&Suggestion> {
+    match &node.data {
+        NodeData::Argument { suggestion, .. } => suggestion.as_ref(),
+        _ => panic!("{} is not an argument", name(node)),
+    }
+}
+
+#[test]
+fn optional_arguments_are_executable() {
+    let tree = tree(&Give::command());
+
+    let give = child(&tree, 0, "give");
+    let item = child(&tree, give, "item");
+    let count = child(&tree, item, "count");
+
+    assert!(!tree.commands[give].executable);
+    assert!(tree.commands[item].executable);
+    assert!(tree.commands[count].executable);
+
+    assert_eq!(parser(&tree.commands[item]), &BrigadierParser::ItemStack);
+    assert_eq!(parser(&tree.commands[count]), &BrigadierParser::Integer {
+        min: Some(0),
+        max: Some(255),
+    });
+
+    // the client knows items and numbers by itself
+    assert_eq!(suggestion(&tree.commands[item]), None);
+    assert_eq!(suggestion(&tree.commands[count]), None);
+}
+
+#[test]
+fn aliases_redirect() {
+    let tree = tree(&Give::command());
+
+    let give = child(&tree, 0, "give");
+    let alias = child(&tree, 0, "g");
+
+    assert_eq!(
+        tree.commands[alias].redirect_node,
+        Some(VarInt(i32::try_from(give).unwrap()))
+    );
+    assert!(tree.commands[alias].children.is_empty());
+    assert_eq!(
+        tree.commands[alias].executable,
+        tree.commands[give].executable
+    );
+}
+
+#[test]
+fn subcommands_are_literals() {
+    let tree = tree(&Group::command());
+
+    let group = child(&tree, 0, "group");
+    assert!(!tree.commands[group].executable);
+    assert_eq!(tree.commands[group].children.len(), 4);
+
+    let list = child(&tree, group, "list");
+    assert!(tree.commands[list].executable);
+    assert!(tree.commands[list].children.is_empty());
+
+    let create = child(&tree, group, "create");
+    let name = child(&tree, create, "name");
+    let parents = child(&tree, name, "parents");
+
+    assert!(!tree.commands[create].executable);
+    assert!(tree.commands[name].executable);
+    assert_eq!(
+        parser(&tree.commands[parents]),
+        &BrigadierParser::String(StringArg::GreedyPhrase)
+    );
+    assert_eq!(
+        suggestion(&tree.commands[parents]),
+        Some(&Suggestion::AskServer)
+    );
+
+    let remove = child(&tree, group, "remove");
+    let rm = child(&tree, group, "rm");
+
+    assert_eq!(
+        tree.commands[rm].redirect_node,
+        Some(VarInt(i32::try_from(remove).unwrap()))
+    );
+    assert_eq!(
+        parser(&tree.commands[child(&tree, remove, "name")]),
+        &BrigadierParser::String(StringArg::SingleWord)
+    );
+}
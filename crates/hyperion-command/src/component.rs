@@ -9,7 +9,8 @@ use indexmap::IndexMap;
 pub struct CommandHandler {
     pub on_execute: fn(input: &str, system: EntityView<'_>, caller: Entity),
     pub on_tab_complete: EventFn<CommandCompletionRequest<'static>>,
-    pub has_permissions: fn(world: &World, caller: Entity) -> bool,
+    /// The permission node needed to use the command.
+    pub permission: &'static str,
 }
 
 #[derive(Component)]
@@ -33,7 +34,7 @@ impl CommandRegistry {
         self.commands
             .iter()
             .filter_map(move |(cmd_name, handler)| {
-                if (handler.has_permissions)(world, caller) {
+                if hyperion_permission::has_permission(world, caller, handler.permission) {
                     Some(cmd_name)
                 } else {
                     None
@@ -12,6 +12,7 @@ pub struct CommandModule;
 
 impl Module for CommandModule {
     fn module(world: &World) {
+        world.import::<hyperion_permission::PermissionModule>();
         world.import::<component::CommandComponentModule>();
         world.import::<system::CommandSystemModule>();
     }
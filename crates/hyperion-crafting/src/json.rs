@@ -0,0 +1,410 @@
+//! Loading recipes and item tags from JSON files in the vanilla format.
+//!
+//! Vanilla and data packs keep them in a `data` directory:
+//!
+//! ```text
+//! data/
+//!     minecraft/
+//!         recipes/oak_planks.json      -> recipe minecraft:oak_planks
+//!         tags/items/planks.json       -> tag minecraft:planks
+//! ```
+//!
+//! Crafting and cooking recipes are loaded. Other kinds, such as stonecutting or the special
+//! recipes which have no ingredients, are skipped.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, bail};
+use serde::Deserialize;
+use tracing::warn;
+use valence_protocol::{ItemKind, ItemStack};
+
+use crate::{
+    CookingKind, CraftingCategory, CraftingRegistry, CraftingShapedData, CraftingShapelessData,
+    RecipeData, SmeltingCategory, SmeltingData,
+};
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum RecipeJson {
+    #[serde(rename = "minecraft:crafting_shaped")]
+    Shaped {
+        #[serde(default)]
+        group: String,
+        #[serde(default)]
+        category: CraftingCategory,
+        pattern: Vec<String>,
+        key: HashMap<char, IngredientJson>,
+        result: ResultJson,
+        #[serde(default = "yes")]
+        show_notification: bool,
+    },
+    #[serde(rename = "minecraft:crafting_shapeless")]
+    Shapeless {
+        #[serde(default)]
+        group: String,
+        #[serde(default)]
+        category: CraftingCategory,
+        ingredients: Vec<IngredientJson>,
+        result: ResultJson,
+    },
+    #[serde(rename = "minecraft:smelting")]
+    Smelting(CookingJson),
+    #[serde(rename = "minecraft:blasting")]
+    Blasting(CookingJson),
+    #[serde(rename = "minecraft:smoking")]
+    Smoking(CookingJson),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Deserialize)]
+struct CookingJson {
+    #[serde(default)]
+    group: String,
+    #[serde(default)]
+    category: SmeltingCategory,
+    ingredient: IngredientJson,
+    /// Unlike crafting recipes, only the id of the item.
+    result: String,
+    #[serde(default)]
+    experience: f32,
+    #[serde(rename = "cookingtime")]
+    cooking_time: Option<u16>,
+}
+
+const fn yes() -> bool {
+    true
+}
+
+/// One item or tag, or a list of them which may all be used.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IngredientJson {
+    One(ItemOrTag),
+    Any(Vec<ItemOrTag>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ItemOrTag {
+    Item { item: String },
+    Tag { tag: String },
+}
+
+#[derive(Deserialize)]
+struct ResultJson {
+    item: String,
+    #[serde(default = "one")]
+    count: i8,
+}
+
+const fn one() -> i8 {
+    1
+}
+
+#[derive(Deserialize)]
+struct TagJson {
+    values: Vec<TagEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TagEntry {
+    Id(String),
+    Optional {
+        id: String,
+        #[serde(default = "yes")]
+        required: bool,
+    },
+}
+
+impl TagEntry {
+    fn id(&self) -> &str {
+        match self {
+            Self::Id(id) | Self::Optional { id, .. } => id,
+        }
+    }
+
+    const fn required(&self) -> bool {
+        match self {
+            Self::Id(_) => true,
+            Self::Optional { required, .. } => *required,
+        }
+    }
+}
+
+/// Parses an item id like `minecraft:stick`. Only vanilla items exist.
+fn item_kind(id: &str) -> anyhow::Result<ItemKind> {
+    let name = id.strip_prefix("minecraft:").unwrap_or(id);
+
+    ItemKind::from_str(name).with_context(|| format!("unknown item {id}"))
+}
+
+impl ResultJson {
+    fn into_stack(self) -> anyhow::Result<ItemStack> {
+        Ok(ItemStack::new(item_kind(&self.item)?, self.count, None))
+    }
+}
+
+impl CraftingRegistry {
+    fn ingredient(&self, json: IngredientJson) -> anyhow::Result<Vec<ItemKind>> {
+        let entries = match json {
+            IngredientJson::One(entry) => vec![entry],
+            IngredientJson::Any(entries) => entries,
+        };
+
+        let mut items = Vec::new();
+
+        for entry in entries {
+            match entry {
+                ItemOrTag::Item { item } => items.push(item_kind(&item)?),
+                ItemOrTag::Tag { tag } => {
+                    let tagged = self
+                        .item_tag(&tag)
+                        .with_context(|| format!("unknown item tag {tag}"))?;
+
+                    items.extend_from_slice(tagged);
+                }
+            }
+        }
+
+        // nothing would be taken for it, so it would match an empty slot
+        if items.is_empty() {
+            bail!("an ingredient matches no items");
+        }
+
+        Ok(items)
+    }
+
+    /// Registers a recipe from the contents of a vanilla recipe file. Returns whether the recipe
+    /// was registered, which it is not if it is not a crafting recipe.
+    pub fn register_json(
+        &mut self,
+        recipe_id: impl Into<String>,
+        json: &str,
+    ) -> anyhow::Result<bool> {
+        let recipe_id = recipe_id.into();
+
+        let recipe: RecipeJson =
+            serde_json::from_str(json).with_context(|| format!("invalid recipe {recipe_id}"))?;
+
+        match recipe {
+            RecipeJson::Shaped {
+                group,
+                category,
+                pattern,
+                key,
+                result,
+                show_notification,
+            } => {
+                let key = key
+                    .into_iter()
+                    .map(|(symbol, ingredient)| Ok((symbol, self.ingredient(ingredient)?)))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                let pattern: Vec<&str> = pattern.iter().map(String::as_str).collect();
+
+                let data = CraftingShapedData::new(&pattern, key, result.into_stack()?)
+                    .with_context(|| format!("invalid pattern in recipe {recipe_id}"))?
+                    .group(group)
+                    .category(category)
+                    .show_notification(show_notification);
+
+                self.register(recipe_id, data)?;
+            }
+            RecipeJson::Shapeless {
+                group,
+                category,
+                ingredients,
+                result,
+            } => {
+                let mut data = CraftingShapelessData::new(result.into_stack()?)
+                    .group(group)
+                    .category(category);
+
+                for ingredient in ingredients {
+                    data = data.ingredient(self.ingredient(ingredient)?);
+                }
+
+                self.register(recipe_id, data)?;
+            }
+            RecipeJson::Smelting(json) => {
+                self.register_cooking(recipe_id, CookingKind::Smelting, json)?;
+            }
+            RecipeJson::Blasting(json) => {
+                self.register_cooking(recipe_id, CookingKind::Blasting, json)?;
+            }
+            RecipeJson::Smoking(json) => {
+                self.register_cooking(recipe_id, CookingKind::Smoking, json)?;
+            }
+            RecipeJson::Unsupported => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    fn register_cooking(
+        &mut self,
+        recipe_id: String,
+        kind: CookingKind,
+        json: CookingJson,
+    ) -> anyhow::Result<()> {
+        // vanilla cooks twice as fast in blast furnaces and smokers
+        let default_time = match kind {
+            CookingKind::Smelting => 200,
+            CookingKind::Blasting | CookingKind::Smoking => 100,
+        };
+
+        let result = ItemStack::new(item_kind(&json.result)?, 1, None);
+
+        let data = SmeltingData::new(
+            self.ingredient(json.ingredient)?,
+            result,
+            json.cooking_time.unwrap_or(default_time),
+        )
+        .group(json.group)
+        .category(json.category)
+        .experience(json.experience);
+
+        self.register(recipe_id, RecipeData::cooking(kind, data))
+    }
+
+    /// Loads the item tags and then the recipes of every namespace in a `data` directory. Returns
+    /// how many recipes were registered. Recipes which cannot be loaded are skipped with a
+    /// warning, so one bad recipe does not keep out the others.
+    pub fn load_data(&mut self, data: impl AsRef<Path>) -> anyhow::Result<usize> {
+        let namespaces = fs::read_dir(data.as_ref())
+            .with_context(|| format!("failed to read {}", data.as_ref().display()))?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<anyhow::Result<Vec<PathBuf>>>()?;
+
+        let mut tags = HashMap::new();
+
+        for dir in namespaces.iter().filter(|dir| dir.is_dir()) {
+            let namespace = namespace(dir)?;
+
+            for (name, path) in json_files(&dir.join("tags/items"))? {
+                let json = fs::read_to_string(&path)?;
+                let tag: TagJson = serde_json::from_str(&json)
+                    .with_context(|| format!("invalid tag {}", path.display()))?;
+
+                tags.insert(format!("{namespace}:{name}"), tag.values);
+            }
+        }
+
+        for name in tags.keys() {
+            let items = resolve_tag(name, &tags, &mut Vec::new())?;
+            self.register_item_tag(name.clone(), items);
+        }
+
+        let mut registered = 0;
+
+        for dir in namespaces.iter().filter(|dir| dir.is_dir()) {
+            let namespace = namespace(dir)?;
+
+            for (name, path) in json_files(&dir.join("recipes"))? {
+                let recipe_id = format!("{namespace}:{name}");
+
+                let result = fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| self.register_json(recipe_id.clone(), &json));
+
+                match result {
+                    Ok(true) => registered += 1,
+                    Ok(false) => {}
+                    Err(e) => warn!("skipped recipe {recipe_id}: {e:#}"),
+                }
+            }
+        }
+
+        Ok(registered)
+    }
+}
+
+fn namespace(dir: &Path) -> anyhow::Result<&str> {
+    dir.file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("invalid namespace {}", dir.display()))
+}
+
+/// Every JSON file below `dir` by its path relative to `dir` without the extension, which is how
+/// recipes and tags are named. A missing directory has no files.
+fn json_files(dir: &Path) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    fn visit(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> anyhow::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let name = format!("{prefix}{stem}");
+
+            if path.is_dir() {
+                visit(&path, &format!("{name}/"), files)?;
+            } else if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                files.push((name, path));
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+
+    if dir.is_dir() {
+        visit(dir, "", &mut files)?;
+    }
+
+    Ok(files)
+}
+
+/// The items in a tag, including those in the tags it refers to with `#`.
+fn resolve_tag(
+    name: &str,
+    tags: &HashMap<String, Vec<TagEntry>>,
+    resolving: &mut Vec<String>,
+) -> anyhow::Result<Vec<ItemKind>> {
+    if resolving.iter().any(|tag| tag == name) {
+        bail!("item tag {name} refers to itself");
+    }
+
+    let Some(entries) = tags.get(name) else {
+        bail!("unknown item tag {name}");
+    };
+
+    resolving.push(name.to_owned());
+
+    let mut resolve = || {
+        let mut items = Vec::new();
+
+        for entry in entries {
+            let resolved = entry.id().strip_prefix('#').map_or_else(
+                || item_kind(entry.id()).map(|item| vec![item]),
+                |tag| resolve_tag(tag, tags, resolving),
+            );
+
+            match resolved {
+                Ok(resolved) => items.extend(resolved),
+                Err(e) if entry.required() => return Err(e),
+                Err(_) => {}
+            }
+        }
+
+        Ok(items)
+    };
+
+    let items = resolve();
+
+    resolving.pop();
+
+    items
+}
@@ -1,9 +1,18 @@
-use std::{collections::HashMap, io::Write};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    ops::RangeInclusive,
+};
 
+use anyhow::{bail, ensure};
 use derive_build::Build;
 use flecs_ecs::macros::Component;
+use serde::{Deserialize, Serialize};
 use slotmap::{SecondaryMap, SlotMap, new_key_type};
-use valence_protocol::{Encode, ItemKind, ItemStack, Packet};
+use valence_protocol::{Encode, ItemKind, ItemStack, Packet, VarInt};
+
+pub mod json;
+pub mod recipe_book;
 
 /// Represents a packet sent from the server to the client to synchronize recipes.
 #[derive(Clone, Debug, Encode, Packet)]
@@ -27,7 +36,7 @@ pub struct Recipe {
 #[derive(Clone, Debug)]
 pub enum RecipeData {
     CraftingShapeless(CraftingShapelessData),
-    // CraftingShaped(CraftingShapedData<'a>),
+    CraftingShaped(CraftingShapedData),
     // CraftingSpecialArmordye(CraftingSpecialData),
     // CraftingSpecialBookcloning(CraftingSpecialData),
     // CraftingSpecialMapcloning(CraftingSpecialData),
@@ -42,9 +51,9 @@ pub enum RecipeData {
     // CraftingSpecialShulkerboxcoloring(CraftingSpecialData),
     // CraftingSpecialSuspiciousstew(CraftingSpecialData),
     // CraftingDecoratedPot(CraftingSpecialData),
-    // Smelting(SmeltingData<'a>),
-    // Blasting(SmeltingData<'a>),
-    // Smoking(SmeltingData<'a>),
+    Smelting(SmeltingData),
+    Blasting(SmeltingData),
+    Smoking(SmeltingData),
     // CampfireCooking(SmeltingData<'a>),
     // Stonecutting(StonecuttingData<'a>),
     // SmithingTransform(SmithingTransformData<'a>),
@@ -55,7 +64,7 @@ impl Encode for RecipeData {
     fn encode(&self, w: impl Write) -> anyhow::Result<()> {
         match self {
             Self::CraftingShapeless(data) => data.encode(w),
-            // RecipeData::CraftingShaped(data) => data.encode(w),
+            Self::CraftingShaped(data) => data.encode(w),
             // RecipeData::CraftingSpecialArmordye(data) => data.encode(w),
             // RecipeData::CraftingSpecialBookcloning(data) => data.encode(w),
             // RecipeData::CraftingSpecialMapcloning(data) => data.encode(w),
@@ -70,9 +79,7 @@ impl Encode for RecipeData {
             // RecipeData::CraftingSpecialShulkerboxcoloring(data) => data.encode(w),
             // RecipeData::CraftingSpecialSuspiciousstew(data) => data.encode(w),
             // RecipeData::CraftingDecoratedPot(data) => data.encode(w),
-            // RecipeData::Smelting(data) => data.encode(w),
-            // RecipeData::Blasting(data) => data.encode(w),
-            // RecipeData::Smoking(data) => data.encode(w),
+            Self::Smelting(data) | Self::Blasting(data) | Self::Smoking(data) => data.encode(w),
             // RecipeData::CampfireCooking(data) => data.encode(w),
             // RecipeData::Stonecutting(data) => data.encode(w),
             // RecipeData::SmithingTransform(data) => data.encode(w),
@@ -95,6 +102,230 @@ pub struct CraftingShapelessData {
     result: ItemStack,
 }
 
+/// Represents data for a shaped crafting recipe.
+///
+/// The pattern may be placed anywhere in the crafting grid and may be mirrored horizontally.
+#[derive(Clone, Debug)]
+pub struct CraftingShapedData {
+    width: u8,
+    height: u8,
+    /// Used to group similar recipes together in the recipe book.
+    group: String,
+    /// The category of the recipe.
+    category: CraftingCategory,
+    /// The ingredients row by row. An ingredient without items stands for an empty slot.
+    ingredients: Vec<Ingredient>,
+    /// The result of the crafting recipe.
+    result: ItemStack,
+    /// Whether a toast is shown when the recipe is unlocked.
+    show_notification: bool,
+}
+
+impl CraftingShapedData {
+    /// Creates a recipe from a pattern like the ones in vanilla recipe files. Each row is a
+    /// string, each character stands for the ingredient it maps to in `key`, and spaces are empty
+    /// slots. Rows and columns which are empty everywhere are removed.
+    ///
+    /// ```ignore
+    /// let sticks = CraftingShapedData::new(
+    ///     &["#", "#"],
+    ///     [('#', ItemKind::OakPlanks)],
+    ///     ItemStack::new(ItemKind::Stick, 4, None),
+    /// )?;
+    /// ```
+    pub fn new<I: Into<Ingredient>>(
+        pattern: &[&str],
+        key: impl IntoIterator<Item = (char, I)>,
+        result: ItemStack,
+    ) -> anyhow::Result<Self> {
+        let key: HashMap<char, Ingredient> = key
+            .into_iter()
+            .map(|(symbol, ingredient)| (symbol, ingredient.into()))
+            .collect();
+
+        ensure!(
+            (1..=3).contains(&pattern.len()),
+            "a pattern has 1 to 3 rows, not {}",
+            pattern.len()
+        );
+
+        let rows: Vec<Vec<char>> = pattern.iter().map(|row| row.chars().collect()).collect();
+        let width = rows[0].len();
+
+        ensure!(
+            (1..=3).contains(&width) && rows.iter().all(|row| row.len() == width),
+            "every row of a pattern has the same width of 1 to 3 slots"
+        );
+
+        let mut used = HashSet::new();
+
+        for &symbol in rows.iter().flatten() {
+            if symbol != ' ' {
+                ensure!(key.contains_key(&symbol), "{symbol:?} is not in the key");
+                used.insert(symbol);
+            }
+        }
+
+        if let Some(unused) = key.keys().find(|symbol| !used.contains(symbol)) {
+            bail!("{unused:?} is in the key but not in the pattern");
+        }
+
+        // vanilla places recipes anywhere in the grid, so empty borders would only get in the way
+        let Some((filled_rows, filled_cols)) =
+            bounds(rows.len(), width, |row, col| rows[row][col] != ' ')
+        else {
+            bail!("a pattern needs at least one ingredient");
+        };
+
+        let size = (filled_cols.clone().count(), filled_rows.clone().count());
+
+        let ingredients = filled_rows
+            .flat_map(|row| filled_cols.clone().map(move |col| (row, col)))
+            .map(|(row, col)| {
+                key.get(&rows[row][col])
+                    .cloned()
+                    .unwrap_or_else(Ingredient::empty)
+            })
+            .collect();
+
+        Ok(Self {
+            width: u8::try_from(size.0)?,
+            height: u8::try_from(size.1)?,
+            group: String::new(),
+            category: CraftingCategory::default(),
+            ingredients,
+            result,
+            show_notification: true,
+        })
+    }
+
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = group.into();
+        self
+    }
+
+    #[must_use]
+    pub const fn category(mut self, category: CraftingCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    #[must_use]
+    pub const fn show_notification(mut self, show_notification: bool) -> Self {
+        self.show_notification = show_notification;
+        self
+    }
+
+    #[must_use]
+    pub const fn result(&self) -> &ItemStack {
+        &self.result
+    }
+
+    /// Whether the items, which fill exactly the size of the pattern row by row, match it.
+    fn matches(&self, items: &[ItemKind], mirrored: bool) -> bool {
+        let width = usize::from(self.width);
+
+        items.iter().enumerate().all(|(idx, &item)| {
+            let (row, mut col) = (idx / width, idx % width);
+
+            if mirrored {
+                col = width - 1 - col;
+            }
+
+            self.ingredients[row * width + col].test(item)
+        })
+    }
+}
+
+/// Represents data for a recipe which cooks one item into another in a furnace, blast furnace or
+/// smoker.
+#[derive(Clone, Debug)]
+pub struct SmeltingData {
+    /// Used to group similar recipes together in the recipe book.
+    group: String,
+    /// The category of the recipe.
+    category: SmeltingCategory,
+    /// The item which is cooked.
+    ingredient: Ingredient,
+    /// The result of the recipe.
+    result: ItemStack,
+    /// The experience points a player gets for each cooked item.
+    experience: f32,
+    /// How many ticks it takes to cook one item.
+    cooking_time: u16,
+}
+
+impl SmeltingData {
+    #[must_use]
+    pub fn new(ingredient: impl Into<Ingredient>, result: ItemStack, cooking_time: u16) -> Self {
+        Self {
+            group: String::new(),
+            category: SmeltingCategory::default(),
+            ingredient: ingredient.into(),
+            result,
+            experience: 0.0,
+            cooking_time,
+        }
+    }
+
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = group.into();
+        self
+    }
+
+    #[must_use]
+    pub const fn category(mut self, category: SmeltingCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    #[must_use]
+    pub const fn experience(mut self, experience: f32) -> Self {
+        self.experience = experience;
+        self
+    }
+
+    #[must_use]
+    pub const fn result(&self) -> &ItemStack {
+        &self.result
+    }
+
+    #[must_use]
+    pub const fn experience_points(&self) -> f32 {
+        self.experience
+    }
+
+    #[must_use]
+    pub const fn cooking_time(&self) -> u16 {
+        self.cooking_time
+    }
+}
+
+/// The block a [`SmeltingData`] recipe is cooked in.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum CookingKind {
+    /// A furnace, which cooks anything.
+    Smelting,
+    /// A blast furnace, which cooks ores and metal twice as fast.
+    Blasting,
+    /// A smoker, which cooks food twice as fast.
+    Smoking,
+}
+
+impl CookingKind {
+    /// The type of the recipe in the recipe packet and recipe files.
+    #[must_use]
+    pub const fn recipe_type(self) -> &'static str {
+        match self {
+            Self::Smelting => "minecraft:smelting",
+            Self::Blasting => "minecraft:blasting",
+            Self::Smoking => "minecraft:smoking",
+        }
+    }
+}
+
 /// Represents data for special crafting recipes.
 #[derive(Clone, Debug)]
 pub struct CraftingSpecialData {
@@ -103,7 +334,8 @@ pub struct CraftingSpecialData {
 }
 
 /// Represents the categories for crafting recipes.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CraftingCategory {
     Building,
     Redstone,
@@ -113,16 +345,47 @@ pub enum CraftingCategory {
 }
 
 /// Represents the categories for smelting recipes.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SmeltingCategory {
     Food,
     Blocks,
+    #[default]
     Misc,
 }
 
 /// Represents an ingredient in a recipe, which can be multiple possible items.
 #[derive(Encode, Clone, Debug)]
-struct Ingredient(Vec<ItemStack>);
+pub struct Ingredient(Vec<ItemStack>);
+
+impl Ingredient {
+    /// The ingredient of an empty slot in a shaped recipe.
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Whether `item` can be used as this ingredient.
+    #[must_use]
+    pub fn test(&self, item: ItemKind) -> bool {
+        if self.0.is_empty() {
+            return item == ItemKind::Air;
+        }
+
+        self.0.iter().any(|stack| stack.item == item)
+    }
+}
+
+impl From<Vec<ItemKind>> for Ingredient {
+    fn from(value: Vec<ItemKind>) -> Self {
+        Self(
+            value
+                .into_iter()
+                .map(|item| ItemStack::new(item, 1, None))
+                .collect(),
+        )
+    }
+}
 
 impl From<Vec<ItemStack>> for Ingredient {
     fn from(value: Vec<ItemStack>) -> Self {
@@ -152,31 +415,79 @@ impl Encode for CraftingShapelessData {
     }
 }
 
+impl Encode for CraftingShapedData {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        VarInt(i32::from(self.width)).encode(&mut w)?;
+        VarInt(i32::from(self.height)).encode(&mut w)?;
+        self.group.encode(&mut w)?;
+        self.category.encode(&mut w)?;
+
+        // the length is known from the width and height
+        for ingredient in &self.ingredients {
+            ingredient.encode(&mut w)?;
+        }
+
+        self.result.encode(&mut w)?;
+        self.show_notification.encode(w)
+    }
+}
+
+impl Encode for SmeltingData {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        self.group.encode(&mut w)?;
+        self.category.encode(&mut w)?;
+        self.ingredient.encode(&mut w)?;
+        self.result.encode(&mut w)?;
+        self.experience.encode(&mut w)?;
+        VarInt(i32::from(self.cooking_time)).encode(w)
+    }
+}
+
 impl Encode for CraftingSpecialData {
     fn encode(&self, w: impl Write) -> anyhow::Result<()> {
         self.category.encode(w)
     }
 }
 
-#[derive(Debug, Encode, Packet)]
+#[derive(Debug, Packet)]
 pub struct UnlockRecipesS2c {
     pub action: Action,
     pub crafting_recipe_book: RecipeBookState,
     pub smelting_recipe_book: RecipeBookState,
     pub blast_furnace_recipe_book: RecipeBookState,
     pub smoker_recipe_book: RecipeBookState,
+    /// The recipes which are unlocked, added or removed.
     pub recipe_ids_1: Vec<String>,
+    /// The recipes which are highlighted as new. Only sent with [`Action::Init`].
     pub recipe_ids_2: Vec<String>,
 }
 
-#[derive(Debug, Encode)]
+impl Encode for UnlockRecipesS2c {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        self.action.encode(&mut w)?;
+        self.crafting_recipe_book.encode(&mut w)?;
+        self.smelting_recipe_book.encode(&mut w)?;
+        self.blast_furnace_recipe_book.encode(&mut w)?;
+        self.smoker_recipe_book.encode(&mut w)?;
+        self.recipe_ids_1.encode(&mut w)?;
+
+        // the client reads the second list only when initializing
+        if self.action == Action::Init {
+            self.recipe_ids_2.encode(w)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode)]
 pub enum Action {
     Init,
     Add,
     Remove,
 }
 
-#[derive(Debug, Encode)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Encode, Serialize, Deserialize)]
 pub struct RecipeBookState {
     pub open: bool,
     pub filter_active: bool,
@@ -193,6 +504,23 @@ impl RecipeBookState {
 pub type Crafting3x3 = [ItemKind; 9];
 pub type Crafting2x2 = [ItemKind; 4];
 
+/// The rows and columns of a grid from the first to the last one which contain anything.
+fn bounds(
+    height: usize,
+    width: usize,
+    filled: impl Fn(usize, usize) -> bool,
+) -> Option<(RangeInclusive<usize>, RangeInclusive<usize>)> {
+    let row_filled = |row: &usize| (0..width).any(|col| filled(*row, col));
+    let col_filled = |col: &usize| (0..height).any(|row| filled(row, *col));
+
+    let top = (0..height).find(row_filled)?;
+    let bottom = (0..height).rev().find(row_filled)?;
+    let left = (0..width).find(col_filled)?;
+    let right = (0..width).rev().find(col_filled)?;
+
+    Some((top..=bottom, left..=right))
+}
+
 #[derive(Debug, Hash, PartialEq, Eq)]
 struct SortedItemList(Crafting3x3);
 
@@ -216,32 +544,179 @@ impl FromIterator<ItemKind> for SortedItemList {
     }
 }
 
+impl CraftingShapelessData {
+    /// Whether every item which is not air can be used as a different ingredient.
+    fn matches(&self, items: &[ItemKind]) -> bool {
+        fn assign(items: &[ItemKind], ingredients: &[Ingredient], used: &mut [bool]) -> bool {
+            let Some((&item, rest)) = items.split_first() else {
+                return true;
+            };
+
+            for (idx, ingredient) in ingredients.iter().enumerate() {
+                if used[idx] || !ingredient.test(item) {
+                    continue;
+                }
+
+                used[idx] = true;
+
+                if assign(rest, ingredients, used) {
+                    return true;
+                }
+
+                used[idx] = false;
+            }
+
+            false
+        }
+
+        let items: Vec<ItemKind> = items
+            .iter()
+            .copied()
+            .filter(|&item| item != ItemKind::Air)
+            .collect();
+
+        items.len() == self.ingredients.len()
+            && assign(&items, &self.ingredients, &mut vec![
+                false;
+                self.ingredients.len()
+            ])
+    }
+}
+
+impl From<CraftingShapelessData> for RecipeData {
+    fn from(value: CraftingShapelessData) -> Self {
+        Self::CraftingShapeless(value)
+    }
+}
+
+impl From<CraftingShapedData> for RecipeData {
+    fn from(value: CraftingShapedData) -> Self {
+        Self::CraftingShaped(value)
+    }
+}
+
+impl RecipeData {
+    /// Wraps a cooking recipe in the variant for the block it is cooked in.
+    #[must_use]
+    pub const fn cooking(kind: CookingKind, data: SmeltingData) -> Self {
+        match kind {
+            CookingKind::Smelting => Self::Smelting(data),
+            CookingKind::Blasting => Self::Blasting(data),
+            CookingKind::Smoking => Self::Smoking(data),
+        }
+    }
+}
+
 // Define a custom key type
 new_key_type! { struct SortedItemId; }
+new_key_type! { struct ShapedId; }
+new_key_type! { struct SmeltingId; }
 
 #[derive(Component)]
 pub struct CraftingRegistry {
     // changes when the registry is updated
     epoch: u64,
 
+    recipe_ids: HashSet<String>,
+
     shapeless_lookup: HashMap<SortedItemList, SortedItemId>,
+    /// Shapeless recipes with an ingredient which can be one of several items. They cannot be
+    /// looked up by their items and are matched one by one instead.
+    shapeless_alternatives: Vec<SortedItemId>,
     shapeless: SlotMap<SortedItemId, CraftingShapelessData>,
     shapeless_ids: SecondaryMap<SortedItemId, String>,
+
+    /// Shaped recipes by their width and height.
+    shaped_lookup: HashMap<(u8, u8), Vec<ShapedId>>,
+    shaped: SlotMap<ShapedId, CraftingShapedData>,
+    shaped_ids: SecondaryMap<ShapedId, String>,
+
+    /// Cooking recipes by the block they are cooked in and each item they accept.
+    smelting_lookup: HashMap<(CookingKind, ItemKind), SmeltingId>,
+    smelting: SlotMap<SmeltingId, (CookingKind, SmeltingData)>,
+    smelting_ids: SecondaryMap<SmeltingId, String>,
+
+    /// Items by the name of the tag they are in, such as `minecraft:planks`. Recipes loaded from
+    /// JSON refer to them.
+    item_tags: HashMap<String, Vec<ItemKind>>,
 }
 
 impl Default for CraftingRegistry {
     fn default() -> Self {
         let mut result = Self {
             epoch: 0,
+            recipe_ids: HashSet::default(),
             shapeless_lookup: HashMap::default(),
+            shapeless_alternatives: Vec::default(),
             shapeless: SlotMap::default(),
             shapeless_ids: SecondaryMap::default(),
+            shaped_lookup: HashMap::default(),
+            shaped: SlotMap::default(),
+            shaped_ids: SecondaryMap::default(),
+            smelting_lookup: HashMap::default(),
+            smelting: SlotMap::default(),
+            smelting_ids: SecondaryMap::default(),
+            item_tags: HashMap::default(),
         };
 
         let shapeless = CraftingShapelessData::new(ItemStack::new(ItemKind::OakPlanks, 4, None))
             .ingredient(ItemKind::OakLog);
 
-        result.register_shapeless("hyperion:plank".to_string(), shapeless);
+        result.register("hyperion:plank", shapeless).unwrap();
+
+        let stick = CraftingShapedData::new(
+            &["#", "#"],
+            [('#', ItemKind::OakPlanks)],
+            ItemStack::new(ItemKind::Stick, 4, None),
+        )
+        .unwrap();
+
+        result.register("hyperion:stick", stick).unwrap();
+
+        let crafting_table = CraftingShapedData::new(
+            &["##", "##"],
+            [('#', ItemKind::OakPlanks)],
+            ItemStack::new(ItemKind::CraftingTable, 1, None),
+        )
+        .unwrap()
+        .category(CraftingCategory::Misc);
+
+        result
+            .register("hyperion:crafting_table", crafting_table)
+            .unwrap();
+
+        let charcoal = SmeltingData::new(
+            ItemKind::OakLog,
+            ItemStack::new(ItemKind::Charcoal, 1, None),
+            200,
+        )
+        .experience(0.15);
+
+        result
+            .register("hyperion:charcoal", RecipeData::Smelting(charcoal))
+            .unwrap();
+
+        let iron_ingots = [
+            ("hyperion:iron_ingot", CookingKind::Smelting, 200),
+            (
+                "hyperion:iron_ingot_from_blasting",
+                CookingKind::Blasting,
+                100,
+            ),
+        ];
+
+        for (recipe_id, kind, cooking_time) in iron_ingots {
+            let iron_ingot = SmeltingData::new(
+                ItemKind::RawIron,
+                ItemStack::new(ItemKind::IronIngot, 1, None),
+                cooking_time,
+            )
+            .experience(0.7);
+
+            result
+                .register(recipe_id, RecipeData::cooking(kind, iron_ingot))
+                .unwrap();
+        }
 
         result
     }
@@ -264,29 +739,98 @@ impl CraftingRegistry {
             return None;
         }
 
-        let recipes: Vec<_> = self
-            .shapeless
-            .iter()
-            .map(|(id, data)| {
-                let recipe_id = self.shapeless_ids.get(id).unwrap();
+        let shapeless = self.shapeless.iter().map(|(id, data)| {
+            let recipe_id = self.shapeless_ids.get(id).unwrap();
 
-                Recipe {
-                    kind: "minecraft:crafting_shapeless",
-                    recipe_id: recipe_id.to_string(),
-                    data: RecipeData::CraftingShapeless(data.clone()),
-                }
-            })
-            .collect();
+            Recipe {
+                kind: "minecraft:crafting_shapeless",
+                recipe_id: recipe_id.to_string(),
+                data: RecipeData::CraftingShapeless(data.clone()),
+            }
+        });
+
+        let shaped = self.shaped.iter().map(|(id, data)| {
+            let recipe_id = self.shaped_ids.get(id).unwrap();
+
+            Recipe {
+                kind: "minecraft:crafting_shaped",
+                recipe_id: recipe_id.to_string(),
+                data: RecipeData::CraftingShaped(data.clone()),
+            }
+        });
+
+        let smelting = self.smelting.iter().map(|(id, (kind, data))| {
+            let recipe_id = self.smelting_ids.get(id).unwrap();
+
+            Recipe {
+                kind: kind.recipe_type(),
+                recipe_id: recipe_id.to_string(),
+                data: RecipeData::cooking(*kind, data.clone()),
+            }
+        });
+
+        let recipes: Vec<_> = shapeless.chain(shaped).chain(smelting).collect();
 
         Some(SynchronizeRecipesS2c { recipes })
     }
 
+    /// Adds a recipe which is sent to players when they join. Ids look like `namespace:name` and
+    /// must be unique.
+    pub fn register(
+        &mut self,
+        recipe_id: impl Into<String>,
+        data: impl Into<RecipeData>,
+    ) -> anyhow::Result<()> {
+        let recipe_id = recipe_id.into();
+
+        ensure!(
+            !self.recipe_ids.contains(&recipe_id),
+            "recipe {recipe_id} is already registered"
+        );
+
+        self.recipe_ids.insert(recipe_id.clone());
+
+        match data.into() {
+            RecipeData::CraftingShapeless(data) => self.register_shapeless(recipe_id, data),
+            RecipeData::CraftingShaped(data) => self.register_shaped(recipe_id, data),
+            RecipeData::Smelting(data) => {
+                self.register_smelting(recipe_id, CookingKind::Smelting, data);
+            }
+            RecipeData::Blasting(data) => {
+                self.register_smelting(recipe_id, CookingKind::Blasting, data);
+            }
+            RecipeData::Smoking(data) => {
+                self.register_smelting(recipe_id, CookingKind::Smoking, data);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the items of a tag, replacing the ones it had before. Tags are only resolved when a
+    /// recipe is loaded from JSON, so they must be registered first.
+    pub fn register_item_tag(&mut self, name: impl Into<String>, items: Vec<ItemKind>) {
+        self.item_tags.insert(name.into(), items);
+    }
+
+    #[must_use]
+    pub fn item_tag(&self, name: &str) -> Option<&[ItemKind]> {
+        self.item_tags.get(name).map(Vec::as_slice)
+    }
+
     pub fn get_shapeless(
         &self,
         input: impl IntoIterator<Item = ItemKind>,
     ) -> Option<ShapelessRecipe<'_>> {
-        let list: SortedItemList = input.into_iter().collect();
-        let id = self.shapeless_lookup.get(&list).copied()?;
+        let items: Vec<ItemKind> = input.into_iter().collect();
+        let list: SortedItemList = items.iter().copied().collect();
+
+        let id = self.shapeless_lookup.get(&list).copied().or_else(|| {
+            self.shapeless_alternatives
+                .iter()
+                .copied()
+                .find(|&id| self.shapeless[id].matches(&items))
+        })?;
 
         // let recipe_id = self.shapeless_ids.get(id).unwrap();
         let data = self.shapeless.get(id).unwrap();
@@ -294,7 +838,30 @@ impl CraftingRegistry {
         Some(ShapelessRecipe { data })
     }
 
+    /// The shaped recipe the items in the grid match, wherever they are in the grid.
+    #[must_use]
+    pub fn get_shaped(&self, grid: Crafting3x3) -> Option<&CraftingShapedData> {
+        let (rows, cols) = bounds(3, 3, |row, col| grid[row * 3 + col] != ItemKind::Air)?;
+
+        let size = (
+            u8::try_from(cols.clone().count()).ok()?,
+            u8::try_from(rows.clone().count()).ok()?,
+        );
+
+        let items: Vec<ItemKind> = rows
+            .flat_map(|row| cols.clone().map(move |col| grid[row * 3 + col]))
+            .collect();
+
+        self.shaped_lookup
+            .get(&size)?
+            .iter()
+            .map(|&id| &self.shaped[id])
+            .find(|data| data.matches(&items, false) || data.matches(&items, true))
+    }
+
     fn register_shapeless(&mut self, recipe_id: String, data: CraftingShapelessData) {
+        let exact = data.ingredients.iter().all(|x| x.0.len() == 1);
+
         let list: SortedItemList = data
             .ingredients
             .iter()
@@ -305,17 +872,217 @@ impl CraftingRegistry {
         let entity_id = self.shapeless.insert(data);
         self.shapeless_ids.insert(entity_id, recipe_id);
 
-        self.shapeless_lookup.insert(list, entity_id);
+        if exact {
+            self.shapeless_lookup.insert(list, entity_id);
+        } else {
+            self.shapeless_alternatives.push(entity_id);
+        }
+
+        self.mark_changed();
+    }
+
+    fn register_shaped(&mut self, recipe_id: String, data: CraftingShapedData) {
+        let size = (data.width, data.height);
+
+        let id = self.shaped.insert(data);
+        self.shaped_ids.insert(id, recipe_id);
+
+        self.shaped_lookup.entry(size).or_default().push(id);
+
+        self.mark_changed();
+    }
+
+    fn register_smelting(&mut self, recipe_id: String, kind: CookingKind, data: SmeltingData) {
+        let items: Vec<ItemKind> = data.ingredient.0.iter().map(|stack| stack.item).collect();
+
+        let id = self.smelting.insert((kind, data));
+        self.smelting_ids.insert(id, recipe_id);
+
+        for item in items {
+            self.smelting_lookup.insert((kind, item), id);
+        }
 
         self.mark_changed();
     }
 
+    /// The recipe which cooks `item` in the block of the given kind.
     #[must_use]
-    pub fn get_result_2x2(&self, grid: Crafting2x2) -> Option<&ItemStack> {
+    pub fn get_smelting(&self, kind: CookingKind, item: ItemKind) -> Option<&SmeltingData> {
+        let id = self.smelting_lookup.get(&(kind, item))?;
+        self.smelting.get(*id).map(|(_, data)| data)
+    }
+
+    /// The result of the items in a crafting table.
+    #[must_use]
+    pub fn get_result_3x3(&self, grid: Crafting3x3) -> Option<&ItemStack> {
+        if let Some(shaped) = self.get_shaped(grid) {
+            return Some(&shaped.result);
+        }
+
         if let Some(shapeless) = self.get_shapeless(grid) {
             return Some(&shapeless.data.result);
         }
 
         None
     }
+
+    /// The result of the items in the crafting grid of the player inventory.
+    #[must_use]
+    pub fn get_result_2x2(&self, grid: Crafting2x2) -> Option<&ItemStack> {
+        let [a, b, c, d] = grid;
+        let air = ItemKind::Air;
+
+        self.get_result_3x3([a, b, air, c, d, air, air, air, air])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AIR: ItemKind = ItemKind::Air;
+
+    fn hoe() -> CraftingShapedData {
+        CraftingShapedData::new(
+            &["##", " |", " |"],
+            [('#', ItemKind::Diamond), ('|', ItemKind::Stick)],
+            ItemStack::new(ItemKind::DiamondHoe, 1, None),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn shaped_matches_offset_and_mirrored() {
+        let mut registry = CraftingRegistry::default();
+        registry.register("test:hoe", hoe()).unwrap();
+
+        let (d, s) = (ItemKind::Diamond, ItemKind::Stick);
+
+        let right = [AIR, d, d, AIR, AIR, s, AIR, AIR, s];
+        let mirrored = [d, d, AIR, s, AIR, AIR, s, AIR, AIR];
+        let upside_down = [AIR, AIR, s, AIR, AIR, s, AIR, d, d];
+
+        assert_eq!(
+            registry.get_result_3x3(right).map(|stack| stack.item),
+            Some(ItemKind::DiamondHoe)
+        );
+        assert_eq!(
+            registry.get_result_3x3(mirrored).map(|stack| stack.item),
+            Some(ItemKind::DiamondHoe)
+        );
+        assert!(registry.get_result_3x3(upside_down).is_none());
+    }
+
+    #[test]
+    fn shaped_fits_player_grid() {
+        let registry = CraftingRegistry::default();
+        let p = ItemKind::OakPlanks;
+
+        assert_eq!(
+            registry
+                .get_result_2x2([p, p, p, p])
+                .map(|stack| stack.item),
+            Some(ItemKind::CraftingTable)
+        );
+        assert_eq!(
+            registry
+                .get_result_2x2([AIR, p, AIR, p])
+                .map(|stack| stack.count),
+            Some(4)
+        );
+        assert!(registry.get_result_2x2([p, AIR, AIR, p]).is_none());
+    }
+
+    #[test]
+    fn shapeless_with_alternatives() {
+        let mut registry = CraftingRegistry::default();
+
+        let data = CraftingShapelessData::new(ItemStack::new(ItemKind::Stick, 1, None))
+            .ingredient(vec![ItemKind::OakPlanks, ItemKind::BirchPlanks])
+            .ingredient(ItemKind::OakPlanks);
+
+        registry.register("test:stick", data).unwrap();
+
+        let (oak, birch) = (ItemKind::OakPlanks, ItemKind::BirchPlanks);
+
+        assert!(registry.get_shapeless([birch, AIR, oak, AIR]).is_some());
+        assert!(registry.get_shapeless([birch, AIR, birch, AIR]).is_none());
+        assert!(registry.register("test:stick", hoe()).is_err());
+    }
+
+    #[test]
+    fn register_json_resolves_tags() {
+        let mut registry = CraftingRegistry::default();
+        registry.register_item_tag("minecraft:planks", vec![
+            ItemKind::OakPlanks,
+            ItemKind::BirchPlanks,
+        ]);
+
+        let json = r##"{
+            "type": "minecraft:crafting_shaped",
+            "category": "misc",
+            "key": { "#": { "tag": "minecraft:planks" } },
+            "pattern": ["#", "#"],
+            "result": { "count": 4, "item": "minecraft:stick" }
+        }"##;
+
+        assert!(registry.register_json("minecraft:stick", json).unwrap());
+        assert!(
+            !registry
+                .register_json("minecraft:cut", r#"{ "type": "minecraft:stonecutting" }"#)
+                .unwrap()
+        );
+
+        let b = ItemKind::BirchPlanks;
+        let grid = [AIR, AIR, AIR, AIR, b, AIR, AIR, b, AIR];
+
+        assert_eq!(
+            registry.get_result_3x3(grid).map(|stack| stack.item),
+            Some(ItemKind::Stick)
+        );
+    }
+
+    #[test]
+    fn register_json_rejects_empty_ingredients() {
+        let mut registry = CraftingRegistry::default();
+        registry.register_item_tag("minecraft:nothing", Vec::new());
+
+        let json = r#"{
+            "type": "minecraft:crafting_shapeless",
+            "category": "misc",
+            "ingredients": [{ "tag": "minecraft:nothing" }],
+            "result": { "item": "minecraft:stick" }
+        }"#;
+
+        assert!(registry.register_json("minecraft:stick", json).is_err());
+        assert!(registry.get_result_2x2([AIR; 4]).is_none());
+    }
+
+    #[test]
+    fn register_json_cooking() {
+        let mut registry = CraftingRegistry::default();
+
+        let json = r#"{
+            "type": "minecraft:blasting",
+            "category": "misc",
+            "cookingtime": 80,
+            "experience": 1.0,
+            "ingredient": { "item": "minecraft:diamond_hoe" },
+            "result": "minecraft:diamond"
+        }"#;
+
+        assert!(registry.register_json("minecraft:diamond", json).unwrap());
+
+        let recipe = registry
+            .get_smelting(CookingKind::Blasting, ItemKind::DiamondHoe)
+            .unwrap();
+
+        assert_eq!(recipe.result().item, ItemKind::Diamond);
+        assert_eq!(recipe.cooking_time(), 80);
+        assert!(
+            registry
+                .get_smelting(CookingKind::Smelting, ItemKind::DiamondHoe)
+                .is_none()
+        );
+    }
 }
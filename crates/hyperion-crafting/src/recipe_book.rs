@@ -0,0 +1,147 @@
+//! The recipes a player has unlocked in their recipe book.
+//!
+//! Recipes are referred to by the id they are registered with in the
+//! [`CraftingRegistry`](crate::CraftingRegistry). The client ignores ids it does not know.
+
+use std::collections::BTreeSet;
+
+use flecs_ecs::macros::Component;
+use serde::{Deserialize, Serialize};
+
+use crate::{Action, RecipeBookState, UnlockRecipesS2c};
+
+/// The tabs of the recipe book, each of which has its own settings.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RecipeBook {
+    Crafting,
+    Furnace,
+    BlastFurnace,
+    Smoker,
+}
+
+/// The recipes a player has unlocked and the settings of their recipe book.
+///
+/// Unlocking and locking recipes is remembered until [`UnlockedRecipes::take_changes`], which
+/// returns the packets telling the client about it.
+#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct UnlockedRecipes {
+    recipes: BTreeSet<String>,
+    books: [RecipeBookState; 4],
+    #[serde(skip)]
+    unlocked: Vec<String>,
+    #[serde(skip)]
+    locked: Vec<String>,
+}
+
+impl UnlockedRecipes {
+    /// Unlocks a recipe. Returns whether it was locked before.
+    pub fn unlock(&mut self, recipe_id: impl Into<String>) -> bool {
+        let recipe_id = recipe_id.into();
+
+        if !self.recipes.insert(recipe_id.clone()) {
+            return false;
+        }
+
+        // the client still has a recipe locked in the same tick
+        if let Some(idx) = self.locked.iter().position(|id| *id == recipe_id) {
+            self.locked.swap_remove(idx);
+        } else {
+            self.unlocked.push(recipe_id);
+        }
+
+        true
+    }
+
+    /// Locks a recipe again. Returns whether it was unlocked before.
+    pub fn lock(&mut self, recipe_id: &str) -> bool {
+        if !self.recipes.remove(recipe_id) {
+            return false;
+        }
+
+        if let Some(idx) = self.unlocked.iter().position(|id| id == recipe_id) {
+            self.unlocked.swap_remove(idx);
+        } else {
+            self.locked.push(recipe_id.to_owned());
+        }
+
+        true
+    }
+
+    #[must_use]
+    pub fn contains(&self, recipe_id: &str) -> bool {
+        self.recipes.contains(recipe_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.recipes.iter().map(String::as_str)
+    }
+
+    #[must_use]
+    pub const fn book(&self, book: RecipeBook) -> RecipeBookState {
+        self.books[book as usize]
+    }
+
+    /// Changes whether a tab of the recipe book is open and whether it only shows craftable
+    /// recipes. The client changes these itself, so nothing is sent.
+    pub const fn set_book(&mut self, book: RecipeBook, state: RecipeBookState) {
+        self.books[book as usize] = state;
+    }
+
+    /// The packet which initializes the recipe book when the player joins.
+    #[must_use]
+    pub fn init_packet(&self) -> UnlockRecipesS2c {
+        self.packet(Action::Init, self.recipes.iter().cloned().collect())
+    }
+
+    /// The packets for the recipes unlocked and locked since the last call.
+    pub fn take_changes(&mut self) -> Vec<UnlockRecipesS2c> {
+        let unlocked = std::mem::take(&mut self.unlocked);
+        let locked = std::mem::take(&mut self.locked);
+
+        [(Action::Add, unlocked), (Action::Remove, locked)]
+            .into_iter()
+            .filter(|(_, recipe_ids)| !recipe_ids.is_empty())
+            .map(|(action, recipe_ids)| self.packet(action, recipe_ids))
+            .collect()
+    }
+
+    fn packet(&self, action: Action, recipe_ids: Vec<String>) -> UnlockRecipesS2c {
+        UnlockRecipesS2c {
+            action,
+            crafting_recipe_book: self.book(RecipeBook::Crafting),
+            smelting_recipe_book: self.book(RecipeBook::Furnace),
+            blast_furnace_recipe_book: self.book(RecipeBook::BlastFurnace),
+            smoker_recipe_book: self.book(RecipeBook::Smoker),
+            recipe_ids_1: recipe_ids,
+            recipe_ids_2: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_in_one_tick_cancel_out() {
+        let mut recipes = UnlockedRecipes::default();
+
+        assert!(recipes.unlock("hyperion:stick"));
+        assert!(!recipes.unlock("hyperion:stick"));
+        assert!(recipes.unlock("hyperion:plank"));
+        assert!(recipes.lock("hyperion:plank"));
+
+        let changes = recipes.take_changes();
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].action, Action::Add);
+        assert_eq!(changes[0].recipe_ids_1, ["hyperion:stick"]);
+
+        assert!(recipes.lock("hyperion:stick"));
+        assert!(recipes.unlock("hyperion:stick"));
+
+        assert!(recipes.take_changes().is_empty());
+        assert!(recipes.contains("hyperion:stick"));
+        assert!(!recipes.contains("hyperion:plank"));
+    }
+}
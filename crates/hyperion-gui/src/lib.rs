@@ -1,23 +1,49 @@
-#![feature(thread_local)]
-
-use std::{borrow::Cow, cell::Cell, collections::HashMap};
-
-use flecs_ecs::core::{Entity, EntityView, EntityViewGet, WorldGet, WorldProvider};
+//! Menus made of items which run a callback when they are clicked.
+//!
+//! ```ignore
+//! let clicks = Arc::new(AtomicU32::new(0));
+//!
+//! let mut gui = Gui::new(WindowType::Generic9x3, "Menu");
+//!
+//! gui.add_item(
+//!     13,
+//!     GuiItem::new(ItemStack::new(ItemKind::GoldIngot, 1, None)).on_click(move |click| {
+//!         let clicks = clicks.fetch_add(1, Ordering::Relaxed) + 1;
+//!         click.gui.add_item(13, counter(clicks)).unwrap();
+//!     }),
+//! )?;
+//!
+//! player.open_gui(gui);
+//! ```
+//!
+//! The menu a player has open is stored on them as [`OpenGui`], so it goes away when they close
+//! it. Players cannot take items out of a menu: after every click the whole window is drawn again,
+//! which also undoes whatever the client predicted.
+
+use std::{borrow::Cow, ops::Range, sync::Arc};
+
+use flecs_ecs::prelude::*;
 use hyperion::{
-    net::{Compose, ConnectionId},
+    net::{Compose, ConnectionId, DataBundle},
+    simulation::handlers::PacketSwitchQuery,
     storage::GlobalEventHandlers,
     valence_protocol::{
-        ItemStack, VarInt,
+        ItemKind, ItemStack, VarInt,
         packets::play::{
+            self,
             click_slot_c2s::ClickMode,
-            close_screen_s2c::CloseScreenS2c,
-            inventory_s2c::InventoryS2c,
             open_screen_s2c::{OpenScreenS2c, WindowType},
         },
         text::IntoText,
     },
 };
+use hyperion_inventory::{
+    CarriedItem, PlayerInventory,
+    container::{OpenContainer, container_slot_count, shows_player_inventory},
+};
+use hyperion_item::builder::ItemBuilder;
 use serde::{Deserialize, Serialize};
+use tracing::error;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InventoryItem {
@@ -27,179 +53,508 @@ pub struct InventoryItem {
     pub quantity: u32,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum ContainerType {
-    Chest,
-    ShulkerBox,
-    Furnace,
-    Dispenser,
-    Hopper,
-}
+/// Called when an item of a [`Gui`] is clicked.
+pub type ClickFn = Arc<dyn Fn(&mut GuiClick<'_, '_>) + Send + Sync>;
+
+/// Called with the player when they close a [`Gui`], or it is closed for them.
+pub type CloseFn = Arc<dyn Fn(EntityView<'_>) + Send + Sync>;
+
+/// The player inventory slots shown below the menu.
+const PLAYER_SLOTS: Range<usize> = 9..45;
 
 #[derive(Clone)]
 pub struct Gui {
-    items: HashMap<usize, GuiItem>,
-    size: usize,
+    window_type: WindowType,
     title: String,
-    window_id: u8,
-    container_type: ContainerType,
+    items: Vec<Option<GuiItem>>,
+    pages: Option<Pages>,
+    on_close: Option<CloseFn>,
+    dirty: bool,
 }
 
 #[derive(Clone)]
 pub struct GuiItem {
     item: ItemStack,
-    on_click: fn(Entity, ClickMode),
+    on_click: Option<ClickFn>,
+}
+
+/// Where the entries of a paginated [`Gui`] are drawn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageLayout {
+    /// The slots showing the entries of the current page.
+    pub slots: Range<u16>,
+    /// The slot of the button to the previous page.
+    pub previous: u16,
+    /// The slot of the button to the next page.
+    pub next: u16,
+}
+
+#[derive(Clone)]
+struct Pages {
+    entries: Vec<GuiItem>,
+    layout: PageLayout,
+    page: usize,
+}
+
+/// A click on an item of a [`Gui`].
+pub struct GuiClick<'a, 'b> {
+    /// The menu, which can be changed to draw something else.
+    pub gui: &'a mut Gui,
+    pub query: &'a mut PacketSwitchQuery<'b>,
+    pub slot: u16,
+    pub mode: ClickMode,
+    pub button: i8,
+    close: bool,
+}
+
+impl GuiClick<'_, '_> {
+    #[must_use]
+    pub const fn player(&self) -> EntityView<'_> {
+        self.query.view
+    }
+
+    /// Closes the menu once the click has been handled.
+    pub const fn close(&mut self) {
+        self.close = true;
+    }
 }
 
-/// Thread-local non-zero id means that it will be very unlikely that one player will have two
-/// of the same IDs at the same time when opening GUIs in succession.
-///
-/// We are skipping 0 because it is reserved for the player's inventory.
-fn non_zero_window_id() -> u8 {
-    #[thread_local]
-    static ID: Cell<u8> = Cell::new(0);
+/// The menu a player has open.
+#[derive(Component)]
+pub struct OpenGui {
+    window_id: u8,
+    gui: Gui,
+}
 
-    ID.set(ID.get().wrapping_add(1));
+impl OpenGui {
+    #[must_use]
+    pub const fn window_id(&self) -> u8 {
+        self.window_id
+    }
 
-    if ID.get() == 0 {
-        ID.set(1);
+    #[must_use]
+    pub const fn gui(&self) -> &Gui {
+        &self.gui
     }
 
-    ID.get()
+    /// Changes made through this are drawn at the end of the tick.
+    pub const fn gui_mut(&mut self) -> &mut Gui {
+        &mut self.gui
+    }
 }
 
+/// Event which closes the menu a player has open.
+#[derive(Component)]
+pub struct CloseGui;
+
 impl Gui {
     #[must_use]
-    pub fn new(size: usize, title: String, container_type: ContainerType) -> Self {
+    pub fn new(window_type: WindowType, title: impl Into<String>) -> Self {
+        let size = usize::from(container_slot_count(window_type));
+
         Self {
-            window_id: non_zero_window_id(),
-            title,
-            size,
-            container_type,
-            items: HashMap::new(),
+            window_type,
+            title: title.into(),
+            items: vec![None; size],
+            pages: None,
+            on_close: None,
+            dirty: true,
         }
     }
 
     #[must_use]
-    pub const fn get_window_type(&self) -> WindowType {
-        match self.container_type {
-            ContainerType::Chest => WindowType::Generic9x3,
-            ContainerType::ShulkerBox => WindowType::ShulkerBox,
-            ContainerType::Furnace => WindowType::Furnace,
-            ContainerType::Dispenser => WindowType::Generic3x3,
-            ContainerType::Hopper => WindowType::Hopper,
-        }
+    pub const fn window_type(&self) -> WindowType {
+        self.window_type
+    }
+
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The number of slots in the menu, not counting the player inventory below it.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.items.len()
     }
 
     pub fn add_item(&mut self, slot: usize, item: GuiItem) -> Result<(), String> {
-        if slot >= self.size {
+        let size = self.size();
+
+        let Some(existing) = self.items.get_mut(slot) else {
+            return Err(format!(
+                "Slot {slot} is out of bounds for GUI of size {size}"
+            ));
+        };
+
+        *existing = Some(item);
+        self.dirty = true;
+
+        Ok(())
+    }
+
+    pub fn remove_item(&mut self, slot: usize) -> Option<GuiItem> {
+        let removed = self.items.get_mut(slot)?.take();
+        self.dirty = true;
+        removed
+    }
+
+    #[must_use]
+    pub fn item(&self, slot: usize) -> Option<&GuiItem> {
+        self.items.get(slot)?.as_ref()
+    }
+
+    /// Shows `entries` a page at a time in the slots of `layout`. The items added to those slots
+    /// are covered by the entries.
+    pub fn set_pages(&mut self, entries: Vec<GuiItem>, layout: PageLayout) -> Result<(), String> {
+        let size = self.size();
+
+        let in_bounds = |slot: u16| usize::from(slot) < size;
+
+        if layout.slots.is_empty()
+            || !in_bounds(layout.slots.end - 1)
+            || !in_bounds(layout.previous)
+            || !in_bounds(layout.next)
+        {
             return Err(format!(
-                "Slot {} is out of bounds for GUI of size {}",
-                slot, self.size
+                "Page layout {layout:?} does not fit a GUI of size {size}"
             ));
         }
 
-        self.items.insert(slot, item);
+        self.pages = Some(Pages {
+            entries,
+            layout,
+            page: 0,
+        });
+        self.dirty = true;
 
         Ok(())
     }
 
-    pub fn draw<'a>(&'a self, system: EntityView<'_>, player: Entity) {
-        let container_items: Cow<'a, [ItemStack]> = (0..self.size)
+    /// The page shown, starting at 0.
+    #[must_use]
+    pub fn page(&self) -> usize {
+        self.pages.as_ref().map_or(0, |pages| pages.page)
+    }
+
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.pages.as_ref().map_or(1, Pages::count)
+    }
+
+    pub fn set_page(&mut self, page: usize) {
+        if let Some(pages) = &mut self.pages {
+            pages.page = page.min(pages.count() - 1);
+            self.dirty = true;
+        }
+    }
+
+    #[must_use]
+    pub fn on_close(mut self, on_close: impl Fn(EntityView<'_>) + Send + Sync + 'static) -> Self {
+        self.on_close = Some(Arc::new(on_close));
+        self
+    }
+
+    /// Draws the items of the menu, including the page buttons and entries.
+    fn slots(&self) -> Vec<ItemStack> {
+        let mut slots: Vec<_> = (0..self.size())
             .map(|slot| {
-                self.items
-                    .get(&slot)
-                    .map(|gui_item| gui_item.item.clone())
-                    .unwrap_or_default()
+                self.resolve(slot)
+                    .map_or(ItemStack::EMPTY, |item| item.item.clone())
             })
             .collect();
 
-        let binding = ItemStack::default();
-        let set_content_packet = InventoryS2c {
-            window_id: self.window_id,
-            state_id: VarInt(0),
-            slots: container_items,
-            carried_item: Cow::Borrowed(&binding),
-        };
+        if let Some(pages) = &self.pages {
+            if pages.page > 0 {
+                slots[usize::from(pages.layout.previous)] = page_button("Previous page");
+            }
 
-        let world = system.world();
+            if pages.page + 1 < pages.count() {
+                slots[usize::from(pages.layout.next)] = page_button("Next page");
+            }
+        }
 
-        world.get::<&Compose>(|compose| {
-            player.entity_view(world).get::<&ConnectionId>(|stream| {
-                compose
-                    .unicast(&set_content_packet, *stream, system)
-                    .unwrap();
-            });
-        });
+        slots
     }
 
-    pub fn open(&mut self, system: EntityView<'_>, player: Entity) {
-        let open_screen_packet = OpenScreenS2c {
-            window_id: VarInt(i32::from(self.window_id)),
-            window_type: self.get_window_type(),
-            window_title: self.title.clone().into_cow_text(),
+    /// The item shown in `slot`.
+    fn resolve(&self, slot: usize) -> Option<&GuiItem> {
+        let Some(pages) = &self.pages else {
+            return self.item(slot);
         };
 
-        let world = system.world();
+        let layout = &pages.layout;
 
-        world.get::<&Compose>(|compose| {
-            player.entity_view(world).get::<&ConnectionId>(|stream| {
-                compose
-                    .unicast(&open_screen_packet, *stream, system)
-                    .unwrap();
-            });
-        });
+        let page_slot = u16::try_from(slot)
+            .ok()
+            .filter(|slot| layout.slots.contains(slot));
 
-        self.draw(system, player);
+        let Some(page_slot) = page_slot else {
+            return self.item(slot);
+        };
 
-        world.get::<&mut GlobalEventHandlers>(|event_handlers| {
-            let window_id = self.window_id;
-            let items = self.items.clone();
-            let gui = self.clone();
-            event_handlers.click.register(move |query, event| {
-                let system = query.system;
-                let button = event.mode;
+        let index = pages.page * layout.slots.len() + usize::from(page_slot - layout.slots.start);
+        pages.entries.get(index)
+    }
 
-                if event.window_id != window_id {
-                    return;
-                }
+    /// Turns the page if a page button was clicked, or returns the callback of the clicked item.
+    fn click(&mut self, slot: u16) -> Option<ClickFn> {
+        if let Some(pages) = &self.pages {
+            let page = pages.page;
 
-                let slot = usize::from(event.slot_idx);
-                let Some(item) = items.get(&slot) else {
-                    return;
-                };
+            if slot == pages.layout.previous && page > 0 {
+                self.set_page(page - 1);
+                return None;
+            }
 
-                (item.on_click)(player, button);
-                gui.draw(query.system, player);
+            if slot == pages.layout.next && page + 1 < pages.count() {
+                self.set_page(page + 1);
+                return None;
+            }
+        }
 
-                let inventory = &*query.inventory;
-                let compose = query.compose;
-                let stream = query.io_ref;
+        self.resolve(usize::from(slot))?.on_click.clone()
+    }
 
-                // re-draw the inventory
-                let player_inv = inventory.slots();
+    /// The whole window: the menu followed by the player's main inventory and hotbar.
+    fn window(&self, inventory: &PlayerInventory) -> Vec<ItemStack> {
+        let mut window = self.slots();
 
-                let set_content_packet = InventoryS2c {
-                    window_id: 0,
-                    state_id: VarInt(0),
-                    slots: Cow::Borrowed(player_inv),
-                    carried_item: Cow::Borrowed(&ItemStack::EMPTY),
-                };
+        if shows_player_inventory(self.window_type) {
+            window.extend_from_slice(&inventory.slots()[PLAYER_SLOTS]);
+        }
 
-                compose
-                    .unicast(&set_content_packet, stream, system)
-                    .unwrap();
-            });
-        });
+        window
     }
+}
 
-    pub fn handle_close(&mut self, _player: Entity, _close_packet: CloseScreenS2c) {
-        todo!()
+impl Pages {
+    fn count(&self) -> usize {
+        self.entries.len().div_ceil(self.layout.slots.len()).max(1)
     }
 }
 
+fn page_button(name: &str) -> ItemStack {
+    ItemBuilder::new(ItemKind::Arrow).name(name).build()
+}
+
 impl GuiItem {
-    pub fn new(item: ItemStack, on_click: fn(Entity, ClickMode)) -> Self {
-        Self { item, on_click }
+    #[must_use]
+    pub const fn new(item: ItemStack) -> Self {
+        Self {
+            item,
+            on_click: None,
+        }
+    }
+
+    /// Runs `on_click` whenever the item is clicked. State shared between clicks can be captured,
+    /// e.g. in an [`Arc`].
+    #[must_use]
+    pub fn on_click(
+        mut self,
+        on_click: impl Fn(&mut GuiClick<'_, '_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_click = Some(Arc::new(on_click));
+        self
+    }
+
+    #[must_use]
+    pub const fn item(&self) -> &ItemStack {
+        &self.item
+    }
+}
+
+pub trait GuiExt {
+    /// Opens `gui` for this player, replacing any window they have open.
+    fn open_gui(self, gui: Gui);
+
+    /// Closes the menu the player has open, if any.
+    fn close_gui(self);
+}
+
+impl GuiExt for EntityView<'_> {
+    fn open_gui(self, gui: Gui) {
+        // the client replaces whatever window it shows
+        self.remove::<OpenContainer>();
+
+        let window_id = self.get::<&mut CarriedItem>(CarriedItem::next_window_id);
+
+        self.set(OpenGui { window_id, gui });
+    }
+
+    fn close_gui(self) {
+        self.enqueue(CloseGui);
+    }
+}
+
+fn send_window(
+    compose: &Compose,
+    io: ConnectionId,
+    system: EntityView<'_>,
+    open: &OpenGui,
+    inventory: &PlayerInventory,
+    carried: &mut CarriedItem,
+) -> anyhow::Result<()> {
+    let pkt = play::InventoryS2c {
+        window_id: open.window_id,
+        state_id: VarInt(carried.next_state_id()),
+        slots: Cow::Owned(open.gui.window(inventory)),
+        carried_item: Cow::Borrowed(&carried.stack),
+    };
+
+    compose.unicast(&pkt, io, system)?;
+
+    Ok(())
+}
+
+#[derive(Component)]
+pub struct GuiModule;
+
+impl Module for GuiModule {
+    fn module(world: &World) {
+        world.import::<hyperion_inventory::InventoryModule>();
+        world.component::<OpenGui>();
+        world.component::<CloseGui>();
+
+        observer!(
+            world,
+            flecs::OnSet,
+            &mut OpenGui,
+            &Compose($),
+            &ConnectionId,
+            &PlayerInventory,
+            &mut CarriedItem,
+        )
+        .each_iter(|it, _, (open, compose, io, inventory, carried)| {
+            let system = it.system();
+
+            let mut bundle = DataBundle::new(compose, system);
+
+            let mut run = || {
+                bundle.add_packet(&OpenScreenS2c {
+                    window_id: VarInt(i32::from(open.window_id)),
+                    window_type: open.gui.window_type,
+                    window_title: open.gui.title.as_str().into_cow_text(),
+                })?;
+
+                bundle.add_packet(&play::InventoryS2c {
+                    window_id: open.window_id,
+                    state_id: VarInt(carried.next_state_id()),
+                    slots: Cow::Owned(open.gui.window(inventory)),
+                    carried_item: Cow::Borrowed(&carried.stack),
+                })?;
+
+                bundle.unicast(*io)
+            };
+
+            if let Err(e) = run() {
+                error!("failed to open gui: {e}");
+            }
+
+            open.gui.dirty = false;
+        });
+
+        observer!(
+            world,
+            CloseGui,
+            &Compose($),
+            &ConnectionId,
+            &OpenGui,
+        )
+        .each_iter(|it, row, (compose, io, open)| {
+            let system = it.system();
+            let player = it.entity(row);
+
+            let pkt = play::CloseScreenS2c {
+                window_id: open.window_id,
+            };
+
+            if let Err(e) = compose.unicast(&pkt, *io, system) {
+                error!("failed to close gui: {e}");
+            }
+
+            if let Some(on_close) = &open.gui.on_close {
+                on_close(player);
+            }
+
+            player.remove::<OpenGui>();
+        });
+
+        system!(
+            "draw_guis",
+            world,
+            &Compose($),
+            &mut OpenGui,
+            &PlayerInventory,
+            &mut CarriedItem,
+            &ConnectionId,
+        )
+        .multi_threaded()
+        .kind::<flecs::pipeline::OnStore>()
+        .each_iter(|it, _, (compose, open, inventory, carried, io)| {
+            if !open.gui.dirty {
+                return;
+            }
+
+            if let Err(e) = send_window(compose, *io, it.system(), open, inventory, carried) {
+                error!("failed to draw gui: {e}");
+            }
+
+            open.gui.dirty = false;
+        });
+
+        world.get::<&mut GlobalEventHandlers>(|handlers| {
+            handlers.click.register(|query, event| {
+                let view = query.view;
+
+                view.try_get::<&mut OpenGui>(|open| {
+                    if open.window_id != event.window_id {
+                        return;
+                    }
+
+                    // draw the whole window again to undo what the client predicted
+                    open.gui.dirty = true;
+
+                    let Some(on_click) = open.gui.click(event.slot_idx) else {
+                        return;
+                    };
+
+                    let mut click = GuiClick {
+                        gui: &mut open.gui,
+                        query,
+                        slot: event.slot_idx,
+                        mode: event.mode,
+                        button: event.button,
+                        close: false,
+                    };
+
+                    on_click(&mut click);
+
+                    if click.close {
+                        view.close_gui();
+                    }
+                });
+            });
+
+            handlers.close.register(|query, event| {
+                let view = query.view;
+
+                let closed = view.try_get::<&OpenGui>(|open| {
+                    if open.window_id != event.window_id {
+                        return false;
+                    }
+
+                    if let Some(on_close) = &open.gui.on_close {
+                        on_close(view);
+                    }
+
+                    true
+                });
+
+                if closed == Some(true) {
+                    view.remove::<OpenGui>();
+                }
+            });
+        });
     }
 }
@@ -1,6 +1,13 @@
-use valence_protocol::ItemStack;
+use std::ops::Range;
 
-use super::{OFFHAND_SLOT, PlayerInventory, slot_index_from_hand};
+use hyperion_crafting::{CookingKind, CraftingRegistry};
+use valence_protocol::{ItemKind, ItemStack, packets::play::open_screen_s2c::WindowType};
+
+use super::{
+    CarriedItem, InventoryAccessError, OFFHAND_SLOT, PlayerInventory,
+    container::{Container, shows_player_inventory},
+    furnace, slot_index_from_hand,
+};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum FullMouseButton {
@@ -42,8 +49,12 @@ pub enum InventoryAction {
     },
 
     /// 'Q' key
-    Drop,
-    CtrlDrop,
+    Drop {
+        slot: u16,
+    },
+    CtrlDrop {
+        slot: u16,
+    },
 
     DragStart {
         button: FullMouseButton,
@@ -64,108 +75,1020 @@ pub enum InventoryAction {
     },
 }
 
-pub struct InventoryAndCursor {
-    pub inventory: PlayerInventory,
-    pub cursor: ItemStack,
+pub const CRAFTING_RESULT_SLOT: u16 = 0;
+const CRAFTING_GRID_SLOTS: Range<u16> = 1..5;
+const CRAFTING_TABLE_GRID_SLOTS: Range<u16> = 1..10;
+const ARMOR_SLOTS: Range<u16> = 5..9;
+const MAIN_SLOTS: Range<u16> = 9..36;
+const HOTBAR_SLOTS: Range<u16> = 36..45;
+const STORAGE_SLOTS: Range<u16> = 9..45;
+const SLOT_COUNT: u16 = 46;
+
+/// A drag which has been started but not yet released.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Drag {
+    button: FullMouseButton,
+    slots: Vec<u16>,
+}
+
+/// Applies [`InventoryAction`]s to a player's inventory and the item they are carrying, the same
+/// way the vanilla client predicts them.
+pub struct InventoryAndCursor<'a> {
+    pub inventory: &'a mut PlayerInventory,
+    pub carried: &'a mut CarriedItem,
+    pub crafting_registry: &'a CraftingRegistry,
+}
+
+/// Applies [`InventoryAction`]s to a window showing a [`Container`] above the player's inventory.
+pub struct ContainerAndCursor<'a> {
+    pub container: &'a mut Container,
+    pub inventory: &'a mut PlayerInventory,
+    pub carried: &'a mut CarriedItem,
+    pub crafting_registry: &'a CraftingRegistry,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Amount {
-    TrySingle,
-    All,
+fn can_stack(a: &ItemStack, b: &ItemStack) -> bool {
+    a.item == b.item && a.nbt == b.nbt
 }
 
-fn take_one(item: &mut ItemStack) -> ItemStack {
-    if item.is_empty() {
+fn remove(stack: &mut ItemStack, count: i8) -> ItemStack {
+    let count = count.min(stack.count);
+
+    if count <= 0 {
         return ItemStack::EMPTY;
     }
 
-    item.count -= 1;
+    let removed = stack.clone().with_count(count);
+
+    stack.count -= count;
+
+    if stack.count == 0 {
+        *stack = ItemStack::EMPTY;
+    }
+
+    removed
+}
+
+/// Moves as much of `from` as fits into `into`, which must be empty or stackable with `from`.
+fn merge(from: &mut ItemStack, into: &mut ItemStack, limit: i8) {
+    if from.is_empty() {
+        return;
+    }
+
+    if into.is_empty() {
+        *into = remove(from, limit.min(from.item.max_stack()));
+        return;
+    }
+
+    let space = into.item.max_stack() - into.count;
+    into.count += remove(from, limit.min(space)).count;
+}
+
+/// The armor slot an item can be equipped in, if any.
+fn armor_slot(item: ItemKind) -> Option<u16> {
+    let name = item.to_str();
 
-    ItemStack::new(item.item, 1, item.nbt.clone())
+    if name.ends_with("_helmet") || item == ItemKind::CarvedPumpkin {
+        Some(PlayerInventory::HELMET_SLOT)
+    } else if name.ends_with("_chestplate") || item == ItemKind::Elytra {
+        Some(PlayerInventory::CHESTPLATE_SLOT)
+    } else if name.ends_with("_leggings") {
+        Some(PlayerInventory::LEGGINGS_SLOT)
+    } else if name.ends_with("_boots") {
+        Some(PlayerInventory::BOOTS_SLOT)
+    } else {
+        None
+    }
 }
 
-impl InventoryAndCursor {
-    fn swap_cursor(&mut self, slot: u16, mode: Amount) {
-        let in_inventory = self.inventory.get_mut(slot).unwrap();
+/// The slots of a window, numbered the way the client numbers them.
+trait Window {
+    /// How many slots the client can click.
+    fn len(&self) -> u16;
+
+    fn get(&self, slot: u16) -> Option<&ItemStack>;
+
+    fn get_mut(&mut self, slot: u16) -> Option<&mut ItemStack>;
+
+    /// Whether `stack` may be put into `slot` by the player.
+    fn accepts(&self, slot: u16, stack: &ItemStack) -> bool;
+
+    /// Whether items can only be taken out of `slot`, like the crafting result.
+    fn is_result(&self, slot: u16) -> bool;
+
+    /// The slot of the hotbar slot `key` (0-8), if the window shows the hotbar.
+    fn hotbar(&self, key: u8) -> Option<u16>;
+
+    /// The slot holding the offhand. It is not always shown, but can still be swapped with.
+    fn offhand(&self) -> Option<u16>;
 
-        let cursor = &mut self.cursor;
+    /// The slots of the crafting grid, which is empty if the window has none. Its result is in
+    /// [`CRAFTING_RESULT_SLOT`].
+    fn crafting_grid(&self) -> Range<u16>;
 
-        match mode {
-            Amount::TrySingle => {
-                if in_inventory.is_empty() {
-                    let single_cursor = take_one(cursor);
-                    *in_inventory = single_cursor;
+    /// What the items in the crafting grid craft.
+    fn crafting_result(&self, registry: &CraftingRegistry) -> ItemStack;
+
+    /// The slots a shift click on `slot` moves `stack` into, in order of preference, and whether
+    /// each range is filled back to front.
+    fn shift_targets(&self, slot: u16, stack: &ItemStack) -> Vec<(Range<u16>, bool)>;
+
+    /// How many items of `stack` fit into `slots`.
+    fn space_for(&self, stack: &ItemStack, slots: Range<u16>) -> i32 {
+        let max = i32::from(stack.item.max_stack());
+
+        slots
+            .filter_map(|slot| self.get(slot))
+            .map(|existing| {
+                if existing.is_empty() {
+                    max
+                } else if can_stack(existing, stack) {
+                    max - i32::from(existing.count)
                 } else {
-                    // we must swap as we cannot just put one item down
-                    core::mem::swap(in_inventory, cursor);
+                    0
                 }
+            })
+            .sum()
+    }
+
+    /// Moves `stack` into `slots`, first onto matching stacks and then into empty slots.
+    fn insert(&mut self, stack: &mut ItemStack, slots: Range<u16>, reverse: bool) {
+        let order = |slots: Range<u16>| -> Vec<u16> {
+            if reverse {
+                slots.rev().collect()
+            } else {
+                slots.collect()
             }
-            Amount::All => {
-                // swap the cursor and the item in the slot
-                core::mem::swap(in_inventory, cursor);
+        };
+
+        for fill_empty in [false, true] {
+            for slot in order(slots.clone()) {
+                if stack.is_empty() {
+                    return;
+                }
+
+                let Some(existing) = self.get(slot) else {
+                    continue;
+                };
+
+                let matches = if existing.is_empty() {
+                    fill_empty && self.accepts(slot, stack)
+                } else {
+                    can_stack(existing, stack) && existing.count < existing.item.max_stack()
+                };
+
+                if matches {
+                    if let Some(existing) = self.get_mut(slot) {
+                        merge(stack, existing, i8::MAX);
+                    }
+                }
             }
         }
     }
+}
 
-    #[expect(clippy::unused_self, unused, reason = "todo")]
-    const fn drop_cursor(&self, mode: Amount) {
-        // todo: we will also need to figure out a way to create entity spawn events from this
+/// The window of the player's own inventory.
+struct PlayerWindow<'a>(&'a mut PlayerInventory);
+
+impl Window for PlayerWindow<'_> {
+    fn len(&self) -> u16 {
+        SLOT_COUNT
     }
 
-    pub fn apply(&mut self, action: InventoryAction) {
-        match action {
-            InventoryAction::NormalClick { button, slot } => {
-                let mode = match button {
-                    MouseButton::Left => Amount::All,
-                    MouseButton::Right => Amount::TrySingle,
-                };
+    fn get(&self, slot: u16) -> Option<&ItemStack> {
+        self.0.get(slot).ok()
+    }
 
-                self.swap_cursor(slot, mode);
+    fn get_mut(&mut self, slot: u16) -> Option<&mut ItemStack> {
+        self.0.get_mut(slot).ok()
+    }
+
+    fn accepts(&self, slot: u16, stack: &ItemStack) -> bool {
+        if stack.is_empty() {
+            return slot != CRAFTING_RESULT_SLOT;
+        }
+
+        match slot {
+            CRAFTING_RESULT_SLOT => false,
+            slot if ARMOR_SLOTS.contains(&slot) => armor_slot(stack.item) == Some(slot),
+            _ => true,
+        }
+    }
+
+    fn is_result(&self, slot: u16) -> bool {
+        slot == CRAFTING_RESULT_SLOT
+    }
+
+    fn hotbar(&self, key: u8) -> Option<u16> {
+        Some(slot_index_from_hand(key))
+    }
+
+    fn offhand(&self) -> Option<u16> {
+        Some(OFFHAND_SLOT)
+    }
+
+    fn crafting_grid(&self) -> Range<u16> {
+        CRAFTING_GRID_SLOTS
+    }
+
+    fn crafting_result(&self, registry: &CraftingRegistry) -> ItemStack {
+        self.0.crafting_result(registry)
+    }
+
+    fn shift_targets(&self, slot: u16, stack: &ItemStack) -> Vec<(Range<u16>, bool)> {
+        if slot == CRAFTING_RESULT_SLOT {
+            return vec![(STORAGE_SLOTS, true)];
+        }
+
+        let mut targets = Vec::new();
+
+        if STORAGE_SLOTS.contains(&slot) {
+            if let Some(armor) = armor_slot(stack.item) {
+                targets.push((armor..armor + 1, false));
             }
-            InventoryAction::OutsideClick { button } => {
-                let mode = match button {
-                    MouseButton::Left => Amount::All,
-                    MouseButton::Right => Amount::TrySingle,
-                };
+        }
+
+        let rest = if MAIN_SLOTS.contains(&slot) {
+            HOTBAR_SLOTS
+        } else if HOTBAR_SLOTS.contains(&slot) {
+            MAIN_SLOTS
+        } else {
+            STORAGE_SLOTS
+        };
+
+        targets.push((rest, false));
+        targets
+    }
+}
+
+/// The window of a container, followed by the player's main inventory and hotbar. The offhand
+/// comes right after the last shown slot.
+struct ContainerWindow<'a> {
+    container: &'a mut Container,
+    inventory: &'a mut PlayerInventory,
+    registry: &'a CraftingRegistry,
+}
 
-                self.drop_cursor(mode);
+impl ContainerWindow<'_> {
+    fn is_crafting_table(&self) -> bool {
+        self.container.kind() == WindowType::Crafting
+    }
+
+    const fn cooking_kind(&self) -> Option<CookingKind> {
+        furnace::cooking_kind(self.container.kind())
+    }
+
+    fn player_slot(&self, slot: u16) -> Option<u16> {
+        if Some(slot) == self.offhand() {
+            return Some(OFFHAND_SLOT);
+        }
+
+        self.container.player_slot(slot)
+    }
+}
+
+impl Window for ContainerWindow<'_> {
+    fn len(&self) -> u16 {
+        let player = if shows_player_inventory(self.container.kind()) {
+            STORAGE_SLOTS.end - STORAGE_SLOTS.start
+        } else {
+            0
+        };
+
+        self.container.slot_count() + player
+    }
+
+    fn get(&self, slot: u16) -> Option<&ItemStack> {
+        if let Some(player_slot) = self.player_slot(slot) {
+            return self.inventory.get(player_slot).ok();
+        }
+
+        self.container.get(slot).ok()
+    }
+
+    fn get_mut(&mut self, slot: u16) -> Option<&mut ItemStack> {
+        if let Some(player_slot) = self.player_slot(slot) {
+            return self.inventory.get_mut(player_slot).ok();
+        }
+
+        self.container.get_mut(slot).ok()
+    }
+
+    fn accepts(&self, slot: u16, stack: &ItemStack) -> bool {
+        if stack.is_empty() {
+            return true;
+        }
+
+        if self.cooking_kind().is_some() {
+            return match slot {
+                furnace::FUEL_SLOT => {
+                    stack.item == ItemKind::Bucket || furnace::fuel_time(stack.item).is_some()
+                }
+                furnace::OUTPUT_SLOT => false,
+                _ => true,
+            };
+        }
+
+        !self.is_result(slot)
+    }
+
+    fn is_result(&self, slot: u16) -> bool {
+        self.is_crafting_table() && slot == CRAFTING_RESULT_SLOT
+    }
+
+    fn hotbar(&self, key: u8) -> Option<u16> {
+        self.container.window_slot(slot_index_from_hand(key))
+    }
+
+    fn offhand(&self) -> Option<u16> {
+        shows_player_inventory(self.container.kind()).then(|| self.len())
+    }
+
+    fn crafting_grid(&self) -> Range<u16> {
+        if self.is_crafting_table() {
+            CRAFTING_TABLE_GRID_SLOTS
+        } else {
+            0..0
+        }
+    }
+
+    fn crafting_result(&self, registry: &CraftingRegistry) -> ItemStack {
+        self.container.crafting_result(registry)
+    }
+
+    fn shift_targets(&self, slot: u16, stack: &ItemStack) -> Vec<(Range<u16>, bool)> {
+        let container = 0..self.container.slot_count();
+        let player = container.end..self.len();
+
+        if container.contains(&slot) {
+            // results go to the hotbar first like items taken out of a chest, ingredients do not
+            let reverse = if self.cooking_kind().is_some() {
+                slot == furnace::OUTPUT_SLOT
+            } else {
+                !self.crafting_grid().contains(&slot)
+            };
+
+            return vec![(player, reverse)];
+        }
+
+        if let Some(kind) = self.cooking_kind() {
+            if self.registry.get_smelting(kind, stack.item).is_some() {
+                return vec![(furnace::INPUT_SLOT..furnace::INPUT_SLOT + 1, false)];
+            }
+
+            if furnace::fuel_time(stack.item).is_some() {
+                return vec![(furnace::FUEL_SLOT..furnace::FUEL_SLOT + 1, false)];
+            }
+        } else if !self.is_crafting_table() {
+            return vec![(container, false)];
+        }
+
+        // other items are not moved into the grid or furnace, only between the main inventory and
+        // hotbar
+        let hotbar = player.end - 9..player.end;
+
+        if hotbar.contains(&slot) {
+            vec![(player.start..hotbar.start, false)]
+        } else {
+            vec![(hotbar, false)]
+        }
+    }
+}
+
+/// Applies the actions every window handles the same way.
+struct Clicker<'a, W> {
+    window: W,
+    carried: &'a mut CarriedItem,
+}
+
+impl InventoryAndCursor<'_> {
+    /// Applies the action. Returns the stack the player threw out of the inventory, if any, which
+    /// should be spawned in the world.
+    pub fn apply(
+        &mut self,
+        action: InventoryAction,
+    ) -> Result<Option<ItemStack>, InventoryAccessError> {
+        Clicker {
+            window: PlayerWindow(self.inventory),
+            carried: self.carried,
+        }
+        .apply_crafting(action, self.crafting_registry)
+    }
+}
+
+impl ContainerAndCursor<'_> {
+    /// Applies the action. Returns the stack the player threw out of the window, if any, which
+    /// should be spawned in the world.
+    ///
+    /// The result slot of a crafting table is updated to what the grid crafts afterwards.
+    pub fn apply(
+        &mut self,
+        action: InventoryAction,
+    ) -> Result<Option<ItemStack>, InventoryAccessError> {
+        let dropped = Clicker {
+            window: ContainerWindow {
+                container: self.container,
+                inventory: self.inventory,
+                registry: self.crafting_registry,
+            },
+            carried: self.carried,
+        }
+        .apply_crafting(action, self.crafting_registry);
+
+        self.container
+            .update_crafting_result(self.crafting_registry);
+
+        dropped
+    }
+}
+
+impl<W: Window> Clicker<'_, W> {
+    /// Applies the action, crafting if the crafting result is clicked.
+    fn apply_crafting(
+        &mut self,
+        action: InventoryAction,
+        registry: &CraftingRegistry,
+    ) -> Result<Option<ItemStack>, InventoryAccessError> {
+        if self.window.crafting_grid().is_empty() {
+            return self.apply(action);
+        }
+
+        match action {
+            InventoryAction::NormalClick {
+                slot: CRAFTING_RESULT_SLOT,
+                ..
+            } => {
+                self.carried.drag = None;
+                self.take_crafting_result(registry);
+                Ok(None)
             }
             InventoryAction::ShiftClick {
-                button: MouseButton::Left | MouseButton::Right,
+                slot: CRAFTING_RESULT_SLOT,
                 ..
             } => {
-                // identical behavior so we combine branches
+                self.carried.drag = None;
+                self.craft_into_inventory(registry);
+                Ok(None)
+            }
+            _ => self.apply(action),
+        }
+    }
+
+    /// Crafts one result onto the cursor if it fits there.
+    fn take_crafting_result(&mut self, registry: &CraftingRegistry) {
+        let result = self.window.crafting_result(registry);
+
+        if result.is_empty() {
+            return;
+        }
+
+        let cursor = &self.carried.stack;
+
+        let fits = cursor.is_empty()
+            || (can_stack(cursor, &result)
+                && cursor.count + result.count <= result.item.max_stack());
+
+        if !fits {
+            return;
+        }
+
+        self.consume_ingredients();
+
+        if self.carried.stack.is_empty() {
+            self.carried.stack = result;
+        } else {
+            self.carried.stack.count += result.count;
+        }
+    }
+
+    /// Crafts as many results as fit into the inventory.
+    fn craft_into_inventory(&mut self, registry: &CraftingRegistry) {
+        loop {
+            let mut result = self.window.crafting_result(registry);
+
+            if result.is_empty() {
+                return;
+            }
+
+            let targets = self.window.shift_targets(CRAFTING_RESULT_SLOT, &result);
+
+            let space: i32 = targets
+                .iter()
+                .map(|(slots, _)| self.window.space_for(&result, slots.clone()))
+                .sum();
+
+            if space < i32::from(result.count) {
+                return;
+            }
+
+            self.consume_ingredients();
+
+            for (slots, reverse) in targets {
+                self.window.insert(&mut result, slots, reverse);
+            }
+        }
+    }
+
+    fn consume_ingredients(&mut self) {
+        for slot in self.window.crafting_grid() {
+            if let Some(stack) = self.window.get_mut(slot) {
+                remove(stack, 1);
+            }
+        }
+    }
+
+    fn check(&self, slot: u16) -> Result<(), InventoryAccessError> {
+        if slot < self.window.len() {
+            Ok(())
+        } else {
+            Err(InventoryAccessError::InvalidSlot { index: slot })
+        }
+    }
+
+    fn apply(
+        &mut self,
+        action: InventoryAction,
+    ) -> Result<Option<ItemStack>, InventoryAccessError> {
+        // any other click cancels a drag
+        if !matches!(
+            action,
+            InventoryAction::DragAdd { .. } | InventoryAction::DragEnd { .. }
+        ) {
+            self.carried.drag = None;
+        }
+
+        match action {
+            InventoryAction::NormalClick { button, slot } => {
+                self.check(slot)?;
+                self.click(slot, button);
+            }
+            InventoryAction::OutsideClick { button } => {
+                let count = match button {
+                    MouseButton::Left => i8::MAX,
+                    MouseButton::Right => 1,
+                };
+
+                return Ok(Some(remove(&mut self.carried.stack, count)).filter(|s| !s.is_empty()));
+            }
+            InventoryAction::ShiftClick { slot, .. } => {
+                self.check(slot)?;
+                self.shift_click(slot);
             }
             InventoryAction::NumberKey { key, slot } => {
-                let other = slot_index_from_hand(key - 1);
-                self.inventory.swap(slot, other);
+                self.check(slot)?;
+                self.swap_with(slot, self.window.hotbar(key - 1));
             }
             InventoryAction::OffhandSwap { slot } => {
-                self.inventory.swap(slot, OFFHAND_SLOT);
+                self.check(slot)?;
+                self.swap_with(slot, self.window.offhand());
             }
-            InventoryAction::MiddleClick { .. } => {
-                unimplemented!("Middle click");
+            InventoryAction::Drop { slot } | InventoryAction::CtrlDrop { slot } => {
+                self.check(slot)?;
+
+                if self.window.is_result(slot) || !self.carried.stack.is_empty() {
+                    return Ok(None);
+                }
+
+                let count = if matches!(action, InventoryAction::Drop { .. }) {
+                    1
+                } else {
+                    i8::MAX
+                };
+
+                let Some(stack) = self.window.get_mut(slot) else {
+                    return Ok(None);
+                };
+
+                let dropped = remove(stack, count);
+                return Ok(Some(dropped).filter(|s| !s.is_empty()));
             }
-            InventoryAction::Drop | InventoryAction::CtrlDrop => {
-                // Implement drop logic here
-                self.drop_cursor(Amount::All);
+            InventoryAction::DragStart { button } => {
+                self.carried.drag = Some(Drag {
+                    button,
+                    slots: Vec::new(),
+                });
             }
-            InventoryAction::DragStart { .. } => {
-                unimplemented!("Drag start");
+            InventoryAction::DragAdd { button, slot } => {
+                self.check(slot)?;
+                self.drag_add(button, slot);
             }
-            InventoryAction::DragAdd { .. } => {
-                unimplemented!("Drag add");
+            InventoryAction::DragEnd { button } => self.drag_end(button),
+            InventoryAction::DoubleClick { slot } => {
+                self.check(slot)?;
+                self.collect_to_cursor();
             }
-            InventoryAction::DragEnd { .. } => {
-                unimplemented!("Drag end");
+            // only creative players can clone stacks, and they edit their inventory with
+            // `CreativeInventoryActionC2s` instead. Picking up in reverse is impossible in vanilla.
+            InventoryAction::MiddleClick { .. } | InventoryAction::PickupAllReverse { .. } => {}
+        }
+
+        Ok(None)
+    }
+
+    fn click(&mut self, slot: u16, button: MouseButton) {
+        let accepted = self.window.accepts(slot, &self.carried.stack);
+        let cursor = &mut self.carried.stack;
+        let Some(stack) = self.window.get_mut(slot) else {
+            return;
+        };
+
+        match button {
+            MouseButton::Left => {
+                if cursor.is_empty() || stack.is_empty() || !can_stack(cursor, stack) {
+                    if accepted {
+                        core::mem::swap(stack, cursor);
+                    }
+                } else if accepted {
+                    merge(cursor, stack, i8::MAX);
+                } else {
+                    // slots items only come out of, like the furnace output, fill the cursor
+                    merge(stack, cursor, i8::MAX);
+                }
             }
-            InventoryAction::DoubleClick { .. } => {
-                unimplemented!("Double click");
+            MouseButton::Right => {
+                if cursor.is_empty() {
+                    let half = (stack.count + 1) / 2;
+                    *cursor = remove(stack, half);
+                } else if accepted {
+                    if stack.is_empty() || can_stack(cursor, stack) {
+                        merge(cursor, stack, 1);
+                    } else {
+                        core::mem::swap(stack, cursor);
+                    }
+                }
             }
-            InventoryAction::PickupAllReverse { .. } => {
-                unimplemented!("Pickup all reverse"); // impossible in vanilla
+        }
+    }
+
+    fn shift_click(&mut self, slot: u16) {
+        let Some(stack) = self.window.get_mut(slot) else {
+            return;
+        };
+
+        let mut stack = core::mem::replace(stack, ItemStack::EMPTY);
+
+        if stack.is_empty() {
+            return;
+        }
+
+        for (slots, reverse) in self.window.shift_targets(slot, &stack) {
+            self.window.insert(&mut stack, slots, reverse);
+        }
+
+        // whatever did not fit stays where it was
+        if let Some(existing) = self.window.get_mut(slot) {
+            *existing = stack;
+        }
+    }
+
+    fn swap_with(&mut self, slot: u16, other: Option<u16>) {
+        let Some(other) = other else {
+            return;
+        };
+
+        if self.window.is_result(slot) || slot == other {
+            return;
+        }
+
+        let (Some(a), Some(b)) = (self.window.get(slot), self.window.get(other)) else {
+            return;
+        };
+
+        if !self.window.accepts(slot, b) || !self.window.accepts(other, a) {
+            return;
+        }
+
+        let (a, b) = (a.clone(), b.clone());
+
+        *self.window.get_mut(slot).unwrap() = b;
+        *self.window.get_mut(other).unwrap() = a;
+    }
+
+    fn drag_add(&mut self, button: FullMouseButton, slot: u16) {
+        let cursor = &self.carried.stack;
+
+        let Some(existing) = self.window.get(slot) else {
+            return;
+        };
+
+        let valid = self.window.accepts(slot, cursor)
+            && (existing.is_empty() || can_stack(existing, cursor))
+            && !cursor.is_empty();
+
+        let Some(drag) = &mut self.carried.drag else {
+            return;
+        };
+
+        let room = match button {
+            // each slot gets at least one item
+            FullMouseButton::Left | FullMouseButton::Right => {
+                drag.slots.len() < usize::try_from(cursor.count).unwrap_or(0)
             }
+            FullMouseButton::Middle => true,
+        };
+
+        if drag.button == button && valid && room && !drag.slots.contains(&slot) {
+            drag.slots.push(slot);
+        }
+    }
+
+    fn drag_end(&mut self, button: FullMouseButton) {
+        let Some(drag) = self.carried.drag.take() else {
+            return;
+        };
+
+        if drag.button != button || drag.slots.is_empty() {
+            return;
         }
+
+        let per_slot = match button {
+            FullMouseButton::Left => {
+                let slots = i8::try_from(drag.slots.len()).unwrap_or(i8::MAX);
+                self.carried.stack.count / slots
+            }
+            FullMouseButton::Right => 1,
+            // creative only
+            FullMouseButton::Middle => return,
+        };
+
+        for slot in drag.slots {
+            let cursor = &mut self.carried.stack;
+
+            if cursor.is_empty() {
+                break;
+            }
+
+            let Some(existing) = self.window.get_mut(slot) else {
+                continue;
+            };
+
+            if existing.is_empty() || can_stack(existing, cursor) {
+                merge(cursor, existing, per_slot);
+            }
+        }
+    }
+
+    /// Collects items matching the cursor into it. Partial stacks are taken before full ones.
+    fn collect_to_cursor(&mut self) {
+        if self.carried.stack.is_empty() {
+            return;
+        }
+
+        for take_full in [false, true] {
+            for slot in 0..self.window.len() {
+                let cursor = &mut self.carried.stack;
+                let max = cursor.item.max_stack();
+
+                if cursor.count >= max {
+                    return;
+                }
+
+                if self.window.is_result(slot) {
+                    continue;
+                }
+
+                let Some(existing) = self.window.get(slot) else {
+                    continue;
+                };
+
+                if existing.is_empty()
+                    || !can_stack(existing, cursor)
+                    || (existing.count >= existing.item.max_stack() && !take_full)
+                {
+                    continue;
+                }
+
+                if let Some(existing) = self.window.get_mut(slot) {
+                    let moved = remove(existing, max - cursor.count);
+                    cursor.count += moved.count;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::packets::play::open_screen_s2c::WindowType;
+
+    use super::*;
+
+    fn stone(count: i8) -> ItemStack {
+        ItemStack::new(ItemKind::Stone, count, None)
+    }
+
+    fn apply(
+        inventory: &mut PlayerInventory,
+        carried: &mut CarriedItem,
+        action: InventoryAction,
+    ) -> Option<ItemStack> {
+        let registry = CraftingRegistry::default();
+
+        InventoryAndCursor {
+            inventory,
+            carried,
+            crafting_registry: &registry,
+        }
+        .apply(action)
+        .unwrap()
+    }
+
+    #[test]
+    fn right_click_picks_up_half() {
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem::default();
+        inventory.set(9, stone(5)).unwrap();
+
+        apply(&mut inventory, &mut carried, InventoryAction::NormalClick {
+            button: MouseButton::Right,
+            slot: 9,
+        });
+
+        assert_eq!(carried.stack, stone(3));
+        assert_eq!(inventory.get(9).unwrap(), &stone(2));
+    }
+
+    #[test]
+    fn left_click_merges_into_slot() {
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem {
+            stack: stone(10),
+            ..CarriedItem::default()
+        };
+        inventory.set(9, stone(60)).unwrap();
+
+        apply(&mut inventory, &mut carried, InventoryAction::NormalClick {
+            button: MouseButton::Left,
+            slot: 9,
+        });
+
+        assert_eq!(inventory.get(9).unwrap(), &stone(64));
+        assert_eq!(carried.stack, stone(6));
+    }
+
+    #[test]
+    fn armor_slot_rejects_other_items() {
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem {
+            stack: stone(1),
+            ..CarriedItem::default()
+        };
+
+        apply(&mut inventory, &mut carried, InventoryAction::NormalClick {
+            button: MouseButton::Left,
+            slot: PlayerInventory::HELMET_SLOT,
+        });
+
+        assert!(inventory.get_helmet().is_empty());
+        assert_eq!(carried.stack, stone(1));
+    }
+
+    #[test]
+    fn shift_click_moves_hotbar_to_main() {
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem::default();
+        inventory.set(36, stone(5)).unwrap();
+
+        apply(&mut inventory, &mut carried, InventoryAction::ShiftClick {
+            button: MouseButton::Left,
+            slot: 36,
+        });
+
+        assert!(inventory.get(36).unwrap().is_empty());
+        assert_eq!(inventory.get(9).unwrap(), &stone(5));
+    }
+
+    #[test]
+    fn left_drag_splits_evenly() {
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem {
+            stack: stone(7),
+            ..CarriedItem::default()
+        };
+
+        let button = FullMouseButton::Left;
+
+        apply(&mut inventory, &mut carried, InventoryAction::DragStart {
+            button,
+        });
+        for slot in [9, 10, 11] {
+            apply(&mut inventory, &mut carried, InventoryAction::DragAdd {
+                button,
+                slot,
+            });
+        }
+        apply(&mut inventory, &mut carried, InventoryAction::DragEnd {
+            button,
+        });
+
+        for slot in [9, 10, 11] {
+            assert_eq!(inventory.get(slot).unwrap(), &stone(2));
+        }
+        assert_eq!(carried.stack, stone(1));
+    }
+
+    #[test]
+    fn outside_click_drops_cursor() {
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem {
+            stack: stone(4),
+            ..CarriedItem::default()
+        };
+
+        let dropped = apply(
+            &mut inventory,
+            &mut carried,
+            InventoryAction::OutsideClick {
+                button: MouseButton::Right,
+            },
+        );
+
+        assert_eq!(dropped, Some(stone(1)));
+        assert_eq!(carried.stack, stone(3));
+    }
+
+    #[test]
+    fn shift_click_moves_container_to_hotbar_end() {
+        let registry = CraftingRegistry::default();
+        let mut container = Container::new(WindowType::Generic9x1, "Chest");
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem::default();
+        container.set(0, stone(5)).unwrap();
+
+        ContainerAndCursor {
+            container: &mut container,
+            inventory: &mut inventory,
+            carried: &mut carried,
+            crafting_registry: &registry,
+        }
+        .apply(InventoryAction::ShiftClick {
+            button: MouseButton::Left,
+            slot: 0,
+        })
+        .unwrap();
+
+        assert!(container.get(0).unwrap().is_empty());
+        assert_eq!(inventory.get(44).unwrap(), &stone(5));
+    }
+
+    #[test]
+    fn left_click_takes_from_furnace_output() {
+        let registry = CraftingRegistry::default();
+        let mut container = Container::new(WindowType::Furnace, "Furnace");
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem {
+            stack: stone(3),
+            ..CarriedItem::default()
+        };
+        container.set(furnace::OUTPUT_SLOT, stone(5)).unwrap();
+
+        ContainerAndCursor {
+            container: &mut container,
+            inventory: &mut inventory,
+            carried: &mut carried,
+            crafting_registry: &registry,
+        }
+        .apply(InventoryAction::NormalClick {
+            button: MouseButton::Left,
+            slot: furnace::OUTPUT_SLOT,
+        })
+        .unwrap();
+
+        assert!(container.get(furnace::OUTPUT_SLOT).unwrap().is_empty());
+        assert_eq!(carried.stack, stone(8));
+    }
+
+    #[test]
+    fn shift_click_crafts_until_grid_is_empty() {
+        let registry = CraftingRegistry::default();
+        let mut table = Container::new(WindowType::Crafting, "Crafting");
+        let mut inventory = PlayerInventory::default();
+        let mut carried = CarriedItem::default();
+
+        let planks = ItemStack::new(ItemKind::OakPlanks, 3, None);
+        table.set(2, planks.clone()).unwrap();
+        table.set(5, planks).unwrap();
+        table.update_crafting_result(&registry);
+
+        assert_eq!(table.get(0).unwrap().item, ItemKind::Stick);
+
+        ContainerAndCursor {
+            container: &mut table,
+            inventory: &mut inventory,
+            carried: &mut carried,
+            crafting_registry: &registry,
+        }
+        .apply(InventoryAction::ShiftClick {
+            button: MouseButton::Left,
+            slot: CRAFTING_RESULT_SLOT,
+        })
+        .unwrap();
+
+        assert_eq!(
+            inventory.get(44).unwrap(),
+            &ItemStack::new(ItemKind::Stick, 12, None)
+        );
+        assert!(table.get(2).unwrap().is_empty());
+        assert!(table.get(0).unwrap().is_empty());
     }
 }
@@ -0,0 +1,393 @@
+//! Inventories which do not belong to a player, such as chests.
+//!
+//! A [`Container`] lives on its own entity. Players look into it through a window: opening it sets
+//! [`OpenContainer`] on the player, which holds the window id the client uses to refer to it. Every
+//! player looking into a container is one of its viewers, and slot changes are sent to all of them.
+//!
+//! A container of [`WindowType::Crafting`] is a crafting table: slot 0 holds the result of the 3x3
+//! grid in slots 1 to 9.
+
+use flecs_ecs::{core::Entity, macros::Component};
+use hyperion_crafting::{Crafting3x3, CraftingRegistry};
+use roaring::RoaringBitmap;
+use valence_protocol::{ItemKind, ItemStack, packets::play::open_screen_s2c::WindowType};
+
+use crate::{AddItemResult, InventoryAccessError, PlayerInventory};
+
+/// The slots of the player inventory shown below a container, main inventory first.
+const PLAYER_SLOTS: core::ops::Range<u16> = 9..45;
+
+/// How many slots the container part of a window of this type has.
+#[must_use]
+pub const fn container_slot_count(kind: WindowType) -> u16 {
+    match kind {
+        WindowType::Beacon | WindowType::Lectern => 1,
+        WindowType::Enchantment | WindowType::Stonecutter => 2,
+        WindowType::Anvil
+        | WindowType::BlastFurnace
+        | WindowType::Furnace
+        | WindowType::Grindstone
+        | WindowType::Merchant
+        | WindowType::Smoker
+        | WindowType::Cartography => 3,
+        WindowType::Loom | WindowType::Smithing => 4,
+        WindowType::BrewingStand | WindowType::Hopper => 5,
+        WindowType::Generic9x1 | WindowType::Generic3x3 => 9,
+        WindowType::Crafting => 10,
+        WindowType::Generic9x2 => 18,
+        WindowType::Generic9x3 | WindowType::ShulkerBox => 27,
+        WindowType::Generic9x4 => 36,
+        WindowType::Generic9x5 => 45,
+        WindowType::Generic9x6 => 54,
+    }
+}
+
+/// How many properties a window of this type has. Properties are numbers the client shows in
+/// other ways than items, such as the progress arrow of a furnace.
+#[must_use]
+pub const fn property_count(kind: WindowType) -> usize {
+    match kind {
+        WindowType::Furnace | WindowType::BlastFurnace | WindowType::Smoker => 4,
+        WindowType::Enchantment => 10,
+        WindowType::Beacon => 3,
+        WindowType::BrewingStand => 2,
+        WindowType::Anvil | WindowType::Lectern | WindowType::Loom | WindowType::Stonecutter => 1,
+        _ => 0,
+    }
+}
+
+/// Whether the player's own inventory is shown below the container. Lecterns only show the book.
+#[must_use]
+pub const fn shows_player_inventory(kind: WindowType) -> bool {
+    !matches!(kind, WindowType::Lectern)
+}
+
+/// An inventory on its own entity which players can open, e.g. a chest.
+#[derive(Component, Debug)]
+pub struct Container {
+    kind: WindowType,
+    pub title: String,
+    slots: Vec<ItemStack>,
+    viewers: Vec<Viewer>,
+    properties: Vec<i16>,
+    updated_properties: RoaringBitmap,
+    pub updated_since_last_tick: RoaringBitmap,
+}
+
+#[derive(Debug)]
+struct Viewer {
+    player: Entity,
+    /// Slots changed this tick which the player's client predicted itself.
+    predicted: RoaringBitmap,
+}
+
+/// The container a player is looking into.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OpenContainer {
+    /// The id the client refers to the window with. Never 0, which is the player inventory.
+    pub window_id: u8,
+    pub container: Entity,
+}
+
+impl Container {
+    #[must_use]
+    pub fn new(kind: WindowType, title: impl Into<String>) -> Self {
+        Self {
+            kind,
+            title: title.into(),
+            slots: vec![ItemStack::EMPTY; usize::from(container_slot_count(kind))],
+            viewers: Vec::new(),
+            properties: vec![0; property_count(kind)],
+            updated_properties: RoaringBitmap::new(),
+            updated_since_last_tick: RoaringBitmap::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn kind(&self) -> WindowType {
+        self.kind
+    }
+
+    #[must_use]
+    pub fn slots(&self) -> &[ItemStack] {
+        &self.slots
+    }
+
+    #[must_use]
+    pub fn slot_count(&self) -> u16 {
+        u16::try_from(self.slots.len()).unwrap()
+    }
+
+    pub fn get(&self, index: u16) -> Result<&ItemStack, InventoryAccessError> {
+        self.slots
+            .get(usize::from(index))
+            .ok_or(InventoryAccessError::InvalidSlot { index })
+    }
+
+    pub fn get_mut(&mut self, index: u16) -> Result<&mut ItemStack, InventoryAccessError> {
+        let stack = self
+            .slots
+            .get_mut(usize::from(index))
+            .ok_or(InventoryAccessError::InvalidSlot { index })?;
+
+        self.updated_since_last_tick.insert(u32::from(index));
+
+        Ok(stack)
+    }
+
+    pub fn set(&mut self, index: u16, stack: ItemStack) -> Result<(), InventoryAccessError> {
+        *self.get_mut(index)? = stack;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn properties(&self) -> &[i16] {
+        &self.properties
+    }
+
+    /// Sets a property of the window, which is sent to the viewers if it changed.
+    pub fn set_property(&mut self, index: u16, value: i16) -> Result<(), InventoryAccessError> {
+        let property = self
+            .properties
+            .get_mut(usize::from(index))
+            .ok_or(InventoryAccessError::InvalidSlot { index })?;
+
+        if *property != value {
+            *property = value;
+            self.updated_properties.insert(u32::from(index));
+        }
+
+        Ok(())
+    }
+
+    /// The properties changed since the last sync and their new values.
+    pub fn property_updates(&self) -> impl Iterator<Item = (i16, i16)> + '_ {
+        self.updated_properties.iter().map(|index| {
+            let value = self.properties[usize::try_from(index).unwrap()];
+            (i16::try_from(index).unwrap(), value)
+        })
+    }
+
+    /// Whether any slot or property changed since the last sync.
+    #[must_use]
+    pub fn is_updated(&self) -> bool {
+        !self.updated_since_last_tick.is_empty() || !self.updated_properties.is_empty()
+    }
+
+    /// What the items in the grid of a crafting table craft. Other containers craft nothing.
+    #[must_use]
+    pub fn crafting_result(&self, registry: &CraftingRegistry) -> ItemStack {
+        if self.kind != WindowType::Crafting {
+            return ItemStack::EMPTY;
+        }
+
+        let grid: Crafting3x3 = core::array::from_fn(|i| {
+            let stack = &self.slots[i + 1];
+
+            if stack.is_empty() {
+                ItemKind::Air
+            } else {
+                stack.item
+            }
+        });
+
+        registry
+            .get_result_3x3(grid)
+            .cloned()
+            .unwrap_or(ItemStack::EMPTY)
+    }
+
+    /// Puts what the grid of a crafting table crafts into its result slot.
+    pub fn update_crafting_result(&mut self, registry: &CraftingRegistry) {
+        if self.kind != WindowType::Crafting {
+            return;
+        }
+
+        let result = self.crafting_result(registry);
+
+        if self.slots[0] != result {
+            self.slots[0] = result;
+            self.updated_since_last_tick.insert(0);
+        }
+    }
+
+    /// Removes every item, e.g. to drop them when the container is broken. The result of a crafting
+    /// table is not an item yet and is only cleared.
+    pub fn take_all(&mut self) -> Vec<ItemStack> {
+        self.updated_since_last_tick
+            .insert_range(0..u32::from(self.slot_count()));
+
+        if self.kind == WindowType::Crafting {
+            self.slots[0] = ItemStack::EMPTY;
+        }
+
+        self.slots
+            .iter_mut()
+            .map(|stack| core::mem::replace(stack, ItemStack::EMPTY))
+            .filter(|stack| !stack.is_empty())
+            .collect()
+    }
+
+    /// Adds `item` to matching stacks first and then to empty slots.
+    pub fn try_add_item(&mut self, mut item: ItemStack) -> AddItemResult {
+        for fill_empty in [false, true] {
+            for index in 0..self.slot_count() {
+                if item.is_empty() {
+                    return AddItemResult { remaining: None };
+                }
+
+                let slot = &self.slots[usize::from(index)];
+
+                let fits = if slot.is_empty() {
+                    fill_empty
+                } else {
+                    slot.item == item.item && slot.nbt == item.nbt
+                };
+
+                if !fits {
+                    continue;
+                }
+
+                let slot = self.get_mut(index).unwrap();
+                let max = item.item.max_stack();
+
+                if slot.is_empty() {
+                    let moved = item.count.min(max);
+                    *slot = item.clone().with_count(moved);
+                    item.count -= moved;
+                } else {
+                    let moved = item.count.min(max - slot.count).max(0);
+                    slot.count += moved;
+                    item.count -= moved;
+                }
+            }
+        }
+
+        AddItemResult {
+            remaining: (!item.is_empty()).then_some(item),
+        }
+    }
+
+    /// The players looking into this container.
+    pub fn viewers(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.viewers.iter().map(|viewer| viewer.player)
+    }
+
+    pub fn add_viewer(&mut self, player: Entity) {
+        if self.viewers().all(|viewer| viewer != player) {
+            self.viewers.push(Viewer {
+                player,
+                predicted: RoaringBitmap::new(),
+            });
+        }
+    }
+
+    pub fn remove_viewer(&mut self, player: Entity) {
+        self.viewers.retain(|viewer| viewer.player != player);
+    }
+
+    /// Marks a change to `slot` which the client of `player` predicted, so it is not sent back to
+    /// them.
+    pub fn mark_predicted(&mut self, player: Entity, slot: u16) {
+        if let Some(viewer) = self
+            .viewers
+            .iter_mut()
+            .find(|viewer| viewer.player == player)
+        {
+            viewer.predicted.insert(u32::from(slot));
+        }
+    }
+
+    /// The slots changed since the last sync which `player` does not know about yet.
+    #[must_use]
+    pub fn updates_for(&self, player: Entity) -> RoaringBitmap {
+        let predicted = self
+            .viewers
+            .iter()
+            .find(|viewer| viewer.player == player)
+            .map(|viewer| &viewer.predicted);
+
+        predicted.map_or_else(
+            || self.updated_since_last_tick.clone(),
+            |predicted| &self.updated_since_last_tick - predicted,
+        )
+    }
+
+    /// Forgets all changes once they have been sent to the viewers.
+    pub fn clear_updates(&mut self) {
+        self.updated_since_last_tick.clear();
+        self.updated_properties.clear();
+
+        for viewer in &mut self.viewers {
+            viewer.predicted.clear();
+        }
+    }
+
+    /// Every slot of the window as the client numbers them: the container followed by the
+    /// player's main inventory and hotbar.
+    pub fn window_slots<'a>(
+        &'a self,
+        inventory: &'a PlayerInventory,
+    ) -> impl Iterator<Item = &'a ItemStack> + 'a {
+        let player = if shows_player_inventory(self.kind) {
+            &inventory.slots()[usize::from(PLAYER_SLOTS.start)..usize::from(PLAYER_SLOTS.end)]
+        } else {
+            &[]
+        };
+
+        self.slots.iter().chain(player)
+    }
+
+    /// The slot of the player inventory a window slot refers to, if it is not part of the
+    /// container.
+    #[must_use]
+    pub fn player_slot(&self, window_slot: u16) -> Option<u16> {
+        if !shows_player_inventory(self.kind) {
+            return None;
+        }
+
+        let slot = window_slot
+            .checked_sub(self.slot_count())?
+            .checked_add(PLAYER_SLOTS.start)?;
+        PLAYER_SLOTS.contains(&slot).then_some(slot)
+    }
+
+    /// The window slot showing a slot of the player inventory, if any.
+    #[must_use]
+    pub fn window_slot(&self, player_slot: u16) -> Option<u16> {
+        (shows_player_inventory(self.kind) && PLAYER_SLOTS.contains(&player_slot))
+            .then(|| player_slot - PLAYER_SLOTS.start + self.slot_count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn window_slots_follow_container() {
+        let container = Container::new(WindowType::Hopper, "Hopper");
+
+        assert_eq!(container.player_slot(4), None);
+        assert_eq!(container.player_slot(5), Some(9));
+        assert_eq!(container.player_slot(40), Some(44));
+        assert_eq!(container.player_slot(41), None);
+        assert_eq!(container.window_slot(36), Some(32));
+    }
+
+    #[test]
+    fn try_add_item_stacks_first() {
+        let mut container = Container::new(WindowType::Generic9x1, "Chest");
+        container
+            .set(3, ItemStack::new(ItemKind::Stone, 60, None))
+            .unwrap();
+
+        let result = container.try_add_item(ItemStack::new(ItemKind::Stone, 10, None));
+
+        assert!(result.remaining.is_none());
+        assert_eq!(container.get(3).unwrap().count, 64);
+        assert_eq!(container.get(0).unwrap().count, 6);
+    }
+}
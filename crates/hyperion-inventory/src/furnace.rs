@@ -0,0 +1,335 @@
+//! Furnaces, blast furnaces and smokers, which cook items over time while they burn fuel.
+//!
+//! A furnace is a [`Container`] with an input, a fuel and an output slot, together with a
+//! [`Furnace`] on the same entity. [`Furnace::tick`] runs every tick whether or not anyone looks
+//! into the furnace, and keeps the properties of the window up to date.
+
+use flecs_ecs::macros::Component;
+use hyperion_crafting::{CookingKind, CraftingRegistry, SmeltingData};
+use valence_protocol::{ItemKind, ItemStack, packets::play::open_screen_s2c::WindowType};
+
+use crate::container::Container;
+
+pub const INPUT_SLOT: u16 = 0;
+pub const FUEL_SLOT: u16 = 1;
+pub const OUTPUT_SLOT: u16 = 2;
+
+/// The window properties of a furnace.
+mod property {
+    /// Ticks the current fuel keeps burning.
+    pub const BURN_TIME: u16 = 0;
+    /// Ticks the current fuel burns in total.
+    pub const FUEL_TIME: u16 = 1;
+    /// Ticks the input has been cooking.
+    pub const COOK_TIME: u16 = 2;
+    /// Ticks the input has to cook.
+    pub const COOK_TIME_TOTAL: u16 = 3;
+}
+
+/// Woods which burn. Nether woods do not.
+const WOODS: [&str; 9] = [
+    "oak", "spruce", "birch", "jungle", "acacia", "dark_oak", "mangrove", "cherry", "bamboo",
+];
+
+/// The recipes cooked by a window of this type, if it is a furnace.
+#[must_use]
+pub const fn cooking_kind(kind: WindowType) -> Option<CookingKind> {
+    match kind {
+        WindowType::Furnace => Some(CookingKind::Smelting),
+        WindowType::BlastFurnace => Some(CookingKind::Blasting),
+        WindowType::Smoker => Some(CookingKind::Smoking),
+        _ => None,
+    }
+}
+
+/// How many ticks an item burns in a furnace, if it is fuel.
+#[must_use]
+pub fn fuel_time(item: ItemKind) -> Option<u16> {
+    let exact = match item {
+        ItemKind::LavaBucket => 20000,
+        ItemKind::CoalBlock => 16000,
+        ItemKind::DriedKelpBlock => 4001,
+        ItemKind::BlazeRod => 2400,
+        ItemKind::Coal | ItemKind::Charcoal => 1600,
+        ItemKind::Bookshelf
+        | ItemKind::ChiseledBookshelf
+        | ItemKind::Lectern
+        | ItemKind::NoteBlock
+        | ItemKind::Jukebox
+        | ItemKind::Chest
+        | ItemKind::TrappedChest
+        | ItemKind::Barrel
+        | ItemKind::CraftingTable
+        | ItemKind::CartographyTable
+        | ItemKind::FletchingTable
+        | ItemKind::SmithingTable
+        | ItemKind::Loom
+        | ItemKind::Composter
+        | ItemKind::Beehive
+        | ItemKind::BeeNest
+        | ItemKind::DaylightDetector
+        | ItemKind::Bow
+        | ItemKind::Crossbow
+        | ItemKind::FishingRod
+        | ItemKind::Ladder
+        | ItemKind::MangroveRoots => 300,
+        ItemKind::Stick
+        | ItemKind::Bowl
+        | ItemKind::DeadBush
+        | ItemKind::Azalea
+        | ItemKind::FloweringAzalea => 100,
+        ItemKind::Bamboo | ItemKind::Scaffolding => 50,
+        _ => 0,
+    };
+
+    if exact > 0 {
+        return Some(exact);
+    }
+
+    let name = item.to_str();
+
+    let time = if name.starts_with("wooden_") {
+        // tools
+        200
+    } else if name.ends_with("_banner") {
+        300
+    } else if name.ends_with("_wool") {
+        100
+    } else if name.ends_with("_carpet") {
+        67
+    } else {
+        let name = name.strip_prefix("stripped_").unwrap_or(name);
+        let kind = WOODS.iter().find_map(|wood| name.strip_prefix(wood))?;
+
+        match kind {
+            "_log" | "_wood" | "_block" | "_planks" | "_mosaic" | "_stairs" | "_mosaic_stairs"
+            | "_trapdoor" | "_pressure_plate" | "_fence" | "_fence_gate" => 300,
+            "_slab" | "_mosaic_slab" => 150,
+            "_door" | "_sign" => 200,
+            "_hanging_sign" => 800,
+            "_button" | "_sapling" => 100,
+            "_boat" | "_chest_boat" | "_raft" | "_chest_raft" => 1200,
+            _ => return None,
+        }
+    };
+
+    Some(time)
+}
+
+/// The cooking state of a furnace. The items are in the [`Container`] on the same entity.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct Furnace {
+    /// Ticks the current fuel keeps burning.
+    pub burn_time: u16,
+    /// Ticks the current fuel burns in total.
+    pub fuel_time: u16,
+    /// Ticks the input has been cooking.
+    pub cook_time: u16,
+    /// Ticks the input has to cook.
+    pub cook_time_total: u16,
+    /// Experience points for cooked items which nobody has taken out yet.
+    pub experience: f32,
+}
+
+/// Whether the result of `recipe` can be added to the output.
+fn fits_output(output: &ItemStack, recipe: &SmeltingData) -> bool {
+    let result = recipe.result();
+
+    output.is_empty()
+        || (output.item == result.item
+            && output.nbt == result.nbt
+            && output.count + result.count <= output.item.max_stack())
+}
+
+impl Furnace {
+    #[must_use]
+    pub const fn is_burning(&self) -> bool {
+        self.burn_time > 0
+    }
+
+    /// Advances the furnace by one tick: burns fuel, cooks the input and updates the properties of
+    /// the window. Does nothing if `container` is not a furnace.
+    pub fn tick(&mut self, container: &mut Container, registry: &CraftingRegistry) {
+        let Some(kind) = cooking_kind(container.kind()) else {
+            return;
+        };
+
+        if self.is_burning() {
+            self.burn_time -= 1;
+        }
+
+        let input = container.get(INPUT_SLOT).unwrap();
+
+        let recipe = if input.is_empty() {
+            None
+        } else {
+            registry.get_smelting(kind, input.item)
+        };
+
+        let output = container.get(OUTPUT_SLOT).unwrap();
+        let recipe = recipe.filter(|recipe| fits_output(output, recipe));
+
+        if !self.is_burning() && recipe.is_some() {
+            self.light(kind, container);
+        }
+
+        match recipe {
+            Some(recipe) if self.is_burning() => {
+                self.cook_time_total = recipe.cooking_time();
+                self.cook_time += 1;
+
+                if self.cook_time >= self.cook_time_total {
+                    self.cook_time = 0;
+                    self.experience += recipe.experience_points();
+                    cook(container, recipe);
+                }
+            }
+            // the progress is lost when the input is taken out
+            _ if self.is_burning() => self.cook_time = 0,
+            // it cools down slowly without fuel
+            _ => self.cook_time = self.cook_time.saturating_sub(2),
+        }
+
+        self.update_properties(container);
+    }
+
+    /// Starts burning the next piece of fuel, if there is any.
+    fn light(&mut self, kind: CookingKind, container: &mut Container) {
+        let fuel = container.get(FUEL_SLOT).unwrap();
+
+        if fuel.is_empty() {
+            return;
+        }
+
+        let Some(mut time) = fuel_time(fuel.item) else {
+            return;
+        };
+
+        // only take the slot mutably once it changes, so it is not resent every tick
+        let fuel = container.get_mut(FUEL_SLOT).unwrap();
+
+        // blast furnaces and smokers cook twice as fast, so fuel lasts half as long
+        if kind != CookingKind::Smelting {
+            time /= 2;
+        }
+
+        self.burn_time = time;
+        self.fuel_time = time;
+
+        if fuel.item == ItemKind::LavaBucket {
+            *fuel = ItemStack::new(ItemKind::Bucket, 1, None);
+        } else {
+            fuel.count -= 1;
+
+            if fuel.count == 0 {
+                *fuel = ItemStack::EMPTY;
+            }
+        }
+    }
+
+    fn update_properties(&self, container: &mut Container) {
+        let properties = [
+            (property::BURN_TIME, self.burn_time),
+            (property::FUEL_TIME, self.fuel_time),
+            (property::COOK_TIME, self.cook_time),
+            (property::COOK_TIME_TOTAL, self.cook_time_total),
+        ];
+
+        for (index, value) in properties {
+            let value = i16::try_from(value).unwrap_or(i16::MAX);
+            container.set_property(index, value).unwrap();
+        }
+    }
+
+    /// Takes the whole experience points collected so far. Fractions are kept for later.
+    pub fn take_experience(&mut self) -> u16 {
+        let points = self.experience.floor();
+        self.experience -= points;
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "experience is never negative and far below u16::MAX"
+        )]
+        let points = points as u16;
+
+        points
+    }
+}
+
+/// Moves one input item to the output as the result of `recipe`.
+fn cook(container: &mut Container, recipe: &SmeltingData) {
+    let input = container.get_mut(INPUT_SLOT).unwrap();
+    input.count -= 1;
+
+    if input.count == 0 {
+        *input = ItemStack::EMPTY;
+    }
+
+    let output = container.get_mut(OUTPUT_SLOT).unwrap();
+
+    if output.is_empty() {
+        *output = recipe.result().clone();
+    } else {
+        output.count += recipe.result().count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn furnace_cooks_with_fuel() {
+        let registry = CraftingRegistry::default();
+        let mut container = Container::new(WindowType::Furnace, "Furnace");
+        let mut furnace = Furnace::default();
+
+        container
+            .set(INPUT_SLOT, ItemStack::new(ItemKind::RawIron, 2, None))
+            .unwrap();
+        container
+            .set(FUEL_SLOT, ItemStack::new(ItemKind::Coal, 1, None))
+            .unwrap();
+
+        for _ in 0..399 {
+            furnace.tick(&mut container, &registry);
+        }
+
+        assert_eq!(
+            container.get(OUTPUT_SLOT).unwrap(),
+            &ItemStack::new(ItemKind::IronIngot, 1, None)
+        );
+        assert!(container.get(FUEL_SLOT).unwrap().is_empty());
+        assert_eq!(container.properties(), &[1202, 1600, 199, 200]);
+
+        furnace.tick(&mut container, &registry);
+
+        assert_eq!(container.get(OUTPUT_SLOT).unwrap().count, 2);
+        assert!(container.get(INPUT_SLOT).unwrap().is_empty());
+
+        // 1.4 points, the fraction stays in the furnace
+        assert_eq!(furnace.take_experience(), 1);
+        assert!((furnace.experience - 0.4).abs() < 0.001);
+    }
+
+    #[test]
+    fn furnace_without_fuel_does_not_cook() {
+        let registry = CraftingRegistry::default();
+        let mut container = Container::new(WindowType::Furnace, "Furnace");
+        let mut furnace = Furnace::default();
+
+        container
+            .set(INPUT_SLOT, ItemStack::new(ItemKind::RawIron, 1, None))
+            .unwrap();
+        container
+            .set(FUEL_SLOT, ItemStack::new(ItemKind::Stone, 1, None))
+            .unwrap();
+        container.clear_updates();
+
+        furnace.tick(&mut container, &registry);
+
+        assert!(!furnace.is_burning());
+        assert!(!container.is_updated());
+    }
+}
@@ -5,6 +5,8 @@ use roaring::RoaringBitmap;
 use valence_protocol::{ItemKind, ItemStack};
 
 pub mod action;
+pub mod container;
+pub mod furnace;
 pub mod parser;
 
 pub type PlayerInventory = Inventory<46>;
@@ -18,6 +20,36 @@ pub struct Inventory<const T: usize> {
     pub hand_slot_updated_since_last_tick: bool, // todo: maybe make this private
 }
 
+/// The stack a player is holding on their mouse while a window is open.
+///
+/// The client predicts the result of every click; the server applies the same click with
+/// [`action::InventoryAndCursor`] or [`action::ContainerAndCursor`] and resyncs the whole window
+/// when the results differ.
+#[derive(Component, Debug, Default)]
+pub struct CarriedItem {
+    pub stack: ItemStack,
+    /// Incremented whenever the server changes the window, so the client can tell that a click
+    /// was predicted from an outdated view.
+    pub state_id: i32,
+    drag: Option<action::Drag>,
+    window_id: u8,
+}
+
+impl CarriedItem {
+    /// Increments the state id and returns the new value.
+    pub const fn next_state_id(&mut self) -> i32 {
+        self.state_id = self.state_id.wrapping_add(1);
+        self.state_id
+    }
+
+    /// The id for the next window opened by the player. Ids cycle through 1 to 100 like in
+    /// vanilla; 0 is the player inventory.
+    pub const fn next_window_id(&mut self) -> u8 {
+        self.window_id = self.window_id % 100 + 1;
+        self.window_id
+    }
+}
+
 #[derive(Debug)]
 pub struct AddItemResult {
     pub remaining: Option<ItemStack>,
@@ -139,6 +171,9 @@ impl<const N: usize> Inventory<N> {
     }
 
     pub fn swap(&mut self, index_a: u16, index_b: u16) {
+        self.updated_since_last_tick.insert(u32::from(index_a));
+        self.updated_since_last_tick.insert(u32::from(index_b));
+
         let index_a = usize::from(index_a);
         let index_b = usize::from(index_b);
 
@@ -375,5 +410,9 @@ pub struct InventoryModule;
 impl Module for InventoryModule {
     fn module(world: &World) {
         world.component::<PlayerInventory>();
+        world.component::<CarriedItem>();
+        world.component::<container::Container>();
+        world.component::<container::OpenContainer>();
+        world.component::<furnace::Furnace>();
     }
 }
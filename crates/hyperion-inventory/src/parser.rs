@@ -79,6 +79,11 @@ fn handle_shift_click(button: u8, slot: u16) -> InventoryActionResult {
 }
 
 fn handle_number_key(button: u8, slot: u16) -> InventoryActionResult {
+    // the swap hands key (F by default) is sent as a number key press with button 40
+    if button == 40 {
+        return Ok(InventoryAction::OffhandSwap { slot });
+    }
+
     let key = button + 1;
     ensure!(button <= 8, InvalidNumberKeySnafu { key });
     Ok(InventoryAction::NumberKey { key, slot })
@@ -124,17 +129,18 @@ pub fn create_inventory_action(mode: u8, button: u8, slot: i16) -> InventoryActi
         1 => handle_shift_click(button, slot.try_into().context(NegativeSlotSnafu)?),
         2 => handle_number_key(button, slot.try_into().context(NegativeSlotSnafu)?),
         3 => match button {
-            40 => Ok(InventoryAction::OffhandSwap {
-                slot: slot.try_into().context(NegativeSlotSnafu)?,
-            }),
             2 => Ok(InventoryAction::MiddleClick {
                 slot: slot.try_into().context(NegativeSlotSnafu)?,
             }),
             _ => InvalidButtonSnafu { mode, button }.fail(),
         },
         4 => match button {
-            0 => Ok(InventoryAction::Drop),
-            1 => Ok(InventoryAction::CtrlDrop),
+            0 => Ok(InventoryAction::Drop {
+                slot: slot.try_into().context(NegativeSlotSnafu)?,
+            }),
+            1 => Ok(InventoryAction::CtrlDrop {
+                slot: slot.try_into().context(NegativeSlotSnafu)?,
+            }),
             _ => InvalidButtonSnafu { mode, button }.fail(),
         },
         5 => handle_drag(button, slot),
@@ -187,6 +193,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_offhand_swap() {
+        assert_eq!(
+            create_inventory_action(2, 40, 36).unwrap(),
+            InventoryAction::OffhandSwap { slot: 36 }
+        );
+
+        assert!(matches!(
+            create_inventory_action(3, 40, 36).unwrap_err(),
+            Error::InvalidButton {
+                mode: 3,
+                button: 40
+            }
+        ));
+    }
+
     #[test]
     fn test_drag() {
         assert_eq!(
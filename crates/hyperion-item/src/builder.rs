@@ -1,6 +1,8 @@
 use flecs_ecs::core::Entity;
 use valence_protocol::{ItemKind, ItemStack, nbt, nbt::Value};
 
+use crate::enchantment::{self, Enchantment};
+
 mod book;
 pub use book::BookBuilder;
 
@@ -145,6 +147,27 @@ impl ItemBuilder {
         self
     }
 
+    /// Sets the lines of lore shown below the name of the item
+    pub fn lore<S: Into<String>>(mut self, lines: impl IntoIterator<Item = S>) -> Self {
+        let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
+
+        let mut display = match nbt.remove("display") {
+            Some(Value::Compound(display)) => display,
+            _ => nbt::Compound::new(),
+        };
+
+        let lines = lines
+            .into_iter()
+            .map(Into::into)
+            .map(|line: String| serde_json::json!({ "text": line }).to_string())
+            .collect();
+
+        display.insert("Lore", Value::List(nbt::list::List::String(lines)));
+
+        nbt.insert("display", Value::Compound(display));
+        self
+    }
+
     pub const fn count(mut self, count: i8) -> Self {
         self.count = count;
         self
@@ -161,6 +184,13 @@ impl ItemBuilder {
         self
     }
 
+    /// Marks the item as the custom item `id`, see [`crate::custom`]
+    pub fn custom_id(mut self, id: impl Into<String>) -> Self {
+        let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
+        nbt.insert(crate::custom::ID_KEY, Value::String(id.into()));
+        self
+    }
+
     pub fn glowing(mut self) -> Self {
         let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
         nbt.insert(
@@ -170,14 +200,38 @@ impl ItemBuilder {
         self
     }
 
-    pub fn add_attribute(mut self, attribute: impl Attribute) -> Self {
+    /// Enchants the item, or removes the enchantment with a level of 0
+    pub fn enchant(mut self, enchantment: Enchantment, level: u16) -> Self {
+        let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
+        enchantment::set_level_nbt(nbt, enchantment, level);
+        self
+    }
+
+    pub fn add_attribute(self, attribute: impl Attribute) -> Self {
+        self.push_modifier(attribute.create_modifier())
+    }
+
+    /// Adds a modifier for any attribute by its name, such as `generic.movement_speed`
+    pub fn modifier(self, attribute: &str, amount: f64, operation: i32) -> Self {
+        let name = attribute.strip_prefix("minecraft:").unwrap_or(attribute);
+
+        let mut modifier = nbt::Compound::new();
+        modifier.insert("AttributeName", format!("minecraft:{name}"));
+        modifier.insert("Name", name.to_string());
+        modifier.insert("Amount", amount);
+        modifier.insert("Operation", operation);
+
+        self.push_modifier(modifier)
+    }
+
+    fn push_modifier(mut self, modifier: nbt::Compound<String>) -> Self {
         let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
         let mut modifiers = match nbt.remove("AttributeModifiers") {
             Some(Value::List(nbt::list::List::Compound(modifiers))) => modifiers,
             _ => Vec::new(),
         };
 
-        modifiers.push(attribute.create_modifier());
+        modifiers.push(modifier);
 
         nbt.insert(
             "AttributeModifiers",
@@ -199,13 +253,32 @@ mod tests {
 
     #[test]
     fn test_item_builder() {
-        let _sword = ItemBuilder::new(ItemKind::DiamondSword)
+        let sword = ItemBuilder::new(ItemKind::DiamondSword)
             .count(1)
             .glowing()
             .add_attribute(AttackDamage(7.0))
             .add_attribute(AttackSpeed(1.6))
+            .enchant(Enchantment::Sharpness, 2)
             .build();
 
-        // Add assertions here
+        assert_eq!(enchantment::level(&sword, Enchantment::Sharpness), 2);
+    }
+
+    #[test]
+    fn lore_is_escaped() {
+        let item = ItemBuilder::new(ItemKind::Stick)
+            .lore([r#"a "quoted" \ line"#])
+            .build();
+
+        let Some(Value::Compound(display)) = item.nbt.as_ref().unwrap().get("display") else {
+            panic!("the item has no display");
+        };
+
+        let Some(Value::List(nbt::list::List::String(lore))) = display.get("Lore") else {
+            panic!("the item has no lore");
+        };
+
+        let line: serde_json::Value = serde_json::from_str(&lore[0]).unwrap();
+        assert_eq!(line["text"], r#"a "quoted" \ line"#);
     }
 }
@@ -0,0 +1,624 @@
+//! Custom items defined by data rather than code.
+//!
+//! A custom item is an ordinary item carrying its id in the [`ID_KEY`] NBT tag. Definitions are
+//! loaded from TOML or JSON files into the [`CustomItems`] registry, and behaviour is attached in
+//! code with [`CustomItems::on`]:
+//!
+//! ```toml
+//! [[item]]
+//! id = "hyperion:dash_feather"
+//! material = "feather"
+//! name = "Dash Feather"
+//! lore = ["Right click to dash forward"]
+//! glint = true
+//! cooldown = 60
+//!
+//! [[item.attributes]]
+//! attribute = "generic.movement_speed"
+//! amount = 0.02
+//! ```
+//!
+//! ```ignore
+//! world.get::<&mut CustomItems>(|items| {
+//!     items.load_file("items.toml")?;
+//!     items.on("hyperion:dash_feather", Trigger::RightClick, |player, _| dash(player))
+//! })?;
+//! ```
+//!
+//! While an item is cooling down for a player none of its handlers run for them. The cooldown
+//! starts whenever its handlers have run and is shown on every item of the same material.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, bail};
+use flecs_ecs::prelude::*;
+use hyperion::{
+    glam::IVec3,
+    net::{Compose, ConnectionId},
+    simulation::{Player, event},
+    storage::EventQueue,
+};
+use hyperion_inventory::{OFFHAND_SLOT, PlayerInventory};
+use hyperion_utils::EntityExt;
+use serde::{Deserialize, Deserializer, de::Error as _};
+use tracing::error;
+use valence_protocol::{Hand, ItemKind, ItemStack, VarInt, nbt, packets::play};
+
+use crate::builder::ItemBuilder;
+
+/// The NBT tag holding the id of a custom item.
+pub const ID_KEY: &str = "CustomItem";
+
+/// The entity status telling a client it has finished using its item.
+const FINISH_USING_STATUS: u8 = 9;
+
+/// The id of the custom item `stack` is, if any.
+#[must_use]
+pub fn custom_id(stack: &ItemStack) -> Option<&str> {
+    match stack.nbt.as_ref()?.get(ID_KEY)? {
+        nbt::Value::String(id) => Some(id),
+        _ => None,
+    }
+}
+
+/// A custom item as written in an item file.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ItemDefinition {
+    pub id: String,
+    /// The vanilla item the custom item looks like, such as `diamond_sword`.
+    #[serde(deserialize_with = "material")]
+    pub material: ItemKind,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub lore: Vec<String>,
+    #[serde(default)]
+    pub attributes: Vec<AttributeDefinition>,
+    /// Whether the item shimmers as if it were enchanted.
+    #[serde(default)]
+    pub glint: bool,
+    /// Ticks before the item can be used again.
+    #[serde(default)]
+    pub cooldown: u16,
+    /// Ticks the item has to be held down for before it is consumed. Only items with a consume
+    /// time can be consumed, and their material should be something the client can eat or drink.
+    #[serde(default)]
+    pub consume_time: Option<u16>,
+}
+
+/// An attribute modifier of a custom item.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AttributeDefinition {
+    /// The name of the attribute, such as `generic.attack_damage`.
+    pub attribute: String,
+    pub amount: f64,
+    /// 0 adds the amount, 1 adds a multiple of the base value and 2 multiplies the total.
+    #[serde(default)]
+    pub operation: i32,
+}
+
+fn material<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ItemKind, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    let id = name.strip_prefix("minecraft:").unwrap_or(&name);
+
+    ItemKind::from_str(id).ok_or_else(|| D::Error::custom(format!("unknown material {name}")))
+}
+
+impl ItemDefinition {
+    /// A stack of `count` of this item.
+    #[must_use]
+    pub fn stack(&self, count: i8) -> ItemStack {
+        let mut builder = ItemBuilder::new(self.material)
+            .count(count)
+            .custom_id(&self.id);
+
+        if let Some(name) = &self.name {
+            builder = builder.name(name);
+        }
+
+        if !self.lore.is_empty() {
+            builder = builder.lore(&self.lore);
+        }
+
+        for modifier in &self.attributes {
+            builder = builder.modifier(&modifier.attribute, modifier.amount, modifier.operation);
+        }
+
+        if self.glint {
+            builder = builder.glowing();
+        }
+
+        builder.build()
+    }
+}
+
+/// What a player did with a custom item, which decides the handlers that run.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    /// Swinging the item at nothing, an entity or a block.
+    LeftClick,
+    RightClick,
+    HitEntity,
+    BreakBlock,
+    /// Finishing eating or drinking the item. Right clicking an item with a consume time starts
+    /// consuming it instead of triggering [`Trigger::RightClick`].
+    Consume,
+}
+
+/// The [`Trigger`] of a handler together with what it happened to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ItemAction {
+    LeftClick,
+    RightClick,
+    HitEntity { target: Entity },
+    BreakBlock { position: IVec3 },
+    Consume,
+}
+
+impl ItemAction {
+    #[must_use]
+    pub const fn trigger(self) -> Trigger {
+        match self {
+            Self::LeftClick => Trigger::LeftClick,
+            Self::RightClick => Trigger::RightClick,
+            Self::HitEntity { .. } => Trigger::HitEntity,
+            Self::BreakBlock { .. } => Trigger::BreakBlock,
+            Self::Consume => Trigger::Consume,
+        }
+    }
+}
+
+/// Runs when the player holding a custom item uses it.
+pub type ItemHandler = Box<dyn Fn(EntityView<'_>, ItemAction) + Send + Sync>;
+
+struct CustomItem {
+    definition: ItemDefinition,
+    handlers: Vec<(Trigger, ItemHandler)>,
+}
+
+#[derive(Deserialize)]
+struct ItemFile {
+    #[serde(default)]
+    item: Vec<ItemDefinition>,
+}
+
+/// Every custom item by its id.
+#[derive(Component, Default)]
+pub struct CustomItems {
+    items: HashMap<String, CustomItem>,
+}
+
+impl CustomItems {
+    pub fn register(&mut self, definition: ItemDefinition) -> anyhow::Result<()> {
+        if self.items.contains_key(&definition.id) {
+            bail!("custom item {} is already registered", definition.id);
+        }
+
+        let item = CustomItem {
+            definition,
+            handlers: Vec::new(),
+        };
+
+        self.items.insert(item.definition.id.clone(), item);
+
+        Ok(())
+    }
+
+    /// Registers the `[[item]]` tables of a TOML file. Returns how many items there were.
+    pub fn load_toml(&mut self, toml: &str) -> anyhow::Result<usize> {
+        let file: ItemFile = toml::from_str(toml).context("invalid item file")?;
+        self.register_all(file.item)
+    }
+
+    /// Registers the items of a JSON array. Returns how many items there were.
+    pub fn load_json(&mut self, json: &str) -> anyhow::Result<usize> {
+        let items: Vec<ItemDefinition> = serde_json::from_str(json).context("invalid item file")?;
+        self.register_all(items)
+    }
+
+    /// Registers the items of a `.toml` or `.json` file.
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> anyhow::Result<usize> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+
+        let result = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => self.load_toml(&contents),
+            Some("json") => self.load_json(&contents),
+            _ => bail!("{} is neither a TOML nor a JSON file", path.display()),
+        };
+
+        result.with_context(|| format!("failed to load {}", path.display()))
+    }
+
+    fn register_all(&mut self, items: Vec<ItemDefinition>) -> anyhow::Result<usize> {
+        let count = items.len();
+
+        for item in items {
+            self.register(item)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Adds a handler which runs when a player holding the item `id` does `trigger`.
+    pub fn on(
+        &mut self,
+        id: &str,
+        trigger: Trigger,
+        handler: impl Fn(EntityView<'_>, ItemAction) + Send + Sync + 'static,
+    ) -> anyhow::Result<()> {
+        let Some(item) = self.items.get_mut(id) else {
+            bail!("unknown custom item {id}");
+        };
+
+        item.handlers.push((trigger, Box::new(handler)));
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&ItemDefinition> {
+        self.items.get(id).map(|item| &item.definition)
+    }
+
+    /// A stack of `count` of the item `id`.
+    #[must_use]
+    pub fn create(&self, id: &str, count: i8) -> Option<ItemStack> {
+        self.get(id).map(|definition| definition.stack(count))
+    }
+
+    /// Runs the handlers of the item `id` for `action` unless the item is cooling down.
+    fn trigger(
+        &self,
+        player: EntityView<'_>,
+        id: &str,
+        action: ItemAction,
+        compose: &Compose,
+        system: EntityView<'_>,
+    ) {
+        let Some(item) = self.items.get(id) else {
+            return;
+        };
+
+        let trigger = action.trigger();
+        let mut handlers = item
+            .handlers
+            .iter()
+            .filter(|(on, _)| *on == trigger)
+            .map(|(_, handler)| handler)
+            .peekable();
+
+        if handlers.peek().is_none() {
+            return;
+        }
+
+        let tick = compose.global().tick;
+        let cooldown = item.definition.cooldown;
+
+        let ready = player
+            .try_get::<&mut ItemCooldowns>(|cooldowns| cooldowns.start(id, cooldown, tick))
+            .unwrap_or(true);
+
+        if !ready {
+            return;
+        }
+
+        if cooldown > 0 {
+            let packet = play::CooldownUpdateS2c {
+                item_id: VarInt(i32::from(item.definition.material.to_raw())),
+                cooldown_ticks: VarInt(i32::from(cooldown)),
+            };
+
+            player.try_get::<&ConnectionId>(|io| {
+                if let Err(e) = compose.unicast(&packet, *io, system) {
+                    error!("failed to send item cooldown: {e}");
+                }
+            });
+        }
+
+        for handler in handlers {
+            handler(player, action);
+        }
+    }
+}
+
+/// When each custom item can be used again by a player.
+#[derive(Component, Debug, Default)]
+pub struct ItemCooldowns {
+    until: HashMap<String, i64>,
+}
+
+impl ItemCooldowns {
+    #[must_use]
+    pub fn is_cooling_down(&self, id: &str, tick: i64) -> bool {
+        self.until.get(id).is_some_and(|until| *until > tick)
+    }
+
+    /// Starts a cooldown of `ticks` for the item `id` unless it is already cooling down. Returns
+    /// whether the item could be used.
+    pub fn start(&mut self, id: &str, ticks: u16, tick: i64) -> bool {
+        if self.is_cooling_down(id, tick) {
+            return false;
+        }
+
+        self.until.retain(|_, until| *until > tick);
+
+        if ticks > 0 {
+            self.until.insert(id.to_owned(), tick + i64::from(ticks));
+        }
+
+        true
+    }
+}
+
+/// A player eating or drinking a custom item.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Consuming {
+    pub item: String,
+    pub hand: Hand,
+    /// The tick at which the item is consumed.
+    pub until: i64,
+}
+
+const fn held_slot(inventory: &PlayerInventory, hand: Hand) -> u16 {
+    match hand {
+        Hand::Main => inventory.get_cursor_index(),
+        Hand::Off => OFFHAND_SLOT,
+    }
+}
+
+/// The id of the custom item in the hand of a player.
+fn held_id(player: EntityView<'_>, hand: Hand) -> Option<String> {
+    player
+        .try_get::<&PlayerInventory>(|inventory| {
+            let stack = inventory.get(held_slot(inventory, hand)).ok()?;
+            custom_id(stack).map(str::to_owned)
+        })
+        .flatten()
+}
+
+#[derive(Component)]
+pub struct CustomItemModule;
+
+impl Module for CustomItemModule {
+    fn module(world: &World) {
+        world.component::<CustomItems>();
+        world.component::<ItemCooldowns>();
+        world.component::<Consuming>();
+
+        world.set(CustomItems::default());
+
+        world
+            .component::<Player>()
+            .add_trait::<(flecs::With, ItemCooldowns)>();
+
+        // runs before the events are drained by anyone else
+        system!(
+            "custom_item_use",
+            world,
+            &CustomItems($),
+            &Compose($),
+            &mut EventQueue<event::SwingArm>($),
+            &mut EventQueue<event::ItemInteract>($),
+            &mut EventQueue<event::AttackEntity>($),
+            &mut EventQueue<event::DestroyBlock>($),
+            &mut EventQueue<event::ReleaseUseItem>($),
+        )
+        .kind::<flecs::pipeline::PreUpdate>()
+        .each_iter(
+            |it, _, (items, compose, swings, interacts, attacks, destroys, releases)| {
+                let world = it.world();
+                let system = it.system();
+
+                let trigger = |player: Entity, hand: Hand, action: ItemAction| {
+                    let player = world.entity_from_id(player);
+
+                    if !player.is_alive() {
+                        return;
+                    }
+
+                    if let Some(id) = held_id(player, hand) {
+                        items.trigger(player, &id, action, compose, system);
+                    }
+                };
+
+                for event in swings.drain() {
+                    if event.hand == Hand::Main {
+                        trigger(event.from, Hand::Main, ItemAction::LeftClick);
+                    }
+                }
+
+                for event in attacks.peek() {
+                    let action = ItemAction::HitEntity {
+                        target: event.target,
+                    };
+
+                    trigger(event.origin, Hand::Main, action);
+                }
+
+                for event in destroys.peek() {
+                    let action = ItemAction::BreakBlock {
+                        position: event.position,
+                    };
+
+                    trigger(event.from, Hand::Main, action);
+                }
+
+                for event in releases.peek() {
+                    let player = world.entity_from_id(event.from);
+
+                    if player.is_alive() {
+                        player.remove::<Consuming>();
+                    }
+                }
+
+                for event in interacts.drain() {
+                    let player = world.entity_from_id(event.entity);
+
+                    if !player.is_alive() {
+                        continue;
+                    }
+
+                    let Some(id) = held_id(player, event.hand) else {
+                        continue;
+                    };
+
+                    let Some(item) = items.get(&id) else {
+                        continue;
+                    };
+
+                    let Some(consume_time) = item.consume_time else {
+                        items.trigger(player, &id, ItemAction::RightClick, compose, system);
+                        continue;
+                    };
+
+                    let tick = compose.global().tick;
+
+                    let cooling_down = player
+                        .try_get::<&ItemCooldowns>(|cooldowns| cooldowns.is_cooling_down(&id, tick))
+                        .unwrap_or_default();
+
+                    if cooling_down {
+                        continue;
+                    }
+
+                    player.set(Consuming {
+                        item: id,
+                        hand: event.hand,
+                        until: tick + i64::from(consume_time),
+                    });
+                }
+            },
+        );
+
+        system!(
+            "finish_consuming",
+            world,
+            &Consuming,
+            &CustomItems($),
+            &Compose($),
+            &ConnectionId,
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, row, (consuming, items, compose, io)| {
+            if compose.global().tick < consuming.until {
+                return;
+            }
+
+            let system = it.system();
+            let player = it.entity(row);
+
+            player.remove::<Consuming>();
+
+            // the player may have switched to another item since
+            let consumed = player
+                .try_get::<&mut PlayerInventory>(|inventory| {
+                    let slot = held_slot(inventory, consuming.hand);
+                    let Ok(stack) = inventory.get(slot) else {
+                        return false;
+                    };
+
+                    if custom_id(stack) != Some(consuming.item.as_str()) {
+                        return false;
+                    }
+
+                    let mut stack = stack.clone();
+                    stack.count -= 1;
+
+                    if stack.count <= 0 {
+                        stack = ItemStack::EMPTY;
+                    }
+
+                    inventory.set(slot, stack).is_ok()
+                })
+                .unwrap_or_default();
+
+            if !consumed {
+                return;
+            }
+
+            let status = play::EntityStatusS2c {
+                entity_id: player.minecraft_id(),
+                entity_status: FINISH_USING_STATUS,
+            };
+
+            if let Err(e) = compose.unicast(&status, *io, system) {
+                error!("failed to finish using item: {e}");
+            }
+
+            items.trigger(
+                player,
+                &consuming.item,
+                ItemAction::Consume,
+                compose,
+                system,
+            );
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_toml_definitions() {
+        let mut items = CustomItems::default();
+
+        let count = items
+            .load_toml(
+                r#"
+                [[item]]
+                id = "hyperion:apple"
+                material = "minecraft:golden_apple"
+                lore = ["Tastes of gold"]
+                cooldown = 20
+                consume_time = 32
+
+                [[item.attributes]]
+                attribute = "generic.max_health"
+                amount = 4.0
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(count, 1);
+
+        let apple = items.get("hyperion:apple").unwrap();
+        assert_eq!(apple.material, ItemKind::GoldenApple);
+        assert_eq!(apple.consume_time, Some(32));
+        assert_eq!(apple.attributes[0].attribute, "generic.max_health");
+
+        let stack = items.create("hyperion:apple", 3).unwrap();
+        assert_eq!(stack.count, 3);
+        assert_eq!(custom_id(&stack), Some("hyperion:apple"));
+
+        assert!(
+            items
+                .load_json(r#"[{ "id": "hyperion:apple", "material": "apple" }]"#)
+                .is_err()
+        );
+        assert!(
+            items
+                .load_json(r#"[{ "id": "hyperion:x", "material": "nothing" }]"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn cooldowns_block_until_they_run_out() {
+        let mut cooldowns = ItemCooldowns::default();
+
+        assert!(cooldowns.start("hyperion:apple", 20, 100));
+        assert!(cooldowns.is_cooling_down("hyperion:apple", 119));
+        assert!(!cooldowns.start("hyperion:apple", 20, 119));
+        assert!(!cooldowns.is_cooling_down("hyperion:apple", 120));
+        assert!(cooldowns.start("hyperion:apple", 20, 120));
+
+        assert!(cooldowns.start("hyperion:feather", 0, 0));
+        assert!(cooldowns.start("hyperion:feather", 0, 0));
+    }
+}
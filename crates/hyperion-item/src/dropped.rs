@@ -0,0 +1,400 @@
+//! Item entities lying in the world.
+//!
+//! Items are spawned from [`event::ItemDropEvent`]s. Once on the ground they fall and rest on
+//! blocks, merge with nearby identical stacks, are picked up by players walking over them and
+//! despawn after [`DESPAWN_AGE`] ticks.
+
+use flecs_ecs::prelude::*;
+use geometry::aabb::Aabb;
+use hyperion::{
+    glam::Vec3,
+    net::Compose,
+    simulation::{
+        Position, Velocity,
+        blocks::Blocks,
+        entity_kind::EntityKind,
+        event,
+        metadata::item::Item,
+        spawn::{DespawnExt, SpawnExt},
+    },
+    storage::EventQueue,
+    util::SendableQuery,
+};
+use hyperion_inventory::PlayerInventory;
+use hyperion_utils::EntityExt;
+use spatial::SpatialIndex;
+use tracing::error;
+use valence_protocol::{ItemStack, VarInt, packets::play};
+
+/// Ticks before an item dropped by the world can be picked up.
+pub const PICKUP_DELAY: u16 = 10;
+
+/// Ticks before an item thrown by a player can be picked up, so it does not go straight back into
+/// their inventory.
+pub const THROWN_PICKUP_DELAY: u16 = 40;
+
+/// Ticks an item stays in the world before it despawns (five minutes).
+pub const DESPAWN_AGE: u16 = 6000;
+
+/// Ticks between two attempts at merging nearby stacks.
+const MERGE_INTERVAL: i64 = 20;
+
+/// The maximum horizontal distance between two stacks that get merged.
+const MERGE_RADIUS: f32 = 0.5;
+
+/// Blocks per tick² items accelerate downward.
+const GRAVITY: f32 = 0.04;
+
+/// The fraction of velocity kept every tick.
+const DRAG: f32 = 0.98;
+
+/// The fraction of horizontal velocity kept every tick while resting on a block.
+const GROUND_FRICTION: f32 = 0.6;
+
+/// Below this speed a grounded item stops moving entirely.
+const REST_SPEED: f32 = 0.001;
+
+/// How far from a player's bounding box an item can be picked up.
+const PICKUP_REACH: Vec3 = Vec3::new(1.0, 0.5, 1.0);
+
+#[derive(Component)]
+pub struct DroppedItemModule;
+
+/// State of an item entity. The stack itself is the [`Item`] metadata.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DroppedItem {
+    /// Ticks until the item can be picked up.
+    pub pickup_delay: u16,
+    /// Ticks since the item was spawned.
+    pub age: u16,
+}
+
+impl DroppedItem {
+    #[must_use]
+    pub const fn new(pickup_delay: u16) -> Self {
+        Self {
+            pickup_delay,
+            age: 0,
+        }
+    }
+}
+
+/// Cells in unloaded chunks count as solid so items do not fall out of the world.
+fn is_solid(blocks: &Blocks, position: Vec3) -> bool {
+    blocks
+        .get_block(position.floor().as_ivec3())
+        .is_none_or(|block| block.collision_shapes().next().is_some())
+}
+
+/// Moves an item by one tick of `velocity`, stopping at blocks. Returns the new position and
+/// velocity.
+fn step(blocks: &Blocks, mut position: Vec3, mut velocity: Vec3) -> (Vec3, Vec3) {
+    velocity.y -= GRAVITY;
+
+    let horizontal = position + velocity.with_y(0.0);
+    if is_solid(blocks, horizontal) {
+        velocity.x = 0.0;
+        velocity.z = 0.0;
+    } else {
+        position = horizontal;
+    }
+
+    let vertical = position + Vec3::Y * velocity.y;
+    let on_ground = velocity.y <= 0.0 && is_solid(blocks, vertical);
+
+    if on_ground {
+        // rest on top of the block
+        position.y = vertical.y.floor() + 1.0;
+        velocity.y = 0.0;
+    } else if velocity.y > 0.0 && is_solid(blocks, vertical) {
+        velocity.y = 0.0;
+    } else {
+        position = vertical;
+    }
+
+    velocity *= DRAG;
+
+    if on_ground {
+        velocity.x *= GROUND_FRICTION;
+        velocity.z *= GROUND_FRICTION;
+
+        if velocity.length_squared() < REST_SPEED * REST_SPEED {
+            velocity = Vec3::ZERO;
+        }
+    }
+
+    (position, velocity)
+}
+
+struct MergeCandidate {
+    entity: Entity,
+    position: Vec3,
+    stack: ItemStack,
+    changed: bool,
+}
+
+fn can_merge(a: &MergeCandidate, b: &MergeCandidate) -> bool {
+    !a.stack.is_empty()
+        && !b.stack.is_empty()
+        && a.stack.item == b.stack.item
+        && a.stack.nbt == b.stack.nbt
+        && a.stack.count < a.stack.item.max_stack()
+        && b.stack.count < b.stack.item.max_stack()
+        && (a.position - b.position).with_y(0.0).length() <= MERGE_RADIUS
+        && (a.position.y - b.position.y).abs() <= MERGE_RADIUS
+}
+
+/// Moves as much as possible of the smaller stack into the larger one.
+fn merge(a: &mut MergeCandidate, b: &mut MergeCandidate) {
+    let (into, from) = if a.stack.count >= b.stack.count {
+        (a, b)
+    } else {
+        (b, a)
+    };
+
+    let moved = from
+        .stack
+        .count
+        .min(into.stack.item.max_stack() - into.stack.count);
+
+    into.stack.count += moved;
+    from.stack.count -= moved;
+
+    if from.stack.count == 0 {
+        from.stack = ItemStack::EMPTY;
+    }
+
+    into.changed = true;
+    from.changed = true;
+}
+
+impl Module for DroppedItemModule {
+    fn module(world: &World) {
+        world.import::<spatial::SpatialModule>();
+
+        world.component::<DroppedItem>();
+
+        system!(
+            "spawn_dropped_items",
+            world,
+            &mut EventQueue<event::ItemDropEvent>($),
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, _, event_queue| {
+            let world = it.world();
+
+            for event in event_queue.drain() {
+                if event.item.is_empty() {
+                    continue;
+                }
+
+                let pickup_delay = if event.thrown_by.is_some() {
+                    THROWN_PICKUP_DELAY
+                } else {
+                    PICKUP_DELAY
+                };
+
+                world
+                    .spawn_entity(EntityKind::Item)
+                    .at(event.location)
+                    .velocity(event.velocity)
+                    .with(|entity| {
+                        entity
+                            .set(Item::new(event.item))
+                            .set(DroppedItem::new(pickup_delay));
+                    })
+                    .spawn();
+            }
+        });
+
+        system!(
+            "dropped_item_physics",
+            world,
+            &Blocks($),
+            &mut Position,
+            &mut Velocity,
+            &mut DroppedItem,
+        )
+        .multi_threaded()
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, row, (blocks, position, velocity, dropped)| {
+            dropped.age = dropped.age.saturating_add(1);
+            dropped.pickup_delay = dropped.pickup_delay.saturating_sub(1);
+
+            if dropped.age >= DESPAWN_AGE {
+                it.entity(row).despawn();
+                return;
+            }
+
+            let (new_position, new_velocity) = step(blocks, **position, velocity.0);
+
+            **position = new_position;
+            velocity.0 = new_velocity;
+        });
+
+        let items = SendableQuery(world.new_query::<(&Position, &Item, &DroppedItem)>());
+
+        system!("merge_dropped_items", world, &Compose($))
+            .kind::<flecs::pipeline::OnUpdate>()
+            .each_iter(move |it, _, compose| {
+                if compose.global().tick % MERGE_INTERVAL != 0 {
+                    return;
+                }
+
+                let world = it.world();
+
+                let mut candidates = Vec::new();
+
+                items
+                    .0
+                    .iter_stage(&world)
+                    .each_entity(|entity, (position, item, _)| {
+                        if item.is_empty() || item.count >= item.item.max_stack() {
+                            return;
+                        }
+
+                        candidates.push(MergeCandidate {
+                            entity: entity.id(),
+                            position: **position,
+                            stack: (**item).clone(),
+                            changed: false,
+                        });
+                    });
+
+                // only stacks of the same kind can merge, so compare within runs of one kind
+                candidates.sort_unstable_by_key(|candidate| candidate.stack.item.to_raw());
+
+                for i in 0..candidates.len() {
+                    let (head, tail) = candidates.split_at_mut(i + 1);
+                    let a = &mut head[i];
+
+                    for b in tail {
+                        if b.stack.item != a.stack.item {
+                            break;
+                        }
+
+                        if can_merge(a, b) {
+                            merge(a, b);
+                        }
+                    }
+                }
+
+                for candidate in candidates.into_iter().filter(|c| c.changed) {
+                    let entity = world.entity_from_id(candidate.entity);
+
+                    if candidate.stack.is_empty() {
+                        entity.despawn();
+                    } else {
+                        entity.set(Item::new(candidate.stack));
+                    }
+                }
+            });
+
+        system!(
+            "pickup_dropped_items",
+            world,
+            &SpatialIndex($),
+            &Compose($),
+            &Position,
+            &mut Item,
+            &DroppedItem,
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, row, (index, compose, position, item, dropped)| {
+            if dropped.pickup_delay > 0 || item.is_empty() {
+                return;
+            }
+
+            let world = it.world();
+            let system = it.system();
+            let entity = it.entity(row);
+
+            let reach = Aabb::new(**position - PICKUP_REACH, **position + PICKUP_REACH);
+
+            for collector in index.get_collisions(reach, &world) {
+                let collector = world.entity_from_id(collector);
+
+                let Some(remaining) = collector.try_get::<&mut PlayerInventory>(|inventory| {
+                    inventory.try_add_item((**item).clone()).remaining
+                }) else {
+                    continue;
+                };
+
+                let picked_up = item.count - remaining.as_ref().map_or(0, |stack| stack.count);
+
+                if picked_up <= 0 {
+                    continue;
+                }
+
+                let packet = play::ItemPickupAnimationS2c {
+                    collected_entity_id: VarInt(entity.minecraft_id()),
+                    collector_entity_id: VarInt(collector.minecraft_id()),
+                    pickup_item_count: VarInt(i32::from(picked_up)),
+                };
+
+                if let Err(e) = compose
+                    .broadcast_local(&packet, position.to_chunk(), system)
+                    .send()
+                {
+                    error!("failed to send item pickup animation: {e}");
+                }
+
+                match remaining {
+                    Some(remaining) => **item = remaining,
+                    None => {
+                        **item = ItemStack::EMPTY;
+                        entity.despawn();
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::ItemKind;
+
+    use super::*;
+
+    fn candidate(position: Vec3, count: i8) -> MergeCandidate {
+        MergeCandidate {
+            entity: Entity::null(),
+            position,
+            stack: ItemStack::new(ItemKind::Stone, count, None),
+            changed: false,
+        }
+    }
+
+    #[test]
+    fn merges_smaller_into_larger() {
+        let mut a = candidate(Vec3::ZERO, 3);
+        let mut b = candidate(Vec3::new(0.2, 0.0, 0.0), 10);
+
+        assert!(can_merge(&a, &b));
+        merge(&mut a, &mut b);
+
+        assert!(a.stack.is_empty());
+        assert_eq!(b.stack.count, 13);
+    }
+
+    #[test]
+    fn merge_leaves_remainder() {
+        let mut a = candidate(Vec3::ZERO, 60);
+        let mut b = candidate(Vec3::ZERO, 10);
+
+        merge(&mut a, &mut b);
+
+        assert_eq!(a.stack.count, 64);
+        assert_eq!(b.stack.count, 6);
+    }
+
+    #[test]
+    fn does_not_merge_distant_stacks() {
+        let a = candidate(Vec3::ZERO, 1);
+        let b = candidate(Vec3::new(2.0, 0.0, 0.0), 1);
+
+        assert!(!can_merge(&a, &b));
+    }
+}
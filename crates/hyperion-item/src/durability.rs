@@ -0,0 +1,176 @@
+//! Items wearing out as they are used.
+//!
+//! The damage an item has taken is stored in its `Damage` NBT tag, and it breaks once the damage
+//! reaches the maximum durability of its kind. Items with the `Unbreakable` tag never wear out.
+
+use flecs_ecs::core::EntityView;
+use hyperion::{
+    net::{Compose, agnostic},
+    simulation::Position,
+    valence_protocol::ident,
+};
+use hyperion_inventory::PlayerInventory;
+use tracing::error;
+use valence_protocol::{ItemKind, ItemStack, nbt};
+
+use crate::enchantment::{self, Enchantment};
+
+const DAMAGE_KEY: &str = "Damage";
+const UNBREAKABLE_KEY: &str = "Unbreakable";
+
+/// The damage `stack` has taken.
+#[must_use]
+pub fn damage(stack: &ItemStack) -> u16 {
+    match stack.nbt.as_ref().and_then(|nbt| nbt.get(DAMAGE_KEY)) {
+        Some(nbt::Value::Int(damage)) => u16::try_from(*damage).unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+#[must_use]
+pub fn is_unbreakable(stack: &ItemStack) -> bool {
+    matches!(
+        stack.nbt.as_ref().and_then(|nbt| nbt.get(UNBREAKABLE_KEY)),
+        Some(nbt::Value::Byte(1..))
+    )
+}
+
+/// The uses `stack` has left before it breaks, if it can break at all.
+#[must_use]
+pub fn remaining(stack: &ItemStack) -> Option<u16> {
+    let max = stack.item.max_durability();
+
+    if max == 0 || is_unbreakable(stack) {
+        return None;
+    }
+
+    Some(max.saturating_sub(damage(stack)))
+}
+
+fn is_armor(item: ItemKind) -> bool {
+    let name = item.to_str();
+
+    ["_helmet", "_chestplate", "_leggings", "_boots"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+fn is_tool(item: ItemKind) -> bool {
+    let name = item.to_str();
+
+    ["_pickaxe", "_axe", "_shovel", "_hoe"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+/// The durability an item loses when it is used to hit an entity.
+#[must_use]
+pub fn attack_wear(item: ItemKind) -> u16 {
+    if is_tool(item) {
+        2
+    } else {
+        u16::from(item.to_str().ends_with("_sword") || item == ItemKind::Trident)
+    }
+}
+
+/// The durability an item loses when it is used to break a block.
+#[must_use]
+pub fn mining_wear(item: ItemKind) -> u16 {
+    if is_tool(item) || item == ItemKind::Shears {
+        1
+    } else {
+        2 * u16::from(item.to_str().ends_with("_sword") || item == ItemKind::Trident)
+    }
+}
+
+/// The durability each piece of armor loses when its wearer takes `damage`.
+#[must_use]
+pub fn armor_wear(damage: f32) -> u16 {
+    // truncating like vanilla
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let wear = (damage / 4.0) as u16;
+
+    wear.max(1)
+}
+
+/// Wears `stack` out by `amount` uses, each of which Unbreaking may prevent. Returns whether it
+/// broke, which leaves the stack empty.
+pub fn wear(stack: &mut ItemStack, amount: u16) -> bool {
+    let Some(remaining) = remaining(stack) else {
+        return false;
+    };
+
+    let unbreaking = enchantment::level(stack, Enchantment::Unbreaking);
+    let armor = is_armor(stack.item);
+
+    let amount = (0..amount)
+        .filter(|_| !enchantment::unbreaking_prevents(unbreaking, armor))
+        .count();
+    let amount = u16::try_from(amount).unwrap_or(u16::MAX);
+
+    if amount == 0 {
+        return false;
+    }
+
+    if amount >= remaining {
+        *stack = ItemStack::EMPTY;
+        return true;
+    }
+
+    let damage = damage(stack) + amount;
+    let nbt = stack.nbt.get_or_insert_with(nbt::Compound::new);
+    nbt.insert(DAMAGE_KEY, i32::from(damage));
+
+    false
+}
+
+/// Wears out the item in `slot` of an inventory. Returns whether it broke.
+pub fn wear_slot(inventory: &mut PlayerInventory, slot: u16, amount: u16) -> bool {
+    if amount == 0 {
+        return false;
+    }
+
+    match inventory.get(slot) {
+        Ok(stack) if remaining(stack).is_some() => {}
+        _ => return false,
+    }
+
+    inventory
+        .get_mut(slot)
+        .is_ok_and(|stack| wear(stack, amount))
+}
+
+/// Plays the sound of an item breaking to everyone near `position`.
+pub fn play_break_sound(compose: &Compose, position: &Position, system: EntityView<'_>) {
+    let sound = agnostic::sound(ident!("minecraft:entity.item.break"), **position)
+        .volume(0.8)
+        .pitch(fastrand::f32().mul_add(0.4, 0.8))
+        .build();
+
+    if let Err(e) = compose
+        .broadcast_local(&sound, position.to_chunk(), system)
+        .send()
+    {
+        error!("failed to play the item break sound: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wear_breaks_items() {
+        let mut hoe = ItemStack::new(ItemKind::WoodenHoe, 1, None);
+        let max = ItemKind::WoodenHoe.max_durability();
+
+        assert!(!wear(&mut hoe, max - 1));
+        assert_eq!(remaining(&hoe), Some(1));
+        assert!(wear(&mut hoe, 2));
+        assert!(hoe.is_empty());
+
+        let mut dirt = ItemStack::new(ItemKind::Dirt, 1, None);
+        assert!(!wear(&mut dirt, 10));
+        assert_eq!(dirt.count, 1);
+    }
+}
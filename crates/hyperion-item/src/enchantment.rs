@@ -0,0 +1,204 @@
+//! Enchantments stored in the `Enchantments` NBT list of an item.
+//!
+//! Each entry of the list is a compound with the `id` of the enchantment and its `lvl`. Entries
+//! for enchantments hyperion does not model are kept as they are.
+
+use valence_protocol::{ItemStack, nbt};
+
+const ENCHANTMENTS_KEY: &str = "Enchantments";
+
+/// The enchantments whose effects hyperion applies.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Enchantment {
+    Protection,
+    Sharpness,
+    Knockback,
+    /// Mining speed is worked out by the client, which reads the level from the item itself.
+    Efficiency,
+    Unbreaking,
+}
+
+impl Enchantment {
+    pub const ALL: [Self; 5] = [
+        Self::Protection,
+        Self::Sharpness,
+        Self::Knockback,
+        Self::Efficiency,
+        Self::Unbreaking,
+    ];
+
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Protection => "minecraft:protection",
+            Self::Sharpness => "minecraft:sharpness",
+            Self::Knockback => "minecraft:knockback",
+            Self::Efficiency => "minecraft:efficiency",
+            Self::Unbreaking => "minecraft:unbreaking",
+        }
+    }
+
+    #[must_use]
+    pub fn from_id(id: &str) -> Option<Self> {
+        let id = id.strip_prefix("minecraft:").unwrap_or(id);
+        Self::ALL
+            .into_iter()
+            .find(|enchantment| enchantment.id().strip_prefix("minecraft:") == Some(id))
+    }
+
+    /// The highest level obtainable in vanilla.
+    #[must_use]
+    pub const fn max_level(self) -> u16 {
+        match self {
+            Self::Sharpness | Self::Efficiency => 5,
+            Self::Protection => 4,
+            Self::Unbreaking => 3,
+            Self::Knockback => 2,
+        }
+    }
+}
+
+fn entry_level(entry: &nbt::Compound<String>) -> Option<u16> {
+    let level = match entry.get("lvl")? {
+        nbt::Value::Byte(level) => i32::from(*level),
+        nbt::Value::Short(level) => i32::from(*level),
+        nbt::Value::Int(level) => *level,
+        _ => return None,
+    };
+
+    u16::try_from(level).ok()
+}
+
+fn entry_id(entry: &nbt::Compound<String>) -> Option<&str> {
+    match entry.get("id")? {
+        nbt::Value::String(id) => Some(id),
+        _ => None,
+    }
+}
+
+/// The enchantments on `stack` with their levels.
+#[must_use]
+pub fn enchantments(stack: &ItemStack) -> Vec<(Enchantment, u16)> {
+    let Some(nbt::Value::List(nbt::list::List::Compound(entries))) =
+        stack.nbt.as_ref().and_then(|nbt| nbt.get(ENCHANTMENTS_KEY))
+    else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let enchantment = Enchantment::from_id(entry_id(entry)?)?;
+            Some((enchantment, entry_level(entry)?))
+        })
+        .collect()
+}
+
+/// The level of `enchantment` on `stack`, which is 0 if it does not have it.
+#[must_use]
+pub fn level(stack: &ItemStack, enchantment: Enchantment) -> u16 {
+    enchantments(stack)
+        .into_iter()
+        .find(|(on, _)| *on == enchantment)
+        .map_or(0, |(_, level)| level)
+}
+
+/// Sets the level of `enchantment` in the NBT of an item. A level of 0 removes it.
+pub fn set_level_nbt(nbt: &mut nbt::Compound<String>, enchantment: Enchantment, level: u16) {
+    let mut entries = match nbt.remove(ENCHANTMENTS_KEY) {
+        Some(nbt::Value::List(nbt::list::List::Compound(entries))) => entries,
+        _ => Vec::new(),
+    };
+
+    entries.retain(|entry| entry_id(entry).and_then(Enchantment::from_id) != Some(enchantment));
+
+    if level > 0 {
+        let mut entry = nbt::Compound::new();
+        entry.insert("id", enchantment.id().to_string());
+        entry.insert("lvl", i16::try_from(level).unwrap_or(i16::MAX));
+        entries.push(entry);
+    }
+
+    if !entries.is_empty() {
+        nbt.insert(
+            ENCHANTMENTS_KEY,
+            nbt::Value::List(nbt::list::List::Compound(entries)),
+        );
+    }
+}
+
+/// Sets the level of `enchantment` on `stack`. A level of 0 removes it.
+pub fn set_level(stack: &mut ItemStack, enchantment: Enchantment, level: u16) {
+    let nbt = stack.nbt.get_or_insert_with(nbt::Compound::new);
+    set_level_nbt(nbt, enchantment, level);
+}
+
+/// The damage Sharpness adds to an attack.
+#[must_use]
+pub fn sharpness_bonus(level: u16) -> f32 {
+    if level == 0 {
+        return 0.0;
+    }
+
+    f32::from(level).mul_add(0.5, 0.5)
+}
+
+/// The enchantment protection factor of one piece of armor with Protection. The factors of all
+/// pieces are added up, and every point up to 20 reduces damage by 4%.
+#[must_use]
+pub fn protection_factor(level: u16) -> f32 {
+    f32::from(level)
+}
+
+/// The knockback in blocks per tick Knockback adds to an attack.
+#[must_use]
+pub fn knockback_bonus(level: u16) -> f32 {
+    f32::from(level) * 0.5
+}
+
+/// Whether Unbreaking saves an item from losing one point of durability. Armor loses it at least
+/// 60% of the time whatever the level.
+#[must_use]
+pub fn unbreaking_prevents(level: u16, armor: bool) -> bool {
+    if level == 0 || (armor && fastrand::f32() < 0.6) {
+        return false;
+    }
+
+    fastrand::u16(..=level) > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn set_level_keeps_other_enchantments() {
+        let mut sword = ItemStack::new(ItemKind::DiamondSword, 1, None);
+
+        let mut mending = nbt::Compound::new();
+        mending.insert("id", "minecraft:mending".to_string());
+        mending.insert("lvl", 1_i16);
+
+        sword.nbt.get_or_insert_with(nbt::Compound::new).insert(
+            ENCHANTMENTS_KEY,
+            nbt::Value::List(nbt::list::List::Compound(vec![mending])),
+        );
+
+        set_level(&mut sword, Enchantment::Sharpness, 3);
+        set_level(&mut sword, Enchantment::Knockback, 2);
+        set_level(&mut sword, Enchantment::Sharpness, 5);
+        set_level(&mut sword, Enchantment::Knockback, 0);
+
+        assert_eq!(enchantments(&sword), [(Enchantment::Sharpness, 5)]);
+        assert_eq!(level(&sword, Enchantment::Knockback), 0);
+
+        let Some(nbt::Value::List(entries)) = sword.nbt.as_ref().unwrap().get(ENCHANTMENTS_KEY)
+        else {
+            panic!("the enchantments are gone");
+        };
+
+        assert_eq!(entries.len(), 2);
+    }
+}
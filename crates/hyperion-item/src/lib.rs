@@ -8,6 +8,10 @@ use hyperion::storage::{EventFn, GlobalEventHandlers, InteractEvent};
 use valence_protocol::nbt;
 
 pub mod builder;
+pub mod custom;
+pub mod dropped;
+pub mod durability;
+pub mod enchantment;
 
 #[derive(Component)]
 pub struct ItemModule;
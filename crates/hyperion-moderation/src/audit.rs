@@ -0,0 +1,231 @@
+//! An append-only log of every moderation action, kept in the [`LocalDb`].
+
+use std::time::{Duration, SystemTime};
+
+use flecs_ecs::macros::Component;
+use heed::{Database, Env, byteorder::BigEndian, types};
+use hyperion::storage::LocalDb;
+use serde::{Deserialize, Serialize};
+
+/// What a moderator did.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Kick,
+    Ban,
+    IpBan,
+    TempBan,
+    TempIpBan,
+    Unban,
+    Mute,
+    Unmute,
+    Warn,
+    Freeze,
+    Unfreeze,
+    TeleportTo,
+}
+
+impl Action {
+    /// How the action is shown in the log.
+    #[must_use]
+    pub const fn describe(self) -> &'static str {
+        match self {
+            Self::Kick => "kicked",
+            Self::Ban => "banned",
+            Self::IpBan => "IP banned",
+            Self::TempBan => "temporarily banned",
+            Self::TempIpBan => "temporarily IP banned",
+            Self::Unban => "unbanned",
+            Self::Mute => "muted",
+            Self::Unmute => "unmuted",
+            Self::Warn => "warned",
+            Self::Freeze => "froze",
+            Self::Unfreeze => "unfroze",
+            Self::TeleportTo => "teleported to",
+        }
+    }
+}
+
+/// One moderation action.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: SystemTime,
+    /// The name of the moderator.
+    pub moderator: String,
+    pub action: Action,
+    /// The name of the player the action was taken against, or the UUID or IP address if the
+    /// name is unknown.
+    pub target: String,
+    pub target_uuid: Option<uuid::Uuid>,
+    pub reason: Option<String>,
+    /// How long the action lasts, for temporary bans and mutes.
+    pub duration: Option<Duration>,
+}
+
+impl AuditEntry {
+    #[must_use]
+    pub fn new(moderator: impl Into<String>, action: Action, target: impl Into<String>) -> Self {
+        Self {
+            time: SystemTime::now(),
+            moderator: moderator.into(),
+            action,
+            target: target.into(),
+            target_uuid: None,
+            reason: None,
+            duration: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_uuid(mut self, uuid: uuid::Uuid) -> Self {
+        self.target_uuid = Some(uuid);
+        self
+    }
+
+    #[must_use]
+    pub fn with_reason(mut self, reason: Option<String>) -> Self {
+        self.reason = reason;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Whether the entry is about `target`, which is a name or a UUID.
+    #[must_use]
+    pub fn concerns(&self, target: &str) -> bool {
+        match target.parse::<uuid::Uuid>() {
+            Ok(uuid) => self.target_uuid == Some(uuid),
+            Err(_) => self.target.eq_ignore_ascii_case(target),
+        }
+    }
+
+    /// The entry as a line of chat, with how long ago it happened relative to `now`.
+    #[must_use]
+    pub fn format(&self, id: u64, now: SystemTime) -> String {
+        let ago = now.duration_since(self.time).unwrap_or_default();
+        let ago = humantime::format_duration(Duration::from_secs(ago.as_secs()));
+
+        let mut line = format!(
+            "§8#{id} §7{ago} ago §b{} §7{} §f{}",
+            self.moderator,
+            self.action.describe(),
+            self.target
+        );
+
+        if let Some(duration) = self.duration {
+            line.push_str(&format!(
+                " §7for §f{}",
+                humantime::format_duration(duration)
+            ));
+        }
+
+        if let Some(reason) = &self.reason {
+            line.push_str(&format!("§7: §f{reason}"));
+        }
+
+        line
+    }
+}
+
+/// A handler for the audit log. Entries are numbered from 1 in the order they were added, and
+/// are never changed or removed.
+#[derive(Component, Debug, Clone)]
+pub struct AuditLog {
+    env: Env,
+    entries: Database<types::U64<BigEndian>, types::Bytes>,
+}
+
+impl AuditLog {
+    /// Creates a new [`AuditLog`] from a given [`LocalDb`].
+    pub fn new(db: &LocalDb) -> anyhow::Result<Self> {
+        let mut wtxn = db.write_txn()?;
+        let entries = db.create_database(&mut wtxn, Some("audit-log"))?;
+        wtxn.commit()?;
+
+        Ok(Self {
+            env: (**db).clone(),
+            entries,
+        })
+    }
+
+    /// Adds `entry` to the end of the log, returning its number.
+    pub fn append(&self, entry: &AuditEntry) -> anyhow::Result<u64> {
+        let entry = serde_json::to_vec(entry)?;
+
+        let mut wtxn = self.env.write_txn()?;
+
+        let id = match self.entries.last(&wtxn)? {
+            Some((last, _)) => last + 1,
+            None => 1,
+        };
+
+        self.entries.put(&mut wtxn, &id, &entry)?;
+        wtxn.commit()?;
+
+        Ok(id)
+    }
+
+    /// Up to `limit` entries, newest first, after skipping the `skip` newest. Only entries which
+    /// [concern](AuditEntry::concerns) `target` are counted if it is given.
+    pub fn recent(
+        &self,
+        target: Option<&str>,
+        skip: usize,
+        limit: usize,
+    ) -> anyhow::Result<Vec<(u64, AuditEntry)>> {
+        let rtxn = self.env.read_txn()?;
+        let mut entries = Vec::new();
+        let mut skipped = 0;
+
+        for entry in self.entries.rev_iter(&rtxn)? {
+            let (id, entry) = entry?;
+            let entry: AuditEntry = serde_json::from_slice(entry)?;
+
+            if target.is_some_and(|target| !entry.concerns(target)) {
+                continue;
+            }
+
+            if skipped < skip {
+                skipped += 1;
+                continue;
+            }
+
+            entries.push((id, entry));
+
+            if entries.len() == limit {
+                break;
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_match_names_and_uuids() {
+        let uuid = uuid::Uuid::from_u128(0x1234);
+
+        let entry = AuditEntry::new("jeb_", Action::TempBan, "Notch")
+            .with_uuid(uuid)
+            .with_reason(Some("griefing".to_owned()))
+            .with_duration(Some(Duration::from_secs(3600)));
+
+        assert!(entry.concerns("notch"));
+        assert!(entry.concerns(&uuid.to_string()));
+        assert!(!entry.concerns("jeb_"));
+        assert!(!entry.concerns(&uuid::Uuid::nil().to_string()));
+
+        let line = entry.format(7, entry.time + Duration::from_secs(60));
+        assert_eq!(
+            line,
+            "§8#7 §71m ago §bjeb_ §7temporarily banned §fNotch §7for §f1h§7: §fgriefing"
+        );
+    }
+}
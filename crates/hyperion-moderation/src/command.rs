@@ -0,0 +1,819 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use clap::Parser;
+use flecs_ecs::{
+    core::{Entity, EntityView, EntityViewGet, WorldGet, WorldProvider},
+    prelude::*,
+};
+use hyperion::{
+    ingress::PendingRemove,
+    net::{Compose, ConnectionId, agnostic},
+    simulation::{
+        IgnMap, Name, Pitch, PlayerIp, Position, Uuid, Yaw, game_mode::GameMode, keep_alive::Ping,
+    },
+    storage::{Ban, BanList},
+    valence_protocol::{
+        VarInt,
+        packets::play::{self, player_position_look_s2c::PlayerPositionLookFlags},
+    },
+};
+use hyperion_chat::Muted;
+use hyperion_clap::{
+    CommandPermission, MinecraftCommand, PlayerArg, ProfileArg, hyperion_command::CommandRegistry,
+};
+use hyperion_permission::{Groups, PermissionStorage, Permissions, has_permission};
+use tracing::error;
+
+use crate::{Action, AuditEntry, AuditLog, EXEMPT_NODE, Frozen, PendingKick, PendingKicks};
+
+/// How many audit log entries `/modlog` shows at once.
+const PAGE_SIZE: usize = 8;
+
+pub fn register(registry: &mut CommandRegistry, world: &World) {
+    BanCommand::register(registry, world);
+    FreezeCommand::register(registry, world);
+    InspectCommand::register(registry, world);
+    KickCommand::register(registry, world);
+    ModLogCommand::register(registry, world);
+    MuteCommand::register(registry, world);
+    TeleportToCommand::register(registry, world);
+    TempBanCommand::register(registry, world);
+    UnbanCommand::register(registry, world);
+    UnmuteCommand::register(registry, world);
+    WarnCommand::register(registry, world);
+}
+
+/// Sends `message` to `player` in chat.
+fn tell(system: EntityView<'_>, player: EntityView<'_>, message: impl Into<String>) {
+    let chat = agnostic::chat(message);
+
+    system.world().get::<&Compose>(|compose| {
+        player.try_get::<&ConnectionId>(|io| {
+            if let Err(e) = compose.unicast(&chat, *io, system) {
+                error!("failed to send chat message: {e}");
+            }
+        });
+    });
+}
+
+/// The online player called `name`, telling `caller` if there is none.
+fn find_player<'a>(
+    system: EntityView<'a>,
+    caller: EntityView<'a>,
+    name: &str,
+) -> Option<EntityView<'a>> {
+    let world = system.world();
+
+    let player = world.get::<&IgnMap>(|ign_map| ign_map.get(name).copied());
+
+    if player.is_none() {
+        tell(system, caller, format!("§c{name} is not online"));
+    }
+
+    player.map(|player| player.entity_view(world))
+}
+
+fn identity(player: EntityView<'_>) -> (String, uuid::Uuid) {
+    player.get::<(&Name, &Uuid)>(|(name, uuid)| (name.to_string(), **uuid))
+}
+
+fn name_of(player: EntityView<'_>) -> String {
+    player.get::<&Name>(ToString::to_string)
+}
+
+/// Whether `caller` may take action against `target`, telling them if not. Moderators cannot
+/// act against themselves, or against players with the [exempt node](EXEMPT_NODE) unless they
+/// have it too.
+fn may_moderate(system: EntityView<'_>, caller: EntityView<'_>, target: EntityView<'_>) -> bool {
+    let world = system.world();
+    let exempt = |player: EntityView<'_>| has_permission(&world, *player, EXEMPT_NODE);
+
+    if target == caller {
+        tell(system, caller, "§cYou cannot do that to yourself");
+        return false;
+    }
+
+    if exempt(target) && !exempt(caller) {
+        let name = name_of(target);
+        tell(system, caller, format!("§cYou cannot do that to {name}"));
+        return false;
+    }
+
+    true
+}
+
+/// Like [`may_moderate`] for a player who is not online, whose stored permissions are checked.
+fn may_moderate_offline(
+    system: EntityView<'_>,
+    caller: EntityView<'_>,
+    target: &Offender<'_>,
+) -> bool {
+    let world = system.world();
+
+    let exempt = world.get::<&Groups>(|groups| {
+        world.get::<&PermissionStorage>(|storage| {
+            groups.check(&storage.get(target.uuid), EXEMPT_NODE)
+        })
+    });
+
+    if exempt && !has_permission(&world, *caller, EXEMPT_NODE) {
+        tell(
+            system,
+            caller,
+            format!("§cYou cannot do that to {}", target.name),
+        );
+        return false;
+    }
+
+    true
+}
+
+/// Adds `entry` to the audit log, telling `caller` if it could not be written.
+fn record(system: EntityView<'_>, caller: EntityView<'_>, entry: &AuditEntry) {
+    let result = system.world().get::<&AuditLog>(|log| log.append(entry));
+
+    if let Err(e) = result {
+        error!("failed to write to the audit log: {e}");
+        tell(
+            system,
+            caller,
+            "§cThis action could not be written to the audit log",
+        );
+    }
+}
+
+/// The words of a reason, or [`None`] if none were given.
+fn reason(words: &[String]) -> Option<String> {
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+/// A player to ban, who does not have to be online.
+struct Offender<'a> {
+    name: String,
+    uuid: uuid::Uuid,
+    /// The address the player is connected from, if they are online and it is known.
+    ip: Option<IpAddr>,
+    online: Option<EntityView<'a>>,
+}
+
+impl<'a> Offender<'a> {
+    fn online(player: EntityView<'a>) -> Self {
+        player.get::<(&Name, &Uuid, Option<&PlayerIp>)>(|(name, uuid, ip)| Self {
+            name: name.to_string(),
+            uuid: **uuid,
+            ip: ip.map(|ip| ip.0).filter(|ip| !ip.is_unspecified()),
+            online: Some(player),
+        })
+    }
+}
+
+/// The player `target` refers to, which is either the name of an online player or the UUID of
+/// any player, telling `caller` if it is neither.
+fn find_offender<'a>(
+    system: EntityView<'a>,
+    caller: EntityView<'a>,
+    target: &str,
+) -> Option<Offender<'a>> {
+    let world = system.world();
+
+    if let Some(player) = world.get::<&IgnMap>(|ign_map| ign_map.get(target).copied()) {
+        return Some(Offender::online(player.entity_view(world)));
+    }
+
+    let Ok(uuid) = target.parse::<uuid::Uuid>() else {
+        tell(
+            system,
+            caller,
+            format!("§c{target} is not online, so use their UUID instead"),
+        );
+        return None;
+    };
+
+    // the player may be online under a name the caller did not use
+    let online = world.get::<&IgnMap>(|ign_map| {
+        ign_map.values().copied().find(|player| {
+            player
+                .entity_view(world)
+                .try_get::<&Uuid>(|id| **id == uuid)
+                .unwrap_or_default()
+        })
+    });
+
+    if let Some(player) = online {
+        return Some(Offender::online(player.entity_view(world)));
+    }
+
+    Some(Offender {
+        name: target.to_owned(),
+        uuid,
+        ip: None,
+        online: None,
+    })
+}
+
+/// Bans `target`, and the IP address they are connected from if `ip` is set, kicking them if
+/// they are online.
+fn ban(
+    system: EntityView<'_>,
+    caller: EntityView<'_>,
+    target: &str,
+    ip: bool,
+    duration: Option<Duration>,
+    reason: Option<String>,
+) {
+    let world = system.world();
+
+    let Some(offender) = find_offender(system, caller, target) else {
+        return;
+    };
+
+    let allowed = match offender.online {
+        Some(player) => may_moderate(system, caller, player),
+        None => may_moderate_offline(system, caller, &offender),
+    };
+
+    if !allowed {
+        return;
+    }
+
+    let ip = match (ip, offender.ip) {
+        (false, _) => None,
+        (true, Some(ip)) => Some(ip),
+        (true, None) => {
+            tell(
+                system,
+                caller,
+                format!("§cThe IP address of {} is not known", offender.name),
+            );
+            return;
+        }
+    };
+
+    let now = SystemTime::now();
+
+    let ban = Ban {
+        name: Some(offender.name.clone()),
+        reason: reason
+            .clone()
+            .unwrap_or_else(|| "Banned by a moderator".to_owned()),
+        by: name_of(caller),
+        since: now,
+        until: duration.map(|duration| now + duration),
+    };
+
+    let result = world.get::<&BanList>(|bans| {
+        bans.ban_player(offender.uuid, &ban)?;
+
+        if let Some(ip) = ip {
+            bans.ban_ip(ip, &ban)?;
+        }
+
+        anyhow::Ok(())
+    });
+
+    if let Err(e) = result {
+        error!("failed to ban {}: {e}", offender.name);
+        tell(system, caller, "§cThe ban could not be saved");
+        return;
+    }
+
+    let message = ban.disconnect_message(now);
+
+    // the player may still be online under another name, and others may share the IP address
+    world.get::<&mut PendingKicks>(|kicks| {
+        kicks.0.push(PendingKick {
+            uuid: offender.uuid,
+            ip,
+            message,
+        });
+    });
+
+    let action = match (duration, ip) {
+        (Some(_), Some(_)) => Action::TempIpBan,
+        (Some(_), None) => Action::TempBan,
+        (None, Some(_)) => Action::IpBan,
+        (None, None) => Action::Ban,
+    };
+
+    record(
+        system,
+        caller,
+        &AuditEntry::new(ban.by, action, &offender.name)
+            .with_uuid(offender.uuid)
+            .with_reason(reason)
+            .with_duration(duration),
+    );
+
+    let message = match duration {
+        Some(duration) => format!(
+            "§7Banned §b{} §7for §f{}",
+            offender.name,
+            humantime::format_duration(duration)
+        ),
+        None => format!("§7Banned §b{}", offender.name),
+    };
+
+    tell(system, caller, message);
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "kick")]
+#[command(about = "Disconnect a player")]
+#[command_permission(group = "Moderator")]
+pub struct KickCommand {
+    player: PlayerArg,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    reason: Vec<String>,
+}
+
+impl MinecraftCommand for KickCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let caller = caller.entity_view(system.world());
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        if !may_moderate(system, caller, target) {
+            return;
+        }
+
+        let reason = reason(&self.reason);
+
+        let message = match &reason {
+            Some(reason) => format!("§cYou were kicked: §f{reason}"),
+            None => "§cYou were kicked".to_owned(),
+        };
+
+        target.set(PendingRemove::new(message));
+
+        let (name, uuid) = identity(target);
+
+        record(
+            system,
+            caller,
+            &AuditEntry::new(name_of(caller), Action::Kick, &name)
+                .with_uuid(uuid)
+                .with_reason(reason),
+        );
+
+        tell(system, caller, format!("§7Kicked §b{name}"));
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "ban")]
+#[command(about = "Ban a player for good")]
+#[command_permission(group = "Moderator")]
+pub struct BanCommand {
+    /// The name of an online player, or the UUID of any player.
+    player: ProfileArg,
+    /// Also ban the IP address the player is connected from.
+    #[arg(long)]
+    ip: bool,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    reason: Vec<String>,
+}
+
+impl MinecraftCommand for BanCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let caller = caller.entity_view(system.world());
+
+        ban(
+            system,
+            caller,
+            &self.player,
+            self.ip,
+            None,
+            reason(&self.reason),
+        );
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "tempban")]
+#[command(about = "Ban a player for a while")]
+#[command_permission(group = "Moderator")]
+pub struct TempBanCommand {
+    /// The name of an online player, or the UUID of any player.
+    player: ProfileArg,
+    /// How long the ban lasts, such as `30m` or `7days`.
+    duration: humantime::Duration,
+    /// Also ban the IP address the player is connected from.
+    #[arg(long)]
+    ip: bool,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    reason: Vec<String>,
+}
+
+impl MinecraftCommand for TempBanCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let caller = caller.entity_view(system.world());
+
+        ban(
+            system,
+            caller,
+            &self.player,
+            self.ip,
+            Some(*self.duration),
+            reason(&self.reason),
+        );
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "unban")]
+#[command(about = "Lift the bans of a player or IP address")]
+#[command_permission(group = "Moderator")]
+pub struct UnbanCommand {
+    /// The name or UUID of a banned player, or a banned IP address.
+    target: String,
+}
+
+impl MinecraftCommand for UnbanCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+        let target = self.target;
+
+        match world.get::<&BanList>(|bans| bans.unban(&target)) {
+            Ok(0) => tell(system, caller, format!("§c{target} is not banned")),
+            Ok(_) => {
+                let mut entry = AuditEntry::new(name_of(caller), Action::Unban, &target);
+                entry.target_uuid = target.parse().ok();
+
+                record(system, caller, &entry);
+                tell(system, caller, format!("§7Unbanned §b{target}"));
+            }
+            Err(e) => {
+                error!("failed to unban {target}: {e}");
+                tell(system, caller, "§cThe ban could not be lifted");
+            }
+        }
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "mute")]
+#[command(about = "Stop a player from chatting")]
+#[command_permission(group = "Moderator")]
+pub struct MuteCommand {
+    player: PlayerArg,
+    /// How long the mute lasts, such as `10m` or `1h30m`. Without one, the mute is permanent.
+    #[arg(short, long)]
+    duration: Option<humantime::Duration>,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    reason: Vec<String>,
+}
+
+impl MinecraftCommand for MuteCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let caller = caller.entity_view(system.world());
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        if !may_moderate(system, caller, target) {
+            return;
+        }
+
+        let duration = self.duration.map(Into::into);
+        let reason = reason(&self.reason);
+
+        let mut muted = match duration {
+            Some(duration) => Muted::temporary(duration),
+            None => Muted::permanent(),
+        };
+        muted.reason.clone_from(&reason);
+
+        target.set(muted);
+
+        let length = match duration {
+            Some(duration) => format!(" §7for §f{}", humantime::format_duration(duration)),
+            None => String::new(),
+        };
+
+        tell(system, target, format!("§cYou were muted{length}"));
+
+        let (name, uuid) = identity(target);
+
+        record(
+            system,
+            caller,
+            &AuditEntry::new(name_of(caller), Action::Mute, &name)
+                .with_uuid(uuid)
+                .with_reason(reason)
+                .with_duration(duration),
+        );
+
+        tell(system, caller, format!("§7Muted §b{name}{length}"));
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "unmute")]
+#[command(about = "Let a muted player chat again")]
+#[command_permission(group = "Moderator")]
+pub struct UnmuteCommand {
+    player: PlayerArg,
+}
+
+impl MinecraftCommand for UnmuteCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let caller = caller.entity_view(system.world());
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        let (name, uuid) = identity(target);
+
+        if !target.has::<Muted>() {
+            tell(system, caller, format!("§c{name} is not muted"));
+            return;
+        }
+
+        target.remove::<Muted>();
+
+        tell(system, target, "§aYou can chat again");
+
+        record(
+            system,
+            caller,
+            &AuditEntry::new(name_of(caller), Action::Unmute, &name).with_uuid(uuid),
+        );
+
+        tell(system, caller, format!("§7Unmuted §b{name}"));
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "warn")]
+#[command(about = "Warn a player")]
+#[command_permission(group = "Moderator")]
+pub struct WarnCommand {
+    player: PlayerArg,
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    reason: Vec<String>,
+}
+
+impl MinecraftCommand for WarnCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let caller = caller.entity_view(system.world());
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        if !may_moderate(system, caller, target) {
+            return;
+        }
+
+        let reason = self.reason.join(" ");
+
+        tell(system, target, format!("§c§lWarning: §f{reason}"));
+
+        let (name, uuid) = identity(target);
+
+        record(
+            system,
+            caller,
+            &AuditEntry::new(name_of(caller), Action::Warn, &name)
+                .with_uuid(uuid)
+                .with_reason(Some(reason)),
+        );
+
+        tell(system, caller, format!("§7Warned §b{name}"));
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "freeze")]
+#[command(about = "Stop or let a player move")]
+#[command_permission(group = "Moderator")]
+pub struct FreezeCommand {
+    player: PlayerArg,
+}
+
+impl MinecraftCommand for FreezeCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let caller = caller.entity_view(system.world());
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        if !may_moderate(system, caller, target) {
+            return;
+        }
+
+        let (name, uuid) = identity(target);
+
+        let action = if target.has::<Frozen>() {
+            target.remove::<Frozen>();
+            tell(system, target, "§aYou can move again");
+            tell(system, caller, format!("§7Unfroze §b{name}"));
+            Action::Unfreeze
+        } else {
+            let at = target.get::<&Position>(|position| **position);
+            target.set(Frozen { at });
+            tell(system, target, "§cYou were frozen by a moderator");
+            tell(system, caller, format!("§7Froze §b{name}"));
+            Action::Freeze
+        };
+
+        record(
+            system,
+            caller,
+            &AuditEntry::new(name_of(caller), action, &name).with_uuid(uuid),
+        );
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "tpto")]
+#[command(about = "Teleport to a player")]
+#[command_permission(group = "Moderator")]
+pub struct TeleportToCommand {
+    player: PlayerArg,
+}
+
+impl MinecraftCommand for TeleportToCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        if target == caller {
+            tell(system, caller, "§cYou cannot teleport to yourself");
+            return;
+        }
+
+        let destination = target.get::<&Position>(|position| **position);
+
+        caller.get::<(&mut Position, &Yaw, &Pitch, &ConnectionId)>(|(position, yaw, pitch, io)| {
+            **position = destination;
+
+            let pkt = play::PlayerPositionLookS2c {
+                position: destination.as_dvec3(),
+                yaw: **yaw,
+                pitch: **pitch,
+                flags: PlayerPositionLookFlags::default(),
+                teleport_id: VarInt(fastrand::i32(..)),
+            };
+
+            world.get::<&Compose>(|compose| {
+                if let Err(e) = compose.unicast(&pkt, *io, system) {
+                    error!("failed to teleport player: {e}");
+                }
+            });
+        });
+
+        let (name, uuid) = identity(target);
+
+        record(
+            system,
+            caller,
+            &AuditEntry::new(name_of(caller), Action::TeleportTo, &name).with_uuid(uuid),
+        );
+
+        tell(system, caller, format!("§7Teleported to §b{name}"));
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "inspect")]
+#[command(about = "Show what is known about a player")]
+#[command_permission(group = "Moderator")]
+pub struct InspectCommand {
+    player: PlayerArg,
+}
+
+impl MinecraftCommand for InspectCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+
+        let Some(target) = find_player(system, caller, &self.player) else {
+            return;
+        };
+
+        let (name, uuid) = identity(target);
+
+        let details = target.get::<(
+            Option<&PlayerIp>,
+            Option<&Permissions>,
+            &Position,
+            Option<&GameMode>,
+            Option<&Ping>,
+        )>(|(ip, permissions, position, game_mode, ping)| {
+            let ip = ip.map_or_else(|| "unknown".to_owned(), ToString::to_string);
+            let groups = permissions.map_or_else(
+                || "none".to_owned(),
+                |permissions| permissions.groups().collect::<Vec<_>>().join(", "),
+            );
+
+            [
+                format!("§7UUID: §f{uuid}"),
+                format!("§7IP: §f{ip}"),
+                format!("§7Groups: §f{groups}"),
+                format!(
+                    "§7Position: §f{:.1} {:.1} {:.1}",
+                    position.x, position.y, position.z
+                ),
+                format!(
+                    "§7Game mode: §f{:?}",
+                    game_mode.copied().unwrap_or_default()
+                ),
+                format!("§7Ping: §f{}ms", ping.map_or(0, |ping| ping.millis)),
+            ]
+        });
+
+        let muted = target
+            .try_get::<&Muted>(|muted| !muted.is_expired(SystemTime::now()))
+            .unwrap_or_default();
+        let frozen = target.has::<Frozen>();
+
+        let history = world
+            .get::<&AuditLog>(|log| log.recent(Some(&name), 0, usize::MAX))
+            .map_or_else(
+                |e| {
+                    error!("failed to read the audit log: {e}");
+                    "unknown".to_owned()
+                },
+                |entries| entries.len().to_string(),
+            );
+
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+
+        let mut lines = vec![format!("§7--- §b{name} §7---")];
+        lines.extend(details);
+        lines.push(format!(
+            "§7Muted: §f{} §7Frozen: §f{}",
+            yes_no(muted),
+            yes_no(frozen)
+        ));
+        lines.push(format!(
+            "§7Moderation log: §f{history} entries §7(§b/modlog {name}§7)"
+        ));
+
+        tell(system, caller, lines.join("\n"));
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "modlog")]
+#[command(about = "Show the latest moderation actions")]
+#[command_permission(group = "Moderator")]
+pub struct ModLogCommand {
+    /// Only show actions against this player, given by name or UUID.
+    player: Option<String>,
+    #[arg(short, long, default_value_t = 1)]
+    page: usize,
+}
+
+impl MinecraftCommand for ModLogCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+
+        let page = self.page.max(1);
+        let skip = (page - 1) * PAGE_SIZE;
+
+        let entries =
+            world.get::<&AuditLog>(|log| log.recent(self.player.as_deref(), skip, PAGE_SIZE));
+
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("failed to read the audit log: {e}");
+                tell(system, caller, "§cThe audit log could not be read");
+                return;
+            }
+        };
+
+        let about = self
+            .player
+            .as_ref()
+            .map(|player| format!(" §7of §b{player}"))
+            .unwrap_or_default();
+
+        let mut lines = vec![format!("§7--- Moderation log{about}§7, page {page} ---")];
+
+        if entries.is_empty() {
+            lines.push("§7Nothing to show".to_owned());
+        }
+
+        let now = SystemTime::now();
+        lines.extend(entries.iter().map(|(id, entry)| entry.format(*id, now)));
+
+        tell(system, caller, lines.join("\n"));
+    }
+}
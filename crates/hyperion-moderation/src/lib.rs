@@ -0,0 +1,151 @@
+//! Commands for moderators, and an [`AuditLog`] of everything they do with them.
+//!
+//! | command | |
+//! |---------|-|
+//! | `/kick <player> [reason]` | disconnects a player |
+//! | `/ban <player> [--ip] [reason]` | bans a player, and optionally their IP address, for good |
+//! | `/tempban <player> <duration> [--ip] [reason]` | bans a player for a while |
+//! | `/unban <player or ip>` | lifts bans |
+//! | `/mute <player> [-d duration] [reason]` | stops a player from chatting |
+//! | `/unmute <player>` | lets a player chat again |
+//! | `/warn <player> <reason>` | warns a player |
+//! | `/freeze <player>` | stops or lets a player move |
+//! | `/tpto <player>` | teleports to a player |
+//! | `/inspect <player>` | shows what is known about a player |
+//! | `/modlog [player] [-p page]` | shows the audit log |
+//!
+//! Players who are not online can be banned by their UUID. Bans are kept in the
+//! [`BanList`](hyperion::storage::BanList), which is checked when players log in.
+
+use std::net::IpAddr;
+
+use flecs_ecs::prelude::*;
+use hyperion::{
+    glam::Vec3,
+    ingress::PendingRemove,
+    net::{Compose, ConnectionId},
+    simulation::{Pitch, Player, PlayerIp, Position, Uuid, Yaw},
+    storage::LocalDb,
+    valence_protocol::{
+        VarInt,
+        packets::play::{self, player_position_look_s2c::PlayerPositionLookFlags},
+    },
+};
+use hyperion_clap::hyperion_command::{self, CommandRegistry};
+use tracing::error;
+
+mod audit;
+mod command;
+
+pub use audit::{Action, AuditEntry, AuditLog};
+pub use command::{
+    BanCommand, FreezeCommand, InspectCommand, KickCommand, ModLogCommand, MuteCommand,
+    TeleportToCommand, TempBanCommand, UnbanCommand, UnmuteCommand, WarnCommand,
+};
+
+/// Players with this permission node can only be moderated by players who have it too. Admins
+/// have it by default.
+pub const EXEMPT_NODE: &str = "hyperion_moderation.exempt";
+
+/// Keeps a player in place until they are unfrozen.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct Frozen {
+    pub at: Vec3,
+}
+
+/// A ban which kicks whoever is online with the banned UUID or, for IP bans, from the banned IP
+/// address.
+struct PendingKick {
+    uuid: uuid::Uuid,
+    ip: Option<IpAddr>,
+    message: String,
+}
+
+impl PendingKick {
+    fn applies_to(&self, uuid: uuid::Uuid, ip: Option<IpAddr>) -> bool {
+        self.uuid == uuid || (self.ip.is_some() && self.ip == ip)
+    }
+}
+
+/// Bans whose players are kicked at the end of the tick.
+#[derive(Component, Default)]
+struct PendingKicks(Vec<PendingKick>);
+
+#[derive(Component)]
+pub struct ModerationModule;
+
+impl Module for ModerationModule {
+    fn module(world: &World) {
+        world.component::<Frozen>();
+        world.component::<AuditLog>();
+        world.component::<PendingKicks>();
+
+        world.set(PendingKicks::default());
+
+        world.get::<&LocalDb>(|db| {
+            let log = AuditLog::new(db).unwrap();
+            world.set(log);
+        });
+
+        // mutes are kept by the chat module
+        world.import::<hyperion_chat::ChatModule>();
+        world.import::<hyperion_command::CommandModule>();
+
+        world.get::<&mut CommandRegistry>(|registry| {
+            command::register(registry, world);
+        });
+
+        let players = world
+            .query::<(&Uuid, Option<&PlayerIp>)>()
+            .with::<Player>()
+            .build();
+
+        system!("kick_banned_players", world, &mut PendingKicks($)).each_iter(
+            move |_, _, kicks| {
+                if kicks.0.is_empty() {
+                    return;
+                }
+
+                players.each_entity(|player, (uuid, ip)| {
+                    let ip = ip.map(|ip| ip.0);
+
+                    if let Some(kick) = kicks.0.iter().find(|kick| kick.applies_to(**uuid, ip)) {
+                        player.set(PendingRemove::new(kick.message.clone()));
+                    }
+                });
+
+                kicks.0.clear();
+            },
+        );
+
+        system!(
+            "hold_frozen_players",
+            world,
+            &Compose($),
+            &mut Position,
+            &Frozen,
+            &Yaw,
+            &Pitch,
+            &ConnectionId,
+        )
+        .each_iter(|it, _, (compose, position, frozen, yaw, pitch, io)| {
+            if **position == frozen.at {
+                return;
+            }
+
+            **position = frozen.at;
+
+            let pkt = play::PlayerPositionLookS2c {
+                position: frozen.at.as_dvec3(),
+                yaw: **yaw,
+                pitch: **pitch,
+                flags: PlayerPositionLookFlags::default(),
+                teleport_id: VarInt(fastrand::i32(..)),
+            };
+
+            if let Err(e) = compose.unicast(&pkt, *io, it.system()) {
+                error!("failed to hold frozen player in place: {e}");
+            }
+        });
+    }
+}
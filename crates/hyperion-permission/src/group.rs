@@ -0,0 +1,257 @@
+//! Groups of players who share permissions, such as `moderator` or `builder`.
+
+use std::collections::BTreeMap;
+
+use flecs_ecs::macros::Component;
+use serde::{Deserialize, Serialize};
+
+use crate::{Permissions, node};
+
+/// The group of players who are in no group.
+pub const DEFAULT_GROUP: &str = "normal";
+
+/// Players in this group are kicked when they join.
+pub const BANNED_GROUP: &str = "banned";
+
+/// The groups which always exist.
+const BUILT_IN: [&str; 4] = [BANNED_GROUP, DEFAULT_GROUP, "moderator", "admin"];
+
+/// The nodes of a group, and the groups it inherits from.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionGroup {
+    /// The groups whose nodes apply to this group unless its own nodes say otherwise.
+    pub parents: Vec<String>,
+    /// The nodes given to or taken from the group in game.
+    pub nodes: Vec<String>,
+    /// The nodes given to the group by code, such as the nodes of the commands it may use. They
+    /// are not stored, and its other nodes win over them.
+    #[serde(skip)]
+    pub defaults: Vec<String>,
+}
+
+impl PermissionGroup {
+    fn inheriting(parent: &str) -> Self {
+        Self {
+            parents: vec![parent.to_owned()],
+            ..Self::default()
+        }
+    }
+
+    fn decide(&self, node: &str) -> Option<bool> {
+        node::decide(&self.nodes, node).or_else(|| node::decide(&self.defaults, node))
+    }
+}
+
+/// Every permission group. The groups `banned`, `normal`, `moderator` and `admin` always exist,
+/// and admins have every node.
+#[derive(Component, Debug)]
+pub struct Groups {
+    groups: BTreeMap<String, PermissionGroup>,
+    /// Whether the groups changed since players were last sent the commands they may use.
+    pub(crate) changed: bool,
+}
+
+impl Default for Groups {
+    fn default() -> Self {
+        let admin = PermissionGroup {
+            defaults: vec!["*".to_owned()],
+            ..PermissionGroup::inheriting("moderator")
+        };
+
+        let groups = [
+            PermissionGroup::default(),
+            PermissionGroup::default(),
+            PermissionGroup::inheriting(DEFAULT_GROUP),
+            admin,
+        ];
+
+        Self {
+            groups: BUILT_IN
+                .into_iter()
+                .map(str::to_owned)
+                .zip(groups)
+                .collect(),
+            changed: false,
+        }
+    }
+}
+
+impl Groups {
+    /// The built-in groups, with the nodes and parents of the stored groups in place of theirs.
+    #[must_use]
+    pub fn with_stored(stored: impl IntoIterator<Item = (String, PermissionGroup)>) -> Self {
+        let mut groups = Self::default();
+
+        for (name, group) in stored {
+            let entry = groups.groups.entry(name).or_default();
+            entry.parents = group.parents;
+            entry.nodes = group.nodes;
+        }
+
+        groups
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&PermissionGroup> {
+        self.groups.get(name)
+    }
+
+    #[must_use]
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut PermissionGroup> {
+        let group = self.groups.get_mut(name)?;
+        self.changed = true;
+        Some(group)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.groups.keys().map(String::as_str)
+    }
+
+    /// Adds an empty group, returning `false` if it already exists.
+    pub fn create(&mut self, name: &str, parents: Vec<String>) -> bool {
+        if self.groups.contains_key(name) {
+            return false;
+        }
+
+        self.groups.insert(name.to_owned(), PermissionGroup {
+            parents,
+            ..PermissionGroup::default()
+        });
+
+        self.changed = true;
+        true
+    }
+
+    /// Removes a group. The built-in groups cannot be removed.
+    pub fn remove(&mut self, name: &str) -> Option<PermissionGroup> {
+        if BUILT_IN.contains(&name) {
+            return None;
+        }
+
+        self.changed = true;
+        self.groups.remove(name)
+    }
+
+    /// Gives `node` to `group` by default, creating the group if it does not exist. This is how
+    /// code sets up permissions, such as the commands a group may use.
+    pub fn grant_default(&mut self, group: &str, node: impl Into<String>) {
+        let node = node.into();
+        let group = self.groups.entry(group.to_owned()).or_default();
+
+        if !group.defaults.contains(&node) {
+            group.defaults.push(node);
+            self.changed = true;
+        }
+    }
+
+    /// Whether a player with `permissions` has `node`. The nodes of the player come first, then
+    /// those of their groups in order, and then those of the parents of the groups. The first of
+    /// these to match the node decides.
+    #[must_use]
+    pub fn check(&self, permissions: &Permissions, node: &str) -> bool {
+        if let Some(allowed) = node::decide(&permissions.nodes, node) {
+            return allowed;
+        }
+
+        let mut queue: Vec<&str> = permissions.groups().collect();
+        let mut next = 0;
+
+        // breadth first, so that a group wins over the groups it inherits from
+        while let Some(&name) = queue.get(next) {
+            next += 1;
+
+            let Some(group) = self.groups.get(name) else {
+                continue;
+            };
+
+            if let Some(allowed) = group.decide(node) {
+                return allowed;
+            }
+
+            for parent in &group.parents {
+                if !queue.contains(&parent.as_str()) {
+                    queue.push(parent);
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_inherit_nodes() {
+        let mut groups = Groups::with_stored([
+            ("builder".to_owned(), PermissionGroup {
+                parents: vec![DEFAULT_GROUP.to_owned()],
+                nodes: vec!["tag.command.replace".to_owned()],
+                ..PermissionGroup::default()
+            }),
+            ("moderator".to_owned(), PermissionGroup {
+                parents: vec![DEFAULT_GROUP.to_owned()],
+                nodes: vec!["-tag.command.fly".to_owned()],
+                ..PermissionGroup::default()
+            }),
+        ]);
+
+        groups.grant_default(DEFAULT_GROUP, "tag.command.spawn");
+        groups.grant_default("moderator", "tag.command.fly");
+
+        let normal = Permissions::default();
+        let builder = Permissions {
+            groups: vec!["builder".to_owned()],
+            nodes: Vec::new(),
+        };
+        let moderator = Permissions {
+            groups: vec!["moderator".to_owned()],
+            nodes: Vec::new(),
+        };
+        let admin = Permissions {
+            groups: vec!["admin".to_owned()],
+            nodes: vec!["-tag.command.spawn".to_owned()],
+        };
+
+        assert!(groups.check(&normal, "tag.command.spawn"));
+        assert!(!groups.check(&normal, "tag.command.replace"));
+
+        assert!(groups.check(&builder, "tag.command.spawn"));
+        assert!(groups.check(&builder, "tag.command.replace"));
+
+        // stored nodes win over the defaults
+        assert!(!groups.check(&moderator, "tag.command.fly"));
+
+        assert!(groups.check(&admin, "tag.command.replace"));
+        assert!(!groups.check(&admin, "tag.command.spawn"));
+    }
+
+    #[test]
+    fn inheritance_cycles_end() {
+        let groups = Groups::with_stored([
+            ("a".to_owned(), PermissionGroup::inheriting("b")),
+            ("b".to_owned(), PermissionGroup::inheriting("a")),
+        ]);
+
+        let permissions = Permissions {
+            groups: vec!["a".to_owned()],
+            nodes: Vec::new(),
+        };
+
+        assert!(!groups.check(&permissions, "anything"));
+    }
+
+    #[test]
+    fn only_found_groups_are_changed() {
+        let mut groups = Groups::with_stored([("builder".to_owned(), PermissionGroup::default())]);
+        groups.changed = false;
+
+        assert!(groups.get_mut("missing").is_none());
+        assert!(!groups.changed);
+
+        assert!(groups.get_mut("builder").is_some());
+        assert!(groups.changed);
+    }
+}
@@ -1,72 +1,134 @@
-use clap::ValueEnum;
+//! Permission nodes, groups with inheritance and per-player permissions.
+//!
+//! Every player has [`Permissions`]: the groups they are in and the [nodes](node) given to or
+//! taken from them directly. Both are stored in the [`LocalDb`], as are the groups changed in
+//! game. Code gives its nodes to groups by default with [`Groups::grant_default`], and asks
+//! whether a player has one with [`has_permission`].
+
 use flecs_ecs::{
-    core::{EntityViewGet, QueryBuilderImpl, SystemAPI, TermBuilderImpl, World, WorldGet},
-    macros::{Component, observer},
+    core::{
+        Entity, EntityViewGet, QueryBuilderImpl, SystemAPI, TermBuilderImpl, World, WorldGet,
+        WorldProvider,
+    },
+    macros::{Component, observer, system},
     prelude::{Module, flecs},
 };
 use hyperion::{
+    ingress::PendingRemove,
     net::{Compose, ConnectionId},
     simulation::{Player, Uuid, command::get_command_packet},
     storage::LocalDb,
 };
-use num_derive::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+mod group;
+pub mod node;
+mod storage;
+
+pub use group::{BANNED_GROUP, DEFAULT_GROUP, Groups, PermissionGroup};
+pub use storage::PermissionStorage;
 
 #[derive(Component)]
 pub struct PermissionModule;
 
-mod storage;
-
+/// The groups of a player, and the nodes given to or taken from them directly.
 #[derive(
-    Default,
     Component,
-    FromPrimitive,
-    ToPrimitive,
-    Copy,
     Clone,
     Debug,
+    Default,
     PartialEq,
-    ValueEnum,
-    Eq
+    Eq,
+    Serialize,
+    Deserialize
 )]
-#[repr(C)]
-pub enum Group {
-    Banned,
-    #[default]
-    Normal,
-    Moderator,
-    Admin,
+pub struct Permissions {
+    /// The groups the player is in, earlier ones first. Players in no group are in the
+    /// [default group](DEFAULT_GROUP).
+    pub groups: Vec<String>,
+    /// Nodes which only apply to this player. They win over the nodes of their groups.
+    pub nodes: Vec<String>,
 }
 
-// todo:
+impl Permissions {
+    /// The groups the player is in, including the default group if they are in no other.
+    pub fn groups(&self) -> impl Iterator<Item = &str> {
+        let default = self.groups.is_empty().then_some(DEFAULT_GROUP);
+
+        self.groups.iter().map(String::as_str).chain(default)
+    }
+
+    #[must_use]
+    pub fn is_banned(&self) -> bool {
+        self.groups.iter().any(|group| group == BANNED_GROUP)
+    }
+}
+
+/// Whether `player` has the permission `node`. Entities without [`Permissions`] have none.
+#[must_use]
+pub fn has_permission(world: &World, player: Entity, node: &str) -> bool {
+    world.get::<&Groups>(|groups| {
+        player
+            .entity_view(world)
+            .try_get::<&Permissions>(|permissions| groups.check(permissions, node))
+            .unwrap_or_default()
+    })
+}
 
 impl Module for PermissionModule {
     fn module(world: &World) {
-        world.component::<Group>();
-        world.component::<storage::PermissionStorage>();
+        world.component::<Permissions>();
+        world.component::<Groups>();
+        world.component::<PermissionStorage>();
 
         world.get::<&LocalDb>(|db| {
-            let storage = storage::PermissionStorage::new(db).unwrap();
+            let storage = PermissionStorage::new(db).unwrap();
+            let groups = Groups::with_stored(storage.groups().unwrap());
+
             world.set(storage);
+            world.set(groups);
         });
 
-        observer!(world, flecs::OnSet, &Uuid, &storage::PermissionStorage($))
+        observer!(world, flecs::OnSet, &Uuid, &PermissionStorage($))
             .with::<Player>()
-            .each_entity(|entity, (uuid, permissions)| {
-                let group = permissions.get(**uuid);
-                entity.set(group);
+            .each_entity(|entity, (uuid, storage)| {
+                let permissions = storage.get(**uuid);
+                entity.set(permissions);
             });
 
-        observer!(world, flecs::OnRemove, &Uuid, &Group, &storage::PermissionStorage($))
+        observer!(world, flecs::OnRemove, &Uuid, &Permissions, &PermissionStorage($))
             .with::<Player>()
-            .each(|(uuid, group, permissions)| {
-                permissions.set(**uuid, *group).unwrap();
+            .each(|(uuid, permissions, storage)| {
+                storage.set(**uuid, permissions).unwrap();
             });
 
-        observer!(world, flecs::OnSet, &Group).each_iter(|it, row, _group| {
+        let players = world
+            .query::<()>()
+            .with::<Permissions>()
+            .with::<Player>()
+            .build();
+
+        // groups changing may change the commands of every player
+        system!("refresh_permissions", world, &mut Groups($)).each(move |groups| {
+            if !std::mem::take(&mut groups.changed) {
+                return;
+            }
+
+            players.each_entity(|player, ()| {
+                player.modified::<Permissions>();
+            });
+        });
+
+        observer!(world, flecs::OnSet, &Permissions).each_iter(|it, row, permissions| {
             let system = it.system();
             let world = it.world();
             let entity = it.entity(row);
 
+            if permissions.is_banned() {
+                entity.set(PendingRemove::new("§cYou are banned from this server"));
+                return;
+            }
+
             let root_command = hyperion::simulation::command::get_root_command_entity();
 
             let cmd_pkt = get_command_packet(&world, root_command, Some(*entity));
@@ -0,0 +1,155 @@
+//! Permission nodes, such as `tag.command.xp`.
+//!
+//! A node is a list of segments separated by dots. In the nodes given to groups and players, a
+//! `*` segment matches any one segment, and a `*` at the end matches every remaining segment, so
+//! `tag.*` matches both `tag.command` and `tag.command.xp`, and `*` matches everything. A node
+//! starting with `-` takes the permission away instead.
+
+/// Whether `node` is a valid node to give or take away, wildcards included.
+#[must_use]
+pub fn is_valid(node: &str) -> bool {
+    let node = node.strip_prefix('-').unwrap_or(node);
+
+    !node.is_empty()
+        && node.split('.').all(|segment| {
+            segment == "*"
+                || (!segment.is_empty()
+                    && segment
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-')))
+        })
+}
+
+/// Whether `pattern`, which may contain wildcards, matches `node`.
+#[must_use]
+pub fn matches(pattern: &str, node: &str) -> bool {
+    let mut pattern = pattern.split('.').peekable();
+    let mut node = node.split('.');
+
+    while let Some(expected) = pattern.next() {
+        let Some(segment) = node.next() else {
+            return false;
+        };
+
+        // a trailing wildcard takes the rest of the node
+        if expected == "*" && pattern.peek().is_none() {
+            return true;
+        }
+
+        if expected != "*" && expected != segment {
+            return false;
+        }
+    }
+
+    node.next().is_none()
+}
+
+/// How many segments of `pattern` are not wildcards. Of the patterns matching a node, the most
+/// specific one decides.
+fn specificity(pattern: &str) -> usize {
+    pattern.split('.').filter(|segment| *segment != "*").count()
+}
+
+/// Whether `nodes` give or take away `node`, or [`None`] if none of them match it. The most
+/// specific match decides, and taking away wins ties.
+#[must_use]
+pub fn decide(nodes: &[String], node: &str) -> Option<bool> {
+    let mut decision: Option<(usize, bool)> = None;
+
+    for entry in nodes {
+        let (pattern, allowed) = entry
+            .strip_prefix('-')
+            .map_or((entry.as_str(), true), |pattern| (pattern, false));
+
+        if !matches(pattern, node) {
+            continue;
+        }
+
+        let specificity = specificity(pattern);
+
+        let replaces = match decision {
+            None => true,
+            Some((best, best_allowed)) => {
+                specificity > best || (specificity == best && best_allowed && !allowed)
+            }
+        };
+
+        if replaces {
+            decision = Some((specificity, allowed));
+        }
+    }
+
+    decision.map(|(_, allowed)| allowed)
+}
+
+/// Gives `node` in `nodes` if `value` is `Some(true)`, takes it away if it is `Some(false)`, and
+/// leaves it to the groups if it is [`None`]. Returns whether `nodes` changed.
+pub fn set(nodes: &mut Vec<String>, node: &str, value: Option<bool>) -> bool {
+    let before = nodes.len();
+    nodes.retain(|entry| entry.strip_prefix('-').unwrap_or(entry) != node);
+    let removed = nodes.len() != before;
+
+    match value {
+        Some(true) => nodes.push(node.to_owned()),
+        Some(false) => nodes.push(format!("-{node}")),
+        None => return removed,
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nodes(nodes: &[&str]) -> Vec<String> {
+        nodes.iter().map(|node| (*node).to_owned()).collect()
+    }
+
+    #[test]
+    fn wildcards_match_segments() {
+        assert!(matches("*", "tag.command.xp"));
+        assert!(matches("tag.*", "tag.command.xp"));
+        assert!(matches("tag.*.xp", "tag.command.xp"));
+        assert!(matches("tag.command.xp", "tag.command.xp"));
+        assert!(!matches("tag.*", "tag"));
+        assert!(!matches("tag.*.xp", "tag.command.fly"));
+        assert!(!matches("tag.command", "tag.command.xp"));
+        assert!(!matches("tag.command.xp", "tag.command"));
+    }
+
+    #[test]
+    fn most_specific_node_decides() {
+        let given = nodes(&["tag.command.*", "-tag.command.xp"]);
+
+        assert_eq!(decide(&given, "tag.command.fly"), Some(true));
+        assert_eq!(decide(&given, "tag.command.xp"), Some(false));
+        assert_eq!(decide(&given, "hyperion_chat.command.msg"), None);
+
+        let tied = nodes(&["tag.*", "-tag.*"]);
+        assert_eq!(decide(&tied, "tag.command.xp"), Some(false));
+    }
+
+    #[test]
+    fn setting_replaces_nodes() {
+        let mut given = nodes(&["tag.command.xp", "tag.command.fly"]);
+
+        assert!(set(&mut given, "tag.command.xp", Some(false)));
+        assert_eq!(given, nodes(&["tag.command.fly", "-tag.command.xp"]));
+
+        assert!(set(&mut given, "tag.command.xp", None));
+        assert!(!set(&mut given, "tag.command.xp", None));
+        assert_eq!(given, nodes(&["tag.command.fly"]));
+    }
+
+    #[test]
+    fn validates_nodes() {
+        assert!(is_valid("tag.command.xp"));
+        assert!(is_valid("-tag.*"));
+        assert!(is_valid("*"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("tag..xp"));
+        assert!(!is_valid("tag.command.x p"));
+        assert!(!is_valid("tag.comm*"));
+    }
+}
@@ -1,51 +1,95 @@
 use flecs_ecs::macros::Component;
 use heed::{Database, Env, byteorder::NativeEndian, types};
 use hyperion::storage::LocalDb;
-use num_traits::{FromPrimitive, ToPrimitive};
 
-use crate::Group;
+use crate::{Permissions, group::PermissionGroup};
+
+/// The groups players were stored with when groups were a fixed list, by their index.
+const LEGACY_GROUPS: [&str; 4] = ["banned", "normal", "moderator", "admin"];
 
 #[derive(Component)]
 pub struct PermissionStorage {
     env: Env,
-    perms: Database<types::U128<NativeEndian>, types::U8>,
+    perms: Database<types::U128<NativeEndian>, types::Bytes>,
+    groups: Database<types::Str, types::Bytes>,
 }
 
 impl PermissionStorage {
     pub fn new(db: &LocalDb) -> anyhow::Result<Self> {
-        // We open the default unnamed database
-        let perms = {
-            let mut wtxn = db.write_txn()?;
-            let db = db.create_database(&mut wtxn, Some("uuid-to-perms"))?;
-            wtxn.commit()?;
-            db
-        };
+        let mut wtxn = db.write_txn()?;
+        let perms = db.create_database(&mut wtxn, Some("uuid-to-perms"))?;
+        let groups = db.create_database(&mut wtxn, Some("permission-groups"))?;
+        wtxn.commit()?;
 
         Ok(Self {
             env: (**db).clone(),
             perms,
+            groups,
         })
     }
 
-    pub fn get(&self, uuid: uuid::Uuid) -> Group {
+    pub fn get(&self, uuid: uuid::Uuid) -> Permissions {
         let uuid = uuid.as_u128();
         let rtxn = self.env.read_txn().unwrap();
         let Some(perms) = self.perms.get(&rtxn, &uuid).unwrap() else {
-            return Group::default();
+            return Permissions::default();
         };
 
-        let Some(group) = Group::from_u8(perms) else {
-            tracing::error!("invalid group {perms:?}");
-            return Group::default();
-        };
+        // players stored before permission nodes only have the index of their group
+        if let [legacy] = *perms {
+            let Some(group) = LEGACY_GROUPS.get(usize::from(legacy)) else {
+                tracing::error!("invalid group {legacy:?}");
+                return Permissions::default();
+            };
+
+            return Permissions {
+                groups: vec![(*group).to_owned()],
+                nodes: Vec::new(),
+            };
+        }
 
-        group
+        match serde_json::from_slice(perms) {
+            Ok(permissions) => permissions,
+            Err(e) => {
+                tracing::error!("invalid permissions: {e}");
+                Permissions::default()
+            }
+        }
     }
 
-    pub fn set(&self, uuid: uuid::Uuid, group: Group) -> anyhow::Result<()> {
+    pub fn set(&self, uuid: uuid::Uuid, permissions: &Permissions) -> anyhow::Result<()> {
         let uuid = uuid.as_u128();
+        let permissions = serde_json::to_vec(permissions)?;
+        let mut wtxn = self.env.write_txn()?;
+        self.perms.put(&mut wtxn, &uuid, &permissions)?;
+        wtxn.commit()?;
+        Ok(())
+    }
+
+    /// Every group which was changed in game.
+    pub fn groups(&self) -> anyhow::Result<Vec<(String, PermissionGroup)>> {
+        let rtxn = self.env.read_txn()?;
+
+        self.groups
+            .iter(&rtxn)?
+            .map(|entry| {
+                let (name, group) = entry?;
+                Ok((name.to_owned(), serde_json::from_slice(group)?))
+            })
+            .collect()
+    }
+
+    pub fn set_group(&self, name: &str, group: &PermissionGroup) -> anyhow::Result<()> {
+        let group = serde_json::to_vec(group)?;
+        let mut wtxn = self.env.write_txn()?;
+        self.groups.put(&mut wtxn, name, &group)?;
+        wtxn.commit()?;
+        Ok(())
+    }
+
+    pub fn remove_group(&self, name: &str) -> anyhow::Result<()> {
         let mut wtxn = self.env.write_txn()?;
-        self.perms.put(&mut wtxn, &uuid, &group.to_u8().unwrap())?;
+        self.groups.delete(&mut wtxn, name)?;
         wtxn.commit()?;
         Ok(())
     }
@@ -11,6 +11,10 @@ pub struct PlayerPackets<'a> {
 #[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
 pub struct PlayerConnect {
     pub stream: u64,
+    /// The IP address of the player as an IPv6 address, where IPv4 addresses are mapped. It is
+    /// unspecified if the proxy does not know it, such as for players connecting over a Unix
+    /// socket.
+    pub ip: [u8; 16],
 }
 
 #[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
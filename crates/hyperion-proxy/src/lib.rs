@@ -16,7 +16,7 @@
     clippy::future_not_send
 )]
 
-use std::fmt::Debug;
+use std::{fmt::Debug, net::IpAddr};
 
 use anyhow::Context;
 use colored::Colorize;
@@ -191,13 +191,13 @@ async fn connect_to_server_and_run_proxy(
 
     loop {
         let mut shutdown_rx = shutdown_rx.clone();
-        let socket = tokio::select! {
+        let (socket, ip) = tokio::select! {
             _ = shutdown_rx.wait_for(Option::is_some) => {
                 return Ok(())
             }
             Ok((socket, addr)) = listener.accept() => {
                 info!("New client connection from {addr:?}");
-                socket
+                (socket, addr.peer_ip())
             }
         };
 
@@ -212,6 +212,7 @@ async fn connect_to_server_and_run_proxy(
 
         initiate_player_connection(
             socket,
+            ip,
             shutdown_rx.clone(),
             player_id_on,
             rx,
@@ -290,6 +291,25 @@ impl IngressHandler {
     }
 }
 
-trait HyperionListener: Listener<Io: Send, Addr: Debug> + 'static {}
+/// The address of a connected player.
+trait PeerAddress {
+    /// The IP address of the player, if the connection has one.
+    fn peer_ip(&self) -> Option<IpAddr>;
+}
+
+impl PeerAddress for std::net::SocketAddr {
+    fn peer_ip(&self) -> Option<IpAddr> {
+        Some(self.ip())
+    }
+}
+
+#[cfg(unix)]
+impl PeerAddress for tokio::net::unix::SocketAddr {
+    fn peer_ip(&self) -> Option<IpAddr> {
+        None
+    }
+}
+
+trait HyperionListener: Listener<Io: Send, Addr: Debug + PeerAddress> + 'static {}
 
-impl<L: Listener<Io: Send, Addr: Debug> + 'static> HyperionListener for L {}
+impl<L: Listener<Io: Send, Addr: Debug + PeerAddress> + 'static> HyperionListener for L {}
@@ -1,6 +1,9 @@
 //! Player connection handling and packet processing.
 
-use std::io::IoSlice;
+use std::{
+    io::IoSlice,
+    net::{IpAddr, Ipv6Addr},
+};
 
 use hyperion_proto::{
     ChunkPosition, PlayerConnect, PlayerDisconnect, PlayerDisconnectReason, PlayerPackets,
@@ -33,8 +36,10 @@ const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
 ///
 /// It also handles player disconnection and shutdown scenarios.
 #[instrument(skip_all, fields(player_id = player_id))]
+#[expect(clippy::too_many_arguments, reason = "todo; refactor")]
 pub fn initiate_player_connection(
     socket: impl tokio::io::AsyncRead + AsyncWrite + Send + 'static,
+    ip: Option<IpAddr>,
     mut shutdown_signal: tokio::sync::watch::Receiver<Option<ShutdownType>>,
     player_id: u64,
     incoming_packet_receiver: kanal::AsyncReceiver<OrderedBytes>,
@@ -58,9 +63,16 @@ pub fn initiate_player_connection(
             let mut read_buffer = Vec::new();
             let player_stream_id = player_id;
 
+            let ip = match ip {
+                Some(IpAddr::V4(ip)) => ip.to_ipv6_mapped(),
+                Some(IpAddr::V6(ip)) => ip,
+                None => Ipv6Addr::UNSPECIFIED,
+            };
+
             let connect = rkyv::to_bytes::<rkyv::rancor::Error>(
                 &ProxyToServerMessage::PlayerConnect(PlayerConnect {
                     stream: player_stream_id,
+                    ip: ip.octets(),
                 }),
             )
             .unwrap();
@@ -1,3 +1,4 @@
+use anyhow::Context;
 use clap::ValueEnum;
 use flecs_ecs::{
     core::{Entity, IdOperations, World, flecs},
@@ -6,7 +7,7 @@ use flecs_ecs::{
 };
 use hyperion::{
     simulation::{Player, handlers::PacketSwitchQuery},
-    storage::{EventFn, InteractEvent},
+    storage::{EventFn, InteractEvent, Persist},
 };
 
 pub mod inventory;
@@ -43,6 +44,41 @@ pub enum Team {
     Yellow,
 }
 
+/// Stores a variant by its name, so reordering the variants keeps stored data valid.
+fn encode_variant<T: ValueEnum>(value: &T) -> anyhow::Result<Vec<u8>> {
+    let name = value.to_possible_value().context("variant is skipped")?;
+    Ok(name.get_name().as_bytes().to_vec())
+}
+
+fn decode_variant<T: ValueEnum>(bytes: &[u8]) -> anyhow::Result<T> {
+    let name = std::str::from_utf8(bytes)?;
+    T::from_str(name, false).map_err(|e| anyhow::anyhow!(e))
+}
+
+impl Persist for Class {
+    const KEY: &'static str = "hyperion-rank-tree:class";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        encode_variant(self)
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        decode_variant(bytes)
+    }
+}
+
+impl Persist for Team {
+    const KEY: &'static str = "hyperion-rank-tree:team";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        encode_variant(self)
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        decode_variant(bytes)
+    }
+}
+
 #[derive(Component)]
 pub struct RankTree;
 
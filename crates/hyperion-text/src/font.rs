@@ -13,3 +13,26 @@ pub enum Font {
     #[serde(rename = "minecraft:alt")]
     Alt,
 }
+
+impl Font {
+    /// Returns the identifier of the font.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Default => "minecraft:default",
+            Self::Uniform => "minecraft:uniform",
+            Self::Alt => "minecraft:alt",
+        }
+    }
+
+    /// Finds a font by its identifier, with or without the `minecraft:` namespace.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.strip_prefix("minecraft:").unwrap_or(name) {
+            "default" => Some(Self::Default),
+            "uniform" => Some(Self::Uniform),
+            "alt" => Some(Self::Alt),
+            _ => None,
+        }
+    }
+}
@@ -25,6 +25,16 @@ impl<'a> Text<'a> {
     }
 }
 
+impl From<String> for Text<'_> {
+    fn from(s: String) -> Self {
+        let mut text = Text::new("");
+        text.content = TextContent::Text {
+            text: Cow::Owned(s),
+        };
+        text
+    }
+}
+
 // Implement From trait for &str to Text conversion
 impl<'a> From<&'a str> for Text<'a> {
     fn from(s: &'a str) -> Self {
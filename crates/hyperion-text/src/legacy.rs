@@ -0,0 +1,231 @@
+//! Legacy formatting codes, such as `§cHello §lworld`.
+//!
+//! A color code also turns off every decoration, and `§r` turns off everything. The format has
+//! no RGB colors, so they are written as the closest named color, and events are left out.
+
+use std::borrow::Cow;
+
+use crate::{
+    Color, NamedColor, Text, TextContent,
+    segment::{self, Decoration, Segment, Style},
+};
+
+const fn code(decoration: Decoration) -> char {
+    match decoration {
+        Decoration::Bold => 'l',
+        Decoration::Italic => 'o',
+        Decoration::Underlined => 'n',
+        Decoration::Strikethrough => 'm',
+        Decoration::Obfuscated => 'k',
+    }
+}
+
+fn named_color(code: char) -> Option<NamedColor> {
+    let idx = code.to_digit(16)?;
+
+    [
+        NamedColor::Black,
+        NamedColor::DarkBlue,
+        NamedColor::DarkGreen,
+        NamedColor::DarkAqua,
+        NamedColor::DarkRed,
+        NamedColor::DarkPurple,
+        NamedColor::Gold,
+        NamedColor::Gray,
+        NamedColor::DarkGray,
+        NamedColor::Blue,
+        NamedColor::Green,
+        NamedColor::Aqua,
+        NamedColor::Red,
+        NamedColor::LightPurple,
+        NamedColor::Yellow,
+        NamedColor::White,
+    ]
+    .get(usize::try_from(idx).ok()?)
+    .copied()
+}
+
+/// Parses text with legacy formatting codes into a [`Text`]. A `§` which is not followed by a
+/// known code is kept as text.
+#[must_use]
+pub fn parse(input: &str) -> Text<'static> {
+    let mut segments = Vec::new();
+    let mut style = Style::default();
+    let mut text = String::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '§' {
+            text.push(c);
+            continue;
+        }
+
+        let Some(&next) = chars.peek() else {
+            text.push(c);
+            continue;
+        };
+
+        let next = next.to_ascii_lowercase();
+
+        let decoration = Decoration::ALL
+            .into_iter()
+            .find(|decoration| code(*decoration) == next);
+
+        let mut new_style = style.clone();
+
+        if let Some(color) = named_color(next) {
+            new_style = Style {
+                color: Some(Color::Named(color)),
+                ..Style::default()
+            };
+        } else if let Some(decoration) = decoration {
+            decoration.set(&mut new_style, true);
+        } else if next == 'r' {
+            new_style = Style::default();
+        } else {
+            text.push(c);
+            continue;
+        }
+
+        chars.next();
+
+        segment::push(
+            &mut segments,
+            Segment::text(style, std::mem::take(&mut text)),
+        );
+        style = new_style;
+    }
+
+    segment::push(&mut segments, Segment::text(style, text));
+    segment::join(segments)
+}
+
+/// The part of a style which legacy codes can express.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+struct LegacyStyle {
+    color: Option<NamedColor>,
+    decorations: [bool; Decoration::ALL.len()],
+}
+
+impl From<&Style<'_>> for LegacyStyle {
+    fn from(style: &Style<'_>) -> Self {
+        Self {
+            color: match style.color {
+                Some(Color::Named(named)) => Some(named),
+                Some(Color::Rgb(rgb)) => Some(rgb.to_named_lossy()),
+                Some(Color::Reset) | None => None,
+            },
+            decorations: Decoration::ALL.map(|decoration| decoration.get(style) == Some(true)),
+        }
+    }
+}
+
+/// Writes `text` with legacy formatting codes. Content other than plain text is written as its
+/// key, such as the translation key of a translated text.
+#[must_use]
+pub fn serialize(text: &Text<'_>) -> String {
+    let mut out = String::new();
+    let mut current = LegacyStyle::default();
+
+    for segment in segment::flatten(text) {
+        let style = LegacyStyle::from(&segment.style);
+
+        if style != current {
+            // color codes turn off decorations, so the whole style is written again
+            out.push('§');
+            out.push(style.color.map_or('r', NamedColor::hex_digit));
+
+            for (decoration, on) in Decoration::ALL.into_iter().zip(style.decorations) {
+                if on {
+                    out.push('§');
+                    out.push(code(decoration));
+                }
+            }
+
+            current = style;
+        }
+
+        let content: Cow<'_, str> = match &segment.content {
+            TextContent::Text { text } => Cow::Borrowed(text),
+            TextContent::Translate { translate, .. } => Cow::Borrowed(translate),
+            TextContent::ScoreboardValue { score } => score.value.clone().unwrap_or_default(),
+            TextContent::EntityNames { selector, .. } => Cow::Borrowed(selector),
+            TextContent::Keybind { keybind } => Cow::Borrowed(keybind),
+            TextContent::BlockNbt { nbt, .. }
+            | TextContent::EntityNbt { nbt, .. }
+            | TextContent::StorageNbt { nbt, .. } => Cow::Borrowed(nbt),
+        };
+
+        out.push_str(&content);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn codes() {
+        let text = parse("§cRed §lbold§r plain §zkept§");
+        let segments = segment::flatten(&text);
+
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[1].style.color, Some(Color::RED));
+        assert_eq!(segments[1].style.bold, Some(true));
+        assert_eq!(
+            segments[2],
+            Segment::text(Style::default(), " plain §zkept§")
+        );
+
+        assert_eq!(serialize(&text), "§cRed §c§lbold§r plain §zkept§");
+    }
+
+    fn style() -> impl Strategy<Value = Style<'static>> {
+        (
+            proptest::option::of(0..16_u32),
+            proptest::collection::vec(any::<bool>(), 5),
+        )
+            .prop_map(|(color, decorations)| {
+                let mut style = Style {
+                    color: color
+                        .and_then(|idx| char::from_digit(idx, 16))
+                        .and_then(named_color)
+                        .map(Color::Named),
+                    ..Style::default()
+                };
+
+                for (decoration, on) in Decoration::ALL.into_iter().zip(decorations) {
+                    if on {
+                        decoration.set(&mut style, true);
+                    }
+                }
+
+                style
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn legacy_round_trips(segments in proptest::collection::vec((style(), "[^§]+"), 0..8)) {
+            let text = segment::join(
+                segments
+                    .into_iter()
+                    .map(|(style, text)| Segment::text(style, text))
+                    .collect(),
+            );
+
+            let legacy = serialize(&text);
+            prop_assert_eq!(segment::flatten(&parse(&legacy)), segment::flatten(&text), "{}", legacy);
+        }
+
+        #[test]
+        fn markup_converts_to_legacy(input in ".*") {
+            let markup = crate::markup::parse(&crate::markup::escape(&input));
+            prop_assert_eq!(serialize(&markup), input);
+        }
+    }
+}
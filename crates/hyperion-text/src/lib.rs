@@ -3,8 +3,8 @@ use std::{borrow::Cow, io::Write};
 use serde::{Deserialize, Serialize};
 use valence_protocol::{Bounded, Encode, anyhow, anyhow::Context};
 
-use crate::{
-    color::Color,
+pub use crate::{
+    color::{Color, ColorError, NamedColor, RgbColor},
     event::{ClickEvent, HoverEvent},
     font::Font,
     scoreboard::ScoreboardValueContent,
@@ -14,7 +14,10 @@ mod color;
 mod event;
 mod font;
 mod helper;
+pub mod legacy;
+pub mod markup;
 mod scoreboard;
+mod segment;
 
 /// Text data and formatting.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
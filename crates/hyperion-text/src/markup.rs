@@ -0,0 +1,894 @@
+//! MiniMessage-style markup, such as `<red>Hello <bold>world</bold>!`.
+//!
+//! The tags are
+//! - colors: `<red>`, `<#ff5555>` and `<color:red>`
+//! - decorations: `<bold>` or `<b>`, `<italic>`, `<i>` or `<em>`, `<underlined>` or `<u>`,
+//!   `<strikethrough>` or `<st>` and `<obfuscated>` or `<obf>`. `<!bold>` turns one off.
+//! - `<font:minecraft:uniform>` and `<insert:'text'>`
+//! - `<click:action:'value'>`, where the action is `open_url`, `run_command`, `suggest_command`,
+//!   `copy_to_clipboard` or `change_page`
+//! - `<hover:show_text:'<red>markup'>`
+//! - `<gradient:red:#5555ff>`, which colors the text inside from the first color to the last
+//! - `<lang:key:'argument'>`, `<key:key.jump>` and `<newline>` or `<br>`, which are content
+//! - `<reset>`, which closes every open tag
+//!
+//! A closing tag such as `</red>` or `</color>` closes the last open tag of its kind and every tag
+//! opened after it. Arguments may be quoted with `'` or `"`, in which `\` escapes the quote.
+//! Anything which is not a known tag is kept as text, and `\<` is a literal `<`. Text typed by
+//! players must go through [`escape`] before it is put into markup, and must not be put into tag
+//! arguments. Tags nested more than [`MAX_DEPTH`] deep are kept as text.
+
+use std::borrow::Cow;
+
+use crate::{
+    ClickEvent, Color, Font, HoverEvent, RgbColor, Text, TextContent,
+    segment::{self, Decoration, Segment, Style},
+};
+
+/// How deeply tags may be nested, counting the markup inside of hover texts and translation
+/// arguments. This keeps parsing and rendering from overflowing the stack.
+pub const MAX_DEPTH: usize = 32;
+
+/// Escapes `input` so it is shown as it is instead of being read as tags. The result is only safe
+/// as text between tags; quotes are not escaped, so it must not be put into a tag argument.
+#[must_use]
+pub fn escape(input: &str) -> Cow<'_, str> {
+    if !input.contains(['<', '\\']) {
+        return Cow::Borrowed(input);
+    }
+
+    let mut escaped = String::with_capacity(input.len() + 8);
+
+    for c in input.chars() {
+        if matches!(c, '<' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    Cow::Owned(escaped)
+}
+
+/// Parses markup into a [`Text`].
+#[must_use]
+pub fn parse(input: &str) -> Text<'static> {
+    parse_nested(input, 0)
+}
+
+/// Parses markup which is `depth` tags deep, e.g. the text of a hover event.
+fn parse_nested(input: &str, depth: usize) -> Text<'static> {
+    let mut parser = Parser {
+        depth,
+        ..Parser::default()
+    };
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '\\' => match rest[1..].chars().next() {
+                Some(escaped @ ('<' | '\\')) => {
+                    parser.push(escaped);
+                    rest = &rest[2..];
+                }
+                _ => {
+                    parser.push('\\');
+                    rest = &rest[1..];
+                }
+            },
+            '<' => {
+                let tag = tag_end(&rest[1..]).and_then(|end| {
+                    let raw = &rest[..end + 2];
+                    parse_tag(&raw[1..raw.len() - 1], parser.depth()).map(|tag| (raw, tag))
+                });
+
+                let Some((raw, tag)) = tag else {
+                    parser.push('<');
+                    rest = &rest[1..];
+                    continue;
+                };
+
+                match tag {
+                    Parsed::Open(_) if parser.depth() >= MAX_DEPTH => parser.push_str(raw),
+                    Parsed::Open(tag) => parser.open.push((tag, Vec::new())),
+                    Parsed::Close(kind) => {
+                        if !parser.close(kind) {
+                            parser.push_str(raw);
+                        }
+                    }
+                    Parsed::Content(content) => parser.nodes().push(Node::Content(content)),
+                    Parsed::Reset => parser.reset(),
+                }
+
+                rest = &rest[raw.len()..];
+            }
+            _ => {
+                parser.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    let nodes = parser.finish();
+
+    let mut segments = Vec::new();
+    render(&nodes, &Style::default(), &mut segments);
+    segment::join(segments)
+}
+
+/// Writes `text` as markup. Content and events which markup cannot express, such as NBT
+/// components or hovering over an item, are left out.
+#[must_use]
+pub fn serialize(text: &Text<'_>) -> String {
+    let segments = segment::flatten(text);
+
+    let mut out = String::new();
+    let mut open: Vec<Written<'_>> = Vec::new();
+
+    for segment in &segments {
+        let wanted = written(&segment.style);
+
+        // tags both segments share stay open
+        let common = open
+            .iter()
+            .zip(&wanted)
+            .take_while(|(open, wanted)| open == wanted)
+            .count();
+
+        for tag in open.drain(common..).rev() {
+            tag.close(&mut out);
+        }
+
+        for tag in &wanted[common..] {
+            tag.open(&mut out);
+        }
+
+        open = wanted;
+
+        write_content(&segment.content, &mut out);
+    }
+
+    out
+}
+
+/// What a closing tag closes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    Color,
+    Decoration(Decoration),
+    Font,
+    Insertion,
+    Click,
+    Hover,
+    Gradient,
+}
+
+impl Kind {
+    fn from_name(name: &str) -> Option<Self> {
+        if let Some(decoration) = decoration(name) {
+            return Some(Self::Decoration(decoration));
+        }
+
+        match name {
+            "color" | "colour" | "c" => Some(Self::Color),
+            "font" => Some(Self::Font),
+            "insert" | "insertion" => Some(Self::Insertion),
+            "click" => Some(Self::Click),
+            "hover" => Some(Self::Hover),
+            "gradient" => Some(Self::Gradient),
+            _ => Color::try_from(name).ok().map(|_| Self::Color),
+        }
+    }
+}
+
+/// A tag which styles everything until it is closed.
+#[derive(Clone, PartialEq, Debug)]
+enum Tag {
+    Color(Color),
+    Decoration(Decoration, bool),
+    Font(Font),
+    Insertion(String),
+    Click(ClickEvent<'static>),
+    Hover(HoverEvent<'static>),
+    Gradient(Vec<RgbColor>),
+}
+
+impl Tag {
+    const fn kind(&self) -> Kind {
+        match self {
+            Self::Color(_) => Kind::Color,
+            Self::Decoration(decoration, _) => Kind::Decoration(*decoration),
+            Self::Font(_) => Kind::Font,
+            Self::Insertion(_) => Kind::Insertion,
+            Self::Click(_) => Kind::Click,
+            Self::Hover(_) => Kind::Hover,
+            Self::Gradient(_) => Kind::Gradient,
+        }
+    }
+
+    fn apply(&self, style: &mut Style<'static>) {
+        match self {
+            Self::Color(color) => style.color = Some(*color),
+            Self::Decoration(decoration, value) => decoration.set(style, *value),
+            Self::Font(font) => style.font = Some(*font),
+            Self::Insertion(insertion) => style.insertion = Some(Cow::Owned(insertion.clone())),
+            Self::Click(click) => style.click_event = Some(click.clone()),
+            Self::Hover(hover) => style.hover_event = Some(hover.clone()),
+            // gradients color every character on their own
+            Self::Gradient(_) => {}
+        }
+    }
+}
+
+/// What the inside of `<...>` means.
+enum Parsed {
+    Open(Tag),
+    Close(Kind),
+    /// Content such as a translated text, which is not closed.
+    Content(TextContent<'static>),
+    Reset,
+}
+
+enum Node {
+    Text(String),
+    Content(TextContent<'static>),
+    Styled(Tag, Vec<Self>),
+}
+
+#[derive(Default)]
+struct Parser {
+    root: Vec<Node>,
+    /// The tags which are open, with what is inside of them so far.
+    open: Vec<(Tag, Vec<Node>)>,
+    /// How deep the markup being parsed is nested in other markup.
+    depth: usize,
+}
+
+impl Parser {
+    /// How many tags the next one would be nested in.
+    fn depth(&self) -> usize {
+        self.depth + self.open.len()
+    }
+
+    fn nodes(&mut self) -> &mut Vec<Node> {
+        match self.open.last_mut() {
+            Some((_, nodes)) => nodes,
+            None => &mut self.root,
+        }
+    }
+
+    fn push(&mut self, c: char) {
+        let mut buf = [0; 4];
+        self.push_str(c.encode_utf8(&mut buf));
+    }
+
+    fn push_str(&mut self, text: &str) {
+        let nodes = self.nodes();
+
+        if let Some(Node::Text(last)) = nodes.last_mut() {
+            last.push_str(text);
+        } else {
+            nodes.push(Node::Text(text.to_owned()));
+        }
+    }
+
+    fn close_last(&mut self) {
+        if let Some((tag, children)) = self.open.pop() {
+            self.nodes().push(Node::Styled(tag, children));
+        }
+    }
+
+    /// Closes the last open tag of `kind` and every tag opened after it. Returns false if no
+    /// such tag is open.
+    fn close(&mut self, kind: Kind) -> bool {
+        let Some(idx) = self.open.iter().rposition(|(tag, _)| tag.kind() == kind) else {
+            return false;
+        };
+
+        while self.open.len() > idx {
+            self.close_last();
+        }
+
+        true
+    }
+
+    fn reset(&mut self) {
+        while !self.open.is_empty() {
+            self.close_last();
+        }
+    }
+
+    fn finish(mut self) -> Vec<Node> {
+        self.reset();
+        self.root
+    }
+}
+
+fn decoration(name: &str) -> Option<Decoration> {
+    Decoration::ALL
+        .into_iter()
+        .find(|decoration| decoration_names(*decoration).contains(&name))
+}
+
+/// The names of the tag of `decoration`. The first one is written.
+const fn decoration_names(decoration: Decoration) -> &'static [&'static str] {
+    match decoration {
+        Decoration::Bold => &["bold", "b"],
+        Decoration::Italic => &["italic", "i", "em"],
+        Decoration::Underlined => &["underlined", "u"],
+        Decoration::Strikethrough => &["strikethrough", "st"],
+        Decoration::Obfuscated => &["obfuscated", "obf"],
+    }
+}
+
+/// Finds the `>` which ends the tag `rest` starts with, skipping over quoted arguments.
+fn tag_end(rest: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut at_start = true;
+    let mut chars = rest.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        match quote {
+            Some(q) => {
+                if c == '\\' {
+                    chars.next();
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '>' => return Some(idx),
+                '<' | '\n' => return None,
+                '\'' | '"' if at_start => quote = Some(c),
+                _ => {}
+            },
+        }
+
+        at_start = quote.is_none() && c == ':';
+    }
+
+    None
+}
+
+/// Splits the inside of a tag at every `:` which is not quoted.
+fn split_args(inner: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut quote = None;
+    let mut at_start = true;
+    let mut chars = inner.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => match c {
+                _ if c == q => quote = None,
+                '\\' => arg.push(
+                    chars
+                        .next_if(|next| *next == q || *next == '\\')
+                        .unwrap_or(c),
+                ),
+                _ => arg.push(c),
+            },
+            None => match c {
+                ':' => {
+                    args.push(std::mem::take(&mut arg));
+                    at_start = true;
+                    continue;
+                }
+                '\'' | '"' if at_start => quote = Some(c),
+                _ => arg.push(c),
+            },
+        }
+
+        at_start = false;
+    }
+
+    args.push(arg);
+    args
+}
+
+fn parse_tag(inner: &str, depth: usize) -> Option<Parsed> {
+    if let Some(close) = inner.strip_prefix('/') {
+        let name = split_args(close).swap_remove(0).to_ascii_lowercase();
+        return Kind::from_name(&name).map(Parsed::Close);
+    }
+
+    let (negated, inner) = inner
+        .strip_prefix('!')
+        .map_or((false, inner), |inner| (true, inner));
+
+    let mut args = split_args(inner);
+    let name = args.remove(0).to_ascii_lowercase();
+
+    if let Some(decoration) = decoration(&name) {
+        return Some(Parsed::Open(Tag::Decoration(decoration, !negated)));
+    }
+
+    if negated {
+        return None;
+    }
+
+    let tag = match name.as_str() {
+        "reset" => return Some(Parsed::Reset),
+        "newline" | "br" => {
+            return Some(Parsed::Content(TextContent::Text {
+                text: Cow::Borrowed("\n"),
+            }));
+        }
+        "lang" | "tr" | "translate" if depth < MAX_DEPTH => {
+            let (key, with) = args.split_first()?;
+
+            return Some(Parsed::Content(TextContent::Translate {
+                translate: Cow::Owned(key.clone()),
+                with: with
+                    .iter()
+                    .map(|arg| parse_nested(arg, depth + 1))
+                    .collect(),
+            }));
+        }
+        "key" => {
+            return Some(Parsed::Content(TextContent::Keybind {
+                keybind: Cow::Owned(args.join(":")),
+            }));
+        }
+        "color" | "colour" | "c" => Tag::Color(Color::try_from(args.first()?.as_str()).ok()?),
+        "font" => Tag::Font(Font::from_name(&args.join(":"))?),
+        "insert" | "insertion" => Tag::Insertion(args.join(":")),
+        "click" => {
+            let (action, value) = args.split_first()?;
+            Tag::Click(click_event(action, value.join(":"))?)
+        }
+        "hover" if depth < MAX_DEPTH => {
+            let (action, value) = args.split_first()?;
+
+            if action != "show_text" {
+                return None;
+            }
+
+            Tag::Hover(HoverEvent::ShowText(parse_nested(
+                &value.join(":"),
+                depth + 1,
+            )))
+        }
+        "gradient" => {
+            let mut colors = args
+                .iter()
+                .map(|color| rgb(color))
+                .collect::<Option<Vec<_>>>()?;
+
+            if colors.is_empty() {
+                colors = vec![RgbColor::new(255, 255, 255), RgbColor::new(0, 0, 0)];
+            }
+
+            Tag::Gradient(colors)
+        }
+        _ => Tag::Color(Color::try_from(name.as_str()).ok()?),
+    };
+
+    Some(Parsed::Open(tag))
+}
+
+fn click_event(action: &str, value: String) -> Option<ClickEvent<'static>> {
+    let click = match action {
+        "open_url" => ClickEvent::OpenUrl(Cow::Owned(value)),
+        "run_command" => ClickEvent::RunCommand(Cow::Owned(value)),
+        "suggest_command" => ClickEvent::SuggestCommand(Cow::Owned(value)),
+        "copy_to_clipboard" => ClickEvent::CopyToClipboard(Cow::Owned(value)),
+        "change_page" => ClickEvent::ChangePage(value.parse().ok()?),
+        _ => return None,
+    };
+
+    Some(click)
+}
+
+fn rgb(color: &str) -> Option<RgbColor> {
+    match Color::try_from(color).ok()? {
+        Color::Rgb(rgb) => Some(rgb),
+        Color::Named(named) => Some(named.into()),
+        Color::Reset => None,
+    }
+}
+
+fn render(nodes: &[Node], style: &Style<'static>, segments: &mut Vec<Segment<'static>>) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => segment::push(segments, Segment::text(style.clone(), text.clone())),
+            Node::Content(content) => segment::push(segments, Segment {
+                style: style.clone(),
+                content: content.clone(),
+            }),
+            Node::Styled(Tag::Gradient(colors), children) => {
+                let mut inside = Vec::new();
+                render(children, style, &mut inside);
+
+                for segment in gradient(inside, colors) {
+                    segment::push(segments, segment);
+                }
+            }
+            Node::Styled(tag, children) => {
+                let mut style = style.clone();
+                tag.apply(&mut style);
+                render(children, &style, segments);
+            }
+        }
+    }
+}
+
+/// Colors every character of `segments` on the way from the first of `colors` to the last.
+fn gradient(segments: Vec<Segment<'static>>, colors: &[RgbColor]) -> Vec<Segment<'static>> {
+    let len = segments
+        .iter()
+        .map(|segment| match &segment.content {
+            TextContent::Text { text } => text.chars().count(),
+            _ => 1,
+        })
+        .sum();
+
+    let mut colored = Vec::with_capacity(len);
+    let mut idx = 0;
+
+    for Segment { mut style, content } in segments {
+        match content {
+            TextContent::Text { text } => {
+                for c in text.chars() {
+                    style.color = Some(Color::Rgb(interpolate(colors, idx, len)));
+                    colored.push(Segment::text(style.clone(), c.to_string()));
+                    idx += 1;
+                }
+            }
+            content => {
+                style.color = Some(Color::Rgb(interpolate(colors, idx, len)));
+                colored.push(Segment { style, content });
+                idx += 1;
+            }
+        }
+    }
+
+    colored
+}
+
+/// The color of the character at `idx` of `len` characters in a gradient.
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "the values are clamped to their range"
+)]
+fn interpolate(colors: &[RgbColor], idx: usize, len: usize) -> RgbColor {
+    let [first, .., last] = colors else {
+        return colors
+            .first()
+            .copied()
+            .unwrap_or(RgbColor::new(255, 255, 255));
+    };
+
+    if len <= 1 {
+        return *first;
+    }
+
+    if idx + 1 >= len {
+        return *last;
+    }
+
+    let progress = idx as f32 / (len - 1) as f32 * (colors.len() - 1) as f32;
+    let from = (progress as usize).min(colors.len() - 2);
+    let t = progress - from as f32;
+
+    let (a, b) = (colors[from], colors[from + 1]);
+    let lerp = |a: u8, b: u8| {
+        (f32::from(b) - f32::from(a))
+            .mul_add(t, f32::from(a))
+            .round() as u8
+    };
+
+    RgbColor::new(lerp(a.r, b.r), lerp(a.g, b.g), lerp(a.b, b.b))
+}
+
+/// A tag as it is written by [`serialize`].
+#[derive(PartialEq)]
+enum Written<'a> {
+    Color(Color),
+    Decoration(Decoration, bool),
+    Font(Font),
+    Insertion(&'a str),
+    Click(&'a ClickEvent<'a>),
+    Hover(&'a Text<'a>),
+}
+
+/// The tags which give a segment `style`, in the order they are opened.
+fn written<'a>(style: &'a Style<'a>) -> Vec<Written<'a>> {
+    let mut tags = Vec::new();
+
+    if let Some(color) = style.color {
+        tags.push(Written::Color(color));
+    }
+
+    for decoration in Decoration::ALL {
+        if let Some(value) = decoration.get(style) {
+            tags.push(Written::Decoration(decoration, value));
+        }
+    }
+
+    if let Some(font) = style.font {
+        tags.push(Written::Font(font));
+    }
+
+    if let Some(insertion) = &style.insertion {
+        tags.push(Written::Insertion(insertion));
+    }
+
+    // files cannot be opened from markup
+    if let Some(click) = style
+        .click_event
+        .as_ref()
+        .filter(|click| !matches!(click, ClickEvent::OpenFile(_)))
+    {
+        tags.push(Written::Click(click));
+    }
+
+    if let Some(HoverEvent::ShowText(text)) = &style.hover_event {
+        tags.push(Written::Hover(text));
+    }
+
+    tags
+}
+
+impl Written<'_> {
+    fn open(&self, out: &mut String) {
+        out.push('<');
+
+        match self {
+            Self::Color(Color::Named(named)) => out.push_str(named.name()),
+            Self::Color(Color::Rgb(rgb)) => out.push_str(&rgb.to_string()),
+            Self::Color(Color::Reset) => out.push_str("color:reset"),
+            Self::Decoration(decoration, value) => {
+                if !value {
+                    out.push('!');
+                }
+                out.push_str(decoration_names(*decoration)[0]);
+            }
+            Self::Font(font) => {
+                out.push_str("font:");
+                out.push_str(font.name());
+            }
+            Self::Insertion(insertion) => {
+                out.push_str("insert:");
+                quote(insertion, out);
+            }
+            Self::Click(click) => {
+                let (action, value) = match click {
+                    ClickEvent::OpenUrl(url) => ("open_url", url.clone()),
+                    ClickEvent::RunCommand(command) => ("run_command", command.clone()),
+                    ClickEvent::SuggestCommand(command) => ("suggest_command", command.clone()),
+                    ClickEvent::CopyToClipboard(text) => ("copy_to_clipboard", text.clone()),
+                    ClickEvent::ChangePage(page) => ("change_page", Cow::Owned(page.to_string())),
+                    ClickEvent::OpenFile(_) => unreachable!("files cannot be opened from markup"),
+                };
+
+                out.push_str("click:");
+                out.push_str(action);
+                out.push(':');
+                quote(&value, out);
+            }
+            Self::Hover(text) => {
+                out.push_str("hover:show_text:");
+                quote(&serialize(text), out);
+            }
+        }
+
+        out.push('>');
+    }
+
+    fn close(&self, out: &mut String) {
+        let name = match self {
+            Self::Color(_) => "color",
+            Self::Decoration(decoration, _) => decoration_names(*decoration)[0],
+            Self::Font(_) => "font",
+            Self::Insertion(_) => "insert",
+            Self::Click(_) => "click",
+            Self::Hover(_) => "hover",
+        };
+
+        out.push_str("</");
+        out.push_str(name);
+        out.push('>');
+    }
+}
+
+fn quote(value: &str, out: &mut String) {
+    out.push('\'');
+
+    for c in value.chars() {
+        if matches!(c, '\'' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+
+    out.push('\'');
+}
+
+fn write_content(content: &TextContent<'_>, out: &mut String) {
+    match content {
+        TextContent::Text { text } => out.push_str(&escape(text)),
+        TextContent::Translate { translate, with } => {
+            out.push_str("<lang:");
+            quote(translate, out);
+
+            for arg in with {
+                out.push(':');
+                quote(&serialize(arg), out);
+            }
+
+            out.push('>');
+        }
+        TextContent::Keybind { keybind } => {
+            out.push_str("<key:");
+            quote(keybind, out);
+            out.push('>');
+        }
+        // scores, selectors and NBT have no markup
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::NamedColor;
+
+    #[test]
+    fn tags() {
+        let text = parse("<red>Hello <bold>world</bold>!</red> <!italic>plain");
+        let segments = segment::flatten(&text);
+
+        let texts: Vec<_> = segments
+            .iter()
+            .map(|segment| match &segment.content {
+                TextContent::Text { text } => text.as_ref(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(texts, ["Hello ", "world", "!", " ", "plain"]);
+
+        assert_eq!(segments[0].style.color, Some(Color::RED));
+        assert_eq!(segments[1].style.bold, Some(true));
+        assert_eq!(segments[2].style.bold, None);
+        assert_eq!(segments[3].style, Style::default());
+        assert_eq!(segments[4].style.italic, Some(false));
+
+        let text = parse("<click:run_command:'/tp Notch'><hover:show_text:'<red>Teleport'>go");
+        let style = &segment::flatten(&text)[0].style;
+        assert_eq!(
+            style.click_event,
+            Some(ClickEvent::RunCommand("/tp Notch".into()))
+        );
+        assert_eq!(
+            style.hover_event,
+            Some(HoverEvent::ShowText(parse("<red>Teleport")))
+        );
+
+        // unknown and unmatched tags are text
+        let text = parse("<unknown>a</red>b");
+        assert_eq!(serialize(&text), "\\<unknown>a\\</red>b");
+    }
+
+    #[test]
+    fn gradients() {
+        let text = parse("<gradient:black:white>abc</gradient>");
+        let colors: Vec<_> = segment::flatten(&text)
+            .into_iter()
+            .map(|segment| segment.style.color.unwrap())
+            .collect();
+
+        assert_eq!(colors, [
+            Color::BLACK,
+            Color::rgb(128, 128, 128),
+            Color::WHITE
+        ]);
+    }
+
+    #[test]
+    fn deep_nesting_is_text() {
+        let text = parse(&format!("{}x", "<red>".repeat(100_000)));
+        let segments = segment::flatten(&text);
+
+        assert_eq!(segments[0].style.color, Some(Color::RED));
+
+        // tags after the limit are kept as text, but can still be closed
+        let markup = format!("{}<bold>x", "<red>".repeat(MAX_DEPTH));
+        let segments = segment::flatten(&parse(&markup));
+        assert_eq!(segments[0].style.bold, None);
+
+        let markup = format!("{}</red><bold>x", "<red>".repeat(MAX_DEPTH));
+        let segments = segment::flatten(&parse(&markup));
+        assert_eq!(segments[0].style.bold, Some(true));
+    }
+
+    fn style() -> impl Strategy<Value = Style<'static>> {
+        let color = prop_oneof![
+            Just(None),
+            Just(Some(Color::Reset)),
+            (0..16_u8).prop_map(|idx| Some(Color::Named(named(idx)))),
+            any::<[u8; 3]>().prop_map(|[r, g, b]| Some(Color::rgb(r, g, b))),
+        ];
+
+        let decorations = proptest::collection::vec(proptest::option::of(any::<bool>()), 5);
+
+        let click = proptest::option::of(prop_oneof![
+            ".*".prop_map(|command| ClickEvent::RunCommand(command.into())),
+            ".*".prop_map(|url| ClickEvent::OpenUrl(url.into())),
+            any::<i32>().prop_map(ClickEvent::ChangePage),
+        ]);
+
+        let hover = proptest::option::of((".+", 0..16_u8).prop_map(|(text, color)| {
+            let style = Style {
+                color: Some(Color::Named(named(color))),
+                ..Style::default()
+            };
+            HoverEvent::ShowText(segment::join(vec![Segment::text(style, text)]))
+        }));
+
+        (color, decorations, proptest::option::of(".*"), click, hover).prop_map(
+            |(color, decorations, insertion, click_event, hover_event)| {
+                let mut style = Style {
+                    color,
+                    insertion: insertion.map(Cow::Owned),
+                    click_event,
+                    hover_event,
+                    ..Style::default()
+                };
+
+                for (decoration, value) in Decoration::ALL.into_iter().zip(decorations) {
+                    if let Some(value) = value {
+                        decoration.set(&mut style, value);
+                    }
+                }
+
+                style
+            },
+        )
+    }
+
+    fn named(idx: u8) -> NamedColor {
+        [
+            NamedColor::Black,
+            NamedColor::DarkBlue,
+            NamedColor::DarkGreen,
+            NamedColor::DarkAqua,
+            NamedColor::DarkRed,
+            NamedColor::DarkPurple,
+            NamedColor::Gold,
+            NamedColor::Gray,
+            NamedColor::DarkGray,
+            NamedColor::Blue,
+            NamedColor::Green,
+            NamedColor::Aqua,
+            NamedColor::Red,
+            NamedColor::LightPurple,
+            NamedColor::Yellow,
+            NamedColor::White,
+        ][usize::from(idx % 16)]
+    }
+
+    proptest! {
+        #[test]
+        fn escaped_text_round_trips(input in ".+") {
+            let text = parse(&escape(&input));
+            prop_assert_eq!(segment::flatten(&text), vec![Segment::text(Style::default(), input)]);
+        }
+
+        #[test]
+        fn markup_round_trips(segments in proptest::collection::vec((style(), ".+"), 0..8)) {
+            let text = segment::join(
+                segments
+                    .into_iter()
+                    .map(|(style, text)| Segment::text(style, text))
+                    .collect(),
+            );
+
+            let markup = serialize(&text);
+            prop_assert_eq!(segment::flatten(&parse(&markup)), segment::flatten(&text), "{}", markup);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Flattening [`Text`] trees into runs of text with one style each, which is what the markup and
+//! legacy formats are made of.
+
+use std::borrow::Cow;
+
+use crate::{ClickEvent, Color, Font, HoverEvent, Text, TextContent};
+
+/// The formatting of a [`Text`], without its content and children.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Style<'a> {
+    pub color: Option<Color>,
+    pub font: Option<Font>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    pub insertion: Option<Cow<'a, str>>,
+    pub click_event: Option<ClickEvent<'a>>,
+    pub hover_event: Option<HoverEvent<'a>>,
+}
+
+impl<'a> Style<'a> {
+    /// The style of `text` when it is a child of something styled with `self`.
+    fn inherit(&self, text: &Text<'a>) -> Self {
+        Self {
+            color: text.color.or(self.color),
+            font: text.font.or(self.font),
+            bold: text.bold.or(self.bold),
+            italic: text.italic.or(self.italic),
+            underlined: text.underlined.or(self.underlined),
+            strikethrough: text.strikethrough.or(self.strikethrough),
+            obfuscated: text.obfuscated.or(self.obfuscated),
+            insertion: text.insertion.clone().or_else(|| self.insertion.clone()),
+            click_event: text
+                .click_event
+                .as_deref()
+                .cloned()
+                .or_else(|| self.click_event.clone()),
+            hover_event: text
+                .hover_event
+                .as_deref()
+                .cloned()
+                .or_else(|| self.hover_event.clone()),
+        }
+    }
+}
+
+/// A formatting which is either on or off.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Decoration {
+    Bold,
+    Italic,
+    Underlined,
+    Strikethrough,
+    Obfuscated,
+}
+
+impl Decoration {
+    pub const ALL: [Self; 5] = [
+        Self::Bold,
+        Self::Italic,
+        Self::Underlined,
+        Self::Strikethrough,
+        Self::Obfuscated,
+    ];
+
+    pub const fn get(self, style: &Style<'_>) -> Option<bool> {
+        match self {
+            Self::Bold => style.bold,
+            Self::Italic => style.italic,
+            Self::Underlined => style.underlined,
+            Self::Strikethrough => style.strikethrough,
+            Self::Obfuscated => style.obfuscated,
+        }
+    }
+
+    pub const fn set(self, style: &mut Style<'_>, value: bool) {
+        let field = match self {
+            Self::Bold => &mut style.bold,
+            Self::Italic => &mut style.italic,
+            Self::Underlined => &mut style.underlined,
+            Self::Strikethrough => &mut style.strikethrough,
+            Self::Obfuscated => &mut style.obfuscated,
+        };
+
+        *field = Some(value);
+    }
+}
+
+/// A piece of content with the style it is shown in.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Segment<'a> {
+    pub style: Style<'a>,
+    pub content: TextContent<'a>,
+}
+
+impl<'a> Segment<'a> {
+    pub fn text(style: Style<'a>, text: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            style,
+            content: TextContent::Text { text: text.into() },
+        }
+    }
+
+    fn into_text(self) -> Text<'a> {
+        let Style {
+            color,
+            font,
+            bold,
+            italic,
+            underlined,
+            strikethrough,
+            obfuscated,
+            insertion,
+            click_event,
+            hover_event,
+        } = self.style;
+
+        Text {
+            content: self.content,
+            color,
+            font,
+            bold,
+            italic,
+            underlined,
+            strikethrough,
+            obfuscated,
+            insertion,
+            click_event: click_event.map(Box::new),
+            hover_event: hover_event.map(Box::new),
+            extra: Vec::new(),
+        }
+    }
+}
+
+/// Appends `segment`, merging it into the last segment if both are text of the same style.
+pub fn push<'a>(segments: &mut Vec<Segment<'a>>, segment: Segment<'a>) {
+    if let TextContent::Text { text } = &segment.content {
+        if text.is_empty() {
+            return;
+        }
+
+        match segments.last_mut() {
+            Some(Segment {
+                style,
+                content: TextContent::Text { text: last },
+            }) if *style == segment.style => {
+                last.to_mut().push_str(text);
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    segments.push(segment);
+}
+
+/// The content of `text` in the order it is shown, with the style every part is shown in.
+pub fn flatten<'a>(text: &Text<'a>) -> Vec<Segment<'a>> {
+    fn walk<'a>(text: &Text<'a>, parent: &Style<'a>, segments: &mut Vec<Segment<'a>>) {
+        let style = parent.inherit(text);
+
+        push(segments, Segment {
+            style: style.clone(),
+            content: text.content.clone(),
+        });
+
+        for child in &text.extra {
+            walk(child, &style, segments);
+        }
+    }
+
+    let mut segments = Vec::new();
+    walk(text, &Style::default(), &mut segments);
+    segments
+}
+
+/// A [`Text`] showing `segments` one after another.
+pub fn join(segments: Vec<Segment<'_>>) -> Text<'_> {
+    let mut text = Text::new("");
+    text.extra = segments.into_iter().map(Segment::into_text).collect();
+    text
+}
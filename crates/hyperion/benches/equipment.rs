@@ -0,0 +1,118 @@
+//! Bytes sent per tick to keep the equipment of players in sync.
+//!
+//! `broadcast_all` is what every tick used to cost: all six slots of every player.
+//! `broadcast_changed` only sends the slots which changed since the last tick. The byte counter
+//! of each bench is the size of the packets one tick produces.
+
+use std::hint::black_box;
+
+use divan::{Bencher, counter::BytesCount};
+use hyperion::{
+    simulation::equipment::{Equipment, equipped},
+    valence_protocol::{
+        Encode, ItemKind, ItemStack, VarInt,
+        packets::play::{self, entity_equipment_update_s2c::EquipmentEntry},
+    },
+};
+use hyperion_inventory::PlayerInventory;
+
+const PLAYERS: &[usize] = &[100, 1_000, 10_000];
+
+/// One in this many players switches their held item each tick.
+const SWITCH_EVERY: usize = 50;
+
+fn main() {
+    divan::main();
+}
+
+fn equipped_inventory() -> PlayerInventory {
+    let mut inventory = PlayerInventory::default();
+    inventory.set_helmet(ItemStack::new(ItemKind::IronHelmet, 1, None));
+    inventory.set_chestplate(ItemStack::new(ItemKind::IronChestplate, 1, None));
+    inventory.set_leggings(ItemStack::new(ItemKind::IronLeggings, 1, None));
+    inventory.set_boots(ItemStack::new(ItemKind::IronBoots, 1, None));
+    inventory.set_hotbar(0, ItemStack::new(ItemKind::IronSword, 1, None));
+    inventory.set_hotbar(1, ItemStack::new(ItemKind::Bow, 1, None));
+    inventory
+}
+
+/// Players which have been in sync for a while, some of which switch their held item.
+fn players(count: usize) -> Vec<(PlayerInventory, Equipment)> {
+    (0..count)
+        .map(|idx| {
+            let mut inventory = equipped_inventory();
+            let mut equipment = Equipment::default();
+            equipment.update(&inventory);
+
+            if idx % SWITCH_EVERY == 0 {
+                inventory.set_cursor(1);
+            }
+
+            (inventory, equipment)
+        })
+        .collect()
+}
+
+fn encode(buf: &mut Vec<u8>, entity_id: usize, equipment: Vec<EquipmentEntry>) {
+    let packet = play::EntityEquipmentUpdateS2c {
+        entity_id: VarInt(i32::try_from(entity_id).unwrap()),
+        equipment,
+    };
+
+    packet.encode(buf).unwrap();
+}
+
+fn broadcast_all_tick(players: &[(PlayerInventory, Equipment)], buf: &mut Vec<u8>) {
+    for (entity_id, (inventory, _)) in players.iter().enumerate() {
+        let equipment = equipped(inventory)
+            .into_iter()
+            .zip(0..)
+            .map(|(item, slot)| EquipmentEntry {
+                slot,
+                item: item.clone(),
+            })
+            .collect();
+
+        encode(buf, entity_id, equipment);
+    }
+}
+
+fn broadcast_changed_tick(players: &mut [(PlayerInventory, Equipment)], buf: &mut Vec<u8>) {
+    for (entity_id, (inventory, equipment)) in players.iter_mut().enumerate() {
+        let changed = equipment.update(inventory);
+
+        if changed.is_empty() {
+            continue;
+        }
+
+        encode(buf, entity_id, changed);
+    }
+}
+
+#[divan::bench(args = PLAYERS)]
+fn broadcast_all(bencher: Bencher<'_, '_>, count: usize) {
+    let players = players(count);
+
+    let mut buf = Vec::new();
+    broadcast_all_tick(&players, &mut buf);
+
+    bencher.counter(BytesCount::new(buf.len())).bench_local(|| {
+        buf.clear();
+        broadcast_all_tick(black_box(&players), &mut buf);
+        black_box(&buf);
+    });
+}
+
+#[divan::bench(args = PLAYERS)]
+fn broadcast_changed(bencher: Bencher<'_, '_>, count: usize) {
+    let mut buf = Vec::new();
+    broadcast_changed_tick(&mut players(count), &mut buf);
+
+    bencher
+        .counter(BytesCount::new(buf.len()))
+        .with_inputs(|| (players(count), Vec::with_capacity(buf.len())))
+        .bench_local_values(|(mut players, mut buf)| {
+            broadcast_changed_tick(&mut players, &mut buf);
+            buf
+        });
+}
@@ -34,9 +34,13 @@ pub struct Global {
     /// Data shared between the IO thread and the ECS framework.
     pub shared: Arc<Shared>,
 
-    /// The amount of time from the last packet a player has sent before the server will kick them.
+    /// The amount of time a player has to answer a keep-alive packet before the server will kick them.
     pub keep_alive_timeout: Duration,
 
+    /// Whether player chat messages are sent with their signatures, so clients can verify them.
+    /// Otherwise every message is sent as a system message.
+    pub secure_chat: bool,
+
     /// The amount of time the last tick took in milliseconds.
     pub ms_last_tick: f32,
 
@@ -52,6 +56,7 @@ impl Global {
             max_hurt_resistant_time: 20, // actually kinda like 10 vanilla mc is weird
             shared,
             keep_alive_timeout: Duration::from_secs(20),
+            secure_chat: true,
             ms_last_tick: 0.0,
             player_count: AtomicUsize::new(0),
         }
@@ -2,7 +2,8 @@ use std::{borrow::Cow, collections::BTreeSet, ops::Index};
 
 use anyhow::Context;
 use flecs_ecs::prelude::*;
-use hyperion_crafting::{Action, CraftingRegistry, RecipeBookState};
+use hyperion_crafting::{CraftingRegistry, recipe_book::UnlockedRecipes};
+use hyperion_inventory::PlayerInventory;
 use hyperion_utils::EntityExt;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use tracing::{info, instrument};
@@ -10,11 +11,7 @@ use valence_protocol::{
     ByteAngle, GameMode, Ident, PacketEncoder, RawBytes, VarInt, Velocity,
     game_mode::OptGameMode,
     ident,
-    packets::play::{
-        self, GameJoinS2c,
-        player_position_look_s2c::PlayerPositionLookFlags,
-        team_s2c::{CollisionRule, Mode, NameTagVisibility, TeamColor, TeamFlags},
-    },
+    packets::play::{self, GameJoinS2c, player_position_look_s2c::PlayerPositionLookFlags},
 };
 use valence_registry::{BiomeRegistry, RegistryCodec};
 use valence_server::entity::EntityKind;
@@ -33,6 +30,9 @@ use crate::{
     simulation::{
         Comms, Name, Position, Uuid, Yaw,
         command::{Command, ROOT_COMMAND, get_command_packet},
+        effect::ActiveEffects,
+        equipment::Equipment,
+        game_mode,
         metadata::{MetadataChanges, entity::EntityFlags},
         skin::PlayerSkin,
         util::registry_codec_raw,
@@ -85,6 +85,10 @@ pub fn player_join_world(
         .map(|value| value.name.as_str_ident().into())
         .collect();
 
+    let mode = entity
+        .try_get::<&game_mode::GameMode>(|mode| *mode)
+        .unwrap_or_default();
+
     let dimension_name = ident!("overworld");
     // let dimension_name: Ident<Cow<str>> = chunk_layer.dimension_type_name().into();
 
@@ -97,14 +101,14 @@ pub fn player_join_world(
         view_distance: VarInt(i32::from(config.view_distance)),
         simulation_distance: config.simulation_distance.into(),
         reduced_debug_info: false,
-        enable_respawn_screen: false,
+        enable_respawn_screen: true,
         dimension_name: dimension_name.into(),
         hashed_seed: 0,
-        game_mode: GameMode::Survival,
+        game_mode: mode.into(),
         is_flat: false,
         last_death_location: None,
         portal_cooldown: 60.into(),
-        previous_game_mode: OptGameMode(Some(GameMode::Survival)),
+        previous_game_mode: OptGameMode(Some(mode.into())),
         dimension_type_name: ident!("minecraft:overworld").into(),
         is_debug: false,
     };
@@ -113,6 +117,10 @@ pub fn player_join_world(
         .add_packet(&pkt)
         .context("failed to send player spawn packet")?;
 
+    bundle
+        .add_packet(&mode.abilities_packet())
+        .context("failed to send player abilities packet")?;
+
     let center_chunk = position.to_chunk();
 
     let pkt = play::ChunkRenderDistanceCenterS2c {
@@ -153,6 +161,13 @@ pub fn player_join_world(
 
     bundle.add_raw(&cached_data);
 
+    // the recipe book refers to the recipes sent above
+    let unlocked = entity
+        .try_get::<&UnlockedRecipes>(UnlockedRecipes::init_packet)
+        .unwrap_or_else(|| UnlockedRecipes::default().init_packet());
+
+    bundle.add_packet(&unlocked)?;
+
     let text = play::GameMessageS2c {
         chat: format!("{name} joined the world").into_cow_text(),
         overlay: false,
@@ -171,8 +186,16 @@ pub fn player_join_world(
         teleport_id: 1.into(),
     })?;
 
+    // the hand slot may have been restored from the last time the player was online
+    if let Some(slot) = entity.try_get::<&PlayerInventory>(|inventory| {
+        inventory.get_cursor_index() - PlayerInventory::HOTBAR_START_SLOT
+    }) {
+        bundle.add_packet(&play::UpdateSelectedSlotS2c {
+            slot: u8::try_from(slot)?,
+        })?;
+    }
+
     let mut entries = Vec::new();
-    let mut all_player_names = Vec::new();
 
     let count = query.iter_stage(world).count();
 
@@ -199,12 +222,9 @@ pub fn player_join_world(
                 };
 
                 entries.push(entry);
-                all_player_names.push(name.to_string());
             });
     }
 
-    let all_player_names = all_player_names.iter().map(String::as_str).collect();
-
     let actions = PlayerListActions::default()
         .with_add_player(true)
         .with_update_listed(true)
@@ -256,6 +276,29 @@ pub fn player_join_world(
                         .add_packet(show_all.borrow_packet())
                         .context("failed to send player spawn packet")?;
 
+                    if let Some(pkt) = query_entity
+                        .try_get::<&Equipment>(|equipment| {
+                            equipment.snapshot(VarInt(query_entity.minecraft_id()))
+                        })
+                        .flatten()
+                    {
+                        bundle
+                            .add_packet(&pkt)
+                            .context("failed to send equipment packet")?;
+                    }
+
+                    if let Some(packets) = query_entity.try_get::<&ActiveEffects>(|effects| {
+                        effects
+                            .packets(VarInt(query_entity.minecraft_id()))
+                            .collect::<Vec<_>>()
+                    }) {
+                        for pkt in packets {
+                            bundle
+                                .add_packet(&pkt)
+                                .context("failed to send status effect packet")?;
+                        }
+                    }
+
                     metadata.encode(*flags);
 
                     Ok(())
@@ -294,7 +337,7 @@ pub fn player_join_world(
         chat_data: None,
         listed: true,
         ping: 20,
-        game_mode: GameMode::Survival,
+        game_mode: mode.into(),
         display_name: Some(name.to_string().into_cow_text()),
     }];
 
@@ -312,22 +355,6 @@ pub fn player_join_world(
         .add_packet(&pkt)
         .context("failed to send player list packet")?;
 
-    let player_name = vec![name];
-
-    compose
-        .broadcast(
-            &play::TeamS2c {
-                team_name: "no_tag",
-                mode: Mode::AddEntities {
-                    entities: player_name,
-                },
-            },
-            system,
-        )
-        .exclude(io)
-        .send()
-        .context("failed to send team packet")?;
-
     let current_entity_id = VarInt(entity.minecraft_id());
 
     let spawn_player = play::PlayerSpawnS2c {
@@ -343,21 +370,24 @@ pub fn player_join_world(
         .send()
         .context("failed to send player spawn packet")?;
 
+    // the changes to the equipment were already sent before the player was spawned for anyone
+    if let Some(pkt) = entity
+        .try_get::<&Equipment>(|equipment| equipment.snapshot(current_entity_id))
+        .flatten()
+    {
+        compose
+            .broadcast(&pkt, system)
+            .exclude(io)
+            .send()
+            .context("failed to send equipment packet")?;
+    }
+
     let show_all = show_all(entity.minecraft_id());
     compose
         .broadcast(show_all.borrow_packet(), system)
         .send()
         .context("failed to send show all packet")?;
 
-    bundle
-        .add_packet(&play::TeamS2c {
-            team_name: "no_tag",
-            mode: Mode::AddEntities {
-                entities: all_player_names,
-            },
-        })
-        .context("failed to send team packet")?;
-
     let command_packet = get_command_packet(world, root_command, Some(**entity));
 
     bundle.add_packet(&command_packet)?;
@@ -407,37 +437,10 @@ fn generate_cached_packet_bytes(
 
     encoder.append_packet(&brand)?;
 
-    encoder.append_packet(&play::TeamS2c {
-        team_name: "no_tag",
-        mode: Mode::CreateTeam {
-            team_display_name: Cow::default(),
-            friendly_flags: TeamFlags::default(),
-            name_tag_visibility: NameTagVisibility::Never,
-            collision_rule: CollisionRule::Always,
-            team_color: TeamColor::Black,
-            team_prefix: Cow::default(),
-            team_suffix: Cow::default(),
-            entities: vec![],
-        },
-    })?;
-
     if let Some(pkt) = crafting_registry.packet() {
         encoder.append_packet(&pkt)?;
     }
 
-    // unlock
-    let pkt = hyperion_crafting::UnlockRecipesS2c {
-        action: Action::Init,
-        crafting_recipe_book: RecipeBookState::FALSE,
-        smelting_recipe_book: RecipeBookState::FALSE,
-        blast_furnace_recipe_book: RecipeBookState::FALSE,
-        smoker_recipe_book: RecipeBookState::FALSE,
-        recipe_ids_1: vec!["hyperion:what".to_string()],
-        recipe_ids_2: vec!["hyperion:what".to_string()],
-    };
-
-    encoder.append_packet(&pkt)?;
-
     Ok(())
 }
 
@@ -3,13 +3,10 @@ use std::{borrow::Cow, fmt::Debug};
 use anyhow::Context;
 use flecs_ecs::prelude::*;
 use glam::Vec3;
-use hyperion_inventory::PlayerInventory;
+use hyperion_inventory::{CarriedItem, PlayerInventory};
 use hyperion_utils::EntityExt;
 use tracing::{debug, error};
-use valence_protocol::{
-    ByteAngle, RawBytes, VarInt,
-    packets::play::{self, entity_equipment_update_s2c::EquipmentEntry},
-};
+use valence_protocol::{ByteAngle, RawBytes, VarInt, packets::play};
 use valence_server::BlockState;
 
 use crate::{
@@ -20,8 +17,9 @@ use crate::{
         animation::ActiveAnimation,
         blocks::Blocks,
         entity_kind::EntityKind,
+        equipment::Equipment,
         handlers::is_grounded,
-        metadata::{MetadataChanges, get_and_clear_metadata},
+        metadata::{MetadataChanges, get_and_clear_metadata, item::Item},
     },
 };
 
@@ -187,15 +185,23 @@ impl Module for EntityStateSyncModule {
             world,
             &Compose($),
             &mut PlayerInventory,
+            &mut CarriedItem,
             &ConnectionId,
         )
         .multi_threaded()
         .kind::<flecs::pipeline::OnStore>()
-        .each_iter(move |it, _, (compose, inventory, io)| {
+        .each_iter(move |it, _, (compose, inventory, carried, io)| {
             let mut run = || {
                 let io = *io;
                 let system = it.system();
 
+                // every change made by the server is a new state of the window
+                if !inventory.updated_since_last_tick.is_empty() {
+                    carried.next_state_id();
+                }
+
+                let state_id = VarInt(carried.state_id);
+
                 for slot in &inventory.updated_since_last_tick {
                     let Ok(slot) = u16::try_from(slot) else {
                         error!("failed to convert slot to u16 {slot}");
@@ -210,7 +216,7 @@ impl Module for EntityStateSyncModule {
                     };
                     let pkt = play::ScreenHandlerSlotUpdateS2c {
                         window_id: 0,
-                        state_id: VarInt::default(),
+                        state_id,
                         slot_idx: slot,
                         slot_data: Cow::Borrowed(item),
                     };
@@ -234,49 +240,28 @@ impl Module for EntityStateSyncModule {
             "sync_equipped_items",
             world,
             &Compose($),
-            &Position,
             &PlayerInventory,
+            &mut Equipment,
         )
         .multi_threaded()
         .kind::<flecs::pipeline::OnStore>()
-        .each_iter(move |it, row, (compose, position, inventory)| {
-            // let entity = it.entity(row);
+        .each_iter(move |it, row, (compose, inventory, equipment)| {
             let system = it.system();
-            // get armor and hand
-            let hand = EquipmentEntry {
-                slot: 0,
-                item: inventory.get_cursor().clone(),
-            };
-            let helmet = EquipmentEntry {
-                slot: 5,
-                item: inventory.get_helmet().clone(),
-            };
-            let chestplate = EquipmentEntry {
-                slot: 4,
-                item: inventory.get_chestplate().clone(),
-            };
-            let leggings = EquipmentEntry {
-                slot: 3,
-                item: inventory.get_leggings().clone(),
-            };
-            let boots = EquipmentEntry {
-                slot: 2,
-                item: inventory.get_boots().clone(),
-            };
-            let off_hand = EquipmentEntry {
-                slot: 1,
-                item: inventory.get_offhand().clone(),
-            };
+
+            // only the slots which changed since the last broadcast, sent as far as players are
+            // spawned so no viewer is left with stale equipment when it comes into range
+            let changed = equipment.update(inventory);
+
+            if changed.is_empty() {
+                return;
+            }
 
             let packet = play::EntityEquipmentUpdateS2c {
                 entity_id: VarInt(it.entity(row).minecraft_id()),
-                equipment: vec![hand, helmet, chestplate, leggings, boots, off_hand],
+                equipment: changed,
             };
 
-            compose
-                .broadcast_local(&packet, position.to_chunk(), system)
-                .send()
-                .unwrap();
+            compose.broadcast(&packet, system).send().unwrap();
         });
 
         // What ever you do DO NOT!!! I REPEAT DO NOT SET VELOCITY ANYWHERE
@@ -408,6 +393,8 @@ impl Module for EntityStateSyncModule {
         .multi_threaded()
         .kind::<flecs::pipeline::OnUpdate>()
         .with_enum_wildcard::<EntityKind>()
+        // item entities have their own physics
+        .without::<Item>()
         .each_iter(|it, row, (position, velocity, connection_id)| {
             if let Some(_connection_id) = connection_id {
                 return;
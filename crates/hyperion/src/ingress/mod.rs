@@ -1,4 +1,8 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{
+    borrow::Cow,
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
 
 use anyhow::Context;
 use colored::Colorize;
@@ -25,15 +29,17 @@ use crate::{
     runtime::AsyncRuntime,
     simulation::{
         AiTargetable, ChunkPosition, Comms, ConfirmBlockSequences, EntitySize, IgnMap,
-        ImmuneStatus, Name, PacketState, Pitch, Player, Position, StreamLookup, Uuid, Velocity, Xp,
-        Yaw,
+        ImmuneStatus, Name, PacketState, Pitch, Player, PlayerIp, Position, StreamLookup, Uuid,
+        Velocity, Xp, Yaw,
         animation::ActiveAnimation,
         blocks::Blocks,
+        equipment::Equipment,
         handlers::PacketSwitchQuery,
+        keep_alive::KeepAlive,
         metadata::{MetadataPrefabs, entity::Pose},
         skin::PlayerSkin,
     },
-    storage::{Events, GlobalEventHandlers, PlayerJoinServer, SkinHandler},
+    storage::{BanList, Events, GlobalEventHandlers, PlayerJoinServer, SkinHandler},
     util::{SendableRef, TracingExt, mojang::MojangClient},
 };
 
@@ -110,6 +116,28 @@ fn process_login(
 
     let username = player_join.username.as_str();
 
+    let uuid = profile_id.unwrap_or_else(|| offline_uuid(username));
+
+    // banned players are turned away before compression is enabled
+    let ip = entity
+        .try_get::<&PlayerIp>(|ip| ip.0)
+        .filter(|ip| !ip.is_unspecified());
+
+    if let Some(ban) = world.get::<&BanList>(|bans| bans.find(uuid, ip))? {
+        info!("{username} tried to join while banned: {}", ban.reason);
+
+        let pkt = login::LoginDisconnectS2c {
+            reason: ban.disconnect_message(SystemTime::now()).into_cow_text(),
+        };
+
+        compose.unicast_no_compression(&pkt, stream_id, system)?;
+
+        *login_state = PacketState::Terminate;
+        entity.set(PendingRemove::new(""));
+
+        return Ok(());
+    }
+
     let global = compose.global();
 
     let pkt = LoginCompressionS2c {
@@ -122,7 +150,6 @@ fn process_login(
 
     let username = Arc::from(username);
 
-    let uuid = profile_id.unwrap_or_else(|| offline_uuid(&username));
     let uuid_s = format!("{uuid:?}").dimmed();
     info!("Starting login: {username} {uuid_s}");
 
@@ -228,6 +255,7 @@ fn process_status(
                 },
                 "description": "Getting 10k Players to PvP at Once on a Minecraft Server to Break the Guinness World Record",
                 // "favicon": favicon,
+                "enforcesSecureChat": compose.global().secure_chat,
             });
 
             let json = serde_json::to_string_pretty(&json)?;
@@ -312,12 +340,16 @@ impl Module for IngressModule {
 
             let mut recv = receive.0.lock();
 
-            for connect in recv.player_connect.drain(..) {
+            for (connect, ip) in recv.player_connect.drain(..) {
                 info!("player_connect");
                 let view = world
                     .entity()
                     .set(ConnectionId::new(connect))
+                    .set(PlayerIp(ip))
                     .set(hyperion_inventory::PlayerInventory::default())
+                    .set(hyperion_inventory::CarriedItem::default())
+                    .set(Equipment::default())
+                    .set(KeepAlive::new(Instant::now()))
                     .set(ConfirmBlockSequences::default())
                     .set(PacketState::Handshake)
                     .set(ActiveAnimation::NONE)
@@ -47,7 +47,10 @@ use ingress::IngressModule;
 use libc::{RLIMIT_NOFILE, getrlimit, setrlimit};
 use libdeflater::CompressionLvl;
 use simulation::{Comms, SimModule, StreamLookup, blocks::Blocks};
-use storage::{Events, GlobalEventHandlers, LocalDb, SkinHandler, ThreadLocal};
+use storage::{
+    BanList, Events, GlobalEventHandlers, LocalDb, PersistedComponents, PlayerDataHandler,
+    RecipeBookHandler, SkinHandler, ThreadLocal,
+};
 use tracing::{info, info_span, warn};
 use util::mojang::MojangClient;
 pub use uuid;
@@ -240,6 +243,10 @@ impl HyperionCore {
 
         world.component::<LocalDb>();
         world.component::<SkinHandler>();
+        world.component::<RecipeBookHandler>();
+        world.component::<PlayerDataHandler>();
+        world.component::<BanList>();
+        world.component::<PersistedComponents>();
         world.component::<MojangClient>();
         world.component::<Events>();
         world.component::<Comms>();
@@ -307,10 +314,17 @@ impl HyperionCore {
         info!("initializing database");
         let db = LocalDb::new()?;
         let skins = SkinHandler::new(&db)?;
+        let recipe_books = RecipeBookHandler::new(&db)?;
+        let player_data = PlayerDataHandler::new(&db)?;
+        let bans = BanList::new(&db)?;
         info!("database initialized");
 
         world.set(db);
         world.set(skins);
+        world.set(recipe_books);
+        world.set(player_data);
+        world.set(bans);
+        world.set(PersistedComponents::default());
 
         world.set(MojangClient::new(&runtime, ApiProvider::MAT_DOES_DEV));
 
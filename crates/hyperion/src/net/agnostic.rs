@@ -1,7 +1,10 @@
 //! Agnostic networking primitives. Translates to correct protocol version.
 
 mod chat;
-pub use chat::{Chat, chat};
+pub use chat::{Chat, chat, chat_markup};
 
 mod sound;
 pub use sound::{Sound, SoundBuilder, sound};
+
+mod title;
+pub use title::{ActionBar, Title, TitleFade, action_bar, title};
@@ -1,22 +1,19 @@
 use std::io::Write;
 
-use valence_protocol::packets::play;
-use valence_text::IntoText;
-
-use crate::PacketBundle;
+use crate::{PacketBundle, net::packets::GameMessageS2c};
 
 pub struct Chat {
-    raw: play::GameMessageS2c<'static>,
+    raw: GameMessageS2c<'static>,
 }
 
 pub fn chat(chat: impl Into<String>) -> Chat {
-    let chat = chat.into();
-    Chat {
-        raw: play::GameMessageS2c {
-            chat: chat.into_cow_text(),
-            overlay: false,
-        },
-    }
+    hyperion_text::Text::from(chat.into()).into()
+}
+
+/// A chat message written in markup, such as `<red>Hello <bold>world`. Text typed by players
+/// has to be escaped with [`hyperion_text::markup::escape`] first.
+pub fn chat_markup(markup: &str) -> Chat {
+    hyperion_text::markup::parse(markup).into()
 }
 
 #[macro_export]
@@ -26,6 +23,17 @@ macro_rules! chat {
     };
 }
 
+impl From<hyperion_text::Text<'static>> for Chat {
+    fn from(chat: hyperion_text::Text<'static>) -> Self {
+        Self {
+            raw: GameMessageS2c {
+                chat,
+                overlay: false,
+            },
+        }
+    }
+}
+
 impl PacketBundle for &Chat {
     fn encode_including_ids(self, mut w: impl Write) -> anyhow::Result<()> {
         self.raw.encode_including_ids(&mut w)
@@ -0,0 +1,100 @@
+use std::io::Write;
+
+use valence_protocol::packets::play;
+use valence_text::{IntoText, Text};
+
+use crate::{PacketBundle, net::DataBundle};
+
+/// How long a title fades in, stays and fades out, in ticks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TitleFade {
+    pub fade_in: i32,
+    pub stay: i32,
+    pub fade_out: i32,
+}
+
+impl Default for TitleFade {
+    /// The timing of vanilla.
+    fn default() -> Self {
+        Self {
+            fade_in: 10,
+            stay: 70,
+            fade_out: 20,
+        }
+    }
+}
+
+/// A title in the middle of the screen, with an optional subtitle below it.
+#[must_use]
+pub struct Title {
+    title: Text,
+    subtitle: Option<Text>,
+    fade: Option<TitleFade>,
+}
+
+impl Title {
+    pub fn subtitle(mut self, subtitle: impl IntoText<'static>) -> Self {
+        self.subtitle = Some(subtitle.into_cow_text().into_owned());
+        self
+    }
+
+    /// Sets the fade timing in ticks. Without it the client uses the timing of the last title.
+    pub const fn fade(mut self, fade_in: i32, stay: i32, fade_out: i32) -> Self {
+        self.fade = Some(TitleFade {
+            fade_in,
+            stay,
+            fade_out,
+        });
+        self
+    }
+
+    /// Adds the packets which show the title to `bundle`.
+    pub fn write(&self, bundle: &mut DataBundle<'_, '_>) -> anyhow::Result<()> {
+        if let Some(fade) = self.fade {
+            bundle.add_packet(&play::TitleFadeS2c {
+                fade_in: fade.fade_in,
+                stay: fade.stay,
+                fade_out: fade.fade_out,
+            })?;
+        }
+
+        // the title packet is what makes the client show the subtitle, so it goes last
+        if let Some(subtitle) = &self.subtitle {
+            bundle.add_packet(&play::SubtitleS2c {
+                subtitle_text: subtitle.into_cow_text(),
+            })?;
+        }
+
+        bundle.add_packet(&play::TitleS2c {
+            title_text: (&self.title).into_cow_text(),
+        })
+    }
+}
+
+pub fn title(title: impl IntoText<'static>) -> Title {
+    Title {
+        title: title.into_cow_text().into_owned(),
+        subtitle: None,
+        fade: None,
+    }
+}
+
+/// Text above the hotbar.
+#[must_use]
+pub struct ActionBar {
+    raw: play::OverlayMessageS2c<'static>,
+}
+
+pub fn action_bar(text: impl IntoText<'static>) -> ActionBar {
+    ActionBar {
+        raw: play::OverlayMessageS2c {
+            action_bar_text: text.into_cow_text(),
+        },
+    }
+}
+
+impl PacketBundle for &ActionBar {
+    fn encode_including_ids(self, mut w: impl Write) -> anyhow::Result<()> {
+        self.raw.encode_including_ids(&mut w)
+    }
+}
@@ -306,6 +306,30 @@ impl Compose {
         .send()
     }
 
+    /// Show a title to a single player. Action bars are sent like any other packet.
+    pub fn title(
+        &self,
+        title: &agnostic::Title,
+        stream_id: ConnectionId,
+        system: EntityView<'_>,
+    ) -> anyhow::Result<()> {
+        let mut bundle = DataBundle::new(self, system);
+        title.write(&mut bundle)?;
+        bundle.unicast(stream_id)
+    }
+
+    /// Show a title to every player within a certain region.
+    pub fn title_local(
+        &self,
+        title: &agnostic::Title,
+        center: I16Vec2,
+        system: EntityView<'_>,
+    ) -> anyhow::Result<()> {
+        let mut bundle = DataBundle::new(self, system);
+        title.write(&mut bundle)?;
+        bundle.broadcast_local(center)
+    }
+
     #[must_use]
     pub(crate) fn encoder(&self) -> PacketEncoder {
         let threshold = self.global.shared.compression_threshold;
@@ -2,7 +2,7 @@ use std::{borrow::Cow, io::Write};
 
 use uuid::Uuid;
 use valence_protocol::{
-    Decode, Encode, ItemStack, Packet, VarInt,
+    Bounded, Decode, Encode, ItemStack, Packet, VarInt,
     packets::play::{
         boss_bar_s2c::{BossBarColor, BossBarDivision, BossBarFlags},
         entity_equipment_update_s2c::EquipmentEntry,
@@ -58,6 +58,14 @@ impl<'a> Decode<'a> for EntityEquipmentUpdateS2c<'static> {
     }
 }
 
+/// [`valence_protocol::packets::play::GameMessageS2c`] with a [`hyperion_text::Text`], which can
+/// be built from markup.
+#[derive(Clone, Debug, Encode, Packet)]
+pub struct GameMessageS2c<'a> {
+    pub chat: hyperion_text::Text<'a>,
+    pub overlay: bool,
+}
+
 #[derive(Clone, Debug, Encode, Packet)]
 pub struct BossBarS2c<'a> {
     pub id: Uuid,
@@ -79,3 +87,118 @@ pub enum BossBarAction<'a> {
     UpdateStyle(BossBarColor, BossBarDivision),
     UpdateFlags(BossBarFlags),
 }
+
+/// The length of the signature of a chat message.
+pub const MESSAGE_SIGNATURE_LEN: usize = 256;
+
+/// [`valence_protocol::packets::play::ChatMessageC2s`] with every field of the message chain.
+#[derive(Copy, Clone, Debug, Decode, Packet)]
+pub struct ChatMessageC2s<'a> {
+    pub message: Bounded<&'a str, 256>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub salt: u64,
+    pub signature: Option<&'a [u8; MESSAGE_SIGNATURE_LEN]>,
+    /// How many messages the client stopped tracking since its last acknowledgement.
+    pub message_count: VarInt,
+    /// Which of the last 20 signed messages the client has seen.
+    pub acknowledged: [u8; 3],
+}
+
+#[derive(Copy, Clone, Debug, Decode)]
+pub struct CommandArgumentSignature<'a> {
+    pub argument_name: Bounded<&'a str, 16>,
+    pub signature: &'a [u8; MESSAGE_SIGNATURE_LEN],
+}
+
+/// [`valence_protocol::packets::play::CommandExecutionC2s`], which acknowledges messages the same
+/// way as [`ChatMessageC2s`].
+#[derive(Clone, Debug, Decode, Packet)]
+pub struct CommandExecutionC2s<'a> {
+    pub command: Bounded<&'a str, 256>,
+    pub timestamp: u64,
+    pub salt: u64,
+    pub argument_signatures: Vec<CommandArgumentSignature<'a>>,
+    pub message_count: VarInt,
+    pub acknowledged: [u8; 3],
+}
+
+/// Sent by clients which did not send a chat message for a while to acknowledge the messages
+/// they were sent.
+#[derive(Copy, Clone, Debug, Decode, Packet)]
+pub struct MessageAcknowledgmentC2s {
+    pub message_count: VarInt,
+}
+
+/// Starts the chat session of a client, whose key signs its chat messages.
+#[derive(Copy, Clone, Debug, Decode, Packet)]
+pub struct PlayerSessionC2s<'a> {
+    pub session_id: Uuid,
+    /// Milliseconds since the Unix epoch.
+    pub expires_at: i64,
+    pub public_key: &'a [u8],
+    pub key_signature: &'a [u8],
+}
+
+/// A signature of a previous message, either by its index in the cache of the client or in full.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PreviousMessage<'a> {
+    Cached(i32),
+    Full(&'a [u8; MESSAGE_SIGNATURE_LEN]),
+}
+
+impl Encode for PreviousMessage<'_> {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        match self {
+            Self::Cached(id) => VarInt(id + 1).encode(&mut w),
+            Self::Full(signature) => {
+                VarInt(0).encode(&mut w)?;
+                signature.encode(&mut w)
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Encode)]
+pub enum MessageFilterType {
+    PassThrough,
+    FullyFiltered,
+}
+
+/// A chat message of a player, which the client verifies if it is signed.
+#[derive(Clone, Debug, Encode, Packet)]
+pub struct ChatMessageS2c<'a> {
+    pub sender: Uuid,
+    /// The index of the message in the session of the sender.
+    pub index: VarInt,
+    pub message_signature: Option<&'a [u8; MESSAGE_SIGNATURE_LEN]>,
+    pub message: Bounded<&'a str, 256>,
+    pub timestamp: u64,
+    pub salt: u64,
+    /// The messages the sender had seen, which are part of the signature.
+    pub previous_messages: Vec<PreviousMessage<'a>>,
+    /// Shown instead of `message` if the server changed it.
+    pub unsigned_content: Option<hyperion_text::Text<'a>>,
+    pub filter_type: MessageFilterType,
+    pub chat_type: VarInt,
+    pub network_name: hyperion_text::Text<'a>,
+    pub network_target_name: Option<hyperion_text::Text<'a>>,
+}
+
+/// A player chat message without a signature, formatted by its chat type.
+#[derive(Clone, Debug, Encode, Packet)]
+pub struct ProfilelessChatMessageS2c<'a> {
+    pub message: hyperion_text::Text<'a>,
+    pub chat_type: VarInt,
+    pub chat_type_name: hyperion_text::Text<'a>,
+    pub target_name: Option<hyperion_text::Text<'a>>,
+}
+
+/// Tells a client whether the server enforces secure chat, which clients warn about on joining if
+/// it does not.
+#[derive(Clone, Debug, Encode, Packet)]
+pub struct ServerMetadataS2c<'a> {
+    pub motd: hyperion_text::Text<'a>,
+    pub icon: Option<&'a [u8]>,
+    pub enforce_secure_chat: bool,
+}
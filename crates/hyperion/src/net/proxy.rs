@@ -1,6 +1,12 @@
 //! Communication to a proxy which forwards packets to the players.
 
-use std::{collections::HashMap, io::Cursor, net::SocketAddr, process::Command, sync::Arc};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    process::Command,
+    sync::Arc,
+};
 
 use bytes::{Buf, BytesMut};
 use flecs_ecs::macros::Component;
@@ -14,8 +20,8 @@ use crate::{runtime::AsyncRuntime, simulation::EgressComm};
 /// This is used
 #[derive(Default)]
 pub struct ReceiveStateInner {
-    /// All players who have recently connected to the server.
-    pub player_connect: Vec<u64>,
+    /// All players who have recently connected to the server, with their IP addresses.
+    pub player_connect: Vec<(u64, IpAddr)>,
     /// All players who have recently disconnected from the server.
     pub player_disconnect: Vec<u64>,
     /// A map of stream ids to the corresponding [`BytesMut`] buffers. This represents data from the client to the server.
@@ -119,8 +125,9 @@ async fn inner(
                         match result {
                             ArchivedProxyToServerMessage::PlayerConnect(message) => {
                                 let Ok(stream) = rkyv::deserialize::<u64, !>(&message.stream);
+                                let ip = Ipv6Addr::from(message.ip).to_canonical();
 
-                                shared.lock().player_connect.push(stream);
+                                shared.lock().player_connect.push((stream, ip));
                             }
                             ArchivedProxyToServerMessage::PlayerDisconnect(message) => {
                                 let Ok(stream) = rkyv::deserialize::<u64, !>(&message.stream);
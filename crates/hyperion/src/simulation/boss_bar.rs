@@ -0,0 +1,498 @@
+//! Boss bars, the bars with a title at the top of the screen.
+//!
+//! A boss bar is an entity with a [`BossBar`]. Its [`Audience`] decides who sees it, and players
+//! are shown and hidden the bar by themselves as they join, leave or walk in and out of range.
+//! Changes to a bar are sent once per tick to everyone who sees it.
+//!
+//! ```ignore
+//! world
+//!     .entity()
+//!     .set(Position::new(0.0, 64.0, 0.0))
+//!     .set(BossBar::new("§cThe Wither").with_audience(Audience::Near(64.0)));
+//! ```
+
+use flecs_ecs::prelude::*;
+use glam::IVec2;
+use rustc_hash::{FxHashMap, FxHashSet};
+use tracing::error;
+use uuid::Uuid;
+pub use valence_protocol::packets::play::boss_bar_s2c::{
+    BossBarColor, BossBarDivision, BossBarFlags,
+};
+
+use crate::{
+    net::{
+        Compose, ConnectionId, DataBundle,
+        packets::{BossBarAction, BossBarS2c},
+    },
+    simulation::{PacketState, Position},
+};
+
+/// Who sees a [`BossBar`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Audience {
+    /// Every player.
+    Everyone,
+    /// Players within this many blocks of the [`Position`] of the bar. A bar without a position
+    /// is seen by no one.
+    Near(f32),
+    /// Only these players.
+    Players(FxHashSet<Entity>),
+}
+
+impl Audience {
+    /// Whether `player` at `position` sees a bar at `bar`.
+    #[must_use]
+    pub fn includes(&self, player: Entity, position: &Position, bar: Option<&Position>) -> bool {
+        match self {
+            Self::Everyone => true,
+            Self::Near(range) => {
+                bar.is_some_and(|bar| bar.distance_squared(**position) <= range * range)
+            }
+            Self::Players(players) => players.contains(&player),
+        }
+    }
+}
+
+/// The chunk `position` is in.
+#[expect(clippy::cast_possible_truncation)]
+fn chunk(position: &Position) -> IVec2 {
+    IVec2::new(
+        (position.x.floor() as i32) >> 4,
+        (position.z.floor() as i32) >> 4,
+    )
+}
+
+/// The players in play by the chunk they are in, rebuilt every tick, so a bar seen by players
+/// near it only looks at the chunks in its range.
+#[derive(Component, Debug, Default)]
+struct PlayerChunks(FxHashMap<IVec2, Vec<(Entity, ConnectionId, Position)>>);
+
+impl PlayerChunks {
+    /// The players in the chunks which may be within `range` blocks of `center`.
+    #[expect(clippy::cast_possible_truncation)]
+    fn near(
+        &self,
+        center: &Position,
+        range: f32,
+    ) -> impl Iterator<Item = &(Entity, ConnectionId, Position)> {
+        let center = chunk(center);
+        let radius = (range / 16.0).ceil().max(0.0) as i32;
+
+        // a huge range covers more chunks than players are in, so those are looked at instead
+        let side = usize::try_from(radius)
+            .unwrap_or(usize::MAX)
+            .saturating_mul(2)
+            .saturating_add(1);
+
+        let chunks: Box<dyn Iterator<Item = _>> = if side.saturating_mul(side) > self.0.len() {
+            Box::new(
+                self.0
+                    .iter()
+                    .filter(move |(chunk, _)| (**chunk - center).abs().max_element() <= radius)
+                    .map(|(_, players)| players),
+            )
+        } else {
+            Box::new(
+                (-radius..=radius)
+                    .flat_map(move |x| (-radius..=radius).map(move |z| center + IVec2::new(x, z)))
+                    .filter_map(|chunk| self.0.get(&chunk)),
+            )
+        };
+
+        chunks.flatten()
+    }
+}
+
+/// What changed about a bar since it was last sent.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct Changes {
+    title: bool,
+    health: bool,
+    style: bool,
+    flags: bool,
+}
+
+/// A boss bar, which is shown to the players in its [`Audience`].
+#[derive(Component, Debug)]
+pub struct BossBar {
+    id: Uuid,
+    title: String,
+    health: f32,
+    color: BossBarColor,
+    division: BossBarDivision,
+    flags: BossBarFlags,
+    audience: Audience,
+    /// The players the bar is shown to.
+    viewers: FxHashMap<Entity, ConnectionId>,
+    changes: Changes,
+}
+
+impl BossBar {
+    /// A full, white bar seen by everyone.
+    #[must_use]
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            title: title.into(),
+            health: 1.0,
+            color: BossBarColor::White,
+            division: BossBarDivision::NoDivision,
+            flags: BossBarFlags::default(),
+            audience: Audience::Everyone,
+            viewers: FxHashMap::default(),
+            changes: Changes::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_style(mut self, color: BossBarColor, division: BossBarDivision) -> Self {
+        self.color = color;
+        self.division = division;
+        self
+    }
+
+    #[must_use]
+    pub fn with_audience(mut self, audience: Audience) -> Self {
+        self.audience = audience;
+        self
+    }
+
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        let title = title.into();
+
+        if self.title != title {
+            self.title = title;
+            self.changes.title = true;
+        }
+    }
+
+    #[must_use]
+    pub const fn health(&self) -> f32 {
+        self.health
+    }
+
+    /// Sets how full the bar is, from 0 to 1.
+    #[expect(clippy::float_cmp, reason = "only exact changes need to be sent")]
+    pub fn set_health(&mut self, health: f32) {
+        let health = health.clamp(0.0, 1.0);
+
+        if self.health != health {
+            self.health = health;
+            self.changes.health = true;
+        }
+    }
+
+    pub fn set_style(&mut self, color: BossBarColor, division: BossBarDivision) {
+        if self.color != color || self.division != division {
+            self.color = color;
+            self.division = division;
+            self.changes.style = true;
+        }
+    }
+
+    pub fn set_flags(&mut self, flags: BossBarFlags) {
+        if self.flags != flags {
+            self.flags = flags;
+            self.changes.flags = true;
+        }
+    }
+
+    #[must_use]
+    pub const fn audience(&self) -> &Audience {
+        &self.audience
+    }
+
+    /// Changes who sees the bar. Viewers are updated on the next tick.
+    pub fn set_audience(&mut self, audience: Audience) {
+        self.audience = audience;
+    }
+
+    /// The players the bar is shown to.
+    pub fn viewers(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.viewers.keys().copied()
+    }
+
+    fn packet(&self, action: BossBarAction<'_>) -> BossBarS2c<'_> {
+        BossBarS2c {
+            id: self.id,
+            action,
+        }
+    }
+
+    fn add_packet(&self) -> BossBarS2c<'_> {
+        self.packet(BossBarAction::Add {
+            title: hyperion_text::Text::new(&self.title),
+            health: self.health,
+            color: self.color,
+            division: self.division,
+            flags: self.flags,
+        })
+    }
+
+    /// Replaces the viewers with `wanted`, returning who has to be shown the bar and who has to
+    /// have it hidden.
+    fn update_viewers(
+        &mut self,
+        wanted: FxHashMap<Entity, ConnectionId>,
+    ) -> (Vec<ConnectionId>, Vec<(Entity, ConnectionId)>) {
+        let added = wanted
+            .iter()
+            .filter(|(player, _)| !self.viewers.contains_key(player))
+            .map(|(_, io)| *io)
+            .collect();
+
+        let removed = self
+            .viewers
+            .iter()
+            .filter(|(player, _)| !wanted.contains_key(player))
+            .map(|(player, io)| (*player, *io))
+            .collect();
+
+        self.viewers = wanted;
+
+        (added, removed)
+    }
+
+    /// Adds the packets for what changed since the last call to `bundle`.
+    fn write_changes(&mut self, bundle: &mut DataBundle<'_, '_>) -> anyhow::Result<()> {
+        let changes = std::mem::take(&mut self.changes);
+
+        if changes.title {
+            bundle.add_packet(&self.packet(BossBarAction::UpdateTitle(
+                hyperion_text::Text::new(&self.title),
+            )))?;
+        }
+
+        if changes.health {
+            bundle.add_packet(&self.packet(BossBarAction::UpdateHealth(self.health)))?;
+        }
+
+        if changes.style {
+            bundle
+                .add_packet(&self.packet(BossBarAction::UpdateStyle(self.color, self.division)))?;
+        }
+
+        if changes.flags {
+            bundle.add_packet(&self.packet(BossBarAction::UpdateFlags(self.flags)))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Component)]
+pub struct BossBarModule;
+
+impl Module for BossBarModule {
+    fn module(world: &World) {
+        world.component::<BossBar>();
+        world.component::<PlayerChunks>();
+
+        world.set(PlayerChunks::default());
+
+        let players = world
+            .query::<(&ConnectionId, &Position)>()
+            .with_enum(PacketState::Play)
+            .build();
+
+        system!("index_boss_bar_viewers", world, &mut PlayerChunks($))
+            .kind::<flecs::pipeline::OnStore>()
+            .each_iter(move |_, _, chunks| {
+                chunks.0.values_mut().for_each(Vec::clear);
+
+                players.each_entity(|player, (io, position)| {
+                    chunks.0.entry(chunk(position)).or_default().push((
+                        player.id(),
+                        *io,
+                        *position,
+                    ));
+                });
+
+                // chunks nobody is in any more
+                chunks.0.retain(|_, players| !players.is_empty());
+            });
+
+        system!(
+            "sync_boss_bars",
+            world,
+            &Compose($),
+            &PlayerChunks($),
+            &mut BossBar,
+            ?&Position,
+        )
+        .kind::<flecs::pipeline::OnStore>()
+        .each_iter(move |it, _, (compose, chunks, bar, position)| {
+            let world = it.world();
+            let system = it.system();
+
+            // only bars for everyone look at every player
+            let wanted: FxHashMap<_, _> = match &bar.audience {
+                Audience::Everyone => chunks
+                    .0
+                    .values()
+                    .flatten()
+                    .map(|(player, io, _)| (*player, *io))
+                    .collect(),
+                Audience::Near(range) => position
+                    .map(|position| {
+                        chunks
+                            .near(position, *range)
+                            .filter(|(player, _, player_position)| {
+                                bar.audience
+                                    .includes(*player, player_position, Some(position))
+                            })
+                            .map(|(player, io, _)| (*player, *io))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                Audience::Players(players) => players
+                    .iter()
+                    .map(|player| world.entity_from_id(*player))
+                    .filter(|player| player.is_alive() && player.has_enum(PacketState::Play))
+                    .filter_map(|player| player.try_get::<&ConnectionId>(|io| (player.id(), *io)))
+                    .collect(),
+            };
+
+            // players who keep seeing the bar get what changed, the others are sent all of it or
+            // nothing
+            let mut bundle = DataBundle::new(compose, system);
+
+            if let Err(e) = bar.write_changes(&mut bundle) {
+                error!("failed to write boss bar changes: {e}");
+                return;
+            }
+
+            for (player, io) in &bar.viewers {
+                if !wanted.contains_key(player) {
+                    continue;
+                }
+
+                if let Err(e) = bundle.unicast(*io) {
+                    error!("failed to send boss bar changes: {e}");
+                }
+            }
+
+            let (added, removed) = bar.update_viewers(wanted);
+
+            for io in added {
+                if let Err(e) = compose.unicast(&bar.add_packet(), io, system) {
+                    error!("failed to show boss bar: {e}");
+                }
+            }
+
+            let remove = bar.packet(BossBarAction::Remove);
+
+            for (player, io) in removed {
+                // players who disconnected are gone already
+                if !world.is_alive(player) {
+                    continue;
+                }
+
+                if let Err(e) = compose.unicast(&remove, io, system) {
+                    error!("failed to hide boss bar: {e}");
+                }
+            }
+        });
+
+        // bars which are removed disappear from the screens of their viewers
+        observer!(world, flecs::OnRemove, &BossBar, &Compose($)).each_iter(
+            |it, _, (bar, compose)| {
+                let world = it.world();
+                let system = it.system();
+
+                let remove = bar.packet(BossBarAction::Remove);
+
+                for (player, io) in &bar.viewers {
+                    if !world.is_alive(*player) {
+                        continue;
+                    }
+
+                    if let Err(e) = compose.unicast(&remove, *io, system) {
+                        error!("failed to hide boss bar: {e}");
+                    }
+                }
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewers_follow_the_audience() {
+        let world = World::new();
+        let alice = world.entity().id();
+        let bob = world.entity().id();
+
+        let bar_position = Position::new(0.0, 64.0, 0.0);
+        let near = Position::new(10.0, 64.0, 0.0);
+        let far = Position::new(100.0, 64.0, 0.0);
+
+        let audience = Audience::Near(16.0);
+        assert!(audience.includes(alice, &near, Some(&bar_position)));
+        assert!(!audience.includes(alice, &far, Some(&bar_position)));
+        assert!(!audience.includes(alice, &near, None));
+
+        let mut bar = BossBar::new("Boss");
+
+        let (added, removed) = bar.update_viewers(FxHashMap::from_iter([
+            (alice, ConnectionId::new(1)),
+            (bob, ConnectionId::new(2)),
+        ]));
+        assert_eq!(added.len(), 2);
+        assert!(removed.is_empty());
+
+        // bob walked away
+        let (added, removed) =
+            bar.update_viewers(FxHashMap::from_iter([(alice, ConnectionId::new(1))]));
+        assert!(added.is_empty());
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].0, bob);
+
+        bar.set_health(0.5);
+        bar.set_health(2.0);
+        assert!((bar.health() - 1.0).abs() < f32::EPSILON);
+
+        bar.set_title("Boss");
+        assert_eq!(bar.changes, Changes {
+            health: true,
+            ..Changes::default()
+        });
+    }
+
+    #[test]
+    fn near_looks_at_chunks_in_range() {
+        let world = World::new();
+        let players: Vec<_> = (0..3).map(|_| world.entity().id()).collect();
+
+        let mut chunks = PlayerChunks::default();
+
+        for (player, x) in players.iter().zip([-20.0, 60.0, 1000.0]) {
+            let position = Position::new(x, 64.0, 0.0);
+            chunks.0.entry(chunk(&position)).or_default().push((
+                *player,
+                ConnectionId::new(0),
+                position,
+            ));
+        }
+
+        let near = |range| -> Vec<_> {
+            chunks
+                .near(&Position::new(0.0, 64.0, 0.0), range)
+                .map(|(player, ..)| *player)
+                .collect()
+        };
+
+        assert_eq!(near(32.0).len(), 1);
+        assert_eq!(near(48.0).len(), 2);
+        assert_eq!(near(100_000.0).len(), 3);
+    }
+}
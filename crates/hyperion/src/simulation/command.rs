@@ -2,17 +2,22 @@ use flecs_ecs::{
     core::{Entity, EntityViewGet, IdOperations, World},
     macros::Component,
 };
+use rustc_hash::FxHashMap;
 use tracing::warn;
-pub use valence_protocol::packets::play::command_tree_s2c::Parser;
+pub use valence_protocol::packets::play::command_tree_s2c::{Parser, Suggestion};
 use valence_protocol::{
     VarInt,
-    packets::play::command_tree_s2c::{Node, NodeData, Suggestion},
+    packets::play::command_tree_s2c::{Node, NodeData},
 };
 
 #[derive(Component)]
 pub struct Command {
     data: NodeData,
     has_permission: fn(world: &World, caller: Entity) -> bool,
+    /// Whether the input may end at this node.
+    executable: bool,
+    /// The node parsing continues at after this one, as it does for aliases.
+    redirect: Option<Entity>,
 }
 
 pub(crate) static ROOT_COMMAND: once_cell::sync::OnceCell<Entity> =
@@ -26,6 +31,8 @@ impl Command {
     pub const ROOT: Self = Self {
         data: NodeData::Root,
         has_permission: |_: _, _: _| true,
+        executable: false,
+        redirect: None,
     };
 
     #[must_use]
@@ -37,6 +44,8 @@ impl Command {
         Self {
             data: NodeData::Literal { name },
             has_permission,
+            executable: true,
+            redirect: None,
         }
     }
 
@@ -50,8 +59,42 @@ impl Command {
                 suggestion: Some(Suggestion::AskServer),
             },
             has_permission: |_: _, _: _| true,
+            executable: true,
+            redirect: None,
         }
     }
+
+    /// Sets whether the command can run when the input ends at this node. Nodes are executable
+    /// unless this says otherwise.
+    #[must_use]
+    pub const fn with_executable(mut self, executable: bool) -> Self {
+        self.executable = executable;
+        self
+    }
+
+    /// Sets how the client completes this argument: [`Suggestion::AskServer`] by default, or
+    /// [`None`] for what the parser suggests by itself, such as coordinates or entity selectors.
+    /// Literals have no suggestions.
+    #[must_use]
+    pub fn with_suggestion(mut self, suggestion: Option<Suggestion>) -> Self {
+        if let NodeData::Argument {
+            suggestion: current,
+            ..
+        } = &mut self.data
+        {
+            *current = suggestion;
+        }
+
+        self
+    }
+
+    /// Continues parsing at `target` after this node, which is how aliases work. Nodes which
+    /// redirect should have no children.
+    #[must_use]
+    pub const fn with_redirect(mut self, target: Entity) -> Self {
+        self.redirect = Some(target);
+        self
+    }
 }
 
 // we want a get command packet
@@ -71,6 +114,12 @@ pub fn get_command_packet(
 
     let mut commands = Vec::new();
 
+    // where each node ended up, so redirects can point to nodes added after them
+    let mut indices = FxHashMap::default();
+    let mut redirects = Vec::new();
+
+    indices.insert(root, 0);
+
     let mut stack = vec![StackElement {
         depth: 0,
         ptr: 0,
@@ -107,11 +156,17 @@ pub fn get_command_packet(
 
                 commands.push(Node {
                     data: command.data.clone(),
-                    executable: true,
+                    executable: command.executable,
                     children: Vec::new(),
                     redirect_node: None,
                 });
 
+                indices.insert(child.id(), ptr);
+
+                if let Some(target) = command.redirect {
+                    redirects.push((ptr, target));
+                }
+
                 let node = &mut commands[parent_ptr];
                 node.children.push(i32::try_from(ptr).unwrap().into());
 
@@ -124,6 +179,15 @@ pub fn get_command_packet(
         });
     }
 
+    for (ptr, target) in redirects {
+        let Some(&index) = indices.get(&target) else {
+            // the player cannot use the target, so they cannot use the redirect either
+            continue;
+        };
+
+        commands[ptr].redirect_node = Some(i32::try_from(index).unwrap().into());
+    }
+
     valence_protocol::packets::play::CommandTreeS2c {
         commands,
         root_index: VarInt(0),
@@ -162,6 +226,8 @@ mod tests {
                     name: "test".to_string(),
                 },
                 has_permission: |_: _, _: _| true,
+                executable: true,
+                redirect: None,
             })
             .child_of_id(root);
 
@@ -190,6 +256,8 @@ mod tests {
                     name: "parent".to_string(),
                 },
                 has_permission: |_: _, _: _| true,
+                executable: true,
+                redirect: None,
             })
             .child_of_id(root);
 
@@ -200,6 +268,8 @@ mod tests {
                     name: "child".to_string(),
                 },
                 has_permission: |_: _, _: _| true,
+                executable: true,
+                redirect: None,
             })
             .child_of_id(parent);
 
@@ -217,6 +287,52 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_redirect() {
+        let world = World::new();
+        world.component::<Command>();
+        let root = world.entity();
+
+        let teleport = world
+            .entity()
+            .set(Command::literal("teleport", |_: _, _: _| true).with_executable(false))
+            .child_of_id(root);
+
+        world
+            .entity()
+            .set(
+                Command::literal("tp", |_: _, _: _| true)
+                    .with_executable(false)
+                    .with_redirect(teleport.id()),
+            )
+            .child_of_id(root);
+
+        let packet = get_command_packet(&world, root.id(), None);
+
+        let index = |name: &str| {
+            packet
+                .commands
+                .iter()
+                .position(|node| {
+                    node.data
+                        == NodeData::Literal {
+                            name: name.to_string(),
+                        }
+                })
+                .unwrap()
+        };
+
+        let teleport = index("teleport");
+        let tp = index("tp");
+
+        assert!(!packet.commands[teleport].executable);
+        assert_eq!(packet.commands[teleport].redirect_node, None);
+        assert_eq!(
+            packet.commands[tp].redirect_node,
+            Some(VarInt(i32::try_from(teleport).unwrap()))
+        );
+    }
+
     #[test]
     fn test_max_depth() {
         let world = World::new();
@@ -0,0 +1,564 @@
+//! Containers shared between players, such as chests.
+//!
+//! ```ignore
+//! use hyperion::simulation::container::ContainerExt;
+//! use hyperion_inventory::container::Container;
+//!
+//! let chest = world
+//!     .entity()
+//!     .set(Container::new(WindowType::Generic9x3, "Chest"));
+//!
+//! player.open_container(chest.id());
+//! ```
+//!
+//! Clicks in a container window are applied on the server like clicks in the player inventory.
+//! Every change to a container is sent to all players looking into it, except to the player whose
+//! client already predicted it.
+//!
+//! Using a crafting table opens a container of its own for the player, which is removed again
+//! when they close it. Whatever is left in its grid goes back into their inventory.
+//!
+//! Window properties, such as the progress of a furnace, are sent to every viewer when they
+//! change. Furnaces themselves are in [`super::furnace`].
+
+use std::borrow::Cow;
+
+use flecs_ecs::prelude::*;
+use hyperion_crafting::CraftingRegistry;
+use hyperion_inventory::{
+    CarriedItem, PlayerInventory,
+    action::{CRAFTING_RESULT_SLOT, ContainerAndCursor},
+    container::{Container, OpenContainer},
+    furnace::{self, Furnace},
+};
+use tracing::{error, warn};
+use valence_protocol::{
+    ItemStack, VarInt,
+    packets::play::{self, open_screen_s2c::WindowType},
+};
+use valence_text::IntoText;
+
+use crate::{
+    net::{Compose, ConnectionId, DataBundle},
+    simulation::{
+        Pitch, Position, Xp, Yaw, event,
+        handlers::{same_stack, thrown_item},
+    },
+    storage::{EventQueue, Events},
+};
+
+/// Event which closes the container window a player has open.
+#[derive(Component)]
+pub struct CloseContainer;
+
+/// A crafting table container which belongs to the one player who opened it.
+#[derive(Component)]
+struct CraftingTable;
+
+pub trait ContainerExt {
+    /// Opens the window of `container`, an entity with a [`Container`], for this player. Any
+    /// container the player was looking into before is closed.
+    fn open_container(self, container: Entity);
+
+    /// Closes the container window the player has open, if any.
+    fn close_container(self);
+}
+
+impl ContainerExt for EntityView<'_> {
+    fn open_container(self, container: Entity) {
+        let world = self.world();
+
+        if let Some(previous) = self.try_get::<&OpenContainer>(|open| open.container) {
+            stop_viewing(&world, previous, self.id());
+        }
+
+        let window_id = self.get::<&mut CarriedItem>(CarriedItem::next_window_id);
+
+        self.set(OpenContainer {
+            window_id,
+            container,
+        });
+    }
+
+    fn close_container(self) {
+        self.enqueue(CloseContainer);
+    }
+}
+
+fn stop_viewing(world: &WorldRef<'_>, container: Entity, player: Entity) {
+    let container = world.entity_from_id(container);
+
+    if !container.is_alive() {
+        return;
+    }
+
+    container.try_get::<&mut Container>(|container| container.remove_viewer(player));
+
+    if container.has::<CraftingTable>() {
+        let items = container
+            .try_get::<&mut Container>(Container::take_all)
+            .unwrap_or_default();
+
+        give_back(world, world.entity_from_id(player), items);
+        container.destruct();
+    }
+}
+
+/// Puts items into the inventory of a player and throws whatever does not fit.
+fn give_back(world: &WorldRef<'_>, player: EntityView<'_>, items: Vec<ItemStack>) {
+    if items.is_empty() {
+        return;
+    }
+
+    world.get::<&Events>(|events| {
+        player.try_get::<(&mut PlayerInventory, &Position, &Yaw, &Pitch)>(
+            |(inventory, position, yaw, pitch)| {
+                for item in items {
+                    if let Some(remaining) = inventory.try_add_item(item).remaining {
+                        let event = thrown_item(player.id(), **position, **yaw, **pitch, remaining);
+                        events.push(event, world);
+                    }
+                }
+            },
+        );
+    });
+}
+
+/// Sends the whole window of `container` with a new state id.
+fn send_window(
+    compose: &Compose,
+    io: ConnectionId,
+    system: EntityView<'_>,
+    window_id: u8,
+    container: &Container,
+    inventory: &PlayerInventory,
+    carried: &mut CarriedItem,
+) -> anyhow::Result<()> {
+    let pkt = play::InventoryS2c {
+        window_id,
+        state_id: VarInt(carried.next_state_id()),
+        slots: Cow::Owned(container.window_slots(inventory).cloned().collect()),
+        carried_item: Cow::Borrowed(&carried.stack),
+    };
+
+    compose.unicast(&pkt, io, system)?;
+
+    Ok(())
+}
+
+/// Sends the whole player inventory with a new state id, for a click in a window which was closed
+/// before the click could be applied. The client already showed the click, so it has to be told
+/// what the inventory really holds.
+fn send_inventory(
+    compose: &Compose,
+    io: ConnectionId,
+    system: EntityView<'_>,
+    inventory: &mut PlayerInventory,
+    carried: &mut CarriedItem,
+    crafting_registry: &CraftingRegistry,
+) -> anyhow::Result<()> {
+    let mut slots = inventory.slots().to_vec();
+    slots[usize::from(CRAFTING_RESULT_SLOT)] = inventory.crafting_result(crafting_registry);
+
+    let pkt = play::InventoryS2c {
+        window_id: 0,
+        state_id: VarInt(carried.next_state_id()),
+        slots: Cow::Owned(slots),
+        carried_item: Cow::Borrowed(&carried.stack),
+    };
+
+    compose.unicast(&pkt, io, system)?;
+
+    inventory.updated_since_last_tick.clear();
+
+    Ok(())
+}
+
+/// How many items are in the output of a furnace, or 0 if `container` is not a furnace.
+fn furnace_output(container: &Container) -> i8 {
+    if furnace::cooking_kind(container.kind()).is_none() {
+        return 0;
+    }
+
+    container
+        .get(furnace::OUTPUT_SLOT)
+        .map_or(0, |output| output.count)
+}
+
+/// Gives the experience collected by `furnace` to the player who took items out of it.
+fn take_furnace_experience(world: &WorldRef<'_>, player: EntityView<'_>, furnace: Entity) {
+    let points = world
+        .entity_from_id(furnace)
+        .try_get::<&mut Furnace>(Furnace::take_experience)
+        .unwrap_or_default();
+
+    if points == 0 {
+        return;
+    }
+
+    player.try_get::<&mut Xp>(|xp| xp.amount = xp.amount.saturating_add(points));
+}
+
+/// Whether the slots and carried item the client claims after a click match the server.
+fn predicted_correctly(
+    click: &event::ContainerClick,
+    container: &Container,
+    inventory: &PlayerInventory,
+    carried: &CarriedItem,
+) -> bool {
+    same_stack(&click.carried_item, &carried.stack)
+        && click.slot_changes.iter().all(|(idx, stack)| {
+            usize::try_from(*idx)
+                .ok()
+                .and_then(|idx| container.window_slots(inventory).nth(idx))
+                .is_some_and(|existing| same_stack(existing, stack))
+        })
+}
+
+#[derive(Component)]
+pub struct ContainerModule;
+
+impl Module for ContainerModule {
+    fn module(world: &World) {
+        world.component::<CloseContainer>();
+        world.component::<CraftingTable>();
+
+        observer!(
+            world,
+            flecs::OnSet,
+            &OpenContainer,
+            &Compose($),
+            &ConnectionId,
+            &PlayerInventory,
+            &mut CarriedItem,
+        )
+        .each_iter(|it, row, (open, compose, io, inventory, carried)| {
+            let world = it.world();
+            let system = it.system();
+            let player = it.entity(row);
+
+            let container = world.entity_from_id(open.container);
+
+            let opened = container.try_get::<&mut Container>(|container| {
+                container.add_viewer(player.id());
+
+                let mut bundle = DataBundle::new(compose, system);
+
+                bundle.add_packet(&play::OpenScreenS2c {
+                    window_id: VarInt(i32::from(open.window_id)),
+                    window_type: container.kind(),
+                    window_title: container.title.as_str().into_cow_text(),
+                })?;
+
+                bundle.add_packet(&play::InventoryS2c {
+                    window_id: open.window_id,
+                    state_id: VarInt(carried.next_state_id()),
+                    slots: Cow::Owned(container.window_slots(inventory).cloned().collect()),
+                    carried_item: Cow::Borrowed(&carried.stack),
+                })?;
+
+                for (property, &value) in (0..).zip(container.properties()) {
+                    bundle.add_packet(&play::ScreenHandlerPropertyUpdateS2c {
+                        window_id: open.window_id,
+                        property,
+                        value,
+                    })?;
+                }
+
+                bundle.unicast(*io)
+            });
+
+            match opened {
+                Some(Ok(())) => {}
+                Some(Err(e)) => error!("failed to open container: {e}"),
+                None => {
+                    error!(
+                        "tried to open {:?}, which is not a container",
+                        open.container
+                    );
+                    player.remove::<OpenContainer>();
+                }
+            }
+        });
+
+        observer!(world, flecs::OnRemove, &OpenContainer).each_iter(|it, row, open| {
+            let world = it.world();
+            stop_viewing(&world, open.container, it.entity(row).id());
+        });
+
+        // players looking into a container which is removed are thrown out of its window
+        observer!(world, flecs::OnRemove, &Container).each_iter(|it, _, container| {
+            let world = it.world();
+
+            for viewer in container.viewers() {
+                world.entity_from_id(viewer).close_container();
+            }
+        });
+
+        observer!(
+            world,
+            CloseContainer,
+            &Compose($),
+            &Events($),
+            &ConnectionId,
+            &OpenContainer,
+            &mut PlayerInventory,
+            &mut CarriedItem,
+            &Position,
+            &Yaw,
+            &Pitch,
+        )
+        .each_iter(
+            |it, row, (compose, events, io, open, inventory, carried, position, yaw, pitch)| {
+                let world = it.world();
+                let system = it.system();
+                let player = it.entity(row);
+
+                let pkt = play::CloseScreenS2c {
+                    window_id: open.window_id,
+                };
+
+                if let Err(e) = compose.unicast(&pkt, *io, system) {
+                    error!("failed to close container: {e}");
+                }
+
+                // the carried item goes back into the inventory, or is thrown if it is full
+                let carried = std::mem::take(&mut carried.stack);
+
+                if !carried.is_empty() {
+                    if let Some(remaining) = inventory.try_add_item(carried).remaining {
+                        let event = thrown_item(player.id(), **position, **yaw, **pitch, remaining);
+                        events.push(event, &world);
+                    }
+                }
+
+                player.remove::<OpenContainer>();
+            },
+        );
+
+        system!(
+            "open_crafting_tables",
+            world,
+            &mut EventQueue<event::OpenCraftingTable>($),
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, _, event_queue| {
+            let world = it.world();
+
+            for event in event_queue.drain() {
+                let player = world.entity_from_id(event.by);
+
+                if !player.is_alive() {
+                    continue;
+                }
+
+                let table = world
+                    .entity()
+                    .add::<CraftingTable>()
+                    .set(Container::new(WindowType::Crafting, "Crafting"));
+
+                player.open_container(table.id());
+            }
+        });
+
+        system!(
+            "container_clicks",
+            world,
+            &mut EventQueue<event::ContainerClick>($),
+            &Compose($),
+            &Events($),
+            &CraftingRegistry($),
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, _, (event_queue, compose, events, crafting_registry)| {
+            let world = it.world();
+            let system = it.system();
+
+            for click in event_queue.drain() {
+                let player = world.entity_from_id(click.by);
+
+                if !player.is_alive() {
+                    continue;
+                }
+
+                // the furnace the player took smelted items out of
+                let mut smelted_from = None;
+
+                player.try_get::<(
+                    &mut PlayerInventory,
+                    &mut CarriedItem,
+                    Option<&OpenContainer>,
+                    &ConnectionId,
+                    &Position,
+                    &Yaw,
+                    &Pitch,
+                )>(|(inventory, carried, open, io, position, yaw, pitch)| {
+                    // the window was closed or replaced since the click, such as by a close in
+                    // the same tick
+                    let Some(open) = open.filter(|open| open.window_id == click.window_id) else {
+                        if let Err(e) = send_inventory(
+                            compose,
+                            *io,
+                            system,
+                            inventory,
+                            carried,
+                            crafting_registry,
+                        ) {
+                            error!("failed to resync inventory: {e}");
+                        }
+
+                        return;
+                    };
+
+                    world
+                        .entity_from_id(open.container)
+                        .try_get::<&mut Container>(|container| {
+                            let stale = click.state_id != carried.state_id;
+                            let output = furnace_output(container);
+
+                            let result = click.action.map(|action| {
+                                ContainerAndCursor {
+                                    container,
+                                    inventory,
+                                    carried,
+                                    crafting_registry,
+                                }
+                                .apply(action)
+                            });
+
+                            let in_sync = match result {
+                                Some(Ok(dropped)) => {
+                                    if let Some(item) = dropped {
+                                        let event = thrown_item(
+                                            player.id(),
+                                            **position,
+                                            **yaw,
+                                            **pitch,
+                                            item,
+                                        );
+                                        events.push(event, &world);
+                                    }
+
+                                    !stale
+                                        && predicted_correctly(
+                                            &click, container, inventory, carried,
+                                        )
+                                }
+                                Some(Err(e)) => {
+                                    warn!("invalid container click: {e}");
+                                    false
+                                }
+                                None => false,
+                            };
+
+                            if furnace_output(container) < output {
+                                smelted_from = Some(open.container);
+                            }
+
+                            if !in_sync {
+                                if let Err(e) = send_window(
+                                    compose,
+                                    *io,
+                                    system,
+                                    open.window_id,
+                                    container,
+                                    inventory,
+                                    carried,
+                                ) {
+                                    error!("failed to resync container: {e}");
+                                }
+
+                                return;
+                            }
+
+                            // the client already shows these slots
+                            for (idx, _) in &click.slot_changes {
+                                let Ok(idx) = u16::try_from(*idx) else {
+                                    continue;
+                                };
+
+                                match container.player_slot(idx) {
+                                    Some(slot) => {
+                                        inventory.updated_since_last_tick.remove(u32::from(slot));
+                                    }
+                                    None => container.mark_predicted(player.id(), idx),
+                                }
+                            }
+                        });
+                });
+
+                if let Some(furnace) = smelted_from {
+                    take_furnace_experience(&world, player, furnace);
+                }
+            }
+        });
+
+        system!("container_sync", world, &Compose($), &mut Container)
+            .kind::<flecs::pipeline::OnStore>()
+            .each_iter(|it, _, (compose, container)| {
+                if !container.is_updated() {
+                    return;
+                }
+
+                let world = it.world();
+                let system = it.system();
+
+                let properties: Vec<_> = container.property_updates().collect();
+
+                for viewer in container.viewers() {
+                    let updates = container.updates_for(viewer);
+
+                    if updates.is_empty() && properties.is_empty() {
+                        continue;
+                    }
+
+                    world
+                        .entity_from_id(viewer)
+                        .try_get::<(&ConnectionId, &OpenContainer, &mut CarriedItem)>(
+                            |(io, open, carried)| {
+                                let mut run = || {
+                                    for (property, value) in properties.iter().copied() {
+                                        let pkt = play::ScreenHandlerPropertyUpdateS2c {
+                                            window_id: open.window_id,
+                                            property,
+                                            value,
+                                        };
+
+                                        compose.unicast(&pkt, *io, system)?;
+                                    }
+
+                                    if updates.is_empty() {
+                                        return anyhow::Ok(());
+                                    }
+
+                                    // every change made by the server to a slot is a new state of
+                                    // the window; properties are not part of it
+                                    let state_id = VarInt(carried.next_state_id());
+
+                                    for slot in &updates {
+                                        let slot = u16::try_from(slot)?;
+
+                                        let pkt = play::ScreenHandlerSlotUpdateS2c {
+                                            window_id: i8::try_from(open.window_id)?,
+                                            state_id,
+                                            slot_idx: i16::try_from(slot)?,
+                                            slot_data: Cow::Borrowed(container.get(slot)?),
+                                        };
+
+                                        compose.unicast(&pkt, *io, system)?;
+                                    }
+
+                                    anyhow::Ok(())
+                                };
+
+                                if let Err(e) = run() {
+                                    error!("failed to sync container: {e}");
+                                }
+                            },
+                        );
+                }
+
+                container.clear_updates();
+            });
+    }
+}
@@ -0,0 +1,373 @@
+//! Entities dying and players respawning.
+//!
+//! An entity whose [`Health`] reaches zero is marked [`Dead`], plays the death animation for
+//! everyone nearby and emits a [`Death`] event. Observers of [`Death`] can decide what the entity
+//! drops; by default players drop their inventory unless they have [`KeepInventory`].
+//!
+//! Dead players see the respawn screen. When they click respawn they are sent back to their
+//! [`RespawnPoint`], or to the spawn in the [`Config`] if they do not have one. Other dead entities
+//! are despawned once the death animation has finished.
+//!
+//! Damage should be dealt with [`DamageExt`], which leaves dead and invulnerable entities alone.
+
+use std::borrow::Cow;
+
+use flecs_ecs::prelude::*;
+use glam::{IVec3, Vec3};
+use hyperion_inventory::PlayerInventory;
+use hyperion_utils::EntityExt;
+use tracing::error;
+use valence_protocol::{
+    ByteAngle, VarInt,
+    game_mode::OptGameMode,
+    ident,
+    packets::play::{self, player_position_look_s2c::PlayerPositionLookFlags},
+};
+use valence_text::IntoText;
+
+use crate::{
+    Prev,
+    config::Config,
+    egress::metadata::show_all,
+    net::{Compose, ConnectionId, DataBundle},
+    simulation::{
+        Pitch, Position, Uuid, Yaw,
+        effect::ActiveEffects,
+        equipment::Equipment,
+        event,
+        game_mode::GameMode,
+        metadata::{entity::Pose, living_entity::Health},
+        spawn::DespawnExt,
+    },
+    storage::{EventQueue, Events},
+};
+
+/// Ticks the death animation takes before a dead entity is removed.
+const DEATH_ANIMATION_TICKS: i64 = 20;
+
+/// The entity status which plays the death animation.
+const DEATH_STATUS: u8 = 3;
+
+/// Marks an entity whose [`Health`] reached zero.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Dead {
+    /// The tick the entity died on.
+    pub since: i64,
+}
+
+/// Event emitted on an entity when it dies.
+#[derive(Component)]
+pub struct Death;
+
+/// The message shown on a player's death screen. It is removed once the player has died, so set it
+/// right before dealing the final damage, e.g. to name the attacker.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct DeathMessage {
+    pub message: String,
+}
+
+impl DeathMessage {
+    #[must_use]
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+/// Where a player respawns. Players without one respawn at the spawn in the [`Config`].
+#[derive(Component, Copy, Clone, Debug, PartialEq)]
+pub struct RespawnPoint {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// Players with this tag keep their inventory when they die.
+#[derive(Component)]
+pub struct KeepInventory;
+
+/// Dealing damage in a way that respects [`Dead`] and the invulnerability of a [`GameMode`].
+pub trait DamageExt {
+    /// Whether the entity can be hurt, i.e. it is alive and its game mode does not protect it.
+    fn is_vulnerable(self) -> bool;
+
+    /// Takes `amount` from the [`Health`] of the entity if it is vulnerable. Returns whether it
+    /// was damaged.
+    fn damage(self, amount: f32) -> bool;
+}
+
+impl DamageExt for EntityView<'_> {
+    fn is_vulnerable(self) -> bool {
+        !self.has::<Dead>()
+            && !self
+                .try_get::<&GameMode>(|mode| mode.is_invulnerable())
+                .unwrap_or(false)
+    }
+
+    fn damage(self, amount: f32) -> bool {
+        self.is_vulnerable()
+            && self
+                .try_get::<&mut Health>(|health| health.damage(amount))
+                .is_some()
+    }
+}
+
+fn config_spawn(config: &Config) -> RespawnPoint {
+    let spawn = &config.spawn;
+    let block = IVec3::new(spawn.x, spawn.y, spawn.z);
+
+    RespawnPoint {
+        position: block.as_vec3() + Vec3::new(0.5, 0.0, 0.5),
+        yaw: 0.0,
+        pitch: 0.0,
+    }
+}
+
+#[derive(Component)]
+pub struct DeathModule;
+
+impl Module for DeathModule {
+    fn module(world: &World) {
+        world.component::<Dead>();
+        world.component::<Death>();
+        world.component::<DeathMessage>();
+        world.component::<RespawnPoint>();
+        world.component::<KeepInventory>();
+
+        system!(
+            "detect_deaths",
+            world,
+            &Compose($),
+            &Health,
+            &Position,
+            &mut Pose,
+            ?&ConnectionId,
+            ?&DeathMessage,
+        )
+        .without::<Dead>()
+        .kind::<flecs::pipeline::PostUpdate>()
+        .each_iter(|it, row, (compose, health, position, pose, io, message)| {
+            if !health.is_dead() {
+                return;
+            }
+
+            let system = it.system();
+            let entity = it.entity(row);
+
+            let status = play::EntityStatusS2c {
+                entity_id: entity.minecraft_id(),
+                entity_status: DEATH_STATUS,
+            };
+
+            if let Err(e) = compose
+                .broadcast_local(&status, position.to_chunk(), system)
+                .send()
+            {
+                error!("failed to send death status: {e}");
+            }
+
+            if let Some(io) = io {
+                let message = message.map_or("You died!", |message| message.message.as_str());
+
+                // the client only shows the respawn screen once it receives this
+                let packet = play::DeathMessageS2c {
+                    player_id: VarInt(entity.minecraft_id()),
+                    message: message.into_cow_text(),
+                };
+
+                if let Err(e) = compose.unicast(&packet, *io, system) {
+                    error!("failed to send death message: {e}");
+                }
+            }
+
+            *pose = Pose::Dying;
+
+            entity
+                .set(Dead {
+                    since: compose.global().tick,
+                })
+                .remove::<DeathMessage>()
+                .enqueue(Death);
+        });
+
+        observer!(
+            world,
+            Death,
+            &Events($),
+            &Position,
+            &mut PlayerInventory,
+        )
+        .without::<KeepInventory>()
+        .each_iter(|it, _, (events, position, inventory)| {
+            let world = it.world();
+
+            for (_, item) in inventory.items() {
+                let velocity = Vec3::new(
+                    fastrand::f32() - 0.5,
+                    fastrand::f32(),
+                    fastrand::f32() - 0.5,
+                ) * 0.2;
+
+                let event = event::ItemDropEvent {
+                    item: item.clone(),
+                    location: **position + Vec3::Y,
+                    velocity,
+                    thrown_by: None,
+                };
+
+                events.push(event, &world);
+            }
+
+            inventory.clear();
+        });
+
+        system!("despawn_dead_entities", world, &Compose($), &Dead)
+            .without::<ConnectionId>()
+            .kind::<flecs::pipeline::PostUpdate>()
+            .each_iter(|it, row, (compose, dead)| {
+                if compose.global().tick - dead.since >= DEATH_ANIMATION_TICKS {
+                    it.entity(row).despawn();
+                }
+            });
+
+        system!(
+            "respawn_players",
+            world,
+            &mut EventQueue<event::RequestRespawn>($),
+            &Compose($),
+            &Config($),
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, _, (event_queue, compose, config)| {
+            let world = it.world();
+            let system = it.system();
+
+            for event in event_queue.drain() {
+                let entity = world.entity_from_id(event.from);
+
+                if !entity.has::<Dead>() {
+                    continue;
+                }
+
+                let respawn_point = entity
+                    .try_get::<&RespawnPoint>(|point| *point)
+                    .unwrap_or_else(|| config_spawn(config));
+
+                entity.get::<(
+                    &mut Health,
+                    &mut Pose,
+                    &mut Position,
+                    &mut Yaw,
+                    &mut Pitch,
+                    &GameMode,
+                    &ConnectionId,
+                    &Uuid,
+                    &Equipment,
+                    &mut ActiveEffects,
+                )>(
+                    |(
+                        health,
+                        pose,
+                        position,
+                        yaw,
+                        pitch,
+                        game_mode,
+                        io,
+                        uuid,
+                        equipment,
+                        effects,
+                    )| {
+                        *health = Health::default();
+                        // like in vanilla, effects do not survive death
+                        effects.clear();
+                        *pose = Pose::Standing;
+                        **position = respawn_point.position;
+                        **yaw = respawn_point.yaw;
+                        **pitch = respawn_point.pitch;
+
+                        let minecraft_id = entity.minecraft_id();
+                        let mut bundle = DataBundle::new(compose, system);
+
+                        let mut run = || {
+                            bundle.add_packet(&play::PlayerRespawnS2c {
+                                dimension_type_name: ident!("minecraft:overworld").into(),
+                                dimension_name: ident!("overworld").into(),
+                                hashed_seed: 0,
+                                game_mode: (*game_mode).into(),
+                                previous_game_mode: OptGameMode(Some((*game_mode).into())),
+                                is_debug: false,
+                                is_flat: false,
+                                copy_metadata: false,
+                                last_death_location: None,
+                                portal_cooldown: VarInt::default(),
+                            })?;
+
+                            bundle.add_packet(&game_mode.abilities_packet())?;
+
+                            bundle.add_packet(&play::PlayerPositionLookS2c {
+                                position: position.as_dvec3(),
+                                yaw: **yaw,
+                                pitch: **pitch,
+                                flags: PlayerPositionLookFlags::default(),
+                                teleport_id: VarInt(fastrand::i32(..)),
+                            })?;
+
+                            bundle.add_packet(&play::HealthUpdateS2c {
+                                health: **health,
+                                food: VarInt(20),
+                                food_saturation: 5.0,
+                            })?;
+
+                            bundle.add_packet(show_all(minecraft_id).borrow_packet())?;
+
+                            bundle.unicast(*io)?;
+
+                            // the old entity finished its death animation on other clients, so
+                            // spawn it again
+                            let entity_ids = [VarInt(minecraft_id)];
+
+                            compose
+                                .broadcast(
+                                    &play::EntitiesDestroyS2c {
+                                        entity_ids: Cow::Borrowed(&entity_ids),
+                                    },
+                                    system,
+                                )
+                                .exclude(*io)
+                                .send()?;
+
+                            compose
+                                .broadcast(
+                                    &play::PlayerSpawnS2c {
+                                        entity_id: VarInt(minecraft_id),
+                                        player_uuid: uuid.0,
+                                        position: position.as_dvec3(),
+                                        yaw: ByteAngle::from_degrees(**yaw),
+                                        pitch: ByteAngle::from_degrees(**pitch),
+                                    },
+                                    system,
+                                )
+                                .exclude(*io)
+                                .send()?;
+
+                            if let Some(packet) = equipment.snapshot(VarInt(minecraft_id)) {
+                                compose.broadcast(&packet, system).exclude(*io).send()?;
+                            }
+
+                            anyhow::Ok(())
+                        };
+
+                        if let Err(e) = run() {
+                            error!("failed to respawn player: {e}");
+                        }
+                    },
+                );
+
+                entity
+                    .set_pair::<Prev, Position>(Position::from(respawn_point.position))
+                    .remove::<Dead>();
+            }
+        });
+    }
+}
@@ -0,0 +1,451 @@
+//! Status effects (potion effects).
+//!
+//! Effects live in the [`ActiveEffects`] component, which every [`Player`] has. Every tick their durations count down and
+//! expired effects are removed. Additions and removals are sent to nearby clients with
+//! [`play::EntityStatusEffectS2c`] and [`play::RemoveEntityStatusEffectS2c`].
+//!
+//! The built-in effects hook into the rest of the simulation:
+//!
+//! - [`EffectKind::Speed`] and [`EffectKind::Slowness`] scale [`ActiveEffects::speed_multiplier`],
+//!   which is used for movement limits.
+//! - [`EffectKind::Regeneration`] and [`EffectKind::Poison`] change [`Health`] over time.
+//! - [`EffectKind::Strength`] and [`EffectKind::Resistance`] are exposed as
+//!   [`ActiveEffects::attack_damage_bonus`] and [`ActiveEffects::damage_multiplier`] for whichever
+//!   module applies attack damage.
+//! - [`EffectKind::Invisibility`] and [`EffectKind::Glowing`] set the matching [`EntityFlags`].
+
+use flecs_ecs::prelude::*;
+use hyperion_utils::EntityExt;
+use tracing::error;
+use valence_protocol::{
+    VarInt,
+    packets::play::{self, entity_status_effect_s2c},
+};
+
+use crate::{
+    net::{Compose, DataBundle},
+    simulation::{
+        Player, Position,
+        game_mode::GameMode,
+        metadata::{entity::EntityFlags, living_entity::Health},
+    },
+};
+
+/// The status effects with built-in behaviour. The discriminant is the protocol id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum EffectKind {
+    Speed = 1,
+    Slowness = 2,
+    Strength = 5,
+    Regeneration = 10,
+    Resistance = 11,
+    Invisibility = 14,
+    Poison = 19,
+    Glowing = 24,
+}
+
+impl EffectKind {
+    #[must_use]
+    pub const fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// A duration which never runs out.
+pub const INFINITE_DURATION: i32 = -1;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    /// The level of the effect minus one, i.e. speed II has an amplifier of 1.
+    pub amplifier: u8,
+    /// The remaining duration in ticks, or [`INFINITE_DURATION`].
+    pub duration: i32,
+    /// Ambient effects (e.g. from beacons) show fewer, translucent particles.
+    pub ambient: bool,
+    pub particles: bool,
+    pub icon: bool,
+}
+
+impl StatusEffect {
+    #[must_use]
+    pub const fn new(kind: EffectKind, amplifier: u8, duration: i32) -> Self {
+        Self {
+            kind,
+            amplifier,
+            duration,
+            ambient: false,
+            particles: true,
+            icon: true,
+        }
+    }
+
+    #[must_use]
+    pub const fn infinite(kind: EffectKind, amplifier: u8) -> Self {
+        Self::new(kind, amplifier, INFINITE_DURATION)
+    }
+
+    #[must_use]
+    pub const fn with_ambient(mut self, ambient: bool) -> Self {
+        self.ambient = ambient;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_particles(mut self, particles: bool) -> Self {
+        self.particles = particles;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_icon(mut self, icon: bool) -> Self {
+        self.icon = icon;
+        self
+    }
+
+    #[must_use]
+    pub const fn is_infinite(&self) -> bool {
+        self.duration == INFINITE_DURATION
+    }
+
+    /// Whether this effect should replace `other` of the same kind. Like vanilla, a higher
+    /// amplifier always wins and an equal amplifier wins if it lasts longer.
+    const fn overrides(&self, other: &Self) -> bool {
+        if self.amplifier != other.amplifier {
+            return self.amplifier > other.amplifier;
+        }
+
+        if other.is_infinite() {
+            return false;
+        }
+
+        self.is_infinite() || self.duration > other.duration
+    }
+
+    fn packet(&self, entity_id: VarInt) -> play::EntityStatusEffectS2c {
+        play::EntityStatusEffectS2c {
+            entity_id,
+            effect_id: VarInt(self.kind.id()),
+            amplifier: self.amplifier,
+            duration: VarInt(self.duration),
+            flags: entity_status_effect_s2c::Flags::new()
+                .with_is_ambient(self.ambient)
+                .with_show_particles(self.particles)
+                .with_show_icon(self.icon),
+            factor_codec: None,
+        }
+    }
+}
+
+/// The status effects currently applied to an entity.
+#[derive(Component, Debug, Default)]
+pub struct ActiveEffects {
+    effects: Vec<StatusEffect>,
+    /// Effects which were added or changed since the last sync.
+    added: Vec<EffectKind>,
+    /// Effects which were removed since the last sync.
+    removed: Vec<EffectKind>,
+    /// The [`EntityFlags`] which were set because of an effect.
+    applied_flags: EntityFlags,
+}
+
+impl ActiveEffects {
+    /// Applies an effect. If an effect of the same kind is already active, it is only replaced if
+    /// the new one is stronger or lasts longer. Returns whether the effect was applied.
+    pub fn add(&mut self, effect: StatusEffect) -> bool {
+        match self.effects.iter_mut().find(|e| e.kind == effect.kind) {
+            Some(existing) if !effect.overrides(existing) => return false,
+            Some(existing) => *existing = effect,
+            None => self.effects.push(effect),
+        }
+
+        self.removed.retain(|&kind| kind != effect.kind);
+        if !self.added.contains(&effect.kind) {
+            self.added.push(effect.kind);
+        }
+
+        true
+    }
+
+    pub fn remove(&mut self, kind: EffectKind) -> Option<StatusEffect> {
+        let idx = self.effects.iter().position(|e| e.kind == kind)?;
+        let effect = self.effects.swap_remove(idx);
+
+        self.added.retain(|&added| added != kind);
+        self.removed.push(kind);
+
+        Some(effect)
+    }
+
+    pub fn clear(&mut self) {
+        let kinds: Vec<_> = self.effects.iter().map(|e| e.kind).collect();
+
+        for kind in kinds {
+            self.remove(kind);
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, kind: EffectKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|e| e.kind == kind)
+    }
+
+    #[must_use]
+    pub fn has(&self, kind: EffectKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    /// The level of the effect starting from 1, or 0 if it is not active.
+    #[must_use]
+    pub fn level(&self, kind: EffectKind) -> u16 {
+        self.get(kind).map_or(0, |e| u16::from(e.amplifier) + 1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    /// The packets which show all active effects, for a client which starts tracking the entity.
+    pub fn packets(
+        &self,
+        entity_id: VarInt,
+    ) -> impl Iterator<Item = play::EntityStatusEffectS2c> + '_ {
+        self.effects
+            .iter()
+            .map(move |effect| effect.packet(entity_id))
+    }
+
+    /// How much faster (or slower) than normal the entity moves. Each level of speed adds 20% and
+    /// each level of slowness removes 15%.
+    #[must_use]
+    pub fn speed_multiplier(&self) -> f32 {
+        let speed = f32::from(self.level(EffectKind::Speed));
+        let slowness = f32::from(self.level(EffectKind::Slowness));
+
+        (speed.mul_add(0.2, 1.0) * slowness.mul_add(-0.15, 1.0)).max(0.0)
+    }
+
+    /// Extra damage dealt by attacks. Each level of strength adds 3 damage.
+    #[must_use]
+    pub fn attack_damage_bonus(&self) -> f32 {
+        f32::from(self.level(EffectKind::Strength)) * 3.0
+    }
+
+    /// The fraction of incoming damage which is taken. Each level of resistance blocks 20%.
+    #[must_use]
+    pub fn damage_multiplier(&self) -> f32 {
+        f32::from(self.level(EffectKind::Resistance))
+            .mul_add(-0.2, 1.0)
+            .max(0.0)
+    }
+
+    /// Sets the [`EntityFlags`] of the active effects and clears the ones set for effects which
+    /// are gone, however they were removed. Flags other modules (e.g. vanish) set are left alone.
+    fn apply_flags(&mut self, flags: &mut EntityFlags) {
+        let mut wanted = EntityFlags::default();
+
+        for (kind, flag) in [
+            (EffectKind::Invisibility, EntityFlags::INVISIBLE),
+            (EffectKind::Glowing, EntityFlags::GLOWING),
+        ] {
+            if self.has(kind) {
+                wanted |= flag;
+            }
+        }
+
+        let newly_set = wanted & !*flags;
+
+        *flags &= !(self.applied_flags & !wanted);
+        *flags |= wanted;
+
+        self.applied_flags = (self.applied_flags & wanted) | newly_set;
+    }
+
+    /// Counts down all durations and removes expired effects.
+    fn tick(&mut self) {
+        let mut expired = Vec::new();
+
+        for effect in &mut self.effects {
+            if effect.is_infinite() {
+                continue;
+            }
+
+            effect.duration -= 1;
+
+            if effect.duration <= 0 {
+                expired.push(effect.kind);
+            }
+        }
+
+        for kind in expired {
+            self.remove(kind);
+        }
+    }
+}
+
+/// Returns true every `base >> amplifier` ticks, which is how vanilla spaces out regeneration and
+/// poison ticks.
+fn pulses(tick: i64, base: i64, effect: &StatusEffect) -> bool {
+    let interval = (base >> effect.amplifier.min(5)).max(1);
+    tick % interval == 0
+}
+
+#[derive(Component)]
+pub struct StatusEffectModule;
+
+impl Module for StatusEffectModule {
+    fn module(world: &World) {
+        world.component::<ActiveEffects>();
+
+        world
+            .component::<Player>()
+            .add_trait::<(flecs::With, ActiveEffects)>();
+
+        system!(
+            "tick_status_effects",
+            world,
+            &Compose($),
+            &mut ActiveEffects,
+            ?&mut Health,
+            ?&mut EntityFlags,
+            ?&GameMode,
+        )
+        .multi_threaded()
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|_, _, (compose, effects, health, flags, mode)| {
+            let tick = compose.global().tick;
+
+            // the health bar of a player follows the health metadata, so no health update with
+            // made up food values is needed
+            if let Some(health) = health {
+                if let Some(effect) = effects.get(EffectKind::Regeneration) {
+                    if pulses(tick, 50, effect) {
+                        health.heal(1.0);
+                    }
+                }
+
+                let vulnerable = mode.is_none_or(|mode| !mode.is_invulnerable());
+
+                // poison never kills
+                if let Some(effect) = effects.get(EffectKind::Poison) {
+                    if vulnerable && pulses(tick, 25, effect) && **health > 1.0 {
+                        health.damage(1.0);
+                    }
+                }
+            }
+
+            effects.tick();
+
+            if let Some(flags) = flags {
+                effects.apply_flags(flags);
+            }
+        });
+
+        system!(
+            "status_effect_sync",
+            world,
+            &Compose($),
+            &Position,
+            &mut ActiveEffects,
+        )
+        .multi_threaded()
+        .kind::<flecs::pipeline::OnStore>()
+        .each_iter(|it, row, (compose, position, effects)| {
+            if effects.added.is_empty() && effects.removed.is_empty() {
+                return;
+            }
+
+            let system = it.system();
+            let entity_id = VarInt(it.entity(row).minecraft_id());
+
+            let mut bundle = DataBundle::new(compose, system);
+
+            let mut run = || {
+                for kind in &effects.removed {
+                    bundle.add_packet(&play::RemoveEntityStatusEffectS2c {
+                        entity_id,
+                        effect_id: VarInt(kind.id()),
+                    })?;
+                }
+
+                for kind in &effects.added {
+                    if let Some(effect) = effects.get(*kind) {
+                        bundle.add_packet(&effect.packet(entity_id))?;
+                    }
+                }
+
+                bundle.broadcast_local(position.to_chunk())
+            };
+
+            if let Err(e) = run() {
+                error!("failed to sync status effects: {e}");
+            }
+
+            effects.added.clear();
+            effects.removed.clear();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stronger_effect_replaces_weaker() {
+        let mut effects = ActiveEffects::default();
+
+        assert!(effects.add(StatusEffect::new(EffectKind::Speed, 0, 100)));
+        assert!(effects.add(StatusEffect::new(EffectKind::Speed, 1, 20)));
+        assert!(!effects.add(StatusEffect::new(EffectKind::Speed, 0, 1000)));
+
+        let speed = effects.get(EffectKind::Speed).unwrap();
+        assert_eq!(speed.amplifier, 1);
+        assert_eq!(speed.duration, 20);
+    }
+
+    #[test]
+    fn expires_after_duration() {
+        let mut effects = ActiveEffects::default();
+        effects.add(StatusEffect::new(EffectKind::Poison, 0, 2));
+        effects.add(StatusEffect::infinite(EffectKind::Glowing, 0));
+
+        effects.tick();
+        assert!(effects.has(EffectKind::Poison));
+
+        effects.tick();
+        assert!(!effects.has(EffectKind::Poison));
+        assert!(effects.has(EffectKind::Glowing));
+        assert_eq!(effects.removed, [EffectKind::Poison]);
+    }
+
+    #[test]
+    fn speed_and_slowness_combine() {
+        let mut effects = ActiveEffects::default();
+        effects.add(StatusEffect::new(EffectKind::Speed, 1, 100));
+        effects.add(StatusEffect::new(EffectKind::Slowness, 0, 100));
+
+        let expected = 1.4 * 0.85;
+        assert!((effects.speed_multiplier() - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn flags_follow_effects() {
+        let mut effects = ActiveEffects::default();
+        let mut flags = EntityFlags::GLOWING;
+
+        effects.add(StatusEffect::infinite(EffectKind::Invisibility, 0));
+        effects.add(StatusEffect::infinite(EffectKind::Glowing, 0));
+        effects.apply_flags(&mut flags);
+        assert_eq!(flags, EntityFlags::INVISIBLE | EntityFlags::GLOWING);
+
+        // removed after the last sync cleared what was removed
+        effects.clear();
+        effects.removed.clear();
+        effects.apply_flags(&mut flags);
+
+        // glowing was set by someone else
+        assert_eq!(flags, EntityFlags::GLOWING);
+    }
+}
@@ -1,5 +1,7 @@
 use flecs_ecs::macros::Component;
 
+use crate::simulation::EntitySize;
+
 #[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
 #[meta]
@@ -129,3 +131,384 @@ pub enum EntityKind {
     Player = 122,
     FishingBobber = 123,
 }
+
+impl EntityKind {
+    /// Every entity kind, ordered by protocol ID.
+    pub const ALL: [Self; 124] = [
+        Self::Allay,
+        Self::AreaEffectCloud,
+        Self::ArmorStand,
+        Self::Arrow,
+        Self::Axolotl,
+        Self::Bat,
+        Self::Bee,
+        Self::Blaze,
+        Self::BlockDisplay,
+        Self::Boat,
+        Self::Camel,
+        Self::Cat,
+        Self::CaveSpider,
+        Self::ChestBoat,
+        Self::ChestMinecart,
+        Self::Chicken,
+        Self::Cod,
+        Self::CommandBlockMinecart,
+        Self::Cow,
+        Self::Creeper,
+        Self::Dolphin,
+        Self::Donkey,
+        Self::DragonFireball,
+        Self::Drowned,
+        Self::Egg,
+        Self::ElderGuardian,
+        Self::EndCrystal,
+        Self::EnderDragon,
+        Self::EnderPearl,
+        Self::Enderman,
+        Self::Endermite,
+        Self::Evoker,
+        Self::EvokerFangs,
+        Self::ExperienceBottle,
+        Self::ExperienceOrb,
+        Self::EyeOfEnder,
+        Self::FallingBlock,
+        Self::FireworkRocket,
+        Self::Fox,
+        Self::Frog,
+        Self::FurnaceMinecart,
+        Self::Ghast,
+        Self::Giant,
+        Self::GlowItemFrame,
+        Self::GlowSquid,
+        Self::Goat,
+        Self::Guardian,
+        Self::Hoglin,
+        Self::HopperMinecart,
+        Self::Horse,
+        Self::Husk,
+        Self::Illusioner,
+        Self::Interaction,
+        Self::IronGolem,
+        Self::Item,
+        Self::ItemDisplay,
+        Self::ItemFrame,
+        Self::Fireball,
+        Self::LeashKnot,
+        Self::Lightning,
+        Self::Llama,
+        Self::LlamaSpit,
+        Self::MagmaCube,
+        Self::Marker,
+        Self::Minecart,
+        Self::Mooshroom,
+        Self::Mule,
+        Self::Ocelot,
+        Self::Painting,
+        Self::Panda,
+        Self::Parrot,
+        Self::Phantom,
+        Self::Pig,
+        Self::Piglin,
+        Self::PiglinBrute,
+        Self::Pillager,
+        Self::PolarBear,
+        Self::Potion,
+        Self::Pufferfish,
+        Self::Rabbit,
+        Self::Ravager,
+        Self::Salmon,
+        Self::Sheep,
+        Self::Shulker,
+        Self::ShulkerBullet,
+        Self::Silverfish,
+        Self::Skeleton,
+        Self::SkeletonHorse,
+        Self::Slime,
+        Self::SmallFireball,
+        Self::Sniffer,
+        Self::SnowGolem,
+        Self::Snowball,
+        Self::SpawnerMinecart,
+        Self::SpectralArrow,
+        Self::Spider,
+        Self::Squid,
+        Self::Stray,
+        Self::Strider,
+        Self::Tadpole,
+        Self::TextDisplay,
+        Self::Tnt,
+        Self::TntMinecart,
+        Self::TraderLlama,
+        Self::Trident,
+        Self::TropicalFish,
+        Self::Turtle,
+        Self::Vex,
+        Self::Villager,
+        Self::Vindicator,
+        Self::WanderingTrader,
+        Self::Warden,
+        Self::Witch,
+        Self::Wither,
+        Self::WitherSkeleton,
+        Self::WitherSkull,
+        Self::Wolf,
+        Self::Zoglin,
+        Self::Zombie,
+        Self::ZombieHorse,
+        Self::ZombieVillager,
+        Self::ZombifiedPiglin,
+        Self::Player,
+        Self::FishingBobber,
+    ];
+
+    /// The protocol ID of the entity kind.
+    #[must_use]
+    pub const fn id(self) -> i32 {
+        self as i32
+    }
+
+    /// The vanilla `(width, height)` of the entity's bounding box in blocks.
+    #[must_use]
+    pub const fn dimensions(self) -> (f32, f32) {
+        match self {
+            Self::Allay => (0.35, 0.6),
+            Self::AreaEffectCloud => (6.0, 0.5),
+            Self::ArmorStand => (0.5, 1.975),
+            Self::Arrow
+            | Self::ExperienceOrb
+            | Self::Frog
+            | Self::GlowItemFrame
+            | Self::ItemFrame
+            | Self::Painting
+            | Self::SpectralArrow
+            | Self::Trident => (0.5, 0.5),
+            Self::Axolotl => (0.75, 0.42),
+            Self::Bat | Self::Parrot => (0.5, 0.9),
+            Self::Bee => (0.7, 0.6),
+            Self::Blaze | Self::Player => (0.6, 1.8),
+            Self::BlockDisplay
+            | Self::Interaction
+            | Self::ItemDisplay
+            | Self::Lightning
+            | Self::Marker
+            | Self::TextDisplay => (0.0, 0.0),
+            Self::Boat | Self::ChestBoat => (1.375, 0.5625),
+            Self::Camel => (1.7, 2.375),
+            Self::Cat | Self::Fox | Self::Ocelot => (0.6, 0.7),
+            Self::CaveSpider => (0.7, 0.5),
+            Self::ChestMinecart
+            | Self::CommandBlockMinecart
+            | Self::FurnaceMinecart
+            | Self::HopperMinecart
+            | Self::Minecart
+            | Self::SpawnerMinecart
+            | Self::TntMinecart => (0.98, 0.7),
+            Self::Chicken => (0.4, 0.7),
+            Self::Cod => (0.5, 0.3),
+            Self::Cow | Self::Mooshroom => (0.9, 1.4),
+            Self::Creeper => (0.6, 1.7),
+            Self::Dolphin => (0.9, 0.6),
+            Self::Donkey => (1.396_484_4, 1.5),
+            Self::DragonFireball | Self::Fireball | Self::Shulker => (1.0, 1.0),
+            Self::Drowned
+            | Self::Evoker
+            | Self::Husk
+            | Self::Illusioner
+            | Self::Piglin
+            | Self::PiglinBrute
+            | Self::Pillager
+            | Self::Villager
+            | Self::Vindicator
+            | Self::WanderingTrader
+            | Self::Witch
+            | Self::Zombie
+            | Self::ZombieVillager
+            | Self::ZombifiedPiglin => (0.6, 1.95),
+            Self::Egg
+            | Self::EnderPearl
+            | Self::ExperienceBottle
+            | Self::EyeOfEnder
+            | Self::FireworkRocket
+            | Self::Item
+            | Self::LlamaSpit
+            | Self::Potion
+            | Self::Snowball
+            | Self::FishingBobber => (0.25, 0.25),
+            Self::ElderGuardian => (1.9975, 1.9975),
+            Self::EndCrystal => (2.0, 2.0),
+            Self::EnderDragon => (16.0, 8.0),
+            Self::Enderman => (0.6, 2.9),
+            Self::Endermite | Self::Silverfish | Self::Tadpole => (0.4, 0.3),
+            Self::EvokerFangs => (0.5, 0.8),
+            Self::FallingBlock | Self::Tnt => (0.98, 0.98),
+            Self::Ghast => (4.0, 4.0),
+            Self::Giant => (3.6, 12.0),
+            Self::GlowSquid | Self::Squid => (0.8, 0.8),
+            Self::Goat | Self::Sheep => (0.9, 1.3),
+            Self::Guardian => (0.85, 0.85),
+            Self::Hoglin | Self::Zoglin => (1.396_484_4, 1.4),
+            Self::Horse | Self::Mule | Self::SkeletonHorse | Self::ZombieHorse => {
+                (1.396_484_4, 1.6)
+            }
+            Self::IronGolem => (1.4, 2.7),
+            Self::LeashKnot => (0.375, 0.5),
+            Self::Llama | Self::TraderLlama => (0.9, 1.87),
+            Self::MagmaCube | Self::Slime => (2.04, 2.04),
+            Self::Panda => (1.3, 1.25),
+            Self::Phantom => (0.9, 0.5),
+            Self::Pig => (0.9, 0.9),
+            Self::PolarBear => (1.4, 1.4),
+            Self::Pufferfish => (0.7, 0.7),
+            Self::Rabbit => (0.4, 0.5),
+            Self::Ravager => (1.95, 2.2),
+            Self::Salmon => (0.7, 0.4),
+            Self::ShulkerBullet | Self::SmallFireball | Self::WitherSkull => (0.3125, 0.3125),
+            Self::Skeleton | Self::Stray => (0.6, 1.99),
+            Self::Sniffer => (1.9, 1.75),
+            Self::SnowGolem => (0.7, 1.9),
+            Self::Spider => (1.4, 0.9),
+            Self::Strider => (0.9, 1.7),
+            Self::TropicalFish => (0.5, 0.4),
+            Self::Turtle => (1.2, 0.4),
+            Self::Vex => (0.4, 0.8),
+            Self::Warden => (0.9, 2.9),
+            Self::Wither => (0.9, 3.5),
+            Self::WitherSkeleton => (0.7, 2.4),
+            Self::Wolf => (0.6, 0.85),
+        }
+    }
+
+    /// The vanilla bounding box of the entity.
+    #[must_use]
+    pub const fn size(self) -> EntitySize {
+        let (width, height) = self.dimensions();
+        EntitySize {
+            half_width: width / 2.0,
+            height,
+        }
+    }
+
+    /// Whether the entity extends `LivingEntity`, i.e. has health and the living metadata fields.
+    #[must_use]
+    pub const fn is_living(self) -> bool {
+        matches!(self, Self::ArmorStand | Self::Player) || self.is_mob()
+    }
+
+    /// Whether the entity extends `Mob`. This includes every living entity other than players and
+    /// armor stands.
+    #[must_use]
+    pub const fn is_mob(self) -> bool {
+        matches!(
+            self,
+            Self::Allay
+                | Self::Axolotl
+                | Self::Bat
+                | Self::Bee
+                | Self::Blaze
+                | Self::Camel
+                | Self::Cat
+                | Self::CaveSpider
+                | Self::Chicken
+                | Self::Cod
+                | Self::Cow
+                | Self::Creeper
+                | Self::Dolphin
+                | Self::Donkey
+                | Self::Drowned
+                | Self::ElderGuardian
+                | Self::EnderDragon
+                | Self::Enderman
+                | Self::Endermite
+                | Self::Evoker
+                | Self::Fox
+                | Self::Frog
+                | Self::Ghast
+                | Self::Giant
+                | Self::GlowSquid
+                | Self::Goat
+                | Self::Guardian
+                | Self::Hoglin
+                | Self::Horse
+                | Self::Husk
+                | Self::Illusioner
+                | Self::IronGolem
+                | Self::Llama
+                | Self::MagmaCube
+                | Self::Mooshroom
+                | Self::Mule
+                | Self::Ocelot
+                | Self::Panda
+                | Self::Parrot
+                | Self::Phantom
+                | Self::Pig
+                | Self::Piglin
+                | Self::PiglinBrute
+                | Self::Pillager
+                | Self::PolarBear
+                | Self::Pufferfish
+                | Self::Rabbit
+                | Self::Ravager
+                | Self::Salmon
+                | Self::Sheep
+                | Self::Shulker
+                | Self::Silverfish
+                | Self::Skeleton
+                | Self::SkeletonHorse
+                | Self::Slime
+                | Self::Sniffer
+                | Self::SnowGolem
+                | Self::Spider
+                | Self::Squid
+                | Self::Stray
+                | Self::Strider
+                | Self::Tadpole
+                | Self::TraderLlama
+                | Self::TropicalFish
+                | Self::Turtle
+                | Self::Vex
+                | Self::Villager
+                | Self::Vindicator
+                | Self::WanderingTrader
+                | Self::Warden
+                | Self::Witch
+                | Self::Wither
+                | Self::WitherSkeleton
+                | Self::Wolf
+                | Self::Zoglin
+                | Self::Zombie
+                | Self::ZombieHorse
+                | Self::ZombieVillager
+                | Self::ZombifiedPiglin
+        )
+    }
+
+    /// Whether the entity is a block, item or text display.
+    #[must_use]
+    pub const fn is_display(self) -> bool {
+        matches!(
+            self,
+            Self::BlockDisplay | Self::ItemDisplay | Self::TextDisplay
+        )
+    }
+
+    /// The packet used to spawn the entity on the client.
+    #[must_use]
+    pub const fn spawn_packet(self) -> SpawnPacketKind {
+        match self {
+            Self::ExperienceOrb => SpawnPacketKind::ExperienceOrb,
+            Self::Player => SpawnPacketKind::Player,
+            _ => SpawnPacketKind::Entity,
+        }
+    }
+}
+
+/// Which packet spawns an [`EntityKind`] on the client.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpawnPacketKind {
+    /// `EntitySpawnS2c`, used by almost every entity.
+    Entity,
+    /// `ExperienceOrbSpawnS2c`, which carries the orb's value instead of a UUID.
+    ExperienceOrb,
+    /// `PlayerSpawnS2c`. The player must already be in the tab list of the viewer.
+    Player,
+}
@@ -0,0 +1,161 @@
+//! The equipment other players see an entity wearing and holding.
+//!
+//! Instead of broadcasting every slot each tick, [`Equipment`] remembers what viewers were last
+//! told, so only the slots which changed since are sent. The changes reach every player the entity
+//! is spawned for, not only those nearby, so a viewer which was out of range still knows the
+//! current equipment. Viewers which see an entity for the first time get the whole snapshot with
+//! [`Equipment::snapshot`].
+
+use flecs_ecs::macros::Component;
+use hyperion_inventory::PlayerInventory;
+use valence_protocol::{
+    ItemStack, VarInt,
+    packets::play::{EntityEquipmentUpdateS2c, entity_equipment_update_s2c::EquipmentEntry},
+};
+
+/// The number of equipment slots of the protocol.
+pub const SLOTS: usize = 6;
+
+/// The items of an entity as they were last broadcast, indexed by the protocol slot: main hand,
+/// off hand, boots, leggings, chestplate and helmet.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Equipment {
+    slots: [ItemStack; SLOTS],
+}
+
+impl Default for Equipment {
+    fn default() -> Self {
+        Self {
+            slots: [ItemStack::EMPTY; SLOTS],
+        }
+    }
+}
+
+/// The items of `inventory` in protocol slot order.
+#[must_use]
+pub fn equipped(inventory: &PlayerInventory) -> [&ItemStack; SLOTS] {
+    [
+        inventory.get_cursor(),
+        inventory.get_offhand(),
+        inventory.get_boots(),
+        inventory.get_leggings(),
+        inventory.get_chestplate(),
+        inventory.get_helmet(),
+    ]
+}
+
+fn entry(slot: usize, item: &ItemStack) -> EquipmentEntry {
+    EquipmentEntry {
+        #[expect(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        slot: slot as i8,
+        item: item.clone(),
+    }
+}
+
+impl Equipment {
+    #[must_use]
+    pub const fn slots(&self) -> &[ItemStack; SLOTS] {
+        &self.slots
+    }
+
+    /// Records the current equipment of `inventory` and returns the entries of the slots which
+    /// differ from what was last recorded. Only changed slots are cloned.
+    pub fn update(&mut self, inventory: &PlayerInventory) -> Vec<EquipmentEntry> {
+        let mut changed = Vec::new();
+
+        for (slot, (known, current)) in self.slots.iter_mut().zip(equipped(inventory)).enumerate() {
+            if known == current {
+                continue;
+            }
+
+            known.clone_from(current);
+            changed.push(entry(slot, current));
+        }
+
+        changed
+    }
+
+    /// The packet which shows the whole equipment to a viewer which has just started seeing the
+    /// entity, or [`None`] if it has nothing equipped.
+    #[must_use]
+    pub fn snapshot(&self, entity_id: VarInt) -> Option<EntityEquipmentUpdateS2c> {
+        // viewers assume empty slots for a newly spawned entity
+        let equipment: Vec<_> = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !item.is_empty())
+            .map(|(slot, item)| entry(slot, item))
+            .collect();
+
+        if equipment.is_empty() {
+            return None;
+        }
+
+        Some(EntityEquipmentUpdateS2c {
+            entity_id,
+            equipment,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn update_returns_changed_slots() {
+        let mut inventory = PlayerInventory::default();
+        let mut equipment = Equipment::default();
+
+        assert!(equipment.update(&inventory).is_empty());
+
+        inventory.set_helmet(ItemStack::new(ItemKind::IronHelmet, 1, None));
+        inventory.set_hotbar(0, ItemStack::new(ItemKind::DiamondSword, 1, None));
+
+        let changed = equipment.update(&inventory);
+        let slots: Vec<_> = changed.iter().map(|entry| entry.slot).collect();
+        assert_eq!(slots, [0, 5]);
+
+        assert!(equipment.update(&inventory).is_empty());
+
+        inventory.set_cursor(1);
+
+        let changed = equipment.update(&inventory);
+        assert_eq!(changed.len(), 1);
+        assert!(changed[0].item.is_empty());
+
+        let snapshot = equipment.snapshot(VarInt(1)).unwrap();
+        assert_eq!(snapshot.equipment.len(), 1);
+    }
+
+    #[test]
+    fn distant_viewer_sees_current_equipment() {
+        let mut inventory = PlayerInventory::default();
+        let mut equipment = Equipment::default();
+
+        inventory.set_helmet(ItemStack::new(ItemKind::IronHelmet, 1, None));
+        equipment.update(&inventory);
+
+        // a viewer far away was sent the spawn snapshot and every change since
+        let mut seen = [ItemStack::EMPTY; SLOTS];
+        let mut show = |entries: Vec<EquipmentEntry>| {
+            for entry in entries {
+                seen[usize::try_from(entry.slot).unwrap()] = entry.item;
+            }
+        };
+
+        show(equipment.snapshot(VarInt(1)).unwrap().equipment);
+
+        // such as a restored inventory
+        let mut restored = PlayerInventory::default();
+        restored.set_boots(ItemStack::new(ItemKind::GoldenBoots, 1, None));
+        restored.set_hotbar(0, ItemStack::new(ItemKind::Bow, 1, None));
+        show(equipment.update(&restored));
+
+        assert_eq!(&seen, equipment.slots());
+        assert_eq!(seen.each_ref(), equipped(&restored));
+    }
+}
@@ -3,16 +3,23 @@
 use derive_more::Constructor;
 use flecs_ecs::{core::Entity, macros::Component};
 use glam::{IVec3, Vec3};
+use hyperion_crafting::{RecipeBookState, recipe_book::RecipeBook};
+use hyperion_inventory::action::InventoryAction;
 use valence_generated::block::BlockState;
 use valence_protocol::Hand;
 use valence_server::{ItemKind, entity::item_frame::ItemStack};
 
-use crate::simulation::skin::PlayerSkin;
+use crate::simulation::{secure_chat::SignedMessage, skin::PlayerSkin};
 
+/// Spawns an item entity holding `item` at `location`.
 #[derive(Component, Default, Debug)]
 pub struct ItemDropEvent {
     pub item: ItemStack,
     pub location: Vec3,
+    /// The initial velocity of the item in blocks per tick.
+    pub velocity: Vec3,
+    /// The player who threw the item, if any. Thrown items take longer before they can be picked up.
+    pub thrown_by: Option<Entity>,
 }
 
 #[derive(Component, Default, Debug)]
@@ -26,6 +33,8 @@ pub struct ItemInteract {
 pub struct ChatMessage<'a> {
     pub msg: &'a str,
     pub by: Entity,
+    /// The signature of the message, if it was signed by a valid chat session.
+    pub signed: Option<SignedMessage>,
 }
 
 #[derive(Debug)]
@@ -44,6 +53,48 @@ pub struct AttackEntity {
     pub damage: f32,
 }
 
+/// Sent by a dead player's client when they click the respawn button.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RequestRespawn {
+    pub from: Entity,
+}
+
+/// A click in the window of a [`hyperion_inventory::container::Container`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContainerClick {
+    pub by: Entity,
+    pub window_id: u8,
+    /// The state id of the window the client predicted the click from.
+    pub state_id: i32,
+    /// `None` if the client sent a click which does not exist.
+    pub action: Option<InventoryAction>,
+    /// The slots the client claims changed, and their new contents.
+    pub slot_changes: Vec<(i16, ItemStack)>,
+    /// The item the client claims to carry after the click.
+    pub carried_item: ItemStack,
+}
+
+/// A player used a crafting table.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OpenCraftingTable {
+    pub by: Entity,
+}
+
+/// A player opened or closed a tab of their recipe book, or toggled its filter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RecipeBookSettings {
+    pub by: Entity,
+    pub book: RecipeBook,
+    pub state: RecipeBookState,
+}
+
+/// A player used a furnace, blast furnace or smoker.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OpenFurnace {
+    pub by: Entity,
+    pub position: IVec3,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Constructor)]
 pub struct HealthUpdate {
     pub from: f32,
@@ -74,6 +125,7 @@ pub struct ToggleDoor {
 
 #[derive(Copy, Clone, Debug)]
 pub struct SwingArm {
+    pub from: Entity,
     pub hand: Hand,
 }
 
@@ -0,0 +1,165 @@
+//! Furnaces, blast furnaces and smokers placed in the world.
+//!
+//! The first player to use a furnace block creates a [`Container`] with a [`Furnace`] for it,
+//! which every later player shares. Furnaces cook every tick whether or not anyone is looking into
+//! them, and the block lights up while they burn. Once the block is gone, the items inside drop
+//! where it was.
+
+use std::collections::HashMap;
+
+use derive_more::{Deref, DerefMut};
+use flecs_ecs::prelude::*;
+use glam::{IVec3, Vec3};
+use hyperion_crafting::CraftingRegistry;
+use hyperion_inventory::{container::Container, furnace::Furnace};
+use tracing::error;
+use valence_generated::block::{BlockKind, PropName, PropValue};
+use valence_protocol::packets::play::open_screen_s2c::WindowType;
+
+use crate::{
+    simulation::{blocks::Blocks, container::ContainerExt, event},
+    storage::{EventQueue, Events},
+};
+
+/// A container which belongs to the block at `position`.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BlockEntity {
+    pub position: IVec3,
+}
+
+/// The furnace entities by the position of their block.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub struct Furnaces(HashMap<IVec3, Entity>);
+
+/// The window and its title for a furnace block.
+const fn furnace_window(block: BlockKind) -> Option<(WindowType, &'static str)> {
+    match block {
+        BlockKind::Furnace => Some((WindowType::Furnace, "Furnace")),
+        BlockKind::BlastFurnace => Some((WindowType::BlastFurnace, "Blast Furnace")),
+        BlockKind::Smoker => Some((WindowType::Smoker, "Smoker")),
+        _ => None,
+    }
+}
+
+#[derive(Component)]
+pub struct FurnaceModule;
+
+impl Module for FurnaceModule {
+    fn module(world: &World) {
+        world.component::<BlockEntity>();
+        world.component::<Furnaces>();
+
+        world.set(Furnaces::default());
+
+        system!(
+            "open_furnaces",
+            world,
+            &mut EventQueue<event::OpenFurnace>($),
+            &mut Furnaces($),
+            &Blocks($),
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, _, (event_queue, furnaces, blocks)| {
+            let world = it.world();
+
+            for event in event_queue.drain() {
+                let player = world.entity_from_id(event.by);
+
+                if !player.is_alive() {
+                    continue;
+                }
+
+                // the block may have been broken since it was used
+                let Some((kind, title)) = blocks
+                    .get_block(event.position)
+                    .and_then(|block| furnace_window(block.to_kind()))
+                else {
+                    continue;
+                };
+
+                let furnace = *furnaces.entry(event.position).or_insert_with(|| {
+                    world
+                        .entity()
+                        .set(Container::new(kind, title))
+                        .set(Furnace::default())
+                        .set(BlockEntity {
+                            position: event.position,
+                        })
+                        .id()
+                });
+
+                player.open_container(furnace);
+            }
+        });
+
+        system!(
+            "furnace_tick",
+            world,
+            &mut Furnace,
+            &mut Container,
+            &BlockEntity,
+            &CraftingRegistry($),
+            &mut Blocks($),
+            &mut Furnaces($),
+            &Events($),
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(
+            |it, row, (furnace, container, block_entity, registry, blocks, furnaces, events)| {
+                let world = it.world();
+                let position = block_entity.position;
+
+                // furnaces in unloaded chunks do not cook
+                let Some(block) = blocks.get_block(position) else {
+                    return;
+                };
+
+                let still_there = furnace_window(block.to_kind())
+                    .is_some_and(|(kind, _)| kind == container.kind());
+
+                if !still_there {
+                    let location = position.as_vec3() + Vec3::splat(0.5);
+
+                    for item in container.take_all() {
+                        let velocity = Vec3::new(
+                            fastrand::f32() - 0.5,
+                            fastrand::f32(),
+                            fastrand::f32() - 0.5,
+                        ) * 0.2;
+
+                        let event = event::ItemDropEvent {
+                            item,
+                            location,
+                            velocity,
+                            thrown_by: None,
+                        };
+
+                        events.push(event, &world);
+                    }
+
+                    furnaces.remove(&position);
+                    it.entity(row).destruct();
+                    return;
+                }
+
+                let was_burning = furnace.is_burning();
+
+                furnace.tick(container, registry);
+
+                if furnace.is_burning() != was_burning {
+                    let lit = if furnace.is_burning() {
+                        PropValue::True
+                    } else {
+                        PropValue::False
+                    };
+
+                    let block = block.set(PropName::Lit, lit);
+
+                    if let Err(e) = blocks.set_block(position, block) {
+                        error!("failed to light furnace at {position}: {e:?}");
+                    }
+                }
+            },
+        );
+    }
+}
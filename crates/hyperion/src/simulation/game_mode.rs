@@ -0,0 +1,151 @@
+//! Player game modes.
+//!
+//! Setting [`GameMode`] on a player sends the change to their client together with the abilities
+//! (flight, instant breaking, invulnerability) that come with it, and updates the tab list for
+//! everyone else.
+
+use std::borrow::Cow;
+
+use flecs_ecs::prelude::*;
+use tracing::error;
+use valence_protocol::packets::play::{
+    self,
+    game_state_change_s2c::GameEventKind,
+    player_abilities_s2c::PlayerAbilitiesFlags,
+    player_list_s2c::{PlayerListActions, PlayerListEntry},
+};
+
+use crate::{
+    net::{Compose, ConnectionId, DataBundle},
+    simulation::{PacketState, Player, Uuid},
+};
+
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[repr(C)]
+pub enum GameMode {
+    #[default]
+    Survival = 0,
+    Creative = 1,
+    Adventure = 2,
+    Spectator = 3,
+}
+
+impl GameMode {
+    /// Whether the player can place and break blocks.
+    #[must_use]
+    pub const fn can_build(self) -> bool {
+        matches!(self, Self::Survival | Self::Creative)
+    }
+
+    /// Whether the player ignores damage.
+    #[must_use]
+    pub const fn is_invulnerable(self) -> bool {
+        matches!(self, Self::Creative | Self::Spectator)
+    }
+
+    #[must_use]
+    pub const fn can_fly(self) -> bool {
+        matches!(self, Self::Creative | Self::Spectator)
+    }
+
+    /// Whether blocks break as soon as the player starts digging.
+    #[must_use]
+    pub const fn breaks_instantly(self) -> bool {
+        matches!(self, Self::Creative)
+    }
+
+    /// Whether the player can interact with the world at all, e.g. attack entities or use items.
+    #[must_use]
+    pub const fn can_interact(self) -> bool {
+        !matches!(self, Self::Spectator)
+    }
+
+    #[must_use]
+    pub fn abilities_packet(self) -> play::PlayerAbilitiesS2c {
+        play::PlayerAbilitiesS2c {
+            flags: PlayerAbilitiesFlags::default()
+                .with_invulnerable(self.is_invulnerable())
+                .with_flying(self == Self::Spectator)
+                .with_allow_flying(self.can_fly())
+                .with_instant_break(self.breaks_instantly()),
+            flying_speed: 0.05,
+            fov_modifier: 0.1,
+        }
+    }
+}
+
+impl From<GameMode> for valence_protocol::GameMode {
+    fn from(value: GameMode) -> Self {
+        match value {
+            GameMode::Survival => Self::Survival,
+            GameMode::Creative => Self::Creative,
+            GameMode::Adventure => Self::Adventure,
+            GameMode::Spectator => Self::Spectator,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct GameModeModule;
+
+impl Module for GameModeModule {
+    fn module(world: &World) {
+        world.component::<GameMode>();
+
+        world
+            .component::<Player>()
+            .add_trait::<(flecs::With, GameMode)>();
+
+        // the initial game mode is sent in the join packet
+        observer!(
+            world,
+            flecs::OnSet,
+            &GameMode,
+            &Compose($),
+            &ConnectionId,
+            &Uuid,
+        )
+        .with_enum(PacketState::Play)
+        .each_iter(|it, _, (game_mode, compose, io, uuid)| {
+            let system = it.system();
+
+            let mut bundle = DataBundle::new(compose, system);
+
+            let mut run = || {
+                bundle.add_packet(&play::GameStateChangeS2c {
+                    kind: GameEventKind::ChangeGameMode,
+                    value: f32::from(*game_mode as u8),
+                })?;
+                bundle.add_packet(&game_mode.abilities_packet())?;
+                bundle.unicast(*io)?;
+
+                let entries = [PlayerListEntry {
+                    player_uuid: uuid.0,
+                    username: Cow::Borrowed(""),
+                    properties: Cow::Borrowed(&[]),
+                    chat_data: None,
+                    listed: true,
+                    ping: 0,
+                    game_mode: (*game_mode).into(),
+                    display_name: None,
+                }];
+
+                compose
+                    .broadcast(
+                        &play::PlayerListS2c {
+                            actions: PlayerListActions::default().with_update_game_mode(true),
+                            entries: Cow::Borrowed(&entries),
+                        },
+                        system,
+                    )
+                    .send()?;
+
+                anyhow::Ok(())
+            };
+
+            if let Err(e) = run() {
+                error!("failed to sync game mode: {e}");
+            }
+        });
+    }
+}
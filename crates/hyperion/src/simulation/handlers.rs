@@ -1,11 +1,18 @@
 //! <https://wiki.vg/index.php?title=Protocol&oldid=18375>
 
-use std::borrow::Cow;
+use std::{borrow::Cow, time::Instant};
 
-use anyhow::{Context, bail};
+use anyhow::bail;
 use flecs_ecs::core::{Entity, EntityView, EntityViewGet, World};
 use geometry::aabb::Aabb;
 use glam::{IVec3, Vec3};
+use hyperion_crafting::{RecipeBookState, recipe_book::RecipeBook};
+use hyperion_inventory::{
+    CarriedItem,
+    action::{CRAFTING_RESULT_SLOT, InventoryAction, InventoryAndCursor},
+    container::OpenContainer,
+    parser::create_inventory_action,
+};
 use hyperion_utils::EntityExt;
 use tracing::{info, instrument, trace, warn};
 use valence_generated::{
@@ -18,6 +25,7 @@ use valence_protocol::{
         self, client_command_c2s::ClientCommand, player_action_c2s::PlayerAction,
         player_interact_entity_c2s::EntityInteraction,
         player_position_look_s2c::PlayerPositionLookFlags,
+        recipe_category_options_c2s::RecipeBookId,
     },
 };
 use valence_text::IntoText;
@@ -30,10 +38,20 @@ use super::{
     bow::BowCharging,
 };
 use crate::{
-    net::{Compose, ConnectionId, decoder::BorrowedPacketFrame},
-    simulation::{Pitch, Yaw, aabb, event, event::PluginMessage, metadata::entity::Pose},
+    net::{Compose, ConnectionId, decoder::BorrowedPacketFrame, packets},
+    simulation::{
+        Pitch, Yaw, aabb,
+        effect::ActiveEffects,
+        event,
+        event::PluginMessage,
+        game_mode::GameMode,
+        keep_alive::{KeepAlive, Ping},
+        metadata::entity::Pose,
+        secure_chat::{self, LastSeenMessages},
+    },
     storage::{
-        ClickSlotEvent, CommandCompletionRequest, Events, GlobalEventHandlers, InteractEvent,
+        ClickSlotEvent, CloseScreenEvent, CommandCompletionRequest, Events, GlobalEventHandlers,
+        InteractEvent,
     },
 };
 
@@ -63,7 +81,13 @@ fn full(query: &mut PacketSwitchQuery<'_>, mut data: &[u8]) -> anyhow::Result<()
 fn change_position_or_correct_client(query: &mut PacketSwitchQuery<'_>, proposed: Vec3) {
     let pose = &mut *query.position;
 
-    if let Err(e) = try_change_position(proposed, pose, *query.size, query.blocks) {
+    let max_speed = MAX_BLOCKS_PER_TICK
+        * query
+            .view
+            .try_get::<&ActiveEffects>(ActiveEffects::speed_multiplier)
+            .unwrap_or(1.0);
+
+    if let Err(e) = try_change_position(proposed, pose, *query.size, query.blocks, max_speed) {
         // Send error message to player
         let msg = format!("§c{e}");
         let pkt = play::GameMessageS2c {
@@ -113,8 +137,9 @@ fn try_change_position(
     position: &mut Position,
     size: EntitySize,
     blocks: &Blocks,
+    max_speed: f32,
 ) -> anyhow::Result<()> {
-    is_within_speed_limits(**position, proposed)?;
+    is_within_speed_limits(**position, proposed, max_speed)?;
 
     // Only check collision if we're starting outside a block
     if !has_block_collision(position, size, blocks) && has_block_collision(&proposed, size, blocks)
@@ -140,11 +165,12 @@ pub fn is_grounded(position: &Vec3, blocks: &Blocks) -> bool {
         .unwrap()
         .is_air()
 }
-fn is_within_speed_limits(current: Vec3, proposed: Vec3) -> anyhow::Result<()> {
+/// `max_speed` is [`MAX_BLOCKS_PER_TICK`] scaled by the player's speed effects.
+fn is_within_speed_limits(current: Vec3, proposed: Vec3, max_speed: f32) -> anyhow::Result<()> {
     let delta = proposed - current;
-    if delta.length_squared() > MAX_BLOCKS_PER_TICK.powi(2) {
+    if delta.length_squared() > max_speed.powi(2) {
         return Err(anyhow::anyhow!(
-            "Moving too fast! Maximum speed is {MAX_BLOCKS_PER_TICK} blocks per tick"
+            "Moving too fast! Maximum speed is {max_speed} blocks per tick"
         ));
     }
     Ok(())
@@ -199,7 +225,12 @@ fn position_and_on_ground(
 }
 
 fn chat_command(mut data: &'static [u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
-    let pkt = play::CommandExecutionC2s::decode(&mut data)?;
+    let pkt = packets::CommandExecutionC2s::decode(&mut data)?;
+
+    if let Err(e) = secure_chat::acknowledge(query.view, pkt.message_count, pkt.acknowledged) {
+        secure_chat::reject(query.view, &e);
+        return Ok(());
+    }
 
     let command = pkt.command.0;
 
@@ -226,6 +257,28 @@ fn hand_swing(mut data: &[u8], query: &mut PacketSwitchQuery<'_>) -> anyhow::Res
         }
     }
 
+    let event = event::SwingArm {
+        from: query.id,
+        hand: packet.hand,
+    };
+
+    query.events.push(event, query.world);
+
+    Ok(())
+}
+
+fn keep_alive(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+    let play::KeepAliveC2s { id } = play::KeepAliveC2s::decode(&mut data)?;
+
+    query
+        .view
+        .get::<(&mut KeepAlive, &mut Ping)>(|(keep_alive, ping)| {
+            match keep_alive.answer(id, Instant::now()) {
+                Some(rtt) => *ping = Ping::from(rtt),
+                None => warn!("unexpected keep alive id {id}"),
+            }
+        });
+
     Ok(())
 }
 
@@ -238,6 +291,10 @@ fn player_interact_entity(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> any
         return Ok(());
     }
 
+    if !query.game_mode().can_interact() {
+        return Ok(());
+    }
+
     let target = packet.entity_id.0;
     let target = Entity::from_minecraft_id(target);
 
@@ -274,14 +331,75 @@ pub struct PacketSwitchQuery<'a> {
     pub crafting_registry: &'a hyperion_crafting::CraftingRegistry,
 }
 
+impl PacketSwitchQuery<'_> {
+    /// The [`GameMode`] of the player who sent the packet.
+    fn game_mode(&self) -> GameMode {
+        self.view
+            .try_get::<&GameMode>(|mode| *mode)
+            .unwrap_or_default()
+    }
+}
+
+/// The height of a standing player's eyes above their feet.
+const EYE_HEIGHT: f32 = 1.62;
+
+/// The speed in blocks per tick at which a dropped item leaves the player.
+const THROW_SPEED: f32 = 0.3;
+
+/// The unit vector a player with the given rotation (in degrees) is looking along.
+fn look_direction(yaw: f32, pitch: f32) -> Vec3 {
+    let (yaw, pitch) = (yaw.to_radians(), pitch.to_radians());
+    Vec3::new(
+        -yaw.sin() * pitch.cos(),
+        -pitch.sin(),
+        yaw.cos() * pitch.cos(),
+    )
+}
+
+/// An item thrown by `player` out of their hand in the direction they are looking.
+pub(crate) fn thrown_item(
+    player: Entity,
+    position: Vec3,
+    yaw: f32,
+    pitch: f32,
+    item: ItemStack,
+) -> event::ItemDropEvent {
+    let direction = look_direction(yaw, pitch);
+
+    event::ItemDropEvent {
+        item,
+        location: position + Vec3::Y * (EYE_HEIGHT - 0.3),
+        velocity: direction * THROW_SPEED + Vec3::Y * 0.1,
+        thrown_by: Some(player),
+    }
+}
+
+/// Throws `item` out of the player's hand in the direction they are looking.
+fn throw_item(query: &PacketSwitchQuery<'_>, item: ItemStack) {
+    let event = thrown_item(query.id, **query.position, **query.yaw, **query.pitch, item);
+
+    query.events.push(event, query.world);
+}
+
 // i.e., shooting a bow, digging a block, etc
-fn player_action(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+fn player_action(mut data: &[u8], query: &mut PacketSwitchQuery<'_>) -> anyhow::Result<()> {
     let packet = play::PlayerActionC2s::decode(&mut data)?;
 
     let sequence = packet.sequence.0;
     let position = IVec3::new(packet.position.x, packet.position.y, packet.position.z);
 
     match packet.action {
+        PlayerAction::StartDestroyBlock | PlayerAction::StopDestroyBlock
+            if !query.game_mode().can_build() => {}
+        PlayerAction::StartDestroyBlock if query.game_mode().breaks_instantly() => {
+            let event = event::DestroyBlock {
+                position,
+                from: query.id,
+                sequence,
+            };
+
+            query.events.push(event, query.world);
+        }
         PlayerAction::StopDestroyBlock => {
             let event = event::DestroyBlock {
                 position,
@@ -299,6 +417,19 @@ fn player_action(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Resu
 
             query.events.push(event, query.world);
         }
+        PlayerAction::DropItem | PlayerAction::DropAllItems => {
+            let item = if matches!(packet.action, PlayerAction::DropItem) {
+                query.inventory.take_one_held()
+            } else {
+                std::mem::replace(query.inventory.get_cursor_mut(), ItemStack::EMPTY)
+            };
+
+            if item.is_empty() {
+                return Ok(());
+            }
+
+            throw_item(query, item);
+        }
         action => bail!("unimplemented {action:?}"),
     }
 
@@ -329,6 +460,45 @@ fn client_command(mut data: &[u8], query: &mut PacketSwitchQuery<'_>) -> anyhow:
     Ok(())
 }
 
+fn client_status(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+    let packet = play::ClientStatusC2s::decode(&mut data)?;
+
+    match packet {
+        play::ClientStatusC2s::PerformRespawn => {
+            query
+                .events
+                .push(event::RequestRespawn { from: query.id }, query.world);
+        }
+        play::ClientStatusC2s::RequestStats => {}
+    }
+
+    Ok(())
+}
+
+fn recipe_category_options(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+    let packet = play::RecipeCategoryOptionsC2s::decode(&mut data)?;
+
+    let book = match packet.book_id {
+        RecipeBookId::Crafting => RecipeBook::Crafting,
+        RecipeBookId::Furnace => RecipeBook::Furnace,
+        RecipeBookId::BlastFurnace => RecipeBook::BlastFurnace,
+        RecipeBookId::Smoker => RecipeBook::Smoker,
+    };
+
+    let event = event::RecipeBookSettings {
+        by: query.id,
+        book,
+        state: RecipeBookState {
+            open: packet.book_open,
+            filter_active: packet.filter_active,
+        },
+    };
+
+    query.events.push(event, query.world);
+
+    Ok(())
+}
+
 /// Handles player interaction with items in hand
 ///
 /// Common uses:
@@ -369,6 +539,14 @@ pub fn player_interact_item(
 
     query.handlers.interact.trigger_all(query, &event);
 
+    let event = event::ItemInteract {
+        entity: query.id,
+        hand,
+        sequence: sequence.0,
+    };
+
+    query.events.push(event, query.world);
+
     Ok(())
 }
 
@@ -412,9 +590,32 @@ pub fn player_interact_block(
             },
             query.world,
         );
+    } else if matches!(
+        interacted_block.to_kind(),
+        BlockKind::CraftingTable | BlockKind::Furnace | BlockKind::BlastFurnace | BlockKind::Smoker
+    ) && (*query.pose != Pose::Sneaking || query.inventory.get_cursor().is_empty())
+    {
+        // sneaking players place the block they are holding instead
+        if interacted_block.to_kind() == BlockKind::CraftingTable {
+            query
+                .events
+                .push(event::OpenCraftingTable { by: query.id }, query.world);
+        } else {
+            query.events.push(
+                event::OpenFurnace {
+                    by: query.id,
+                    position: interacted_block_pos_vec,
+                },
+                query.world,
+            );
+        }
     } else {
         // Attempt to place a block
 
+        if !query.game_mode().can_build() {
+            return Ok(());
+        }
+
         let held = query.inventory.get_cursor();
 
         if held.is_empty() {
@@ -486,6 +687,11 @@ pub fn creative_inventory_action(
 
     let play::CreativeInventoryActionC2s { slot, clicked_item } = packet;
 
+    if query.game_mode() != GameMode::Creative {
+        warn!("creative inventory action outside of creative mode");
+        return Ok(());
+    }
+
     info!("creative inventory action: {slot} {clicked_item:?}");
 
     let Ok(slot) = u16::try_from(slot) else {
@@ -524,69 +730,258 @@ pub fn custom_payload(
 fn click_slot(mut data: &'static [u8], query: &mut PacketSwitchQuery<'_>) -> anyhow::Result<()> {
     let pkt = play::ClickSlotC2s::decode(&mut data)?;
 
-    let to_send_pkt = play::ScreenHandlerSlotUpdateS2c {
-        window_id: -1,
-        state_id: VarInt::default(),
+    let container_window = query
+        .view
+        .try_get::<&OpenContainer>(|open| open.window_id)
+        .filter(|&window_id| i32::from(window_id) == i32::from(pkt.window_id));
+
+    // other windows are handled by whoever opened them through `GlobalEventHandlers::click`
+    if pkt.window_id == 0 {
+        let view = query.view;
+        view.get::<&mut CarriedItem>(|carried| click_player_inventory(&pkt, carried, query))?;
+    } else if let Some(window_id) = container_window {
+        // other players may be looking into the same container, so the click is applied by
+        // `ContainerModule` instead of here
+        let action = parse_click(&pkt)
+            .inspect_err(|e| warn!("invalid container click {:?}: {e}", pkt.mode))
+            .ok();
+
+        let click = event::ContainerClick {
+            by: query.id,
+            window_id,
+            state_id: pkt.state_id.0,
+            action,
+            slot_changes: pkt
+                .slot_changes
+                .iter()
+                .map(|change| (change.idx, change.stack.clone()))
+                .collect(),
+            carried_item: pkt.carried_item.clone(),
+        };
+
+        query.events.push(click, query.world);
+    }
+
+    // clicks outside the window have no slot
+    if let Ok(event) = ClickSlotEvent::try_from(pkt) {
+        query.handlers.click.trigger_all(query, &event);
+    }
+
+    Ok(())
+}
+
+/// Applies a click in the player's own inventory on the server and resyncs the client if the
+/// changes it predicted differ.
+fn click_player_inventory(
+    pkt: &play::ClickSlotC2s<'_>,
+    carried: &mut CarriedItem,
+    query: &mut PacketSwitchQuery<'_>,
+) -> anyhow::Result<()> {
+    let stale = pkt.state_id.0 != carried.state_id;
+
+    let result = apply_click(pkt, carried, query);
+
+    let in_sync = match result {
+        Ok(dropped) => {
+            if let Some(item) = dropped {
+                throw_item(query, item);
+            }
+
+            !stale && predicted_correctly(pkt, query.inventory, carried)
+        }
+        Err(e) => {
+            warn!("invalid inventory click {:?}: {e}", pkt.mode);
+            false
+        }
+    };
+
+    if in_sync {
+        // the client already shows these slots
+        for change in pkt.slot_changes.iter() {
+            if let Ok(idx) = u32::try_from(change.idx) {
+                query.inventory.updated_since_last_tick.remove(idx);
+            }
+        }
+    } else {
+        resync_player_inventory(query, carried)?;
+    }
+
+    // the client does not predict crafting results
+    let result = query.inventory.crafting_result(query.crafting_registry);
+
+    let pkt = play::ScreenHandlerSlotUpdateS2c {
+        window_id: 0,
+        state_id: VarInt(carried.state_id),
         slot_idx: 0, // crafting result
-        slot_data: Cow::Borrowed(&ItemStack::EMPTY),
+        slot_data: Cow::Owned(result),
     };
 
-    // negate click
-    query
-        .compose
-        .unicast(&to_send_pkt, query.io_ref, query.system)?;
+    query.compose.unicast(&pkt, query.io_ref, query.system)?;
+
+    Ok(())
+}
 
-    let slot_idx = u16::try_from(pkt.slot_idx).context("slot index is negative")?;
+fn apply_click(
+    pkt: &play::ClickSlotC2s<'_>,
+    carried: &mut CarriedItem,
+    query: &mut PacketSwitchQuery<'_>,
+) -> anyhow::Result<Option<ItemStack>> {
+    let action = parse_click(pkt)?;
+
+    let dropped = InventoryAndCursor {
+        inventory: query.inventory,
+        carried,
+        crafting_registry: query.crafting_registry,
+    }
+    .apply(action)?;
+
+    Ok(dropped)
+}
 
-    let item_in_slot = query.inventory.get(slot_idx)?;
+fn parse_click(pkt: &play::ClickSlotC2s<'_>) -> anyhow::Result<InventoryAction> {
+    let button = u8::try_from(pkt.button)?;
+    let action = create_inventory_action(pkt.mode as u8, button, pkt.slot_idx)?;
 
-    let to_send_pkt = play::ScreenHandlerSlotUpdateS2c {
+    Ok(action)
+}
+
+pub(crate) fn same_stack(a: &ItemStack, b: &ItemStack) -> bool {
+    (a.is_empty() && b.is_empty()) || a == b
+}
+
+/// Whether the slots and carried item the client claims after a click match the server.
+fn predicted_correctly(
+    pkt: &play::ClickSlotC2s<'_>,
+    inventory: &hyperion_inventory::PlayerInventory,
+    carried: &CarriedItem,
+) -> bool {
+    same_stack(&pkt.carried_item, &carried.stack)
+        && pkt.slot_changes.iter().all(|change| {
+            u16::try_from(change.idx)
+                .ok()
+                .and_then(|idx| inventory.get(idx).ok())
+                .is_some_and(|stack| same_stack(stack, &change.stack))
+        })
+}
+
+/// Sends the whole player inventory and carried item with a new state id.
+fn resync_player_inventory(
+    query: &mut PacketSwitchQuery<'_>,
+    carried: &mut CarriedItem,
+) -> anyhow::Result<()> {
+    let mut slots = query.inventory.slots().to_vec();
+    slots[usize::from(CRAFTING_RESULT_SLOT)] =
+        query.inventory.crafting_result(query.crafting_registry);
+
+    let pkt = play::InventoryS2c {
         window_id: 0,
-        state_id: VarInt::default(),
-        slot_idx: pkt.slot_idx,
-        slot_data: Cow::Borrowed(item_in_slot),
+        state_id: VarInt(carried.next_state_id()),
+        slots: Cow::Owned(slots),
+        carried_item: Cow::Borrowed(&carried.stack),
     };
 
-    query
-        .compose
-        .unicast(&to_send_pkt, query.io_ref, query.system)?;
+    query.compose.unicast(&pkt, query.io_ref, query.system)?;
 
-    // info!("click slot\n{pkt:#?}");
+    query.inventory.updated_since_last_tick.clear();
 
-    // // todo(security): validate the player can do this. This is a MAJOR security issue.
-    // // as players will be able to spawn items in their inventory wit current logic.
-    // for SlotChange { idx, stack } in pkt.slot_changes.iter() {
-    //     let idx = u16::try_from(*idx).context("slot index is negative")?;
-    //     query.inventory.set(idx, stack.clone())?;
-    // }
+    Ok(())
+}
 
-    let item = query.inventory.crafting_result(query.crafting_registry);
+fn close_handled_screen(mut data: &[u8], query: &mut PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+    let pkt = play::CloseHandledScreenC2s::decode(&mut data)?;
 
-    let set_item_pkt = play::ScreenHandlerSlotUpdateS2c {
-        window_id: 0,
-        state_id: VarInt(0),
-        slot_idx: 0, // crafting result
-        slot_data: Cow::Owned(item),
+    let event = CloseScreenEvent {
+        window_id: u8::try_from(pkt.window_id)?,
     };
 
-    query
-        .compose
-        .unicast(&set_item_pkt, query.io_ref, query.system)?;
+    query.handlers.close.trigger_all(query, &event);
+
+    let container_window = query
+        .view
+        .try_get::<&OpenContainer>(|open| i32::from(open.window_id));
 
-    let event = ClickSlotEvent::try_from(pkt)?;
-    query.handlers.click.trigger_all(query, &event);
+    let is_container = container_window == Some(i32::from(pkt.window_id));
+
+    if pkt.window_id != 0 && !is_container {
+        return Ok(());
+    }
+
+    if is_container {
+        query.view.remove::<OpenContainer>();
+    }
+
+    // the carried item and the crafting grid go back into the inventory, or are thrown if it is
+    // full
+    let carried = query
+        .view
+        .get::<&mut CarriedItem>(|carried| std::mem::take(&mut carried.stack));
+
+    // the crafting grid is only part of the player's own window
+    let grid = (1..=4).filter(|_| !is_container).filter_map(|slot| {
+        let stack = query.inventory.get_mut(slot).ok()?;
+        Some(std::mem::replace(stack, ItemStack::EMPTY))
+    });
+
+    let returned: Vec<_> = std::iter::once(carried)
+        .chain(grid)
+        .filter(|stack| !stack.is_empty())
+        .collect();
+
+    for stack in returned {
+        if let Some(remaining) = query.inventory.try_add_item(stack).remaining {
+            throw_item(query, remaining);
+        }
+    }
 
     Ok(())
 }
 
 fn chat_message(mut data: &'static [u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
     // todo: we could technically remove allocations &[u8] exists until end of tick
-    let pkt = play::ChatMessageC2s::decode(&mut data)?;
+    let pkt = packets::ChatMessageC2s::decode(&mut data)?;
     let msg = pkt.message.0;
 
-    query
-        .events
-        .push(event::ChatMessage { msg, by: query.id }, query.world);
+    let signed = secure_chat::acknowledge(query.view, pkt.message_count, pkt.acknowledged)
+        .and_then(|last_seen| secure_chat::signed_message(query.view, &pkt, last_seen));
+
+    let signed = match signed {
+        Ok(signed) => signed,
+        Err(e) => {
+            secure_chat::reject(query.view, &e);
+            return Ok(());
+        }
+    };
+
+    query.events.push(
+        event::ChatMessage {
+            msg,
+            by: query.id,
+            signed,
+        },
+        query.world,
+    );
+
+    Ok(())
+}
+
+fn message_acknowledgment(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+    let pkt = packets::MessageAcknowledgmentC2s::decode(&mut data)?;
+
+    let result = query
+        .view
+        .try_get::<&mut LastSeenMessages>(|seen| seen.apply_offset(pkt.message_count.0));
+
+    if let Some(Err(e)) = result {
+        secure_chat::reject(query.view, &e);
+    }
+
+    Ok(())
+}
+
+fn player_session(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+    let pkt = packets::PlayerSessionC2s::decode(&mut data)?;
+
+    secure_chat::start_session(query.view, &pkt);
 
     Ok(())
 }
@@ -625,20 +1020,26 @@ pub fn packet_switch(
     let data: &'static [u8] = unsafe { core::mem::transmute(data) };
 
     match packet_id {
-        play::ChatMessageC2s::ID => chat_message(data, query)?,
+        packets::ChatMessageC2s::ID => chat_message(data, query)?,
         play::ClickSlotC2s::ID => click_slot(data, query)?,
         play::ClientCommandC2s::ID => client_command(data, query)?,
-        play::CommandExecutionC2s::ID => chat_command(data, query)?,
+        play::ClientStatusC2s::ID => client_status(data, query)?,
+        play::CloseHandledScreenC2s::ID => close_handled_screen(data, query)?,
+        packets::CommandExecutionC2s::ID => chat_command(data, query)?,
         play::CreativeInventoryActionC2s::ID => creative_inventory_action(data, query)?,
         play::CustomPayloadC2s::ID => custom_payload(data, query)?,
         play::FullC2s::ID => full(query, data)?,
         play::HandSwingC2s::ID => hand_swing(data, query)?,
+        play::KeepAliveC2s::ID => keep_alive(data, query)?,
         play::LookAndOnGroundC2s::ID => look_and_on_ground(data, query)?,
+        packets::MessageAcknowledgmentC2s::ID => message_acknowledgment(data, query)?,
         play::PlayerActionC2s::ID => player_action(data, query)?,
         play::PlayerInteractBlockC2s::ID => player_interact_block(data, query)?,
         play::PlayerInteractEntityC2s::ID => player_interact_entity(data, query)?,
         play::PlayerInteractItemC2s::ID => player_interact_item(data, query)?,
+        packets::PlayerSessionC2s::ID => player_session(data, query)?,
         play::PositionAndOnGroundC2s::ID => position_and_on_ground(query, data)?,
+        play::RecipeCategoryOptionsC2s::ID => recipe_category_options(data, query)?,
         play::RequestCommandCompletionsC2s::ID => request_command_completions(data, query)?,
         play::UpdateSelectedSlotC2s::ID => update_selected_slot(data, query)?,
         _ => trace!("unknown packet id: 0x{:02X}", packet_id),
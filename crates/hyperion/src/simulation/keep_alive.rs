@@ -0,0 +1,204 @@
+//! Keep-alive packets, which measure the latency of players and find connections which died.
+//!
+//! Every [`KEEP_ALIVE_INTERVAL`] a player is sent a [`play::KeepAliveS2c`] with a new id, which
+//! the client echoes in a [`play::KeepAliveC2s`]. The time in between is stored in [`Ping`] and
+//! shown in the tab list. Players who do not answer within
+//! [`Global::keep_alive_timeout`](crate::Global::keep_alive_timeout) are kicked.
+
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
+
+use flecs_ecs::prelude::*;
+use tracing::error;
+use valence_protocol::packets::play;
+
+use crate::{
+    egress::player_join::{PlayerListActions, PlayerListEntry, PlayerListS2c},
+    ingress::PendingRemove,
+    net::{Compose, ConnectionId},
+    simulation::{PacketState, Player, Uuid},
+};
+
+/// How long to wait after an answered keep-alive before sending the next one, like vanilla.
+pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The round trip time of the connection of a player, as measured by the last keep-alive.
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[meta]
+pub struct Ping {
+    pub millis: u32,
+}
+
+impl From<Duration> for Ping {
+    fn from(rtt: Duration) -> Self {
+        Self {
+            millis: u32::try_from(rtt.as_millis()).unwrap_or(u32::MAX),
+        }
+    }
+}
+
+/// The state of the keep-alive exchange of a connection.
+#[derive(Component, Debug)]
+pub struct KeepAlive {
+    /// The id of the keep-alive the client has not answered yet, and when it was sent.
+    pending: Option<(i64, Instant)>,
+    /// When the last keep-alive was answered, or the connection was opened.
+    last_answer: Instant,
+    /// Whether [`Ping`] changed since it was last shown in the tab list.
+    measured: bool,
+}
+
+impl KeepAlive {
+    #[must_use]
+    pub const fn new(now: Instant) -> Self {
+        Self {
+            pending: None,
+            last_answer: now,
+            measured: false,
+        }
+    }
+
+    /// The id of the keep-alive to send now, if one is due.
+    pub fn next(&mut self, now: Instant) -> Option<i64> {
+        if self.pending.is_some()
+            || now.saturating_duration_since(self.last_answer) < KEEP_ALIVE_INTERVAL
+        {
+            return None;
+        }
+
+        let id = fastrand::i64(..);
+        self.pending = Some((id, now));
+        Some(id)
+    }
+
+    /// Handles the answer of the client, returning the round trip time if `id` is the one which
+    /// was sent.
+    pub fn answer(&mut self, id: i64, now: Instant) -> Option<Duration> {
+        let (pending, sent) = self.pending?;
+
+        if pending != id {
+            return None;
+        }
+
+        self.pending = None;
+        self.last_answer = now;
+        self.measured = true;
+
+        Some(now - sent)
+    }
+
+    /// Whether the client has not answered the pending keep-alive within `timeout`.
+    #[must_use]
+    pub fn timed_out(&self, now: Instant, timeout: Duration) -> bool {
+        self.pending
+            .is_some_and(|(_, sent)| now.saturating_duration_since(sent) > timeout)
+    }
+
+    const fn take_measured(&mut self) -> bool {
+        let measured = self.measured;
+        self.measured = false;
+        measured
+    }
+}
+
+#[derive(Component)]
+pub struct KeepAliveModule;
+
+impl Module for KeepAliveModule {
+    fn module(world: &World) {
+        world.component::<Ping>().meta();
+        world.component::<KeepAlive>();
+
+        world
+            .component::<Player>()
+            .add_trait::<(flecs::With, Ping)>();
+
+        let players = world
+            .query::<(&ConnectionId, &Uuid, &mut KeepAlive, &Ping)>()
+            .with_enum(PacketState::Play)
+            .build();
+
+        system!("keep_alive", world, &Compose($))
+            .kind::<flecs::pipeline::OnUpdate>()
+            .each_iter(move |it, _, compose| {
+                let system = it.system();
+                let timeout = compose.global().keep_alive_timeout;
+                let now = Instant::now();
+
+                let mut latencies = Vec::new();
+
+                players.each_entity(|entity, (io, uuid, keep_alive, ping)| {
+                    if keep_alive.timed_out(now, timeout) {
+                        entity.set(PendingRemove::new("Timed out"));
+                        return;
+                    }
+
+                    if let Some(id) = keep_alive.next(now) {
+                        let pkt = play::KeepAliveS2c { id };
+
+                        if let Err(e) = compose.unicast(&pkt, *io, system) {
+                            error!("failed to send keep alive: {e}");
+                        }
+                    }
+
+                    if keep_alive.take_measured() {
+                        latencies.push(PlayerListEntry {
+                            player_uuid: uuid.0,
+                            ping: i32::try_from(ping.millis).unwrap_or(i32::MAX),
+                            ..Default::default()
+                        });
+                    }
+                });
+
+                if latencies.is_empty() {
+                    return;
+                }
+
+                // one packet for every player measured this tick
+                let pkt = PlayerListS2c {
+                    actions: PlayerListActions::default().with_update_latency(true),
+                    entries: Cow::Owned(latencies),
+                };
+
+                if let Err(e) = compose.broadcast(&pkt, system).send() {
+                    error!("failed to broadcast latencies: {e}");
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_alive_measures_and_times_out() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+
+        assert_eq!(keep_alive.next(start), None);
+
+        let sent = start + KEEP_ALIVE_INTERVAL;
+        let id = keep_alive.next(sent).unwrap();
+
+        // only one keep-alive is in flight at a time
+        assert_eq!(keep_alive.next(sent + Duration::from_secs(1)), None);
+        assert_eq!(keep_alive.answer(id.wrapping_add(1), sent), None);
+
+        let answered = sent + Duration::from_millis(42);
+        assert_eq!(
+            keep_alive.answer(id, answered),
+            Some(Duration::from_millis(42))
+        );
+        assert!(keep_alive.take_measured());
+
+        let timeout = Duration::from_secs(20);
+        let sent = answered + KEEP_ALIVE_INTERVAL;
+        keep_alive.next(sent).unwrap();
+
+        assert!(!keep_alive.timed_out(sent + timeout, timeout));
+        assert!(keep_alive.timed_out(sent + timeout + Duration::from_secs(1), timeout));
+    }
+}
@@ -0,0 +1,43 @@
+// Extends LivingEntity.
+//
+// Index	Type	Meaning	Default
+// 15	Byte (0)	Armor stand flags	0
+// Bit mask	Meaning
+// 0x01	Is small
+// 0x04	Has arms
+// 0x08	Has no base plate
+// 0x10	Is marker
+// 16	Rotations (9)	Head rotation	(0.0, 0.0, 0.0)
+// 17	Rotations (9)	Body rotation	(0.0, 0.0, 0.0)
+// 18	Rotations (9)	Left arm rotation	(-10.0, 0.0, -10.0)
+// 19	Rotations (9)	Right arm rotation	(-15.0, 0.0, 10.0)
+// 20	Rotations (9)	Left leg rotation	(-1.0, 0.0, -1.0)
+// 21	Rotations (9)	Right leg rotation	(1.0, 0.0, 1.0)
+
+use flecs_ecs::prelude::*;
+
+use super::Metadata;
+use crate::define_and_register_components;
+
+define_and_register_components! {
+    15, ArmorStandFlags -> u8,
+    // 16, HeadRotation -> Rotations,
+    // 17, BodyRotation -> Rotations,
+    // 18, LeftArmRotation -> Rotations,
+    // 19, RightArmRotation -> Rotations,
+    // 20, LeftLegRotation -> Rotations,
+    // 21, RightLegRotation -> Rotations,
+}
+
+impl Default for ArmorStandFlags {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl ArmorStandFlags {
+    pub const HAS_ARMS: u8 = 0x04;
+    pub const IS_MARKER: u8 = 0x10;
+    pub const IS_SMALL: u8 = 0x01;
+    pub const NO_BASE_PLATE: u8 = 0x08;
+}
@@ -0,0 +1,70 @@
+// Extends Entity.
+//
+// Index	Type	Meaning	Default
+// 8	Slot (7)	Item	Empty
+
+use derive_more::{Constructor, Deref, DerefMut};
+use flecs_ecs::prelude::*;
+use valence_protocol::ItemStack;
+
+use super::{Metadata, MetadataChanges};
+use crate::Prev;
+
+/// The stack shown by an item entity.
+///
+/// [`ItemStack`] is not [`Copy`], so unlike the metadata defined with
+/// [`crate::define_and_register_components`] this has its own change detection.
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    PartialEq,
+    Default,
+    Deref,
+    DerefMut,
+    Constructor
+)]
+pub struct Item {
+    stack: ItemStack,
+}
+
+impl Metadata for Item {
+    type Type = ItemStack;
+
+    const INDEX: u8 = 8;
+
+    fn to_type(self) -> Self::Type {
+        self.stack
+    }
+}
+
+#[must_use]
+pub fn register_prefab(world: &World, entity_base: Option<Entity>) -> EntityView<'_> {
+    world.component::<Item>();
+
+    system!(
+        "exchange_item",
+        world,
+        &mut (Prev, Item),
+        &Item,
+        &mut MetadataChanges,
+    )
+    .multi_threaded()
+    .kind::<flecs::pipeline::OnUpdate>()
+    .each(|(prev, current, metadata_changes)| {
+        if prev != current {
+            metadata_changes.encode(current.clone());
+            prev.clone_from(current);
+        }
+    });
+
+    let mut entity = world.prefab();
+
+    if let Some(entity_base) = entity_base {
+        entity = entity.is_a_id(entity_base);
+    }
+
+    entity
+        .set_pair::<Prev, _>(Item::default())
+        .set(Item::default())
+}
@@ -0,0 +1,29 @@
+// Extends LivingEntity.
+//
+// Index	Type	Meaning	Default
+// 15	Byte (0)	Mob flags	0
+// Bit mask	Meaning
+// 0x01	NoAI
+// 0x02	Is left handed
+// 0x04	Is aggressive
+
+use flecs_ecs::prelude::*;
+
+use super::Metadata;
+use crate::define_and_register_components;
+
+define_and_register_components! {
+    15, MobFlags -> u8,
+}
+
+impl Default for MobFlags {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl MobFlags {
+    pub const AGGRESSIVE: u8 = 0x04;
+    pub const LEFT_HANDED: u8 = 0x02;
+    pub const NO_AI: u8 = 0x01;
+}
@@ -13,10 +13,13 @@ use crate::{
     simulation::metadata::entity::{EntityFlags, Pose},
 };
 
+pub mod armor_stand;
 pub mod block_display;
 pub mod display;
 pub mod entity;
+pub mod item;
 pub mod living_entity;
+pub mod mob;
 pub mod player;
 
 #[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Default)]
@@ -26,7 +29,11 @@ pub struct MetadataPrefabs {
     pub display_base: Entity,
     pub block_display_base: Entity,
 
+    pub item_base: Entity,
+
     pub living_entity_base: Entity,
+    pub mob_base: Entity,
+    pub armor_stand_base: Entity,
     pub player_base: Entity,
 }
 
@@ -130,7 +137,11 @@ pub fn register_prefabs(world: &World) -> MetadataPrefabs {
     let display_base = display::register_prefab(world, Some(entity_base)).id();
     let block_display_base = block_display::register_prefab(world, Some(display_base)).id();
 
+    let item_base = item::register_prefab(world, Some(entity_base)).id();
+
     let living_entity_base = living_entity::register_prefab(world, Some(entity_base)).id();
+    let mob_base = mob::register_prefab(world, Some(living_entity_base)).id();
+    let armor_stand_base = armor_stand::register_prefab(world, Some(living_entity_base)).id();
     let player_base = player::register_prefab(world, Some(living_entity_base))
         // .add::<Player>()
         .add_enum(EntityKind::Player)
@@ -140,7 +151,10 @@ pub fn register_prefabs(world: &World) -> MetadataPrefabs {
         entity_base,
         display_base,
         block_display_base,
+        item_base,
         living_entity_base,
+        mob_base,
+        armor_stand_base,
         player_base,
     }
 }
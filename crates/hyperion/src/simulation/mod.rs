@@ -1,4 +1,10 @@
-use std::{borrow::Borrow, collections::HashMap, hash::Hash, sync::Arc};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    sync::Arc,
+};
 
 use bow::BowCharging;
 use bytemuck::{Pod, Zeroable};
@@ -16,25 +22,40 @@ use valence_generated::block::BlockState;
 use valence_protocol::{ByteAngle, VarInt, packets::play};
 
 use crate::{
-    Global,
+    Global, Prev,
     net::{Compose, DataBundle},
     simulation::{
         command::Command,
-        entity_kind::EntityKind,
+        entity_kind::{EntityKind, SpawnPacketKind},
         metadata::{Metadata, MetadataPrefabs, entity::EntityFlags},
+        spawn::{Despawn, EntityKindPrefabs, ExperienceOrbValue, Relocation},
     },
     storage::ThreadLocalVec,
 };
 
 pub mod animation;
 pub mod blocks;
+pub mod boss_bar;
 pub mod bow;
 pub mod command;
+pub mod container;
+pub mod death;
+pub mod effect;
 pub mod entity_kind;
+pub mod equipment;
 pub mod event;
+pub mod furnace;
+pub mod game_mode;
 pub mod handlers;
+pub mod keep_alive;
 pub mod metadata;
+pub mod player_data;
+pub mod recipe_book;
+pub mod scoreboard;
+pub mod secure_chat;
 pub mod skin;
+pub mod spawn;
+pub mod tab_list;
 pub mod util;
 
 #[derive(Component, Default, Debug, Deref, DerefMut)]
@@ -88,6 +109,10 @@ impl<K: Eq + Hash, V> DeferredMap<K, V> {
     pub fn remove(&self, key: K, world: &World) {
         self.to_remove.push(key, world);
     }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.map.values()
+    }
 }
 
 impl<K: Eq + Hash, V> DeferredMap<K, V> {
@@ -328,6 +353,10 @@ impl Default for Comms {
     }
 }
 
+/// The IP address a player connected from. It is unspecified if the proxy does not know it.
+#[derive(Component, Copy, Clone, Debug, Deref, Hash, Eq, PartialEq, Display)]
+pub struct PlayerIp(pub IpAddr);
+
 /// A UUID component. Generally speaking, this tends to be tied to entities with a [`Player`] component.
 #[derive(
     Component, Copy, Clone, Debug, Deref, From, Hash, Eq, PartialEq, Display
@@ -604,6 +633,27 @@ impl Module for SimModule {
 
         world.set(prefabs);
 
+        world.import::<effect::StatusEffectModule>();
+        world.import::<game_mode::GameModeModule>();
+        world.import::<death::DeathModule>();
+        world.import::<container::ContainerModule>();
+        world.import::<furnace::FurnaceModule>();
+        world.import::<recipe_book::RecipeBookModule>();
+        world.import::<player_data::PlayerDataModule>();
+        world.import::<keep_alive::KeepAliveModule>();
+        world.import::<scoreboard::ScoreboardModule>();
+        world.import::<secure_chat::SecureChatModule>();
+        world.import::<boss_bar::BossBarModule>();
+        world.import::<tab_list::TabListModule>();
+
+        world.component::<EntityKindPrefabs>();
+        world.component::<ExperienceOrbValue>();
+        world.component::<Despawn>();
+        world.component::<Relocation>();
+
+        let kind_prefabs = spawn::register_prefabs(world, &prefabs);
+        world.set(kind_prefabs);
+
         world.component::<Xp>().meta();
 
         world.component::<PlayerSkin>();
@@ -622,11 +672,14 @@ impl Module for SimModule {
         world.component::<Uuid>();
         component!(world, Uuid).opaque_func(meta_ser_stringify_type_display::<Uuid>);
 
+        world.component::<PlayerIp>();
+
         world.component::<ChunkPosition>().meta();
         world.component::<ConfirmBlockSequences>();
         world.component::<animation::ActiveAnimation>();
 
         world.component::<hyperion_inventory::PlayerInventory>();
+        world.component::<equipment::Equipment>();
 
         world.component::<BowCharging>();
         component!(world, BowCharging).opaque_func(meta_ser_stringify_type_display::<BowCharging>);
@@ -652,32 +705,75 @@ impl Module for SimModule {
             let mut bundle = DataBundle::new(compose, system);
 
             let mut spawn_entity = move |kind: EntityKind| -> anyhow::Result<()> {
-                let kind = kind as i32;
-
                 let velocity = velocity.to_packet_units();
 
-                let packet = play::EntitySpawnS2c {
-                    entity_id: VarInt(minecraft_id),
-                    object_uuid: uuid.0,
-                    kind: VarInt(kind),
-                    position: position.as_dvec3(),
-                    pitch: ByteAngle::from_degrees(**pitch),
-                    yaw: ByteAngle::from_degrees(**yaw),
-                    head_yaw: ByteAngle::from_degrees(0.0), // todo:
-                    data: VarInt::default(),                // todo:
-                    velocity,
-                };
-
-                bundle.add_packet(&packet).unwrap();
+                match kind.spawn_packet() {
+                    SpawnPacketKind::Entity => {
+                        let packet = play::EntitySpawnS2c {
+                            entity_id: VarInt(minecraft_id),
+                            object_uuid: uuid.0,
+                            kind: VarInt(kind.id()),
+                            position: position.as_dvec3(),
+                            pitch: ByteAngle::from_degrees(**pitch),
+                            yaw: ByteAngle::from_degrees(**yaw),
+                            head_yaw: ByteAngle::from_degrees(**yaw),
+                            data: VarInt::default(), // todo:
+                            velocity,
+                        };
+
+                        bundle.add_packet(&packet)?;
+                    }
+                    SpawnPacketKind::ExperienceOrb => {
+                        let count = entity
+                            .try_get::<&ExperienceOrbValue>(|value| value.0)
+                            .unwrap_or_default();
+
+                        let packet = play::ExperienceOrbSpawnS2c {
+                            entity_id: VarInt(minecraft_id),
+                            position: position.as_dvec3(),
+                            count,
+                        };
+
+                        bundle.add_packet(&packet)?;
+                    }
+                    SpawnPacketKind::Player => {
+                        let packet = play::PlayerSpawnS2c {
+                            entity_id: VarInt(minecraft_id),
+                            player_uuid: uuid.0,
+                            position: position.as_dvec3(),
+                            yaw: ByteAngle::from_degrees(**yaw),
+                            pitch: ByteAngle::from_degrees(**pitch),
+                        };
+
+                        bundle.add_packet(&packet)?;
+                    }
+                }
 
                 let packet = play::EntityVelocityUpdateS2c {
                     entity_id: VarInt(minecraft_id),
                     velocity,
                 };
 
-                bundle.add_packet(&packet).unwrap();
+                bundle.add_packet(&packet)?;
+
+                if let Some(packet) = entity
+                    .try_get::<&equipment::Equipment>(|equipment| {
+                        equipment.snapshot(VarInt(minecraft_id))
+                    })
+                    .flatten()
+                {
+                    bundle.add_packet(&packet)?;
+                }
+
+                if let Some(packets) = entity.try_get::<&effect::ActiveEffects>(|effects| {
+                    effects.packets(VarInt(minecraft_id)).collect::<Vec<_>>()
+                }) {
+                    for packet in packets {
+                        bundle.add_packet(&packet)?;
+                    }
+                }
 
-                bundle.broadcast_local(position.to_chunk()).unwrap();
+                bundle.broadcast_local(position.to_chunk())?;
 
                 Ok(())
             };
@@ -691,31 +787,73 @@ impl Module for SimModule {
             });
         });
 
-        // for every new entity without a UUID, give it one
+        spawn::add_uuid_observer(world);
+
         world
-            .observer::<flecs::OnAdd, ()>()
+            .observer::<flecs::OnSet, ()>()
             .with_enum_wildcard::<EntityKind>()
-            .without::<Uuid>()
             .each_entity(|entity, ()| {
-                debug!("adding uuid to entity");
-                let uuid = uuid::Uuid::new_v4();
-                entity.set(Uuid::from(uuid));
+                let world = entity.world();
+                let prefab = world.get::<&EntityKindPrefabs>(|prefabs| {
+                    entity.get::<&EntityKind>(|kind| prefabs.get(*kind))
+                });
+
+                entity.is_a_id(prefab);
             });
 
-        world
-            .observer::<flecs::OnSet, ()>()
+        observer!(world, Despawn, &Compose($), [filter] &Position)
             .with_enum_wildcard::<EntityKind>()
-            .each_entity(move |entity, ()| {
-                entity.get::<&EntityKind>(|kind| match kind {
-                    EntityKind::BlockDisplay => {
-                        entity.is_a_id(prefabs.block_display_base);
-                    }
-                    EntityKind::Player => {
-                        entity.is_a_id(prefabs.player_base);
-                    }
-                    _ => {}
-                });
+            .each_iter(|it, row, (compose, position)| {
+                let system = it.system();
+                let entity = it.entity(row);
+
+                let entity_ids = [VarInt(entity.minecraft_id())];
+                let packet = play::EntitiesDestroyS2c {
+                    entity_ids: Cow::Borrowed(&entity_ids),
+                };
+
+                if let Err(e) = compose
+                    .broadcast_local(&packet, position.to_chunk(), system)
+                    .send()
+                {
+                    error!("failed to despawn entity: {e}");
+                }
+
+                entity.destruct();
             });
+
+        observer!(
+            world,
+            flecs::OnSet,
+            &Relocation,
+            &Compose($),
+            &mut Position,
+        )
+        .with_enum_wildcard::<EntityKind>()
+        .each_iter(|it, row, (relocation, compose, position)| {
+            let system = it.system();
+            let entity = it.entity(row);
+
+            let entity_ids = [VarInt(entity.minecraft_id())];
+            let packet = play::EntitiesDestroyS2c {
+                entity_ids: Cow::Borrowed(&entity_ids),
+            };
+
+            if let Err(e) = compose
+                .broadcast_local(&packet, position.to_chunk(), system)
+                .send()
+            {
+                error!("failed to relocate entity: {e}");
+            }
+
+            **position = relocation.to;
+
+            // the new location is sent with the spawn packet, so there is no movement to sync
+            entity
+                .set_pair::<Prev, Position>(*position)
+                .remove::<Relocation>()
+                .enqueue(Spawn);
+        });
     }
 }
 
@@ -0,0 +1,170 @@
+//! Restoring and storing the [`Persist`] components of players.
+//!
+//! The experience, health and inventory of players are stored by default. Other components are
+//! added with [`PersistExt::persist`]:
+//!
+//! ```ignore
+//! world.persist::<KillCount>();
+//! ```
+
+use anyhow::{Context, bail};
+use flecs_ecs::prelude::*;
+use hyperion_inventory::PlayerInventory;
+use tracing::error;
+use valence_protocol::{Decode, Encode, ItemStack};
+
+use crate::{
+    ingress::PendingRemove,
+    simulation::{Player, Uuid, Xp, metadata::living_entity::Health},
+    storage::{Persist, PersistExt, PersistedComponents, PlayerDataHandler},
+};
+
+impl Persist for Xp {
+    const KEY: &'static str = "hyperion:xp";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.amount.to_le_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let amount = bytes.try_into().context("xp is not a u16")?;
+
+        Ok(Self {
+            amount: u16::from_le_bytes(amount),
+        })
+    }
+}
+
+impl Persist for Health {
+    const KEY: &'static str = "hyperion:health";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.to_le_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let health = bytes.try_into().context("health is not an f32")?;
+        let health = f32::from_le_bytes(health);
+
+        // players who left while dead come back alive
+        if health <= 0.0 {
+            return Ok(Self::default());
+        }
+
+        Ok(Self::new(health))
+    }
+}
+
+impl Persist for PlayerInventory {
+    const KEY: &'static str = "hyperion:inventory";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+
+        let hand_slot = self.get_cursor_index() - Self::HOTBAR_START_SLOT;
+        hand_slot.encode(&mut bytes)?;
+
+        for stack in self.slots() {
+            stack.encode(&mut bytes)?;
+        }
+
+        Ok(bytes)
+    }
+
+    fn decode(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut inventory = Self::default();
+
+        let hand_slot = u16::decode(&mut bytes)?;
+
+        if hand_slot >= 9 {
+            bail!("hand slot {hand_slot} is not in the hotbar");
+        }
+
+        inventory.set_cursor(hand_slot);
+
+        let slots = u16::try_from(inventory.slots().len())?;
+
+        for slot in 0..slots {
+            inventory.set(slot, ItemStack::decode(&mut bytes)?)?;
+        }
+
+        Ok(inventory)
+    }
+}
+
+#[derive(Component)]
+pub struct PlayerDataModule;
+
+impl Module for PlayerDataModule {
+    fn module(world: &World) {
+        world.persist::<Xp>();
+        world.persist::<Health>();
+        world.persist::<PlayerInventory>();
+
+        // the uuid is set during login, before the player joins the world
+        observer!(
+            world,
+            flecs::OnSet,
+            &Uuid,
+            &PlayerDataHandler($),
+            &PersistedComponents($),
+        )
+        .with::<Player>()
+        .each_entity(
+            |entity, (uuid, handler, persisted)| match handler.find(**uuid) {
+                Ok(Some(stored)) => persisted.restore(entity, &stored),
+                Ok(None) => {}
+                Err(e) => error!("failed to load the data of {uuid}: {e}"),
+            },
+        );
+
+        observer!(
+            world,
+            flecs::OnSet,
+            &PendingRemove,
+            [filter] &Uuid,
+            &PlayerDataHandler($),
+            &PersistedComponents($),
+        )
+        .with::<Player>()
+        .each_entity(|entity, (_, uuid, handler, persisted)| {
+            let stored = persisted.save(entity);
+
+            if let Err(e) = handler.insert(**uuid, &stored) {
+                error!("failed to store the data of {uuid}: {e}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn inventory_round_trip() {
+        let mut inventory = PlayerInventory::default();
+        inventory.set_cursor(4);
+        inventory.set_hotbar(4, ItemStack::new(ItemKind::DiamondSword, 1, None));
+        inventory.set_helmet(ItemStack::new(ItemKind::IronHelmet, 1, None));
+        inventory
+            .set(12, ItemStack::new(ItemKind::Cobblestone, 64, None))
+            .unwrap();
+
+        let bytes = Persist::encode(&inventory).unwrap();
+        let restored = <PlayerInventory as Persist>::decode(&bytes).unwrap();
+
+        assert_eq!(restored.slots(), inventory.slots());
+        assert_eq!(restored.get_cursor().item, ItemKind::DiamondSword);
+    }
+
+    #[test]
+    fn hand_slot_outside_hotbar_is_rejected() {
+        let mut bytes = Persist::encode(&PlayerInventory::default()).unwrap();
+        bytes[..2].copy_from_slice(&9_u16.to_be_bytes());
+
+        assert!(<PlayerInventory as Persist>::decode(&bytes).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! The recipe book of each player.
+//!
+//! Players get their [`UnlockedRecipes`] from the [`RecipeBookHandler`] when they connect, and
+//! the recipes are stored again when they leave. Unlocking or locking a recipe, for example as the
+//! reward of an event, is sent to the client at the end of the tick.
+//!
+//! ```ignore
+//! player.get::<&mut UnlockedRecipes>(|recipes| {
+//!     recipes.unlock("hyperion:crafting_table");
+//! });
+//! ```
+
+use flecs_ecs::prelude::*;
+use hyperion_crafting::recipe_book::UnlockedRecipes;
+use tracing::error;
+
+use crate::{
+    net::{Compose, ConnectionId},
+    simulation::{PacketState, Player, Uuid, event},
+    storage::{EventQueue, RecipeBookHandler},
+};
+
+#[derive(Component)]
+pub struct RecipeBookModule;
+
+impl Module for RecipeBookModule {
+    fn module(world: &World) {
+        world.component::<UnlockedRecipes>();
+
+        observer!(world, flecs::OnSet, &Uuid, &RecipeBookHandler($))
+            .with::<Player>()
+            .each_entity(|entity, (uuid, handler)| {
+                let recipes = handler.find(**uuid).unwrap_or_else(|e| {
+                    error!("failed to load the recipe book of {uuid}: {e}");
+                    None
+                });
+
+                entity.set(recipes.unwrap_or_default());
+            });
+
+        observer!(
+            world,
+            flecs::OnRemove,
+            &Uuid,
+            &UnlockedRecipes,
+            &RecipeBookHandler($),
+        )
+        .with::<Player>()
+        .each(|(uuid, recipes, handler)| {
+            if let Err(e) = handler.insert(**uuid, recipes) {
+                error!("failed to store the recipe book of {uuid}: {e}");
+            }
+        });
+
+        system!(
+            "recipe_book_settings",
+            world,
+            &mut EventQueue<event::RecipeBookSettings>($),
+        )
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each_iter(|it, _, event_queue| {
+            let world = it.world();
+
+            for event in event_queue.drain() {
+                let player = world.entity_from_id(event.by);
+
+                if !player.is_alive() {
+                    continue;
+                }
+
+                player.try_get::<&mut UnlockedRecipes>(|recipes| {
+                    recipes.set_book(event.book, event.state);
+                });
+            }
+        });
+
+        system!(
+            "sync_unlocked_recipes",
+            world,
+            &Compose($),
+            &ConnectionId,
+            &mut UnlockedRecipes,
+        )
+        .with_enum(PacketState::Play)
+        .kind::<flecs::pipeline::OnStore>()
+        .each_iter(|it, _, (compose, io, recipes)| {
+            let system = it.system();
+
+            for pkt in recipes.take_changes() {
+                if let Err(e) = compose.unicast(&pkt, *io, system) {
+                    error!("failed to send unlocked recipes: {e}");
+                }
+            }
+        });
+    }
+}
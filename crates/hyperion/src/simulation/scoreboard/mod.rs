@@ -0,0 +1,440 @@
+//! Scoreboard objectives, sidebars and teams.
+//!
+//! [`Scoreboard`] holds the objectives every player sees. Changing it queues only what changed,
+//! which is broadcast once per tick, and players who join are sent the whole state. A
+//! [`Sidebar`] is a sidebar of text lines only its player sees, and [`Teams`] give players
+//! nametag prefixes, colors and collision rules.
+
+use std::{collections::BTreeMap, io::Write};
+
+use flecs_ecs::prelude::*;
+use tracing::{error, warn};
+pub use valence_protocol::packets::play::scoreboard_objective_update_s2c::ObjectiveRenderType;
+use valence_protocol::{
+    Packet, VarInt,
+    packets::play::{
+        ScoreboardDisplayS2c, ScoreboardObjectiveUpdateS2c, ScoreboardPlayerUpdateS2c,
+        scoreboard_display_s2c::ScoreboardPosition, scoreboard_objective_update_s2c::ObjectiveMode,
+        scoreboard_player_update_s2c::ScoreboardPlayerUpdateAction,
+    },
+};
+use valence_text::{IntoText, Text};
+
+use crate::{
+    PacketBundle,
+    ingress::PendingRemove,
+    net::{Compose, ConnectionId, DataBundle},
+    simulation::{Name, PacketState, Player},
+};
+
+pub mod sidebar;
+pub mod team;
+
+pub use sidebar::Sidebar;
+pub use team::{ScoreboardTeam, TeamOptions, Teams};
+
+/// Where the client shows an objective.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DisplaySlot {
+    /// Next to the names in the tab list.
+    List,
+    Sidebar,
+    /// Below the nametags of players.
+    BelowName,
+}
+
+impl DisplaySlot {
+    pub const ALL: [Self; 3] = [Self::List, Self::Sidebar, Self::BelowName];
+
+    const fn position(self) -> ScoreboardPosition {
+        match self {
+            Self::List => ScoreboardPosition::List,
+            Self::Sidebar => ScoreboardPosition::Sidebar,
+            Self::BelowName => ScoreboardPosition::BelowName,
+        }
+    }
+}
+
+/// A change to the scoreboard of a client, kept until it is sent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScoreboardUpdate {
+    Objective {
+        name: String,
+        mode: ObjectiveMode,
+    },
+    /// Shows `objective` in `slot`; an empty name clears the slot.
+    Display {
+        slot: DisplaySlot,
+        objective: String,
+    },
+    /// Sets the score of `holder`, or removes it if `score` is [`None`].
+    Score {
+        objective: String,
+        holder: String,
+        score: Option<i32>,
+    },
+}
+
+impl PacketBundle for &ScoreboardUpdate {
+    fn encode_including_ids(self, w: impl Write) -> anyhow::Result<()> {
+        match self {
+            ScoreboardUpdate::Objective { name, mode } => ScoreboardObjectiveUpdateS2c {
+                objective_name: name,
+                mode: mode.clone(),
+            }
+            .encode_with_id(w),
+            ScoreboardUpdate::Display { slot, objective } => ScoreboardDisplayS2c {
+                position: slot.position(),
+                score_name: objective,
+            }
+            .encode_with_id(w),
+            ScoreboardUpdate::Score {
+                objective,
+                holder,
+                score,
+            } => {
+                let action = match score {
+                    Some(score) => ScoreboardPlayerUpdateAction::Update {
+                        objective_name: objective,
+                        objective_score: VarInt(*score),
+                    },
+                    None => ScoreboardPlayerUpdateAction::Remove {
+                        objective_name: objective,
+                    },
+                };
+
+                ScoreboardPlayerUpdateS2c {
+                    entity_name: holder,
+                    action,
+                }
+                .encode_with_id(w)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Objective {
+    display_name: Text,
+    render_type: ObjectiveRenderType,
+    scores: BTreeMap<String, i32>,
+}
+
+/// The objectives every player sees.
+#[derive(Component, Debug, Default)]
+pub struct Scoreboard {
+    objectives: BTreeMap<String, Objective>,
+    displayed: [Option<String>; 3],
+    pending: Vec<ScoreboardUpdate>,
+}
+
+impl Scoreboard {
+    /// Adds an objective, or changes how it is shown if it already exists.
+    pub fn objective(
+        &mut self,
+        name: &str,
+        display_name: impl IntoText<'static>,
+        render_type: ObjectiveRenderType,
+    ) {
+        let display_name = display_name.into_cow_text().into_owned();
+
+        let mode = if let Some(objective) = self.objectives.get_mut(name) {
+            if objective.display_name == display_name && objective.render_type == render_type {
+                return;
+            }
+
+            objective.display_name.clone_from(&display_name);
+            objective.render_type = render_type;
+
+            ObjectiveMode::Update {
+                objective_display_name: display_name,
+                render_type,
+            }
+        } else {
+            self.objectives.insert(name.to_owned(), Objective {
+                display_name: display_name.clone(),
+                render_type,
+                scores: BTreeMap::new(),
+            });
+
+            ObjectiveMode::Create {
+                objective_display_name: display_name,
+                render_type,
+            }
+        };
+
+        self.pending.push(ScoreboardUpdate::Objective {
+            name: name.to_owned(),
+            mode,
+        });
+    }
+
+    /// Removes an objective with all of its scores.
+    pub fn remove_objective(&mut self, name: &str) {
+        if self.objectives.remove(name).is_none() {
+            return;
+        }
+
+        // the client clears the slots of removed objectives by itself
+        for displayed in &mut self.displayed {
+            if displayed.as_deref() == Some(name) {
+                *displayed = None;
+            }
+        }
+
+        self.pending.push(ScoreboardUpdate::Objective {
+            name: name.to_owned(),
+            mode: ObjectiveMode::Remove,
+        });
+    }
+
+    /// Shows `objective` in `slot`, or clears the slot if it is [`None`].
+    pub fn display(&mut self, slot: DisplaySlot, objective: Option<&str>) {
+        if let Some(objective) = objective
+            && !self.objectives.contains_key(objective)
+        {
+            warn!("cannot display the unknown objective {objective}");
+            return;
+        }
+
+        let displayed = &mut self.displayed[slot as usize];
+
+        if displayed.as_deref() == objective {
+            return;
+        }
+
+        *displayed = objective.map(str::to_owned);
+
+        self.pending.push(ScoreboardUpdate::Display {
+            slot,
+            objective: objective.unwrap_or_default().to_owned(),
+        });
+    }
+
+    /// The objective shown in `slot`.
+    #[must_use]
+    pub fn displayed(&self, slot: DisplaySlot) -> Option<&str> {
+        self.displayed[slot as usize].as_deref()
+    }
+
+    #[must_use]
+    pub fn score(&self, objective: &str, holder: &str) -> Option<i32> {
+        self.objectives.get(objective)?.scores.get(holder).copied()
+    }
+
+    /// Sets the score of `holder`, which is usually the name of a player. Nothing is sent if the
+    /// score did not change.
+    pub fn set_score(&mut self, objective: &str, holder: &str, score: i32) {
+        let Some(scores) = self.objectives.get_mut(objective).map(|o| &mut o.scores) else {
+            warn!("cannot set a score of the unknown objective {objective}");
+            return;
+        };
+
+        if scores.get(holder) == Some(&score) {
+            return;
+        }
+
+        scores.insert(holder.to_owned(), score);
+
+        self.pending.push(ScoreboardUpdate::Score {
+            objective: objective.to_owned(),
+            holder: holder.to_owned(),
+            score: Some(score),
+        });
+    }
+
+    pub fn remove_score(&mut self, objective: &str, holder: &str) {
+        let Some(objective_state) = self.objectives.get_mut(objective) else {
+            return;
+        };
+
+        if objective_state.scores.remove(holder).is_none() {
+            return;
+        }
+
+        self.pending.push(ScoreboardUpdate::Score {
+            objective: objective.to_owned(),
+            holder: holder.to_owned(),
+            score: None,
+        });
+    }
+
+    /// Removes the scores of `holder` in every objective, such as when a player leaves.
+    pub fn remove_holder(&mut self, holder: &str) {
+        for (name, objective) in &mut self.objectives {
+            if objective.scores.remove(holder).is_some() {
+                self.pending.push(ScoreboardUpdate::Score {
+                    objective: name.clone(),
+                    holder: holder.to_owned(),
+                    score: None,
+                });
+            }
+        }
+    }
+
+    /// Takes the changes made since the last call.
+    pub fn take_updates(&mut self) -> Vec<ScoreboardUpdate> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// The updates which recreate the whole scoreboard on a client. Every objective is removed
+    /// first, so it does not matter what the client has already been sent.
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<ScoreboardUpdate> {
+        let mut updates = Vec::new();
+
+        for (name, objective) in &self.objectives {
+            updates.push(ScoreboardUpdate::Objective {
+                name: name.clone(),
+                mode: ObjectiveMode::Remove,
+            });
+
+            updates.push(ScoreboardUpdate::Objective {
+                name: name.clone(),
+                mode: ObjectiveMode::Create {
+                    objective_display_name: objective.display_name.clone(),
+                    render_type: objective.render_type,
+                },
+            });
+
+            for (holder, score) in &objective.scores {
+                updates.push(ScoreboardUpdate::Score {
+                    objective: name.clone(),
+                    holder: holder.clone(),
+                    score: Some(*score),
+                });
+            }
+        }
+
+        for slot in DisplaySlot::ALL {
+            if let Some(objective) = self.displayed(slot) {
+                updates.push(ScoreboardUpdate::Display {
+                    slot,
+                    objective: objective.to_owned(),
+                });
+            }
+        }
+
+        updates
+    }
+}
+
+/// Marks players who have been sent the whole [`Scoreboard`] and [`Teams`].
+#[derive(Component)]
+struct ScoreboardSynced;
+
+#[derive(Component)]
+pub struct ScoreboardModule;
+
+impl Module for ScoreboardModule {
+    fn module(world: &World) {
+        world.component::<Scoreboard>();
+        world.component::<ScoreboardSynced>();
+        world.component::<Sidebar>();
+        world.component::<ScoreboardTeam>();
+        world.component::<Teams>();
+
+        world.set(Scoreboard::default());
+        world.set(Teams::default());
+
+        team::observers(world);
+
+        observer!(
+            world,
+            flecs::OnSet,
+            &PendingRemove,
+            [filter] &Name,
+            &mut Teams($),
+        )
+        .with::<Player>()
+        .each(|(_, name, teams)| {
+            teams.forget(name);
+        });
+
+        let joined = world
+            .query::<&ConnectionId>()
+            .with_enum(PacketState::Play)
+            .without::<ScoreboardSynced>()
+            .build();
+
+        system!(
+            "sync_scoreboard",
+            world,
+            &Compose($),
+            &mut Scoreboard($),
+            &mut Teams($),
+        )
+        .kind::<flecs::pipeline::OnStore>()
+        .each_iter(move |it, _, (compose, scoreboard, teams)| {
+            let system = it.system();
+
+            for update in scoreboard.take_updates() {
+                if let Err(e) = compose.broadcast(&update, system).send() {
+                    error!("failed to broadcast scoreboard update: {e}");
+                }
+            }
+
+            for update in teams.take_updates() {
+                if let Err(e) = compose.broadcast(&update, system).send() {
+                    error!("failed to broadcast team update: {e}");
+                }
+            }
+
+            // players who joined since the last tick get everything, after the changes above so
+            // nothing is created twice
+            joined.each_entity(|entity, io| {
+                let mut bundle = DataBundle::new(compose, system);
+
+                let mut run = || {
+                    for update in teams.snapshot() {
+                        bundle.add_packet(&update)?;
+                    }
+
+                    for update in scoreboard.snapshot() {
+                        bundle.add_packet(&update)?;
+                    }
+
+                    bundle.unicast(*io)
+                };
+
+                if let Err(e) = run() {
+                    error!("failed to send the scoreboard: {e}");
+                }
+
+                entity.add::<ScoreboardSynced>();
+            });
+        });
+
+        sidebar::systems(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_are_diffed() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.objective("kills", "Kills", ObjectiveRenderType::Integer);
+        scoreboard.display(DisplaySlot::Sidebar, Some("kills"));
+        scoreboard.set_score("kills", "Notch", 1);
+        assert_eq!(scoreboard.take_updates().len(), 3);
+
+        scoreboard.set_score("kills", "Notch", 1);
+        scoreboard.display(DisplaySlot::Sidebar, Some("kills"));
+        scoreboard.objective("kills", "Kills", ObjectiveRenderType::Integer);
+        assert!(scoreboard.take_updates().is_empty());
+
+        scoreboard.set_score("kills", "Notch", 2);
+        assert_eq!(scoreboard.take_updates(), [ScoreboardUpdate::Score {
+            objective: "kills".to_owned(),
+            holder: "Notch".to_owned(),
+            score: Some(2),
+        }]);
+
+        scoreboard.remove_objective("kills");
+        assert_eq!(scoreboard.displayed(DisplaySlot::Sidebar), None);
+        assert_eq!(scoreboard.score("kills", "Notch"), None);
+    }
+}
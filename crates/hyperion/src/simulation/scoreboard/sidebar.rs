@@ -0,0 +1,332 @@
+//! A sidebar of lines of text which only its player sees.
+//!
+//! Every line is an invisible score holder in a team of its own, and the text of the line is the
+//! prefix of that team. Changing the text of a line only changes the prefix, so the sidebar can be
+//! rewritten every tick without flickering.
+
+use std::borrow::Cow;
+
+use flecs_ecs::prelude::*;
+use tracing::error;
+use valence_protocol::{
+    VarInt,
+    packets::play::{
+        ScoreboardDisplayS2c, ScoreboardObjectiveUpdateS2c, ScoreboardPlayerUpdateS2c, TeamS2c,
+        scoreboard_display_s2c::ScoreboardPosition,
+        scoreboard_objective_update_s2c::{ObjectiveMode, ObjectiveRenderType},
+        scoreboard_player_update_s2c::ScoreboardPlayerUpdateAction,
+        team_s2c::{CollisionRule, Mode, NameTagVisibility, TeamColor, TeamFlags},
+    },
+};
+use valence_text::{IntoText, Text};
+
+use crate::{
+    net::{Compose, ConnectionId, DataBundle},
+    simulation::PacketState,
+};
+
+const OBJECTIVE: &str = "hyperion:sidebar";
+
+/// The most lines the client shows.
+pub const MAX_LINES: usize = 15;
+
+/// The score holders of the lines. They are formatting codes, so the client shows nothing.
+const HOLDERS: [&str; MAX_LINES] = [
+    "§0§r", "§1§r", "§2§r", "§3§r", "§4§r", "§5§r", "§6§r", "§7§r", "§8§r", "§9§r", "§a§r", "§b§r",
+    "§c§r", "§d§r", "§e§r",
+];
+
+/// The teams whose prefixes are the lines.
+const TEAMS: [&str; MAX_LINES] = [
+    "sidebar_0",
+    "sidebar_1",
+    "sidebar_2",
+    "sidebar_3",
+    "sidebar_4",
+    "sidebar_5",
+    "sidebar_6",
+    "sidebar_7",
+    "sidebar_8",
+    "sidebar_9",
+    "sidebar_10",
+    "sidebar_11",
+    "sidebar_12",
+    "sidebar_13",
+    "sidebar_14",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Shown {
+    title: Text,
+    lines: Vec<Text>,
+}
+
+/// A change to the sidebar on the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Show,
+    Title,
+    AddLine(usize),
+    SetLine(usize),
+    RemoveLine(usize),
+    /// Removes the objective and the teams of this many lines.
+    Hide(usize),
+}
+
+/// The sidebar of a player. Nothing is shown until it has a title or lines.
+#[derive(Component, Debug, Default)]
+pub struct Sidebar {
+    title: Text,
+    lines: Vec<Text>,
+    visible: bool,
+    /// What the client was last sent.
+    shown: Option<Shown>,
+}
+
+impl Sidebar {
+    #[must_use]
+    pub fn new(title: impl IntoText<'static>) -> Self {
+        Self {
+            title: title.into_cow_text().into_owned(),
+            visible: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_title(&mut self, title: impl IntoText<'static>) {
+        self.title = title.into_cow_text().into_owned();
+        self.visible = true;
+    }
+
+    /// Replaces every line. Lines past [`MAX_LINES`] are dropped.
+    pub fn set_lines<T: IntoText<'static>>(&mut self, lines: impl IntoIterator<Item = T>) {
+        self.lines = lines
+            .into_iter()
+            .take(MAX_LINES)
+            .map(|line| line.into_cow_text().into_owned())
+            .collect();
+        self.visible = true;
+    }
+
+    /// Sets the line at `idx`, adding empty lines before it if needed.
+    pub fn set_line(&mut self, idx: usize, line: impl IntoText<'static>) {
+        if idx >= MAX_LINES {
+            return;
+        }
+
+        if self.lines.len() <= idx {
+            self.lines.resize(idx + 1, Text::default());
+        }
+
+        self.lines[idx] = line.into_cow_text().into_owned();
+        self.visible = true;
+    }
+
+    #[must_use]
+    pub fn lines(&self) -> &[Text] {
+        &self.lines
+    }
+
+    /// Removes the sidebar from the screen of the player.
+    pub fn hide(&mut self) {
+        self.title = Text::default();
+        self.lines.clear();
+        self.visible = false;
+    }
+
+    /// The changes which bring the client up to date, after which it is considered up to date.
+    fn changes(&mut self) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        match (&self.shown, self.visible) {
+            (None, false) => {}
+            (Some(shown), false) => {
+                changes.push(Change::Hide(shown.lines.len()));
+                self.shown = None;
+            }
+            (None, true) => {
+                changes.push(Change::Show);
+                changes.extend((0..self.lines.len()).map(Change::AddLine));
+            }
+            (Some(shown), true) => {
+                if shown.title != self.title {
+                    changes.push(Change::Title);
+                }
+
+                for idx in 0..shown.lines.len().max(self.lines.len()) {
+                    match (shown.lines.get(idx), self.lines.get(idx)) {
+                        (Some(old), Some(new)) if old != new => changes.push(Change::SetLine(idx)),
+                        (None, Some(_)) => changes.push(Change::AddLine(idx)),
+                        (Some(_), None) => changes.push(Change::RemoveLine(idx)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if self.visible && !changes.is_empty() {
+            self.shown = Some(Shown {
+                title: self.title.clone(),
+                lines: self.lines.clone(),
+            });
+        }
+
+        changes
+    }
+
+    fn write(&mut self, bundle: &mut DataBundle<'_, '_>) -> anyhow::Result<()> {
+        for change in self.changes() {
+            match change {
+                Change::Show => {
+                    bundle.add_packet(&ScoreboardObjectiveUpdateS2c {
+                        objective_name: OBJECTIVE,
+                        mode: ObjectiveMode::Create {
+                            objective_display_name: self.title.clone(),
+                            render_type: ObjectiveRenderType::Integer,
+                        },
+                    })?;
+
+                    bundle.add_packet(&ScoreboardDisplayS2c {
+                        position: ScoreboardPosition::Sidebar,
+                        score_name: OBJECTIVE,
+                    })?;
+                }
+                Change::Title => {
+                    bundle.add_packet(&ScoreboardObjectiveUpdateS2c {
+                        objective_name: OBJECTIVE,
+                        mode: ObjectiveMode::Update {
+                            objective_display_name: self.title.clone(),
+                            render_type: ObjectiveRenderType::Integer,
+                        },
+                    })?;
+                }
+                Change::AddLine(idx) => {
+                    bundle.add_packet(&TeamS2c {
+                        team_name: TEAMS[idx],
+                        mode: Mode::CreateTeam {
+                            team_display_name: Cow::default(),
+                            friendly_flags: TeamFlags::default(),
+                            name_tag_visibility: NameTagVisibility::Never,
+                            collision_rule: CollisionRule::Never,
+                            team_color: TeamColor::Reset,
+                            team_prefix: Cow::Borrowed(&self.lines[idx]),
+                            team_suffix: Cow::default(),
+                            entities: vec![HOLDERS[idx]],
+                        },
+                    })?;
+
+                    // the top line has the highest score
+                    let score = i32::try_from(MAX_LINES - idx)?;
+
+                    bundle.add_packet(&ScoreboardPlayerUpdateS2c {
+                        entity_name: HOLDERS[idx],
+                        action: ScoreboardPlayerUpdateAction::Update {
+                            objective_name: OBJECTIVE,
+                            objective_score: VarInt(score),
+                        },
+                    })?;
+                }
+                Change::SetLine(idx) => {
+                    bundle.add_packet(&TeamS2c {
+                        team_name: TEAMS[idx],
+                        mode: Mode::UpdateTeamInfo {
+                            team_display_name: Cow::default(),
+                            friendly_flags: TeamFlags::default(),
+                            name_tag_visibility: NameTagVisibility::Never,
+                            collision_rule: CollisionRule::Never,
+                            team_color: TeamColor::Reset,
+                            team_prefix: Cow::Borrowed(&self.lines[idx]),
+                            team_suffix: Cow::default(),
+                        },
+                    })?;
+                }
+                Change::RemoveLine(idx) => {
+                    bundle.add_packet(&ScoreboardPlayerUpdateS2c {
+                        entity_name: HOLDERS[idx],
+                        action: ScoreboardPlayerUpdateAction::Remove {
+                            objective_name: OBJECTIVE,
+                        },
+                    })?;
+
+                    bundle.add_packet(&TeamS2c {
+                        team_name: TEAMS[idx],
+                        mode: Mode::RemoveTeam,
+                    })?;
+                }
+                Change::Hide(lines) => {
+                    bundle.add_packet(&ScoreboardObjectiveUpdateS2c {
+                        objective_name: OBJECTIVE,
+                        mode: ObjectiveMode::Remove,
+                    })?;
+
+                    for team in &TEAMS[..lines] {
+                        bundle.add_packet(&TeamS2c {
+                            team_name: team,
+                            mode: Mode::RemoveTeam,
+                        })?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn systems(world: &World) {
+    system!(
+        "sync_sidebars",
+        world,
+        &Compose($),
+        &ConnectionId,
+        &mut Sidebar,
+    )
+    .with_enum(PacketState::Play)
+    .multi_threaded()
+    .kind::<flecs::pipeline::OnStore>()
+    .each_iter(|it, _, (compose, io, sidebar)| {
+        let mut bundle = DataBundle::new(compose, it.system());
+
+        if let Err(e) = sidebar.write(&mut bundle) {
+            error!("failed to write the sidebar: {e}");
+            return;
+        }
+
+        if let Err(e) = bundle.unicast(*io) {
+            error!("failed to send the sidebar: {e}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changed_lines_are_sent() {
+        let mut sidebar = Sidebar::new("Tag");
+        sidebar.set_lines(["Kills: 0", "Deaths: 0"]);
+
+        assert_eq!(sidebar.changes(), [
+            Change::Show,
+            Change::AddLine(0),
+            Change::AddLine(1)
+        ]);
+        assert!(sidebar.changes().is_empty());
+
+        sidebar.set_lines(["Kills: 1", "Deaths: 0", "Level: 2"]);
+        assert_eq!(sidebar.changes(), [Change::SetLine(0), Change::AddLine(2)]);
+
+        sidebar.set_title("Tag!");
+        sidebar.set_lines(["Kills: 1"]);
+        assert_eq!(sidebar.changes(), [
+            Change::Title,
+            Change::RemoveLine(1),
+            Change::RemoveLine(2)
+        ]);
+
+        sidebar.hide();
+        assert_eq!(sidebar.changes(), [Change::Hide(1)]);
+        assert!(sidebar.changes().is_empty());
+    }
+}
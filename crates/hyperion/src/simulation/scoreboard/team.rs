@@ -0,0 +1,409 @@
+//! Teams, which give the nametags of their members a prefix, suffix and color and decide who
+//! collides with whom.
+//!
+//! Teams are defined on [`Teams`], and players join one by having a [`ScoreboardTeam`]:
+//!
+//! ```ignore
+//! world.get::<&mut Teams>(|teams| {
+//!     teams.define("red", TeamOptions {
+//!         color: TeamColor::Red,
+//!         ..TeamOptions::default()
+//!     });
+//! });
+//!
+//! player.set(ScoreboardTeam::new("red"));
+//! ```
+//!
+//! Players without a [`ScoreboardTeam`] are in [`DEFAULT_TEAM`], which hides their nametag.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+};
+
+use flecs_ecs::prelude::*;
+use rustc_hash::FxHashMap;
+use tracing::warn;
+pub use valence_protocol::packets::play::team_s2c::{
+    CollisionRule, NameTagVisibility, TeamColor, TeamFlags,
+};
+use valence_protocol::{
+    Packet,
+    packets::play::{TeamS2c, team_s2c::Mode},
+};
+use valence_text::Text;
+
+use crate::{
+    PacketBundle,
+    simulation::{Name, Player},
+};
+
+/// The team of players without a [`ScoreboardTeam`].
+pub const DEFAULT_TEAM: &str = "no_tag";
+
+/// How a team looks and behaves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TeamOptions {
+    pub display_name: Text,
+    /// Shown before the names of members.
+    pub prefix: Text,
+    /// Shown after the names of members.
+    pub suffix: Text,
+    /// The color of the names of members.
+    pub color: TeamColor,
+    pub name_tag_visibility: NameTagVisibility,
+    pub collision_rule: CollisionRule,
+    pub friendly_fire: bool,
+    pub see_invisible_teammates: bool,
+}
+
+impl Default for TeamOptions {
+    fn default() -> Self {
+        Self {
+            display_name: Text::default(),
+            prefix: Text::default(),
+            suffix: Text::default(),
+            color: TeamColor::Reset,
+            name_tag_visibility: NameTagVisibility::Always,
+            collision_rule: CollisionRule::Always,
+            friendly_fire: true,
+            see_invisible_teammates: false,
+        }
+    }
+}
+
+impl TeamOptions {
+    fn flags(&self) -> TeamFlags {
+        TeamFlags::new()
+            .with_friendly_fire(self.friendly_fire)
+            .with_see_invisible_teammates(self.see_invisible_teammates)
+    }
+}
+
+/// The team of a player. Removing it puts them back in [`DEFAULT_TEAM`].
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct ScoreboardTeam {
+    pub name: Cow<'static, str>,
+}
+
+impl ScoreboardTeam {
+    #[must_use]
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+/// A change to the teams of a client, kept until it is sent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TeamUpdate {
+    /// Creates a team with `members`, or changes its options if `created` is false.
+    Define {
+        team: String,
+        options: TeamOptions,
+        members: Vec<String>,
+        created: bool,
+    },
+    Remove {
+        team: String,
+    },
+    Join {
+        team: String,
+        members: Vec<String>,
+    },
+}
+
+impl PacketBundle for &TeamUpdate {
+    fn encode_including_ids(self, w: impl Write) -> anyhow::Result<()> {
+        let (team_name, mode) = match self {
+            TeamUpdate::Define {
+                team,
+                options,
+                members,
+                created: true,
+            } => (team, Mode::CreateTeam {
+                team_display_name: Cow::Borrowed(&options.display_name),
+                friendly_flags: options.flags(),
+                name_tag_visibility: options.name_tag_visibility,
+                collision_rule: options.collision_rule,
+                team_color: options.color,
+                team_prefix: Cow::Borrowed(&options.prefix),
+                team_suffix: Cow::Borrowed(&options.suffix),
+                entities: members.iter().map(String::as_str).collect(),
+            }),
+            TeamUpdate::Define {
+                team,
+                options,
+                created: false,
+                ..
+            } => (team, Mode::UpdateTeamInfo {
+                team_display_name: Cow::Borrowed(&options.display_name),
+                friendly_flags: options.flags(),
+                name_tag_visibility: options.name_tag_visibility,
+                collision_rule: options.collision_rule,
+                team_color: options.color,
+                team_prefix: Cow::Borrowed(&options.prefix),
+                team_suffix: Cow::Borrowed(&options.suffix),
+            }),
+            TeamUpdate::Remove { team } => (team, Mode::RemoveTeam),
+            TeamUpdate::Join { team, members } => (team, Mode::AddEntities {
+                entities: members.iter().map(String::as_str).collect(),
+            }),
+        };
+
+        TeamS2c { team_name, mode }.encode_with_id(w)
+    }
+}
+
+#[derive(Debug)]
+struct TeamState {
+    options: TeamOptions,
+    members: BTreeSet<String>,
+}
+
+/// Every team and which players are in it.
+#[derive(Component, Debug)]
+pub struct Teams {
+    teams: BTreeMap<String, TeamState>,
+    /// The team of every player, by name.
+    membership: FxHashMap<String, String>,
+    pending: Vec<TeamUpdate>,
+}
+
+impl Default for Teams {
+    fn default() -> Self {
+        let mut teams = Self {
+            teams: BTreeMap::new(),
+            membership: FxHashMap::default(),
+            pending: Vec::new(),
+        };
+
+        teams.define(DEFAULT_TEAM, TeamOptions {
+            name_tag_visibility: NameTagVisibility::Never,
+            ..TeamOptions::default()
+        });
+
+        teams
+    }
+}
+
+impl Teams {
+    /// Adds a team, or changes the options of an existing one.
+    pub fn define(&mut self, name: &str, options: TeamOptions) {
+        let created = match self.teams.get_mut(name) {
+            Some(team) if team.options == options => return,
+            Some(team) => {
+                team.options = options.clone();
+                false
+            }
+            None => {
+                self.teams.insert(name.to_owned(), TeamState {
+                    options: options.clone(),
+                    members: BTreeSet::new(),
+                });
+                true
+            }
+        };
+
+        self.pending.push(TeamUpdate::Define {
+            team: name.to_owned(),
+            options,
+            members: Vec::new(),
+            created,
+        });
+    }
+
+    /// Removes a team. Its members are moved to [`DEFAULT_TEAM`].
+    pub fn remove(&mut self, name: &str) {
+        if name == DEFAULT_TEAM {
+            warn!("the default team cannot be removed");
+            return;
+        }
+
+        let Some(team) = self.teams.remove(name) else {
+            return;
+        };
+
+        self.pending.push(TeamUpdate::Remove {
+            team: name.to_owned(),
+        });
+
+        if team.members.is_empty() {
+            return;
+        }
+
+        for member in &team.members {
+            self.membership
+                .insert(member.clone(), DEFAULT_TEAM.to_owned());
+        }
+
+        let default = self.teams.get_mut(DEFAULT_TEAM).unwrap();
+        default.members.extend(team.members.iter().cloned());
+
+        self.pending.push(TeamUpdate::Join {
+            team: DEFAULT_TEAM.to_owned(),
+            members: team.members.into_iter().collect(),
+        });
+    }
+
+    #[must_use]
+    pub fn options(&self, team: &str) -> Option<&TeamOptions> {
+        self.teams.get(team).map(|team| &team.options)
+    }
+
+    /// The team `player` is in.
+    #[must_use]
+    pub fn team_of(&self, player: &str) -> Option<&str> {
+        self.membership.get(player).map(String::as_str)
+    }
+
+    /// Moves `player` into `team`. Returns false if the team does not exist.
+    pub fn join(&mut self, player: &str, team: &str) -> bool {
+        if !self.teams.contains_key(team) {
+            warn!("{player} cannot join the unknown team {team}");
+            return false;
+        }
+
+        if self.team_of(player) == Some(team) {
+            return true;
+        }
+
+        // clients move players out of their old team by themselves
+        self.forget(player);
+
+        self.teams
+            .get_mut(team)
+            .unwrap()
+            .members
+            .insert(player.to_owned());
+        self.membership.insert(player.to_owned(), team.to_owned());
+
+        self.pending.push(TeamUpdate::Join {
+            team: team.to_owned(),
+            members: vec![player.to_owned()],
+        });
+
+        true
+    }
+
+    /// Moves `player` back into [`DEFAULT_TEAM`].
+    pub fn leave(&mut self, player: &str) {
+        if self.membership.contains_key(player) {
+            self.join(player, DEFAULT_TEAM);
+        }
+    }
+
+    /// Removes `player` from their team without telling anyone, such as when they disconnect.
+    pub fn forget(&mut self, player: &str) {
+        let Some(team) = self.membership.remove(player) else {
+            return;
+        };
+
+        if let Some(team) = self.teams.get_mut(&team) {
+            team.members.remove(player);
+        }
+    }
+
+    /// Takes the changes made since the last call.
+    pub fn take_updates(&mut self) -> Vec<TeamUpdate> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// The updates which recreate every team on a client. Every team is removed first, so it
+    /// does not matter what the client has already been sent.
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<TeamUpdate> {
+        let mut updates = Vec::with_capacity(self.teams.len() * 2);
+
+        for (name, team) in &self.teams {
+            updates.push(TeamUpdate::Remove { team: name.clone() });
+            updates.push(TeamUpdate::Define {
+                team: name.clone(),
+                options: team.options.clone(),
+                members: team.members.iter().cloned().collect(),
+                created: true,
+            });
+        }
+
+        updates
+    }
+}
+
+pub(super) fn observers(world: &World) {
+    // players join the default team when they log in, unless they already have a team
+    observer!(
+        world,
+        flecs::OnSet,
+        &Name,
+        ?&ScoreboardTeam,
+        &mut Teams($),
+    )
+    .with::<Player>()
+    .each(|(name, team, teams)| {
+        let joined = team.is_some_and(|team| teams.join(name, &team.name));
+
+        if !joined {
+            teams.join(name, DEFAULT_TEAM);
+        }
+    });
+
+    observer!(
+        world,
+        flecs::OnSet,
+        &ScoreboardTeam,
+        [filter] &Name,
+        &mut Teams($),
+    )
+    .with::<Player>()
+    .each(|(team, name, teams)| {
+        if !teams.join(name, &team.name) {
+            teams.leave(name);
+        }
+    });
+
+    observer!(
+        world,
+        flecs::OnRemove,
+        &ScoreboardTeam,
+        [filter] &Name,
+        &mut Teams($),
+    )
+    .with::<Player>()
+    .each(|(_, name, teams)| {
+        teams.leave(name);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn players_move_between_teams() {
+        let mut teams = Teams::default();
+        teams.define("red", TeamOptions {
+            color: TeamColor::Red,
+            ..TeamOptions::default()
+        });
+
+        teams.join("Notch", DEFAULT_TEAM);
+        assert!(teams.join("Notch", "red"));
+        assert!(!teams.join("Notch", "blue"));
+        assert_eq!(teams.team_of("Notch"), Some("red"));
+
+        // joining the same team twice sends nothing
+        let updates = teams.take_updates();
+        assert!(teams.join("Notch", "red"));
+        assert!(teams.take_updates().is_empty());
+        assert_eq!(updates.len(), 4);
+
+        teams.remove("red");
+        assert_eq!(teams.team_of("Notch"), Some(DEFAULT_TEAM));
+
+        teams.forget("Notch");
+        assert_eq!(teams.team_of("Notch"), None);
+
+        let snapshot = teams.snapshot();
+        assert_eq!(snapshot.len(), 2);
+    }
+}
@@ -0,0 +1,480 @@
+//! Chat sessions and signed chat messages.
+//!
+//! A client with a key pair from Mojang starts a [`ChatSession`] with a
+//! [`PlayerSessionC2s`](packets::PlayerSessionC2s) and signs each of its chat messages. A signature
+//! covers the message, its index in the session and the signed messages the client had seen,
+//! which it acknowledges in a window of the last 20. [`LastSeenMessages`] tracks that window for
+//! every player like vanilla does, and clients which acknowledge messages they were never sent
+//! are kicked.
+//!
+//! The server does not verify signatures itself. Sessions are shared with every client, which
+//! verify the messages they are sent. With [`Global::secure_chat`](crate::Global::secure_chat)
+//! off, player messages are sent as system messages instead.
+
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, ensure};
+use flecs_ecs::prelude::*;
+use hyperion_text::Text;
+use tracing::{error, warn};
+use valence_protocol::{Bounded, VarInt};
+
+use crate::{
+    egress::player_join::{ChatData, PlayerListActions, PlayerListEntry, PlayerListS2c},
+    ingress::PendingRemove,
+    net::{
+        Compose, ConnectionId, DataBundle,
+        packets::{
+            self, ChatMessageS2c, MESSAGE_SIGNATURE_LEN, MessageFilterType, PreviousMessage,
+            ProfilelessChatMessageS2c, ServerMetadataS2c,
+        },
+    },
+    simulation::{PacketState, Player, Uuid},
+};
+
+/// The signature of a chat message.
+pub type MessageSignature = Arc<[u8; MESSAGE_SIGNATURE_LEN]>;
+
+/// How many of the last signed messages a client acknowledges in each chat message.
+pub const LAST_SEEN_WINDOW: usize = 20;
+
+/// How many messages a client may leave unacknowledged before it is kicked, like vanilla.
+pub const MAX_PENDING_MESSAGES: usize = 4096;
+
+/// The ids of the chat types in the registry codec.
+pub mod chat_type {
+    /// `<sender> message`
+    pub const CHAT: i32 = 0;
+    pub const EMOTE_COMMAND: i32 = 1;
+    pub const MSG_COMMAND_INCOMING: i32 = 2;
+    pub const MSG_COMMAND_OUTGOING: i32 = 3;
+    /// Only the message, so the server decides how it looks.
+    pub const RAW: i32 = 4;
+    pub const SAY_COMMAND: i32 = 5;
+    pub const TEAM_MSG_COMMAND_INCOMING: i32 = 6;
+    pub const TEAM_MSG_COMMAND_OUTGOING: i32 = 7;
+}
+
+/// The public key a player signs their chat messages with.
+#[derive(Component, Clone, Debug)]
+pub struct ChatSession {
+    pub session_id: uuid::Uuid,
+    /// When the key expires, in milliseconds since the Unix epoch.
+    pub expires_at: i64,
+    pub public_key: Box<[u8]>,
+    /// The signature of the key by Mojang.
+    pub key_signature: Box<[u8]>,
+    /// The index of the next signed message.
+    next_index: i32,
+    /// The timestamp of the last message, which the next one may not be older than.
+    last_timestamp: u64,
+}
+
+impl ChatSession {
+    /// The longest key signature a client may send.
+    pub const MAX_KEY_SIGNATURE_LEN: usize = 4096;
+    /// The longest public key a client may send.
+    pub const MAX_PUBLIC_KEY_LEN: usize = 512;
+
+    #[must_use]
+    pub fn new(
+        session_id: uuid::Uuid,
+        expires_at: i64,
+        public_key: &[u8],
+        key_signature: &[u8],
+    ) -> Self {
+        Self {
+            session_id,
+            expires_at,
+            public_key: public_key.into(),
+            key_signature: key_signature.into(),
+            next_index: 0,
+            last_timestamp: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        now_millis() >= self.expires_at
+    }
+
+    /// The tab list entry which shares the session of `player` with other clients.
+    fn entry(&self, player: uuid::Uuid) -> PlayerListEntry<'_> {
+        PlayerListEntry {
+            player_uuid: player,
+            chat_data: Some(ChatData {
+                session_id: self.session_id,
+                key_expiry_time: self.expires_at,
+                public_key: &self.public_key,
+                public_key_signature: &self.key_signature,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| i64::try_from(now.as_millis()).unwrap_or(i64::MAX))
+}
+
+#[derive(Clone, Debug)]
+struct TrackedMessage {
+    signature: MessageSignature,
+    /// Whether the client has not acknowledged the message yet.
+    pending: bool,
+}
+
+/// The signed messages a client was sent, which it acknowledges in its own messages.
+#[derive(Component, Clone, Debug)]
+pub struct LastSeenMessages {
+    /// Oldest first. The first [`LAST_SEEN_WINDOW`] are the window the client acknowledges next,
+    /// where messages it chose to ignore are [`None`].
+    tracked: VecDeque<Option<TrackedMessage>>,
+}
+
+impl Default for LastSeenMessages {
+    fn default() -> Self {
+        Self {
+            tracked: std::iter::repeat_n(None, LAST_SEEN_WINDOW).collect(),
+        }
+    }
+}
+
+impl LastSeenMessages {
+    /// Remembers a signed message which is sent to the client.
+    pub fn add_pending(&mut self, signature: &MessageSignature) -> anyhow::Result<()> {
+        let repeated = self
+            .tracked
+            .back()
+            .and_then(Option::as_ref)
+            .is_some_and(|last| last.pending && last.signature == *signature);
+
+        if !repeated {
+            self.tracked.push_back(Some(TrackedMessage {
+                signature: signature.clone(),
+                pending: true,
+            }));
+        }
+
+        ensure!(
+            self.tracked.len() <= MAX_PENDING_MESSAGES,
+            "too many unacknowledged chat messages"
+        );
+
+        Ok(())
+    }
+
+    /// Forgets the `offset` oldest messages, which the client no longer tracks.
+    pub fn apply_offset(&mut self, offset: i32) -> anyhow::Result<()> {
+        let max = self.tracked.len() - LAST_SEEN_WINDOW;
+
+        let Some(offset) = usize::try_from(offset).ok().filter(|offset| *offset <= max) else {
+            bail!("invalid acknowledgement offset {offset}, expected at most {max}");
+        };
+
+        self.tracked.drain(..offset);
+
+        Ok(())
+    }
+
+    /// Applies the acknowledgements of a chat message or command, where bit `i` of `acknowledged`
+    /// says whether the client saw the message `i` of its window. Returns the signatures of the
+    /// acknowledged messages, oldest first.
+    pub fn apply_update(
+        &mut self,
+        offset: i32,
+        acknowledged: [u8; 3],
+    ) -> anyhow::Result<Vec<MessageSignature>> {
+        self.apply_offset(offset)?;
+
+        // 20 bits of the 24 are used
+        ensure!(
+            acknowledged[2] >> 4 == 0,
+            "acknowledged messages outside of the window"
+        );
+
+        let mut seen = Vec::new();
+
+        for (i, tracked) in self.tracked.iter_mut().take(LAST_SEEN_WINDOW).enumerate() {
+            let is_acknowledged = (acknowledged[i / 8] >> (i % 8)) & 1 == 1;
+
+            if is_acknowledged {
+                let Some(message) = tracked else {
+                    bail!("acknowledged an unknown or previously ignored message");
+                };
+
+                message.pending = false;
+                seen.push(message.signature.clone());
+            } else {
+                if tracked.as_ref().is_some_and(|message| !message.pending) {
+                    bail!("ignored a previously acknowledged message");
+                }
+
+                *tracked = None;
+            }
+        }
+
+        Ok(seen)
+    }
+}
+
+/// The signature and everything else clients need to verify a chat message.
+#[derive(Clone, Debug)]
+pub struct SignedMessage {
+    pub sender: uuid::Uuid,
+    /// The index of the message in the session of the sender.
+    pub index: i32,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub salt: u64,
+    pub signature: MessageSignature,
+    /// The signed messages the sender had seen, oldest first.
+    pub last_seen: Vec<MessageSignature>,
+}
+
+impl SignedMessage {
+    /// The packet showing the message, where `message` is the text which was signed. `content`
+    /// is shown instead if the server changed the message.
+    #[must_use]
+    pub fn packet<'a>(
+        &'a self,
+        message: &'a str,
+        content: Option<Text<'a>>,
+        sender_name: Text<'a>,
+    ) -> ChatMessageS2c<'a> {
+        ChatMessageS2c {
+            sender: self.sender,
+            index: VarInt(self.index),
+            message_signature: Some(&self.signature),
+            message: Bounded(message),
+            timestamp: self.timestamp,
+            salt: self.salt,
+            previous_messages: self
+                .last_seen
+                .iter()
+                .map(|signature| PreviousMessage::Full(signature))
+                .collect(),
+            unsigned_content: content,
+            filter_type: MessageFilterType::PassThrough,
+            chat_type: VarInt(chat_type::CHAT),
+            network_name: sender_name,
+            network_target_name: None,
+        }
+    }
+}
+
+/// The packet showing a player chat message which is not signed.
+#[must_use]
+pub fn unsigned_message<'a>(
+    content: Text<'a>,
+    sender_name: Text<'a>,
+) -> ProfilelessChatMessageS2c<'a> {
+    ProfilelessChatMessageS2c {
+        message: content,
+        chat_type: VarInt(chat_type::CHAT),
+        chat_type_name: sender_name,
+        target_name: None,
+    }
+}
+
+/// Kicks a player whose chat packets do not match what they were sent.
+pub fn reject(player: EntityView<'_>, error: &anyhow::Error) {
+    warn!("chat validation failed: {error}");
+    player.set(PendingRemove::new("Chat message validation failure"));
+}
+
+/// Applies the acknowledgements of a chat message or command of `player`.
+pub(crate) fn acknowledge(
+    player: EntityView<'_>,
+    offset: VarInt,
+    acknowledged: [u8; 3],
+) -> anyhow::Result<Vec<MessageSignature>> {
+    player
+        .try_get::<&mut LastSeenMessages>(|seen| seen.apply_update(offset.0, acknowledged))
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
+
+/// The signed part of a chat message of `player`, or [`None`] if it is not signed by a session
+/// which is still valid.
+pub(crate) fn signed_message(
+    player: EntityView<'_>,
+    pkt: &packets::ChatMessageC2s<'_>,
+    last_seen: Vec<MessageSignature>,
+) -> anyhow::Result<Option<SignedMessage>> {
+    let sender = player.get::<&Uuid>(|uuid| uuid.0);
+
+    player
+        .try_get::<&mut ChatSession>(|session| {
+            ensure!(
+                pkt.timestamp >= session.last_timestamp,
+                "chat message sent out of order"
+            );
+
+            session.last_timestamp = pkt.timestamp;
+
+            let Some(signature) = pkt.signature else {
+                return Ok(None);
+            };
+
+            if session.is_expired() {
+                return Ok(None);
+            }
+
+            let index = session.next_index;
+            session.next_index += 1;
+
+            Ok(Some(SignedMessage {
+                sender,
+                index,
+                timestamp: pkt.timestamp,
+                salt: pkt.salt,
+                signature: Arc::new(*signature),
+                last_seen,
+            }))
+        })
+        .unwrap_or(Ok(None))
+}
+
+/// Starts the chat session of `player`, unless its key is expired or too large.
+pub(crate) fn start_session(player: EntityView<'_>, pkt: &packets::PlayerSessionC2s<'_>) {
+    if pkt.public_key.len() > ChatSession::MAX_PUBLIC_KEY_LEN
+        || pkt.key_signature.len() > ChatSession::MAX_KEY_SIGNATURE_LEN
+    {
+        warn!("ignoring chat session with an oversized key");
+        return;
+    }
+
+    let session = ChatSession::new(
+        pkt.session_id,
+        pkt.expires_at,
+        pkt.public_key,
+        pkt.key_signature,
+    );
+
+    if session.is_expired() {
+        warn!("ignoring chat session with an expired key");
+        return;
+    }
+
+    player.set(session);
+}
+
+/// Marks players who have been told about secure chat and the sessions of other players.
+#[derive(Component)]
+struct SecureChatSynced;
+
+#[derive(Component)]
+pub struct SecureChatModule;
+
+impl Module for SecureChatModule {
+    fn module(world: &World) {
+        world.component::<ChatSession>();
+        world.component::<LastSeenMessages>();
+        world.component::<SecureChatSynced>();
+
+        world
+            .component::<Player>()
+            .add_trait::<(flecs::With, LastSeenMessages)>();
+
+        // other clients need the session to verify the messages of the player
+        observer!(
+            world,
+            flecs::OnSet,
+            &ChatSession,
+            &Uuid,
+            &Compose($),
+        )
+        .each_iter(|it, _, (session, uuid, compose)| {
+            let pkt = PlayerListS2c {
+                actions: PlayerListActions::default().with_initialize_chat(true),
+                entries: Cow::Owned(vec![session.entry(uuid.0)]),
+            };
+
+            if let Err(e) = compose.broadcast(&pkt, it.system()).send() {
+                error!("failed to broadcast chat session: {e}");
+            }
+        });
+
+        let joined = world
+            .query::<&ConnectionId>()
+            .with_enum(PacketState::Play)
+            .without::<SecureChatSynced>()
+            .build();
+
+        let sessions = world.query::<(&Uuid, &ChatSession)>().build();
+
+        system!("sync_secure_chat", world, &Compose($))
+            .kind::<flecs::pipeline::OnStore>()
+            .each_iter(move |it, _, compose| {
+                let system = it.system();
+
+                joined.each_entity(|entity, io| {
+                    let mut bundle = DataBundle::new(compose, system);
+
+                    let mut run = || {
+                        bundle.add_packet(&ServerMetadataS2c {
+                            motd: Text::new(""),
+                            icon: None,
+                            enforce_secure_chat: compose.global().secure_chat,
+                        })?;
+
+                        let mut entries = Vec::new();
+
+                        sessions.each(|(uuid, session)| entries.push(session.entry(uuid.0)));
+
+                        if !entries.is_empty() {
+                            bundle.add_packet(&PlayerListS2c {
+                                actions: PlayerListActions::default().with_initialize_chat(true),
+                                entries: Cow::Owned(entries),
+                            })?;
+                        }
+
+                        bundle.unicast(*io)
+                    };
+
+                    if let Err(e) = run() {
+                        error!("failed to send chat sessions: {e}");
+                    }
+
+                    entity.add::<SecureChatSynced>();
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(byte: u8) -> MessageSignature {
+        Arc::new([byte; MESSAGE_SIGNATURE_LEN])
+    }
+
+    #[test]
+    fn acknowledgements_follow_the_window() {
+        let mut seen = LastSeenMessages::default();
+        seen.add_pending(&signature(1)).unwrap();
+        seen.add_pending(&signature(2)).unwrap();
+
+        // the window moved by the two new messages, which are its last two
+        let acknowledged = seen.apply_update(2, [0, 0, 0b1100]).unwrap();
+        assert_eq!(acknowledged, [signature(1), signature(2)]);
+
+        // an acknowledged message cannot be ignored later
+        assert!(seen.clone().apply_update(0, [0, 0, 0b0100]).is_err());
+        assert!(seen.clone().apply_update(1, [0, 0, 0b1100]).is_err());
+        assert!(seen.apply_update(0, [0, 0, 0b1100]).is_ok());
+
+        let mut seen = LastSeenMessages::default();
+        assert!(seen.clone().apply_update(0, [1, 0, 0]).is_err());
+        assert!(seen.clone().apply_update(0, [0, 0, 0x10]).is_err());
+        assert!(seen.apply_offset(1).is_err());
+    }
+}
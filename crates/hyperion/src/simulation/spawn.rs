@@ -0,0 +1,303 @@
+//! Spawning, despawning and relocating entities.
+//!
+//! ```ignore
+//! use hyperion::simulation::{entity_kind::EntityKind, spawn::SpawnExt};
+//!
+//! let zombie = world
+//!     .spawn_entity(EntityKind::Zombie)
+//!     .at(Vec3::new(0.0, 64.0, 0.0))
+//!     .rotation(90.0, 0.0)
+//!     .spawn();
+//! ```
+
+use flecs_ecs::prelude::*;
+use glam::Vec3;
+use tracing::debug;
+
+use crate::simulation::{
+    EntitySize, Pitch, Position, Spawn, Uuid, Velocity, Yaw, entity_kind::EntityKind,
+    metadata::MetadataPrefabs,
+};
+
+/// A prefab for every [`EntityKind`] holding its [`EntitySize`] and default metadata.
+///
+/// Each prefab inherits from the matching [`MetadataPrefabs`] base (living entity, mob, display,
+/// armor stand, ...), so instances get every metadata component the client expects for that kind.
+#[derive(Component, Debug)]
+pub struct EntityKindPrefabs {
+    prefabs: Vec<Entity>,
+}
+
+impl EntityKindPrefabs {
+    #[must_use]
+    pub fn get(&self, kind: EntityKind) -> Entity {
+        self.prefabs[kind as usize]
+    }
+}
+
+const fn metadata_base(kind: EntityKind, prefabs: &MetadataPrefabs) -> Entity {
+    match kind {
+        EntityKind::Player => prefabs.player_base,
+        EntityKind::BlockDisplay => prefabs.block_display_base,
+        EntityKind::ItemDisplay | EntityKind::TextDisplay => prefabs.display_base,
+        EntityKind::Item => prefabs.item_base,
+        EntityKind::ArmorStand => prefabs.armor_stand_base,
+        kind if kind.is_mob() => prefabs.mob_base,
+        _ => prefabs.entity_base,
+    }
+}
+
+#[must_use]
+pub fn register_prefabs(world: &World, metadata: &MetadataPrefabs) -> EntityKindPrefabs {
+    let prefabs = EntityKind::ALL
+        .into_iter()
+        .map(|kind| {
+            world
+                .prefab()
+                .is_a_id(metadata_base(kind, metadata))
+                .set(kind.size())
+                .add_enum(kind)
+                .id()
+        })
+        .collect();
+
+    EntityKindPrefabs { prefabs }
+}
+
+/// Gives every new entity without a [`Uuid`] a random one.
+pub(crate) fn add_uuid_observer(world: &World) {
+    world
+        .observer::<flecs::OnAdd, ()>()
+        .with_enum_wildcard::<EntityKind>()
+        .without::<Uuid>()
+        .each_entity(|entity, ()| {
+            debug!("adding uuid to entity");
+            let uuid = uuid::Uuid::new_v4();
+            entity.set(Uuid::from(uuid));
+        });
+}
+
+/// The number of experience points an [`EntityKind::ExperienceOrb`] is worth.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExperienceOrbValue(pub i16);
+
+impl Default for ExperienceOrbValue {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Event which removes an entity from every client that can see it and then deletes it.
+#[derive(Component)]
+pub struct Despawn;
+
+/// Moves an entity to a new position by despawning it for viewers of its old location and spawning
+/// it again at the new one. Use this for non-player entities that move further than a normal
+/// movement packet can express or across chunks that have different viewers.
+///
+/// The component is removed once the relocation has been sent.
+#[derive(Component, Copy, Clone, Debug, PartialEq)]
+pub struct Relocation {
+    pub to: Vec3,
+}
+
+/// Builder returned by [`SpawnExt::spawn_entity`].
+#[must_use]
+pub struct EntityBuilder<'a> {
+    entity: EntityView<'a>,
+    position: Vec3,
+    yaw: f32,
+    pitch: f32,
+    velocity: Vec3,
+}
+
+impl<'a> EntityBuilder<'a> {
+    pub const fn at(mut self, position: Vec3) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub const fn rotation(mut self, yaw: f32, pitch: f32) -> Self {
+        self.yaw = yaw;
+        self.pitch = pitch;
+        self
+    }
+
+    pub const fn velocity(mut self, velocity: Vec3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn uuid(self, uuid: uuid::Uuid) -> Self {
+        self.entity.set(Uuid(uuid));
+        self
+    }
+
+    /// Overrides the default [`EntitySize`] of the kind.
+    pub fn size(self, size: EntitySize) -> Self {
+        self.entity.set(size);
+        self
+    }
+
+    /// Sets the value of an experience orb. This has no effect on other kinds.
+    pub fn experience(self, value: i16) -> Self {
+        self.entity.set(ExperienceOrbValue(value));
+        self
+    }
+
+    /// Runs `f` on the entity before it is spawned, e.g. to set metadata or AI components.
+    pub fn with(self, f: impl FnOnce(EntityView<'a>)) -> Self {
+        f(self.entity);
+        self
+    }
+
+    /// The entity being built. It has not been sent to clients yet.
+    #[must_use]
+    pub const fn entity(&self) -> EntityView<'a> {
+        self.entity
+    }
+
+    /// Sets the position and rotation and sends the spawn packet to nearby players.
+    pub fn spawn(self) -> EntityView<'a> {
+        self.entity
+            .set(Position::from(self.position))
+            .set(Yaw::new(self.yaw))
+            .set(Pitch::new(self.pitch))
+            .set(Velocity(self.velocity))
+            .enqueue(Spawn);
+
+        self.entity
+    }
+}
+
+pub trait SpawnExt {
+    /// Creates an entity of the given kind from its [`EntityKindPrefabs`] prefab.
+    ///
+    /// Nothing is sent to clients until [`EntityBuilder::spawn`] is called.
+    fn spawn_entity(&self, kind: EntityKind) -> EntityBuilder<'_>;
+}
+
+impl SpawnExt for World {
+    fn spawn_entity(&self, kind: EntityKind) -> EntityBuilder<'_> {
+        let prefab = self.get::<&EntityKindPrefabs>(|prefabs| prefabs.get(kind));
+
+        EntityBuilder {
+            entity: self.entity().is_a_id(prefab),
+            position: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            velocity: Vec3::ZERO,
+        }
+    }
+}
+
+pub trait DespawnExt {
+    /// Removes the entity from every client that can see it and deletes it.
+    fn despawn(self);
+
+    /// See [`Relocation`].
+    fn relocate(self, position: Vec3);
+}
+
+impl DespawnExt for EntityView<'_> {
+    fn despawn(self) {
+        self.enqueue(Despawn);
+    }
+
+    fn relocate(self, position: Vec3) {
+        self.set(Relocation { to: position });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::metadata::{
+        self, armor_stand::ArmorStandFlags, living_entity::Health, mob::MobFlags,
+    };
+
+    fn world() -> World {
+        let world = World::new();
+
+        let metadata = metadata::register_prefabs(&world);
+        world.set(register_prefabs(&world, &metadata));
+        add_uuid_observer(&world);
+
+        world
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp, reason = "the values are copied, not computed")]
+    fn prefabs_match_their_kind() {
+        let world = world();
+        let prefabs = world.get::<&EntityKindPrefabs>(|prefabs| {
+            EntityKind::ALL.map(|kind| (kind, prefabs.get(kind)))
+        });
+
+        for (kind, prefab) in prefabs {
+            let prefab = world.entity_from_id(prefab);
+
+            assert!(prefab.has_enum(kind), "{kind:?}");
+            prefab.get::<&EntitySize>(|size| {
+                assert_eq!(size.half_width, kind.size().half_width, "{kind:?}");
+                assert_eq!(size.height, kind.size().height, "{kind:?}");
+            });
+        }
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp, reason = "the values are copied, not computed")]
+    fn spawned_entities_get_their_components() {
+        let world = world();
+
+        let zombie = world
+            .spawn_entity(EntityKind::Zombie)
+            .at(Vec3::new(1.0, 64.0, -2.0))
+            .rotation(90.0, 10.0)
+            .velocity(Vec3::Y)
+            .spawn();
+
+        assert!(zombie.has_enum(EntityKind::Zombie));
+        assert!(zombie.has::<Uuid>());
+        assert!(zombie.has::<Health>());
+        assert!(zombie.has::<MobFlags>());
+        assert!(!zombie.has::<ArmorStandFlags>());
+
+        zombie.get::<(&Position, &Yaw, &Pitch, &Velocity)>(|(position, yaw, pitch, velocity)| {
+            assert_eq!(**position, Vec3::new(1.0, 64.0, -2.0));
+            assert_eq!(**yaw, 90.0);
+            assert_eq!(**pitch, 10.0);
+            assert_eq!(velocity.0, Vec3::Y);
+        });
+
+        let armor_stand = world.spawn_entity(EntityKind::ArmorStand).spawn();
+
+        assert!(armor_stand.has::<Uuid>());
+        assert!(armor_stand.has::<ArmorStandFlags>());
+        assert!(!armor_stand.has::<MobFlags>());
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp, reason = "the values are copied, not computed")]
+    fn builder_overrides_defaults() {
+        let world = world();
+        let uuid = uuid::Uuid::new_v4();
+
+        let orb = world
+            .spawn_entity(EntityKind::ExperienceOrb)
+            .uuid(uuid)
+            .size(EntitySize {
+                half_width: 1.0,
+                height: 2.0,
+            })
+            .experience(7)
+            .spawn();
+
+        assert_eq!(orb.get::<&Uuid>(|id| id.0), uuid);
+        assert_eq!(orb.get::<&EntitySize>(|size| size.height), 2.0);
+        assert_eq!(
+            orb.get::<&ExperienceOrbValue>(|value| *value),
+            ExperienceOrbValue(7)
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! The header and footer of the tab list of a player.
+
+use flecs_ecs::prelude::*;
+use tracing::error;
+use valence_protocol::packets::play;
+use valence_text::{IntoText, Text};
+
+use crate::{
+    net::{Compose, ConnectionId},
+    simulation::PacketState,
+};
+
+/// The text above and below the names in the tab list of a player. It is sent again only when it
+/// changes.
+#[derive(Component, Debug, Default)]
+pub struct TabListHeader {
+    header: Text,
+    footer: Text,
+    changed: bool,
+}
+
+impl TabListHeader {
+    #[must_use]
+    pub fn new(header: impl IntoText<'static>, footer: impl IntoText<'static>) -> Self {
+        Self {
+            header: header.into_cow_text().into_owned(),
+            footer: footer.into_cow_text().into_owned(),
+            changed: true,
+        }
+    }
+
+    #[must_use]
+    pub const fn header(&self) -> &Text {
+        &self.header
+    }
+
+    #[must_use]
+    pub const fn footer(&self) -> &Text {
+        &self.footer
+    }
+
+    pub fn set_header(&mut self, header: impl IntoText<'static>) {
+        let header = header.into_cow_text().into_owned();
+
+        if self.header != header {
+            self.header = header;
+            self.changed = true;
+        }
+    }
+
+    pub fn set_footer(&mut self, footer: impl IntoText<'static>) {
+        let footer = footer.into_cow_text().into_owned();
+
+        if self.footer != footer {
+            self.footer = footer;
+            self.changed = true;
+        }
+    }
+
+    const fn take_changed(&mut self) -> bool {
+        let changed = self.changed;
+        self.changed = false;
+        changed
+    }
+}
+
+#[derive(Component)]
+pub struct TabListModule;
+
+impl Module for TabListModule {
+    fn module(world: &World) {
+        world.component::<TabListHeader>();
+
+        system!(
+            "sync_tab_list_headers",
+            world,
+            &Compose($),
+            &ConnectionId,
+            &mut TabListHeader,
+        )
+        .with_enum(PacketState::Play)
+        .multi_threaded()
+        .kind::<flecs::pipeline::OnStore>()
+        .each_iter(|it, _, (compose, io, tab_list)| {
+            if !tab_list.take_changed() {
+                return;
+            }
+
+            let pkt = play::PlayerListHeaderS2c {
+                header: (&tab_list.header).into_cow_text(),
+                footer: (&tab_list.footer).into_cow_text(),
+            };
+
+            if let Err(e) = compose.unicast(&pkt, *io, it.system()) {
+                error!("failed to send tab list header: {e}");
+            }
+        });
+    }
+}
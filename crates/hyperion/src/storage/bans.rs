@@ -0,0 +1,217 @@
+//! Bans of players and IP addresses, which are checked when a player logs in.
+
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use byteorder::NativeEndian;
+use flecs_ecs::macros::Component;
+use heed::{Database, Env, RwTxn, types};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::storage::LocalDb;
+
+/// Why and until when a player or IP address is banned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ban {
+    /// The name of the banned player, so they can be unbanned by name.
+    pub name: Option<String>,
+    pub reason: String,
+    /// The name of whoever issued the ban.
+    pub by: String,
+    pub since: SystemTime,
+    /// When the ban ends. Bans without an end are permanent.
+    pub until: Option<SystemTime>,
+}
+
+impl Ban {
+    #[must_use]
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.until.is_some_and(|until| until <= now)
+    }
+
+    /// The time left until the ban ends, rounded to seconds, or [`None`] if it is permanent.
+    #[must_use]
+    pub fn remaining(&self, now: SystemTime) -> Option<Duration> {
+        let until = self.until?;
+        let remaining = until.duration_since(now).unwrap_or_default();
+
+        Some(Duration::from_secs(remaining.as_secs()))
+    }
+
+    /// The message shown on the disconnect screen of a banned player.
+    #[must_use]
+    pub fn disconnect_message(&self, now: SystemTime) -> String {
+        let expiry = match self.remaining(now) {
+            Some(remaining) => format!("§7Expires in §f{}", humantime::format_duration(remaining)),
+            None => "§7This ban is permanent".to_owned(),
+        };
+
+        format!(
+            "§c§lYou are banned from this server\n\n§7Reason: §f{}\n{expiry}",
+            self.reason
+        )
+    }
+}
+
+/// A handler for bans
+#[derive(Component, Debug, Clone)]
+pub struct BanList {
+    env: Env,
+    players: Database<types::U128<NativeEndian>, types::Bytes>,
+    /// Keyed by the IPv6 form of the address, so IPv4 addresses are mapped.
+    ips: Database<types::Bytes, types::Bytes>,
+}
+
+fn ip_key(ip: IpAddr) -> [u8; 16] {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped().octets(),
+        IpAddr::V6(ip) => ip.octets(),
+    }
+}
+
+impl BanList {
+    /// Creates a new [`BanList`] from a given [`LocalDb`].
+    pub fn new(db: &LocalDb) -> anyhow::Result<Self> {
+        let mut wtxn = db.write_txn()?;
+        let players = db.create_database(&mut wtxn, Some("uuid-to-bans"))?;
+        let ips = db.create_database(&mut wtxn, Some("ip-to-bans"))?;
+        wtxn.commit()?;
+
+        Ok(Self {
+            env: (**db).clone(),
+            players,
+            ips,
+        })
+    }
+
+    /// Bans a player, replacing any ban they already have.
+    pub fn ban_player(&self, uuid: Uuid, ban: &Ban) -> anyhow::Result<()> {
+        let ban = serde_json::to_vec(ban)?;
+
+        let mut wtxn = self.env.write_txn()?;
+        self.players.put(&mut wtxn, &uuid.as_u128(), &ban)?;
+        wtxn.commit()?;
+
+        Ok(())
+    }
+
+    /// Bans every player connecting from `ip`, replacing any ban it already has.
+    pub fn ban_ip(&self, ip: IpAddr, ban: &Ban) -> anyhow::Result<()> {
+        let ban = serde_json::to_vec(ban)?;
+
+        let mut wtxn = self.env.write_txn()?;
+        self.ips.put(&mut wtxn, &ip_key(ip), &ban)?;
+        wtxn.commit()?;
+
+        Ok(())
+    }
+
+    /// The ban which keeps the player with `uuid` connecting from `ip` out, if any. Bans of the
+    /// player come before bans of the address, and expired bans are ignored.
+    pub fn find(&self, uuid: Uuid, ip: Option<IpAddr>) -> anyhow::Result<Option<Ban>> {
+        let now = SystemTime::now();
+        let rtxn = self.env.read_txn()?;
+
+        let player = self.players.get(&rtxn, &uuid.as_u128())?;
+        let ip = match ip {
+            Some(ip) => self.ips.get(&rtxn, &ip_key(ip))?,
+            None => None,
+        };
+
+        for ban in player.into_iter().chain(ip) {
+            let ban: Ban = serde_json::from_slice(ban)?;
+
+            if !ban.is_expired(now) {
+                return Ok(Some(ban));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Lifts the bans matching `target`, which is a UUID, an IP address or the name of a banned
+    /// player. Returns how many bans were lifted.
+    pub fn unban(&self, target: &str) -> anyhow::Result<usize> {
+        let mut wtxn = self.env.write_txn()?;
+
+        let lifted = if let Ok(uuid) = target.parse::<Uuid>() {
+            usize::from(self.players.delete(&mut wtxn, &uuid.as_u128())?)
+        } else if let Ok(ip) = target.parse::<IpAddr>() {
+            usize::from(self.ips.delete(&mut wtxn, &ip_key(ip))?)
+        } else {
+            self.unban_name(&mut wtxn, target)?
+        };
+
+        wtxn.commit()?;
+
+        Ok(lifted)
+    }
+
+    fn unban_name(&self, wtxn: &mut RwTxn<'_>, name: &str) -> anyhow::Result<usize> {
+        let matches = |ban: &[u8]| -> anyhow::Result<bool> {
+            let ban: Ban = serde_json::from_slice(ban)?;
+            Ok(ban
+                .name
+                .is_some_and(|banned| banned.eq_ignore_ascii_case(name)))
+        };
+
+        let mut players = Vec::new();
+
+        for entry in self.players.iter(wtxn)? {
+            let (uuid, ban) = entry?;
+
+            if matches(ban)? {
+                players.push(uuid);
+            }
+        }
+
+        let mut ips = Vec::new();
+
+        for entry in self.ips.iter(wtxn)? {
+            let (ip, ban) = entry?;
+
+            if matches(ban)? {
+                ips.push(ip.to_vec());
+            }
+        }
+
+        for uuid in &players {
+            self.players.delete(wtxn, uuid)?;
+        }
+
+        for ip in &ips {
+            self.ips.delete(wtxn, ip)?;
+        }
+
+        Ok(players.len() + ips.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tempbans_expire() {
+        let now = SystemTime::now();
+
+        let ban = Ban {
+            name: Some("Notch".to_owned()),
+            reason: "griefing".to_owned(),
+            by: "jeb_".to_owned(),
+            since: now,
+            until: Some(now + Duration::from_secs(90)),
+        };
+
+        assert!(!ban.is_expired(now));
+        assert!(ban.is_expired(now + Duration::from_secs(90)));
+        assert_eq!(ban.remaining(now), Some(Duration::from_secs(90)));
+
+        let message = ban.disconnect_message(now);
+        assert!(message.contains("griefing"));
+        assert!(message.contains("1m 30s"));
+    }
+}
@@ -6,6 +6,7 @@ use byteorder::NativeEndian;
 use derive_more::Deref;
 use flecs_ecs::macros::Component;
 use heed::{Database, Env, EnvOpenOptions, types};
+use hyperion_crafting::recipe_book::UnlockedRecipes;
 use uuid::Uuid;
 
 use crate::simulation::skin::{ArchivedPlayerSkin, PlayerSkin};
@@ -90,3 +91,53 @@ impl SkinHandler {
         Ok(())
     }
 }
+
+/// A handler for the recipes players have unlocked
+#[derive(Component, Debug, Clone)]
+pub struct RecipeBookHandler {
+    env: Env,
+    recipes: Database<types::U128<NativeEndian>, types::Bytes>,
+}
+
+impl RecipeBookHandler {
+    /// Creates a new [`RecipeBookHandler`] from a given [`LocalDb`].
+    pub fn new(db: &LocalDb) -> anyhow::Result<Self> {
+        let recipes = {
+            let mut wtxn = db.write_txn()?;
+            let db = db.create_database(&mut wtxn, Some("uuid-to-recipes"))?;
+            wtxn.commit()?;
+            db
+        };
+
+        Ok(Self {
+            env: db.env.clone(),
+            recipes,
+        })
+    }
+
+    /// Finds the [`UnlockedRecipes`] of a player by their UUID.
+    pub fn find(&self, uuid: Uuid) -> anyhow::Result<Option<UnlockedRecipes>> {
+        let uuid = uuid.as_u128();
+
+        let rtxn = self.env.read_txn()?;
+
+        let Some(recipes) = self.recipes.get(&rtxn, &uuid)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(serde_json::from_slice(recipes)?))
+    }
+
+    /// Inserts the [`UnlockedRecipes`] of a player into the database.
+    pub fn insert(&self, uuid: Uuid, recipes: &UnlockedRecipes) -> anyhow::Result<()> {
+        let uuid = uuid.as_u128();
+
+        let recipes = serde_json::to_vec(recipes)?;
+
+        let mut wtxn = self.env.write_txn()?;
+        self.recipes.put(&mut wtxn, &uuid, &recipes)?;
+        wtxn.commit()?;
+
+        Ok(())
+    }
+}
@@ -58,6 +58,11 @@ define_events! {
     event::SwingArm,
     event::ToggleDoor,
     event::ReleaseUseItem,
+    event::RequestRespawn,
+    event::ContainerClick,
+    event::OpenCraftingTable,
+    event::OpenFurnace,
+    event::RecipeBookSettings,
 }
 
 pub trait ReducedLifetime {
@@ -63,9 +63,15 @@ impl TryFrom<play::ClickSlotC2s<'static>> for ClickSlotEvent {
     }
 }
 
+/// Sent when the client closes a window, including its own inventory.
+pub struct CloseScreenEvent {
+    pub window_id: u8,
+}
+
 #[derive(Component, Default)]
 pub struct GlobalEventHandlers {
     pub click: EventHandlers<ClickSlotEvent>,
+    pub close: EventHandlers<CloseScreenEvent>,
     pub interact: EventHandlers<InteractEvent>,
 
     // todo: this should be a lifetime for<'a>
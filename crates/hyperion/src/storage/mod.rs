@@ -1,11 +1,15 @@
+mod bans;
 mod bits;
 mod buf;
 mod db;
 mod event;
+mod player_data;
 mod thread_local;
 
+pub use bans::*;
 pub use bits::*;
 pub use buf::*;
 pub use db::*;
 pub use event::*;
+pub use player_data::*;
 pub use thread_local::*;
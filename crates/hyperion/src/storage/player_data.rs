@@ -0,0 +1,240 @@
+//! Components of players which are kept across reconnects.
+//!
+//! Components opt in by implementing [`Persist`] and being registered with
+//! [`PersistExt::persist`]. When a player leaves, every registered component they have is encoded
+//! into a [`StoredPlayer`] and written to the [`PlayerDataHandler`]; when they log in again, the
+//! components are decoded and set on them before they join the world.
+
+use byteorder::NativeEndian;
+use flecs_ecs::{
+    core::{ComponentId, ComponentType, DataComponent, EntityView, Struct, World, WorldGet},
+    macros::Component,
+};
+use heed::{Database, Env, types};
+use rkyv::{Archive, util::AlignedVec};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::storage::LocalDb;
+
+/// The version of the [`StoredPlayer`] format. Data stored with another version is discarded.
+pub const PLAYER_DATA_VERSION: u32 = 1;
+
+/// A component which is stored when a player leaves and restored when they log in again.
+pub trait Persist: ComponentId + DataComponent + ComponentType<Struct> + Sized {
+    /// The name the component is stored under. It must never change, or stored data is lost.
+    const KEY: &'static str;
+
+    /// The version of the encoding. Stored data with an older version is ignored, so the player
+    /// keeps the default instead.
+    const VERSION: u16 = 0;
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>>;
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self>;
+}
+
+/// One encoded component of a [`StoredPlayer`].
+#[derive(
+    Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq
+)]
+pub struct StoredComponent {
+    pub key: String,
+    pub version: u16,
+    pub data: Vec<u8>,
+}
+
+/// Everything stored about a player.
+#[derive(
+    Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq
+)]
+pub struct StoredPlayer {
+    pub version: u32,
+    pub components: Vec<StoredComponent>,
+}
+
+impl Default for StoredPlayer {
+    fn default() -> Self {
+        Self {
+            version: PLAYER_DATA_VERSION,
+            components: Vec::new(),
+        }
+    }
+}
+
+struct PersistedComponent {
+    key: &'static str,
+    version: u16,
+    save: fn(EntityView<'_>) -> Option<anyhow::Result<Vec<u8>>>,
+    restore: fn(EntityView<'_>, &[u8]) -> anyhow::Result<()>,
+}
+
+fn save<T: Persist>(entity: EntityView<'_>) -> Option<anyhow::Result<Vec<u8>>> {
+    entity.try_get::<&T>(T::encode)
+}
+
+fn restore<T: Persist>(entity: EntityView<'_>, bytes: &[u8]) -> anyhow::Result<()> {
+    entity.set(T::decode(bytes)?);
+    Ok(())
+}
+
+/// The components which are stored for every player.
+#[derive(Component, Default)]
+pub struct PersistedComponents {
+    components: Vec<PersistedComponent>,
+}
+
+impl PersistedComponents {
+    /// Stores `T` for every player from now on. Registering a component twice does nothing.
+    pub fn register<T: Persist>(&mut self) {
+        if self
+            .components
+            .iter()
+            .any(|component| component.key == T::KEY)
+        {
+            return;
+        }
+
+        self.components.push(PersistedComponent {
+            key: T::KEY,
+            version: T::VERSION,
+            save: save::<T>,
+            restore: restore::<T>,
+        });
+    }
+
+    /// Encodes the registered components `player` has.
+    #[must_use]
+    pub fn save(&self, player: EntityView<'_>) -> StoredPlayer {
+        let components = self
+            .components
+            .iter()
+            .filter_map(|component| match (component.save)(player)? {
+                Ok(data) => Some(StoredComponent {
+                    key: component.key.to_owned(),
+                    version: component.version,
+                    data,
+                }),
+                Err(e) => {
+                    error!("failed to encode {}: {e}", component.key);
+                    None
+                }
+            })
+            .collect();
+
+        StoredPlayer {
+            version: PLAYER_DATA_VERSION,
+            components,
+        }
+    }
+
+    /// Sets the components stored in `stored` on `player`. Components which are no longer
+    /// registered or were stored with another version are skipped.
+    pub fn restore(&self, player: EntityView<'_>, stored: &StoredPlayer) {
+        for data in &stored.components {
+            let Some(component) = self.components.iter().find(|c| c.key == data.key) else {
+                continue;
+            };
+
+            if component.version != data.version {
+                warn!(
+                    "discarding {} stored with version {} instead of {}",
+                    data.key, data.version, component.version
+                );
+                continue;
+            }
+
+            if let Err(e) = (component.restore)(player, &data.data) {
+                error!("failed to restore {}: {e}", data.key);
+            }
+        }
+    }
+}
+
+/// Registers components to be stored for every player.
+pub trait PersistExt {
+    fn persist<T: Persist>(&self);
+}
+
+impl PersistExt for World {
+    fn persist<T: Persist>(&self) {
+        self.get::<&mut PersistedComponents>(PersistedComponents::register::<T>);
+    }
+}
+
+/// A handler for the data stored about players
+#[derive(Component, Debug, Clone)]
+pub struct PlayerDataHandler {
+    env: Env,
+    players: Database<types::U128<NativeEndian>, types::Bytes>,
+}
+
+impl PlayerDataHandler {
+    /// Creates a new [`PlayerDataHandler`] from a given [`LocalDb`].
+    pub fn new(db: &LocalDb) -> anyhow::Result<Self> {
+        let players = {
+            let mut wtxn = db.write_txn()?;
+            let db = db.create_database(&mut wtxn, Some("uuid-to-player-data"))?;
+            wtxn.commit()?;
+            db
+        };
+
+        Ok(Self {
+            env: (**db).clone(),
+            players,
+        })
+    }
+
+    /// Finds the [`StoredPlayer`] of a player by their UUID. Data in an outdated format is treated
+    /// as missing.
+    pub fn find(&self, uuid: Uuid) -> anyhow::Result<Option<StoredPlayer>> {
+        let uuid = uuid.as_u128();
+
+        let rtxn = self.env.read_txn()?;
+
+        let Some(bytes) = self.players.get(&rtxn, &uuid)? else {
+            return Ok(None);
+        };
+
+        // the database does not align values
+        let mut aligned = AlignedVec::<16>::new();
+        aligned.extend_from_slice(bytes);
+
+        let player = rkyv::from_bytes::<StoredPlayer, rkyv::rancor::Error>(&aligned)?;
+
+        if player.version != PLAYER_DATA_VERSION {
+            warn!(
+                "discarding player data stored with version {}",
+                player.version
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(player))
+    }
+
+    /// Inserts the [`StoredPlayer`] of a player into the database.
+    pub fn insert(&self, uuid: Uuid, player: &StoredPlayer) -> anyhow::Result<()> {
+        let uuid = uuid.as_u128();
+
+        let player = rkyv::to_bytes::<rkyv::rancor::Error>(player)?;
+
+        let mut wtxn = self.env.write_txn()?;
+        self.players.put(&mut wtxn, &uuid, &player)?;
+        wtxn.commit()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+#![allow(
+    clippy::float_cmp,
+    reason = "health only changes by whole amounts in these tests"
+)]
+
+use flecs_ecs::{
+    core::{EntityView, EntityViewGet, World, WorldGet},
+    macros::Component,
+    prelude::Module,
+};
+use hyperion::{
+    glam::Vec3,
+    net::ConnectionId,
+    simulation::{
+        Position,
+        death::{DamageExt, Dead, RespawnPoint},
+        effect::{ActiveEffects, EffectKind, StatusEffect},
+        entity_kind::EntityKind,
+        equipment::Equipment,
+        event,
+        game_mode::GameMode,
+        metadata::living_entity::Health,
+        spawn::SpawnExt,
+    },
+    storage::Events,
+};
+
+#[derive(Component)]
+struct TestModule;
+
+impl Module for TestModule {
+    fn module(world: &World) {
+        world.import::<hyperion::HyperionCore>();
+    }
+}
+
+fn health(entity: EntityView<'_>) -> f32 {
+    entity.get::<&Health>(|health| **health)
+}
+
+#[test]
+fn dead_entities_are_despawned() {
+    let world = World::new();
+    world.import::<TestModule>();
+
+    let zombie = world
+        .spawn_entity(EntityKind::Zombie)
+        .at(Vec3::new(0.0, 20.0, 0.0))
+        .spawn();
+
+    assert!(zombie.damage(100.0));
+    world.progress();
+
+    assert!(zombie.has::<Dead>(), "An entity without health should die.");
+    assert!(!zombie.damage(1.0), "Dead entities cannot be damaged.");
+
+    for _ in 0..30 {
+        world.progress();
+    }
+
+    assert!(
+        !zombie.is_alive(),
+        "Dead entities should be removed after the death animation."
+    );
+}
+
+#[test]
+fn players_respawn_at_their_respawn_point() {
+    let world = World::new();
+    world.import::<TestModule>();
+
+    let respawn_point = RespawnPoint {
+        position: Vec3::new(10.5, 64.0, -3.5),
+        yaw: 90.0,
+        pitch: 0.0,
+    };
+
+    let player = world
+        .spawn_entity(EntityKind::Player)
+        .at(Vec3::new(0.0, 20.0, 0.0))
+        .with(|player| {
+            player
+                .set(ConnectionId::new(0))
+                .set(GameMode::Survival)
+                .set(Equipment::default())
+                .set(ActiveEffects::default())
+                .set(respawn_point);
+        })
+        .spawn();
+
+    player.get::<&mut ActiveEffects>(|effects| {
+        effects.add(StatusEffect::new(EffectKind::Poison, 0, 1000));
+    });
+
+    assert!(player.damage(100.0));
+    world.progress();
+
+    assert!(player.has::<Dead>());
+    assert!(
+        player.is_alive(),
+        "Dead players wait for the respawn button."
+    );
+
+    world.get::<&Events>(|events| {
+        events.push(event::RequestRespawn { from: player.id() }, &world);
+    });
+    world.progress();
+
+    assert!(!player.has::<Dead>());
+    assert_eq!(health(player), 20.0);
+    player.get::<(&Position, &ActiveEffects)>(|(position, effects)| {
+        assert_eq!(**position, respawn_point.position);
+        assert!(
+            !effects.has(EffectKind::Poison),
+            "Effects should not survive death."
+        );
+    });
+}
+
+#[test]
+fn invulnerable_game_modes_take_no_damage() {
+    let world = World::new();
+    world.import::<TestModule>();
+
+    let player = world
+        .spawn_entity(EntityKind::Player)
+        .with(|player| {
+            player.set(GameMode::Creative);
+        })
+        .spawn();
+
+    assert!(!player.is_vulnerable());
+    assert!(!player.damage(5.0));
+    assert_eq!(health(player), 20.0);
+
+    player.set(GameMode::Survival);
+
+    assert!(player.is_vulnerable());
+    assert!(player.damage(5.0));
+    assert_eq!(health(player), 15.0);
+}
@@ -100,6 +100,27 @@ impl SpatialIndex {
         Some(world.entity_from_id(*target))
     }
 
+    /// Get the closest entity within `range` blocks of the given position for which `filter` is
+    /// true.
+    #[must_use]
+    pub fn closest_matching<'a>(
+        &self,
+        point: Vec3,
+        range: f32,
+        world: &'a World,
+        filter: impl Fn(EntityView<'a>) -> bool,
+    ) -> Option<EntityView<'a>> {
+        let get_aabb = get_aabb_func(world);
+        let area = Aabb::new(point - Vec3::splat(range), point + Vec3::splat(range));
+
+        self.query
+            .range(area, &get_aabb)
+            .map(|entity| (world.entity_from_id(*entity), get_aabb(entity).dist2(point)))
+            .filter(|(entity, _)| filter(*entity))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity)
+    }
+
     #[must_use]
     pub fn closest_to_ray<'a>(
         &self,
@@ -1,8 +1,13 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU32, Ordering},
+};
+
 use clap::Parser;
-use flecs_ecs::core::{Entity, EntityView};
-use hyperion::valence_protocol::packets::play::click_slot_c2s::ClickMode;
+use flecs_ecs::core::{Entity, EntityView, WorldProvider};
+use hyperion::valence_protocol::packets::play::open_screen_s2c::WindowType;
 use hyperion_clap::{CommandPermission, MinecraftCommand};
-use hyperion_gui::{ContainerType, Gui, GuiItem};
+use hyperion_gui::{Gui, GuiExt, GuiItem, PageLayout};
 use hyperion_item::builder::ItemBuilder;
 use tracing::debug;
 
@@ -11,28 +16,65 @@ use tracing::debug;
 #[command_permission(group = "Normal")]
 pub struct GuiCommand;
 
+const INFO_SLOT: usize = 4;
+const CLOSE_SLOT: usize = 49;
+
+fn info_item(clicks: Arc<AtomicU32>) -> GuiItem {
+    let count = clicks.load(Ordering::Relaxed);
+
+    GuiItem::new(
+        ItemBuilder::new(hyperion::ItemKind::GoldIngot)
+            .name(format!("Clicked {count} times"))
+            .glowing()
+            .build(),
+    )
+    .on_click(move |click| {
+        clicks.fetch_add(1, Ordering::Relaxed);
+
+        // draw the item again with the new count
+        click
+            .gui
+            .add_item(INFO_SLOT, info_item(clicks.clone()))
+            .unwrap();
+    })
+}
+
 impl MinecraftCommand for GuiCommand {
     fn execute(self, system: EntityView<'_>, caller: Entity) {
-        let mut gui = Gui::new(27, "Test Chest GUI".to_string(), ContainerType::Chest);
-
-        let info_item = GuiItem::new(
-            ItemBuilder::new(hyperion::ItemKind::GoldIngot)
-                .name("Information")
-                .glowing()
-                .build(),
-            |_player, click_mode| match click_mode {
-                ClickMode::Click => debug!("Left Click"),
-                ClickMode::ShiftClick => debug!("Shift Click"),
-                ClickMode::Hotbar => debug!("Hotbar"),
-                ClickMode::CreativeMiddleClick => debug!("Creative Middle Click"),
-                ClickMode::DropKey => debug!("Drop Key"),
-                ClickMode::Drag => debug!("Drag"),
-                ClickMode::DoubleClick => debug!("Double Click"),
-            },
-        );
-
-        gui.add_item(13, info_item).unwrap();
-
-        gui.open(system, caller);
+        let mut gui = Gui::new(WindowType::Generic9x6, "Test GUI")
+            .on_close(|player| debug!("{:?} closed the test GUI", player.id()));
+
+        gui.add_item(INFO_SLOT, info_item(Arc::default())).unwrap();
+
+        gui.add_item(
+            CLOSE_SLOT,
+            GuiItem::new(
+                ItemBuilder::new(hyperion::ItemKind::Barrier)
+                    .name("Close")
+                    .build(),
+            )
+            .on_click(|click| click.close()),
+        )
+        .unwrap();
+
+        let entries = (1..=100)
+            .map(|i| {
+                GuiItem::new(
+                    ItemBuilder::new(hyperion::ItemKind::Paper)
+                        .name(format!("Entry {i}"))
+                        .build(),
+                )
+                .on_click(move |click| debug!("clicked entry {i} with {:?}", click.mode))
+            })
+            .collect();
+
+        gui.set_pages(entries, PageLayout {
+            slots: 9..45,
+            previous: 45,
+            next: 53,
+        })
+        .unwrap();
+
+        caller.entity_view(system.world()).open_gui(gui);
     }
 }
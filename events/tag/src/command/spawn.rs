@@ -5,19 +5,19 @@ use flecs_ecs::{
 };
 use hyperion::{
     BlockState,
+    glam::Vec3,
     simulation::{
-        Pitch, Position, Spawn, Uuid, Velocity, Yaw,
         entity_kind::EntityKind,
         metadata::{
             block_display::DisplayedBlockState,
             display::{Height, Width},
         },
+        spawn::SpawnExt,
     },
 };
+use hyperion_ai::Chase;
 use hyperion_clap::{CommandPermission, MinecraftCommand};
 
-use crate::FollowClosestPlayer;
-
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "spawn")]
 #[command_permission(group = "Normal")]
@@ -28,21 +28,15 @@ impl MinecraftCommand for SpawnCommand {
         let world = system.world();
 
         world
-            .entity()
-            .add_enum(EntityKind::BlockDisplay)
-            // .set(EntityFlags::ON_FIRE)
-            .set(Uuid::new_v4())
-            .set(Width::new(1.0))
-            .set(Height::new(1.0))
-            // .set(ViewRange::new(100.0))
-            // .add_enum(EntityKind::Zombie)
-            .set(Position::new(0.0, 22.0, 0.0))
-            .set(Pitch::new(0.0))
-            .set(Yaw::new(0.0))
-            .set(Velocity::new(0.0, 0.0, 0.0))
-            .add::<FollowClosestPlayer>()
-            .set(DisplayedBlockState::new(BlockState::DIRT))
-            // .is_a_id(prefabs.block_display_base)
-            .enqueue(Spawn);
+            .spawn_entity(EntityKind::BlockDisplay)
+            .at(Vec3::new(0.0, 22.0, 0.0))
+            .with(|entity| {
+                entity
+                    .set(Width::new(1.0))
+                    .set(Height::new(1.0))
+                    .set(Chase { range: 64.0 })
+                    .set(DisplayedBlockState::new(BlockState::DIRT));
+            })
+            .spawn();
     }
 }
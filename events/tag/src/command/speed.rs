@@ -1,10 +1,8 @@
 use clap::Parser;
 use flecs_ecs::core::{Entity, EntityView, EntityViewGet, WorldGet, WorldProvider};
 use hyperion::{
-    net::{Compose, ConnectionId, DataBundle, agnostic},
-    valence_protocol::packets::play::{
-        PlayerAbilitiesS2c, player_abilities_s2c::PlayerAbilitiesFlags,
-    },
+    net::{Compose, ConnectionId, agnostic},
+    simulation::effect::{ActiveEffects, EffectKind, StatusEffect},
 };
 use hyperion_clap::{CommandPermission, MinecraftCommand};
 
@@ -12,35 +10,32 @@ use hyperion_clap::{CommandPermission, MinecraftCommand};
 #[command(name = "speed")]
 #[command_permission(group = "Moderator")]
 pub struct SpeedCommand {
-    amount: f32,
+    /// The level of speed, or 0 to remove it
+    level: u8,
 }
 
 impl MinecraftCommand for SpeedCommand {
     fn execute(self, system: EntityView<'_>, caller: Entity) {
         let world = system.world();
-        let msg = format!("Setting speed to {}", self.amount);
-        let chat = agnostic::chat(msg);
 
         world.get::<&Compose>(|compose| {
-            caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                let packet = speed_packet(self.amount);
+            caller
+                .entity_view(world)
+                .get::<(&mut ActiveEffects, &ConnectionId)>(|(effects, stream)| {
+                    // replace the current speed even if it was stronger
+                    effects.remove(EffectKind::Speed);
 
-                let mut bundle = DataBundle::new(compose, system);
-                bundle.add_packet(&packet).unwrap();
-                bundle.add_packet(&chat).unwrap();
+                    let msg = if let Some(amplifier) = self.level.checked_sub(1) {
+                        effects.add(StatusEffect::infinite(EffectKind::Speed, amplifier));
+                        format!("Setting speed to {}", self.level)
+                    } else {
+                        "Removed speed".to_string()
+                    };
 
-                bundle.unicast(*stream).unwrap();
-            });
+                    compose
+                        .unicast(&agnostic::chat(msg), *stream, system)
+                        .unwrap();
+                });
         });
     }
 }
-
-fn speed_packet(amount: f32) -> PlayerAbilitiesS2c {
-    PlayerAbilitiesS2c {
-        flags: PlayerAbilitiesFlags::default()
-            .with_flying(true)
-            .with_allow_flying(true),
-        flying_speed: amount,
-        fov_modifier: 0.0,
-    }
-}
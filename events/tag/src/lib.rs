@@ -6,20 +6,20 @@
 use std::{collections::HashSet, net::SocketAddr};
 
 use flecs_ecs::prelude::*;
-use hyperion::{Address, HyperionCore, simulation::Player};
+use hyperion::{Address, HyperionCore, simulation::Player, storage::PersistExt};
+use hyperion_chat::{CapsFilter, Channel, ChatConfig, LinkFilter, SpamFilter};
 use hyperion_clap::hyperion_command::CommandRegistry;
-use module::{block::BlockModule, vanish::VanishModule};
+use module::{block::BlockModule, nametag::NametagModule, vanish::VanishModule};
 
 mod module;
 
 use derive_more::{Deref, DerefMut};
-use hyperion::{glam::IVec3, simulation::Position};
+use hyperion::glam::IVec3;
 use hyperion_rank_tree::Team;
 use module::{attack::AttackModule, level::LevelModule, regeneration::RegenerationModule};
-use spatial::SpatialIndex;
 
 use crate::{
-    module::{bow::BowModule, chat::ChatModule, spawn::SpawnModule, stats::StatsModule},
+    module::{bow::BowModule, spawn::SpawnModule, stats::StatsModule},
     skin::SkinModule,
 };
 
@@ -43,14 +43,10 @@ impl Default for MainBlockCount {
     }
 }
 
-#[derive(Component)]
-struct FollowClosestPlayer;
-
 impl Module for TagModule {
     fn module(world: &World) {
         // on entity kind set UUID
 
-        world.component::<FollowClosestPlayer>();
         world.component::<MainBlockCount>();
 
         world
@@ -59,6 +55,10 @@ impl Module for TagModule {
 
         world.import::<hyperion_rank_tree::RankTree>();
 
+        // players keep their team and class when they reconnect
+        world.persist::<Team>();
+        world.persist::<hyperion_rank_tree::Class>();
+
         world.component::<OreVeins>();
         world.set(OreVeins::default());
 
@@ -67,7 +67,8 @@ impl Module for TagModule {
             .add_trait::<(flecs::With, Team)>();
 
         world.import::<SpawnModule>();
-        world.import::<ChatModule>();
+        world.import::<hyperion_chat::ChatModule>();
+        world.import::<hyperion_moderation::ModerationModule>();
         world.import::<StatsModule>();
         world.import::<BlockModule>();
         world.import::<AttackModule>();
@@ -79,12 +80,23 @@ impl Module for TagModule {
         world.import::<hyperion_clap::ClapCommandModule>();
         world.import::<SkinModule>();
         world.import::<VanishModule>();
+        world.import::<NametagModule>();
         world.import::<hyperion_genmap::GenMapModule>();
 
         world.get::<&mut CommandRegistry>(|registry| {
             command::register(registry, world);
         });
 
+        world.get::<&mut ChatConfig>(|config| {
+            // one message every 15 seconds
+            config.cooldown = 15 * 20;
+            // tag chat is proximity chat
+            config.default_channel = Channel::Local;
+            config.add_filter(CapsFilter::default());
+            config.add_filter(LinkFilter);
+            config.add_filter(SpamFilter::default());
+        });
+
         world.set(hyperion_utils::AppId {
             qualifier: "com".to_string(),
             organization: "andrewgazelka".to_string(),
@@ -97,33 +109,10 @@ impl Module for TagModule {
             .component::<Player>()
             .add_trait::<(flecs::With, spatial::Spatial)>();
 
-        system!(
-            "follow_closest_player",
-            world,
-            &SpatialIndex($),
-            &mut Position,
-        )
-        .with::<FollowClosestPlayer>()
-        .each_entity(|entity, (index, position)| {
-            let world = entity.world();
-
-            let Some(closest) = index.closest_to(**position, &world) else {
-                return;
-            };
-
-            closest.get::<&Position>(|target_position| {
-                let delta = **target_position - **position;
-
-                if delta.length_squared() < 0.01 {
-                    // we are already at the target position
-                    return;
-                }
-
-                let delta = delta.normalize() * 0.1;
-
-                **position += delta;
-            });
-        });
+        world.import::<hyperion_ai::AiModule>();
+        world.import::<hyperion_item::dropped::DroppedItemModule>();
+        world.import::<hyperion_item::custom::CustomItemModule>();
+        world.import::<hyperion_gui::GuiModule>();
     }
 }
 
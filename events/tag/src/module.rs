@@ -1,8 +1,8 @@
 pub mod attack;
 pub mod block;
 pub mod bow;
-pub mod chat;
 pub mod level;
+pub mod nametag;
 pub mod regeneration;
 pub mod spawn;
 pub mod stats;
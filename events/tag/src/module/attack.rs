@@ -1,35 +1,37 @@
 use std::borrow::Cow;
 
-use compact_str::format_compact;
+use anyhow::Context;
 use flecs_ecs::{
-    core::{EntityViewGet, QueryBuilderImpl, SystemAPI, TableIter, TermBuilderImpl, World, flecs},
+    core::{
+        Entity, EntityViewGet, QueryBuilderImpl, SystemAPI, TableIter, TermBuilderImpl, World,
+        WorldProvider, flecs,
+    },
     macros::{Component, system},
     prelude::Module,
 };
 use hyperion::{
-    net::{
-        Compose, ConnectionId, agnostic,
-        packets::{BossBarAction, BossBarS2c},
-    },
+    net::{Compose, ConnectionId, agnostic},
     simulation::{
-        PacketState, Player, Position, Velocity, Yaw, event,
-        metadata::{entity::Pose, living_entity::Health},
+        PacketState, Player, Position, Velocity, Yaw,
+        boss_bar::{Audience, BossBar, BossBarColor, BossBarDivision},
+        death::{DamageExt, DeathMessage, KeepInventory},
+        effect::ActiveEffects,
+        event,
+        metadata::living_entity::Health,
     },
-    storage::EventQueue,
-    uuid::Uuid,
+    storage::{EventQueue, Persist, PersistExt},
     valence_protocol::{
         ItemKind, ItemStack, Particle, VarInt, ident,
         math::{DVec3, Vec3},
         nbt,
-        packets::play::{
-            self,
-            boss_bar_s2c::{BossBarColor, BossBarDivision, BossBarFlags},
-            entity_attributes_s2c::AttributeProperty,
-        },
-        text::IntoText,
+        packets::play::{self, entity_attributes_s2c::AttributeProperty},
     },
 };
 use hyperion_inventory::PlayerInventory;
+use hyperion_item::{
+    durability,
+    enchantment::{self, Enchantment},
+};
 use hyperion_utils::EntityExt;
 use tracing::info_span;
 
@@ -64,6 +66,28 @@ pub struct KillCount {
     pub kill_count: u32,
 }
 
+/// The boss bar which shows the kills of a player.
+#[derive(Component, Copy, Clone, Debug)]
+struct KillBar {
+    bar: Entity,
+}
+
+impl Persist for KillCount {
+    const KEY: &'static str = "tag:kill_count";
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.kill_count.to_le_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let kill_count = bytes.try_into().context("kill count is not a u32")?;
+
+        Ok(Self {
+            kill_count: u32::from_le_bytes(kill_count),
+        })
+    }
+}
+
 #[allow(clippy::cast_possible_truncation)]
 impl Module for AttackModule {
     #[allow(clippy::excessive_nesting)]
@@ -72,48 +96,54 @@ impl Module for AttackModule {
         world.component::<Armor>().meta();
         world.component::<CombatStats>().meta();
         world.component::<KillCount>().meta();
+        world.component::<KillBar>();
+
+        world.persist::<KillCount>();
 
         world
             .component::<Player>()
             .add_trait::<(flecs::With, ImmuneUntil)>()
             .add_trait::<(flecs::With, CombatStats)>()
             .add_trait::<(flecs::With, KillCount)>()
-            .add_trait::<(flecs::With, Armor)>();
-
-        let kill_count_uuid = Uuid::new_v4();
+            .add_trait::<(flecs::With, Armor)>()
+            // kits are upgraded on kills, so they are not dropped on death
+            .add_trait::<(flecs::With, KeepInventory)>();
 
         system!(
             "kill_counts",
             world,
-            &Compose($),
             &KillCount,
-            &ConnectionId,
+            ?&KillBar,
         )
         .with_enum(PacketState::Play)
-        .multi_threaded()
         .kind::<flecs::pipeline::OnUpdate>()
-        .each_iter(move |it, _, (compose, kill_count, stream)| {
+        .each_entity(|player, (kill_count, kill_bar)| {
             const MAX_KILLS: usize = 10;
 
-            let system = it.system();
+            let world = player.world();
 
             let kills = kill_count.kill_count;
-            let title = format_compact!("{kills} kills");
-            let title = hyperion_text::Text::new(&title);
+            let title = format!("{kills} kills");
             let health = (kill_count.kill_count as f32 / MAX_KILLS as f32).min(1.0);
 
-            let pkt = BossBarS2c {
-                id: kill_count_uuid,
-                action: BossBarAction::Add {
-                    title,
-                    health,
-                    color: BossBarColor::Red,
-                    division: BossBarDivision::NoDivision,
-                    flags: BossBarFlags::default(),
-                },
+            let Some(kill_bar) = kill_bar else {
+                let mut bar = BossBar::new(title)
+                    .with_style(BossBarColor::Red, BossBarDivision::NoDivision)
+                    .with_audience(Audience::Players([player.id()].into_iter().collect()));
+                bar.set_health(health);
+
+                // the bar is removed together with the player
+                let bar = world.entity().child_of_id(player).set(bar);
+                player.set(KillBar { bar: bar.id() });
+                return;
             };
 
-            compose.unicast(&pkt, *stream, system).unwrap();
+            world
+                .entity_from_id(kill_bar.bar)
+                .get::<&mut BossBar>(|bar| {
+                    bar.set_title(title);
+                    bar.set_health(health);
+                });
         });
 
         system!("handle_attacks", world, &mut EventQueue<event::AttackEntity>($), &Compose($))
@@ -139,17 +169,26 @@ impl Module for AttackModule {
                     for event in event_queue.drain() {
                         let target = world.entity_from_id(event.target);
                         let origin = world.entity_from_id(event.origin);
-                        origin.get::<(&Position, &mut KillCount, &mut PlayerInventory, &mut Armor, &CombatStats, &PlayerInventory)>(|(origin_pos, kill_count, inventory, origin_armor, from_stats, from_inventory)| {
-                            let damage = from_stats.damage + calculate_stats(from_inventory).damage;
+
+                        if !target.is_vulnerable() {
+                            continue;
+                        }
+
+                        origin.get::<(&Position, &mut KillCount, &mut PlayerInventory, &mut Armor, &CombatStats, &PlayerInventory, Option<&ActiveEffects>)>(|(origin_pos, kill_count, inventory, origin_armor, from_stats, from_inventory, from_effects)| {
+                            let damage = from_stats.damage
+                                + calculate_stats(from_inventory).damage
+                                + from_effects.map_or(0.0, ActiveEffects::attack_damage_bonus);
+                            let knockback = enchantment::level(from_inventory.get_cursor(), Enchantment::Knockback);
                             target.try_get::<(
                                 Option<&mut ImmuneUntil>,
                                 &mut Health,
                                 &mut Position,
                                 &Yaw,
                                 &CombatStats,
-                                &PlayerInventory
+                                &mut PlayerInventory,
+                                Option<&ActiveEffects>
                             )>(
-                                |(immune_until, health, target_position, target_yaw, stats, target_inventory)| {
+                                |(immune_until, health, target_position, target_yaw, stats, target_inventory, target_effects)| {
                                     if let Some(immune_until) = immune_until {
                                         if immune_until.tick > current_tick {
                                             return;
@@ -164,10 +203,35 @@ impl Module for AttackModule {
                                     let protection = stats.protection + calculated_stats.protection;
 
                                     let damage_after_armor = get_damage_left(damage, armor, toughness);
-                                    let damage_after_protection = get_inflicted_damage(damage_after_armor, protection);
+                                    let damage_after_protection = get_inflicted_damage(damage_after_armor, protection)
+                                        * target_effects.map_or(1.0, ActiveEffects::damage_multiplier);
 
                                     health.damage(damage_after_protection);
 
+                                    // the weapon and the armor wear out with every hit that lands
+                                    let weapon = inventory.get_cursor_index();
+                                    let weapon_wear = durability::attack_wear(inventory.get_cursor().item);
+
+                                    if durability::wear_slot(inventory, weapon, weapon_wear) {
+                                        durability::play_break_sound(compose, origin_pos, system);
+                                    }
+
+                                    let armor_wear = durability::armor_wear(damage);
+                                    let armor_broke = [
+                                        PlayerInventory::HELMET_SLOT,
+                                        PlayerInventory::CHESTPLATE_SLOT,
+                                        PlayerInventory::LEGGINGS_SLOT,
+                                        PlayerInventory::BOOTS_SLOT,
+                                    ]
+                                    .into_iter()
+                                    .fold(false, |broke, slot| {
+                                        durability::wear_slot(target_inventory, slot, armor_wear) | broke
+                                    });
+
+                                    if armor_broke {
+                                        durability::play_break_sound(compose, target_position, system);
+                                    }
+
                                     let pkt_health = play::HealthUpdateS2c {
                                         health: health.abs(),
                                         food: VarInt(20),
@@ -204,15 +268,6 @@ impl Module for AttackModule {
                                         compose.unicast(&pkt_hurt, *stream, system).unwrap();
                                         compose.unicast(&pkt_health, *stream, system).unwrap();
 
-                                        if health.is_dead() {
-                                            let attacker_name = origin.name();
-                                            // Even if enable_respawn_screen is false, the client needs this to send ClientCommandC2s and initiate its respawn
-                                            let pkt_death_screen = play::DeathMessageS2c {
-                                                player_id: VarInt(target.minecraft_id()),
-                                                message: format!("You were killed by {attacker_name}").into_cow_text()
-                                            };
-                                            compose.unicast(&pkt_death_screen, *stream, system).unwrap();
-                                        }
                                     });
                                     compose.broadcast(&sound, system).send().unwrap();
                                     compose.broadcast(&pkt_damage_event, system).send().unwrap();
@@ -237,11 +292,6 @@ impl Module for AttackModule {
                                             count: 75,
                                             offset: Vec3::new(0.3, 0.3, 0.3),
                                         };
-                                        let pkt_entity_status = play::EntityStatusS2c {
-                                            entity_id: target.minecraft_id(),
-                                            entity_status: 3
-                                        };
-
                                         let origin_entity_id = origin.minecraft_id();
 
                                         origin_armor.armor += 1.0;
@@ -259,8 +309,8 @@ impl Module for AttackModule {
                                         compose.broadcast(&pkt, system).send().unwrap();
                                         compose.broadcast(&particle_pkt, system).send().unwrap();
                                         compose.broadcast(&particle_pkt2, system).send().unwrap();
-                                        compose.broadcast(&pkt_entity_status, system).send().unwrap();
-                                        target.set::<Pose>(Pose::Dying);
+                                        let attacker_name = origin.name();
+                                        target.set(DeathMessage::new(format!("You were killed by {attacker_name}")));
 
                                         // Create NBT for enchantment protection level 1
                                         let mut protection_nbt = nbt::Compound::new();
@@ -441,8 +491,6 @@ impl Module for AttackModule {
                                         // player died, increment kill count
                                         kill_count.kill_count += 1;
 
-                                        // Respawning player TODO : Restore target's health
-                                        // send respawn and teleport packets
                                         return;
                                     }
 
@@ -452,13 +500,13 @@ impl Module for AttackModule {
 
                                     let dir = (this - other).normalize();
 
-                                    let knockback_xz = 8.0;
-                                    let knockback_y = 6.432;
+                                    let knockback_xz = 8.0 / 20.0 + enchantment::knockback_bonus(knockback);
+                                    let knockback_y = 6.432 / 20.0;
 
                                     let new_vel = Velocity::new(
-                                        dir.x * knockback_xz / 20.0,
-                                        knockback_y / 20.0,
-                                        dir.z * knockback_xz / 20.0
+                                        dir.x * knockback_xz,
+                                        knockback_y,
+                                        dir.z * knockback_xz
                                     );
 
                                     // https://github.com/valence-rs/valence/blob/8f3f84d557dacddd7faddb2ad724185ecee2e482/examples/ctf.rs#L987-L989
@@ -541,8 +589,9 @@ const fn calculate_toughness(item: &ItemStack) -> f32 {
 }
 
 fn calculate_stats(inventory: &PlayerInventory) -> CombatStats {
-    let hand = inventory.get_hand_slot(0).unwrap();
-    let damage = calculate_damage(hand);
+    let hand = inventory.get_cursor();
+    let damage = calculate_damage(hand)
+        + enchantment::sharpness_bonus(enchantment::level(hand, Enchantment::Sharpness));
     let armor = calculate_armor(inventory.get_helmet())
         + calculate_armor(inventory.get_chestplate())
         + calculate_armor(inventory.get_leggings())
@@ -553,11 +602,20 @@ fn calculate_stats(inventory: &PlayerInventory) -> CombatStats {
         + calculate_toughness(inventory.get_leggings())
         + calculate_toughness(inventory.get_boots());
 
+    let protection = [
+        inventory.get_helmet(),
+        inventory.get_chestplate(),
+        inventory.get_leggings(),
+        inventory.get_boots(),
+    ]
+    .into_iter()
+    .map(|piece| enchantment::protection_factor(enchantment::level(piece, Enchantment::Protection)))
+    .sum();
+
     CombatStats {
         armor,
         armor_toughness,
         damage,
-        // TODO
-        protection: 0.0,
+        protection,
     }
 }
@@ -12,7 +12,7 @@ use hyperion::{
     BlockKind, chat,
     net::{Compose, ConnectionId, agnostic},
     simulation::{
-        Xp,
+        Position, Xp,
         blocks::{Blocks, EntityAndSequence},
         event,
     },
@@ -27,6 +27,7 @@ use hyperion::{
     },
 };
 use hyperion_inventory::PlayerInventory;
+use hyperion_item::durability;
 use hyperion_rank_tree::inventory;
 use hyperion_scheduled::Scheduled;
 use tracing::{error, info_span};
@@ -244,6 +245,15 @@ impl Module for BlockModule {
 
                         compose.unicast(&sound, net, system).unwrap();
                     });
+
+                    from_entity.get::<(&mut PlayerInventory, &Position)>(|(inventory, position)| {
+                        let tool = inventory.get_cursor_index();
+                        let wear = durability::mining_wear(inventory.get_cursor().item);
+
+                        if durability::wear_slot(inventory, tool, wear) {
+                            durability::play_break_sound(compose, position, system);
+                        }
+                    });
                 }
             });
 
@@ -0,0 +1,75 @@
+use flecs_ecs::{
+    core::{QueryBuilderImpl, SystemAPI, TermBuilderImpl, World, WorldGet, flecs},
+    macros::{Component, observer, system},
+    prelude::Module,
+};
+use hyperion::{
+    simulation::{
+        Player,
+        scoreboard::{
+            ScoreboardTeam, TeamOptions, Teams,
+            team::{CollisionRule, TeamColor},
+        },
+    },
+    valence_protocol::text::IntoText,
+};
+use hyperion_rank_tree::Team;
+
+#[derive(Component)]
+pub struct NametagModule;
+
+const fn team_name(team: Team) -> &'static str {
+    match team {
+        Team::Red => "red",
+        Team::Blue => "blue",
+        Team::Green => "green",
+        Team::Yellow => "yellow",
+    }
+}
+
+const fn team_color(team: Team) -> (TeamColor, &'static str) {
+    match team {
+        Team::Red => (TeamColor::Red, "§c"),
+        Team::Blue => (TeamColor::Blue, "§9"),
+        Team::Green => (TeamColor::BrightGreen, "§a"),
+        Team::Yellow => (TeamColor::Yellow, "§e"),
+    }
+}
+
+impl Module for NametagModule {
+    fn module(world: &World) {
+        world.get::<&mut Teams>(|teams| {
+            for team in [Team::Red, Team::Blue, Team::Green, Team::Yellow] {
+                let name = team_name(team);
+                let (color, code) = team_color(team);
+
+                teams.define(name, TeamOptions {
+                    display_name: format!("{code}{name}").into_cow_text().into_owned(),
+                    prefix: format!("{code}[{}] ", name.to_uppercase())
+                        .into_cow_text()
+                        .into_owned(),
+                    color,
+                    // teammates walk through each other
+                    collision_rule: CollisionRule::PushOtherTeams,
+                    ..TeamOptions::default()
+                });
+            }
+        });
+
+        // the team of a player is changed with /class or restored when they log in
+        observer!(world, flecs::OnSet, &Team)
+            .with::<Player>()
+            .each_entity(|entity, team| {
+                entity.set(ScoreboardTeam::new(team_name(*team)));
+            });
+
+        // players who still have the default team
+        system!("assign_default_nametags", world, &Team)
+            .with::<Player>()
+            .without::<ScoreboardTeam>()
+            .kind::<flecs::pipeline::OnUpdate>()
+            .each_entity(|entity, team| {
+                entity.set(ScoreboardTeam::new(team_name(*team)));
+            });
+    }
+}
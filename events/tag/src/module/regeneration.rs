@@ -6,7 +6,11 @@ use flecs_ecs::{
 use hyperion::{
     Prev,
     net::Compose,
-    simulation::{Player, metadata::living_entity::Health},
+    simulation::{
+        Player,
+        effect::{ActiveEffects, EffectKind, StatusEffect},
+        metadata::living_entity::Health,
+    },
     util::TracingExt,
 };
 use tracing::info_span;
@@ -20,10 +24,26 @@ pub struct LastDamaged {
     pub tick: i64,
 }
 
-const MAX_HEALTH: f32 = 20.0;
+/// Ticks without damage after which natural regeneration gets one level stronger.
+const TICKS_PER_LEVEL: i64 = 300;
+
+/// The highest amplifier natural regeneration ramps up to.
+const MAX_AMPLIFIER: u8 = 2;
+
+/// Natural regeneration is a hidden, infinite regeneration effect so it can be told apart from
+/// regeneration given by anything else.
+const fn natural_regeneration(amplifier: u8) -> StatusEffect {
+    StatusEffect::infinite(EffectKind::Regeneration, amplifier)
+        .with_ambient(true)
+        .with_particles(false)
+        .with_icon(false)
+}
+
+fn is_natural(effect: &StatusEffect) -> bool {
+    *effect == natural_regeneration(effect.amplifier)
+}
 
 impl Module for RegenerationModule {
-    #[allow(clippy::excessive_nesting)]
     fn module(world: &World) {
         world.component::<LastDamaged>().meta();
 
@@ -36,33 +56,38 @@ impl Module for RegenerationModule {
             world,
             &mut LastDamaged,
             &(Prev, Health),
-            &mut Health,
+            &Health,
+            &mut ActiveEffects,
             &Compose($)
         )
         .multi_threaded()
         .tracing_each(
             info_span!("regenerate"),
-            |(last_damaged, prev_health, health, compose)| {
+            |(last_damaged, prev_health, health, effects, compose)| {
                 let current_tick = compose.global().tick;
 
                 if *health < *prev_health {
                     last_damaged.tick = current_tick;
+
+                    if effects
+                        .get(EffectKind::Regeneration)
+                        .is_some_and(is_natural)
+                    {
+                        effects.remove(EffectKind::Regeneration);
+                    }
+
+                    return;
                 }
 
                 let ticks_since_damage = current_tick - last_damaged.tick;
 
-                // Calculate regeneration rate based on time since last damage
-                let base_regen = 0.01; // Base regeneration per tick
-                let ramp_factor = 0.0001_f32; // Increase in regeneration per tick
-                let max_regen = 0.1; // Maximum regeneration per tick
-
-                let regen_rate = ramp_factor
-                    .mul_add(ticks_since_damage as f32, base_regen)
-                    .min(max_regen);
+                // regeneration ramps up the longer the player has not been damaged
+                let amplifier = u8::try_from(ticks_since_damage / TICKS_PER_LEVEL)
+                    .unwrap_or(u8::MAX)
+                    .min(MAX_AMPLIFIER);
 
-                // Apply regeneration, capped at max health
-                health.heal(regen_rate);
-                **health = health.min(MAX_HEALTH);
+                // does nothing if the player already has this or a stronger regeneration
+                effects.add(natural_regeneration(amplifier));
             },
         );
     }